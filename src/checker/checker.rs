@@ -0,0 +1,1892 @@
+use std::collections::HashMap;
+
+use crate::checker::{TraitCall, Type, TypeTable, VarKind};
+use crate::checker::error::CheckErr;
+use crate::diagnostic::similar_name;
+use crate::numeric::{NumericType, Overflow};
+use crate::parser::*;
+use crate::parser::visit::{self, Visitor};
+use crate::prelude;
+use crate::resolver::error::ResolveErr;
+use crate::resolver::{Res, Resolution};
+use crate::span::Span;
+
+struct FunctionType {
+    /// the type of every parameter and the span of its annotation
+    params: Vec<(Type, Span)>,
+    return_type: Type,
+    /// the signature, shown when a call does not match it
+    span: Span,
+    /// whether the function is a method, whose first parameter is `self`
+    has_self: bool,
+    is_pub: bool,
+    /// the trait that declares the function, whose `Self` its impls replace
+    trait_path: Option<String>,
+    /// the type parameters of the class of a method and then those of the
+    /// function itself, which every call gives types of its own
+    type_params: Vec<Type>,
+    /// how many of `type_params` belong to the class
+    class_params: usize,
+}
+
+/// A function a trait declares, and whether the trait gives it a default.
+struct TraitFunction {
+    name: Identifier,
+    has_default: bool,
+}
+
+/// An impl of a trait for `self_type`, and its `impl Trait for Type` header.
+struct Impl {
+    self_type: Type,
+    span: Span,
+}
+
+struct Field {
+    name: Identifier,
+    ty: Type,
+    is_pub: bool,
+    span: Span,
+}
+
+/// How a variant of an enum is written, and so how it is built and matched.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum VariantStyle {
+    Unit,
+    Tuple,
+    Struct,
+}
+
+impl VariantStyle {
+    fn of(fields: &PatternFields) -> Self {
+        match fields {
+            PatternFields::Unit => VariantStyle::Unit,
+            PatternFields::Tuple(_) => VariantStyle::Tuple,
+            PatternFields::Struct(_) => VariantStyle::Struct,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            VariantStyle::Unit => "unit",
+            VariantStyle::Tuple => "tuple",
+            VariantStyle::Struct => "struct",
+        }
+    }
+}
+
+/// How many values a non-exhaustive `match` reports as not matched.
+const MAX_WITNESSES: usize = 3;
+
+/// What builds a value of a type that `match` splits into the values of each
+/// way to build it.
+enum Constructor {
+    /// the full path of a variant, how it is declared, and how a pattern
+    /// names it: by the path, or by the name alone for the prelude
+    Variant { path: String, style: VariantStyle, written: String },
+    Bool(bool),
+    Tuple,
+}
+
+impl Constructor {
+    /// A pattern of values built by the constructor, with `inner` for its fields.
+    fn write(&self, fields: &[(Identifier, Type)], inner: Vec<String>) -> String {
+        match self {
+            Constructor::Variant { written, style: VariantStyle::Unit, .. } => written.clone(),
+            Constructor::Variant { written, style: VariantStyle::Tuple, .. } => format!("{}({})", written, inner.join(", ")),
+            Constructor::Variant { written, style: VariantStyle::Struct, .. } if fields.is_empty() => format!("{} {{}}", written),
+            Constructor::Variant { written, style: VariantStyle::Struct, .. } => {
+                let fields: Vec<String> = fields.iter().zip(inner).map(|((name, _), inner)| format!("{}: {}", name, inner)).collect();
+                format!("{} {{ {} }}", written, fields.join(", "))
+            }
+            Constructor::Bool(value) => value.to_string(),
+            Constructor::Tuple if inner.len() == 1 => format!("({},)", inner[0]),
+            Constructor::Tuple => format!("({})", inner.join(", ")),
+        }
+    }
+}
+
+/// A variant of an enum. The fields of a tuple variant are named by their
+/// index, like `0`.
+struct EnumVariant {
+    name: Identifier,
+    style: VariantStyle,
+    fields: Vec<Field>,
+    span: Span,
+}
+
+struct Variable {
+    ty: Type,
+    is_const: bool,
+}
+
+/// A number literal, whose type is known once the body it is in is checked.
+struct NumberLiteral {
+    /// the value of an integer literal, `None` for a float
+    value: Option<u64>,
+    /// whether the literal is the operand of a `-`
+    negative: bool,
+    ty: Type,
+    span: Span,
+}
+
+/// Checks a program whose names `resolution` has resolved.
+pub struct Checker<'r> {
+    resolution: &'r Resolution,
+    /// functions and statics of every namespace, keyed by their full path like `math::add`
+    functions: HashMap<String, FunctionType>,
+    statics: HashMap<String, Type>,
+    /// the fields of every class in declaration order, keyed by the full path of the class
+    classes: HashMap<String, Vec<Field>>,
+    /// the variants of every enum in declaration order, keyed by the full path of the enum
+    enums: HashMap<String, Vec<EnumVariant>>,
+    /// the type parameters of every generic class and enum, keyed by its full path
+    item_params: HashMap<String, Vec<Type>>,
+    /// every type parameter by the id of its declaration
+    type_params: HashMap<NodeId, Type>,
+    /// the functions of every trait and the impls of it, keyed by the full path of the trait
+    traits: HashMap<String, Vec<TraitFunction>>,
+    impls: HashMap<String, Vec<Impl>>,
+    /// what `Self` is inside every trait and impl, by the id of the declaration
+    self_types: HashMap<NodeId, Type>,
+    namespace: Vec<Identifier>,
+    /// the class whose methods are being checked, which can read its private fields
+    class: Option<String>,
+    /// what `Self` is in the trait or impl whose functions are being checked
+    self_type: Option<Type>,
+    /// locals keyed by the id of the pattern that binds them
+    variables: HashMap<NodeId, Variable>,
+    /// what each type variable was inferred to be, by its index
+    substitution: Vec<Option<Type>>,
+    /// the locals of the function being checked, whose types have to be known by its end
+    locals: Vec<(Identifier, Span, Type)>,
+    /// the number literals of the body being checked
+    literals: Vec<NumberLiteral>,
+    /// operands of `-` in the body being checked, which must not turn out unsigned
+    negated: Vec<(Type, Span)>,
+    /// operands of `==` and `!=` in the body being checked whose type was
+    /// not known yet, with the operator
+    compared: Vec<(BinaryOpe, Type, Span)>,
+    /// the types calls and struct literals of the body being checked give
+    /// type parameters, with the parameter and the call, whose bounds are
+    /// checked once the types are known
+    instances: Vec<(Type, Type, Span)>,
+    types: TypeTable,
+    return_type: Type,
+    errors: Vec<CheckErr>,
+}
+
+type CheckResult = Result<(), Vec<CheckErr>>;
+
+impl<'r> Checker<'r> {
+    pub fn new(resolution: &'r Resolution) -> Self {
+        Checker {
+            resolution,
+            functions: HashMap::new(),
+            statics: HashMap::new(),
+            classes: HashMap::new(),
+            enums: HashMap::new(),
+            item_params: HashMap::new(),
+            type_params: HashMap::new(),
+            traits: HashMap::new(),
+            impls: HashMap::new(),
+            self_types: HashMap::new(),
+            namespace: Vec::new(),
+            class: None,
+            self_type: None,
+            variables: HashMap::new(),
+            substitution: Vec::new(),
+            locals: Vec::new(),
+            literals: Vec::new(),
+            negated: Vec::new(),
+            compared: Vec::new(),
+            instances: Vec::new(),
+            types: TypeTable::default(),
+            return_type: Type::unit(),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn check(&mut self, program: &Program) -> CheckResult {
+        self.collect(program);
+        self.check_recursive_classes();
+        self.check_type_literals(program);
+        self.check_statics(program);
+        self.check_program(program);
+        let mut types = std::mem::take(&mut self.types);
+        for ty in types.values_mut() {
+            *ty = self.apply(ty);
+        }
+        self.types = types;
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    /// The inferred types of what was checked; types that stayed unknown keep a `Type::Var`.
+    pub fn types(&self) -> &TypeTable {
+        &self.types
+    }
+
+    pub fn into_types(self) -> TypeTable {
+        self.types
+    }
+
+    fn full_path(&self, name: &str) -> String {
+        let mut path = self.namespace.join("::");
+        if !path.is_empty() {
+            path.push_str("::");
+        }
+        path.push_str(name);
+        path
+    }
+
+    /// The type an annotation names. Custom types go by the full path they
+    /// resolved to; one that did not resolve was reported by the resolver.
+    fn type_of(&self, type_literal: &TypeLiteral) -> Type {
+        match &type_literal.kind {
+            TypeLiteralKind::IntType => Type::Numeric(NumericType::I32),
+            TypeLiteralKind::FloatType => Type::Numeric(NumericType::F32),
+            TypeLiteralKind::NumericType(numeric) => Type::Numeric(*numeric),
+            TypeLiteralKind::CharType => Type::Char,
+            TypeLiteralKind::BoolType => Type::Bool,
+            TypeLiteralKind::CustomType { name, type_args } => match self.resolution.get(type_literal.id) {
+                // inside a generic class or enum `Self` is the type with its own parameters
+                Some(Res::Class(path) | Res::Enum(path)) if name == "Self" => Type::Custom { name: path.clone(), type_args: self.item_params.get(path).cloned().unwrap_or_default() },
+                Some(Res::Class(path) | Res::Enum(path) | Res::Trait(path)) => Type::Custom {
+                    name: path.clone(),
+                    type_args: type_args.iter().map(|type_arg| self.type_of(type_arg)).collect(),
+                },
+                Some(Res::SelfType(id)) => self.self_types.get(id).cloned().unwrap_or(Type::Never),
+                Some(Res::TypeParam(id)) => self.type_params.get(id).cloned().unwrap_or(Type::Never),
+                _ => Type::Never,
+            },
+            TypeLiteralKind::TupleType { inner } => Type::Tuple(inner.iter().map(|ty| self.type_of(ty)).collect()),
+            TypeLiteralKind::DynType { .. } => match self.resolution.get(type_literal.id) {
+                Some(Res::Trait(path)) => Type::Dyn(path.clone()),
+                _ => Type::Never,
+            },
+        }
+    }
+
+    /// Registers every function, static, class, enum, trait and impl so they can be
+    /// used before their declaration.
+    fn collect(&mut self, program: &Program) {
+        for declaration in &program.declarations {
+            match declaration {
+                Declaration::Function(function) => self.collect_function(self.full_path(&function.name), function, &[]),
+                Declaration::StaticVariable(variable) => {
+                    // statics without annotation get their type once their value is checked
+                    if let Some(type_annotation) = &variable.type_annotation {
+                        self.statics.insert(self.full_path(&variable.name), self.type_of(type_annotation));
+                    }
+                }
+                Declaration::Namespace(namespace) => {
+                    self.namespace.push(namespace.name.clone());
+                    self.collect(&namespace.inner);
+                    self.namespace.pop();
+                }
+                Declaration::Class(class) => {
+                    let class_params = self.collect_type_params(&class.type_params);
+                    if !class_params.is_empty() {
+                        self.item_params.insert(self.full_path(&class.name), class_params.clone());
+                    }
+                    for method in &class.methods {
+                        self.collect_function(self.full_path(&format!("{}::{}", class.name, method.name)), method, &class_params);
+                    }
+                    let fields = class
+                        .fields
+                        .iter()
+                        .map(|field| Field { name: field.name.clone(), ty: self.type_of(&field.type_annotation), is_pub: field.is_pub, span: field.span })
+                        .collect();
+                    self.classes.insert(self.full_path(&class.name), fields);
+                }
+                Declaration::Enum(declaration) => {
+                    let path = self.full_path(&declaration.name);
+                    let params = self.collect_type_params(&declaration.type_params);
+                    if !params.is_empty() {
+                        self.item_params.insert(path.clone(), params);
+                    }
+                    let variants = declaration.variants.iter().map(|variant| self.enum_variant(variant)).collect();
+                    self.enums.insert(path, variants);
+                }
+                Declaration::Trait(declaration) => {
+                    let path = self.full_path(&declaration.name);
+                    self.self_types.insert(declaration.id, Type::Param { name: "Self".into(), bounds: vec![path.clone()] });
+                    let functions = declaration.signatures.iter().map(|signature| (signature, false));
+                    let functions = functions.chain(declaration.functions.iter().map(|function| (&function.signature, true)));
+                    let mut trait_functions = Vec::new();
+                    for (signature, has_default) in functions {
+                        self.check_not_generic(signature);
+                        let mut function_type = self.function_type(signature, true, &[]);
+                        function_type.trait_path = Some(path.clone());
+                        self.functions.insert(format!("{}::{}", path, signature.name), function_type);
+                        trait_functions.push(TraitFunction { name: signature.name.clone(), has_default });
+                    }
+                    self.traits.insert(path, trait_functions);
+                }
+                Declaration::Impl(declaration) => self.collect_impl(declaration),
+                Declaration::Import(_) => {}
+            }
+        }
+    }
+
+    /// An impl for a type conflicts with an earlier impl of the trait for that type.
+    fn collect_impl(&mut self, declaration: &ImplDeclaration) {
+        let self_type = self.type_of(&declaration.self_type);
+        self.self_types.insert(declaration.id, self_type.clone());
+        self.types.insert(declaration.id, self_type.clone());
+        // a trait or type that did not resolve was reported by the resolver
+        let Some(Res::Trait(trait_path)) = self.resolution.get(declaration.trait_type.id) else { return };
+        if self_type == Type::Never {
+            return;
+        }
+        let span = impl_header(declaration);
+        let impls = self.impls.entry(trait_path.clone()).or_default();
+        match impls.iter().find(|other| other.self_type == self_type) {
+            Some(first) => {
+                let error = CheckErr::ConflictingImpls(trait_path.clone(), self_type, first.span, span);
+                self.errors.push(error);
+            }
+            None => impls.push(Impl { self_type, span }),
+        }
+    }
+
+    fn enum_variant(&self, variant: &Variant) -> EnumVariant {
+        let (style, fields) = match &variant.kind {
+            VariantKind::Unit => (VariantStyle::Unit, Vec::new()),
+            VariantKind::Tuple(types) => {
+                let fields = types.iter().enumerate().map(|(index, ty)| Field { name: index.to_string(), ty: self.type_of(ty), is_pub: true, span: ty.span });
+                (VariantStyle::Tuple, fields.collect())
+            }
+            VariantKind::Struct(fields) => {
+                let fields = fields.iter().map(|field| Field { name: field.name.clone(), ty: self.type_of(&field.type_annotation), is_pub: true, span: field.span });
+                (VariantStyle::Struct, fields.collect())
+            }
+        };
+        EnumVariant { name: variant.name.clone(), style, fields, span: variant.span }
+    }
+
+    /// The variant of an enum by its full path like `Shape::Circle`.
+    fn variant(&self, path: &str) -> Option<&EnumVariant> {
+        let (enum_path, name) = path.rsplit_once("::")?;
+        self.enums.get(enum_path)?.iter().find(|variant| variant.name == name)
+    }
+
+    /// The fields a value of the class or enum `item` holds in place: those
+    /// of a class, those of every variant of an enum.
+    fn held_fields(&self, item: &str) -> Vec<&Field> {
+        match (self.classes.get(item), self.enums.get(item)) {
+            (Some(fields), _) => fields.iter().collect(),
+            (None, Some(variants)) => variants.iter().flat_map(|variant| &variant.fields).collect(),
+            (None, None) => Vec::new(),
+        }
+    }
+
+    /// A class or enum that holds itself, directly or through other classes
+    /// and enums, would be infinitely large. It is reported at the field that
+    /// leads to itself.
+    fn check_recursive_classes(&mut self) {
+        let mut items: Vec<&String> = self.classes.keys().chain(self.enums.keys()).collect();
+        items.sort_by_key(|item| self.held_fields(item).first().map(|field| field.span.start));
+        let mut errors = Vec::new();
+        for item in items {
+            if let Some(field) = self.find_cycle(item, item, &mut Vec::new()) {
+                let kind = if self.enums.contains_key(item) { "enum" } else { "class" };
+                errors.push(CheckErr::RecursiveClass(item.clone(), kind, field.name.clone(), field.span));
+            }
+        }
+        self.errors.extend(errors);
+    }
+
+    /// The field of `class` through which a value of `class` contains `target`.
+    /// `path` holds the classes and enums already searched.
+    fn find_cycle<'c>(&'c self, class: &'c String, target: &str, path: &mut Vec<&'c String>) -> Option<&'c Field> {
+        if path.contains(&class) {
+            return None;
+        }
+        path.push(class);
+        self.held_fields(class).into_iter().find(|field| {
+            let mut inner = Vec::new();
+            contained_classes(&field.ty, &mut inner);
+            inner.into_iter().any(|inner| inner == target || self.find_cycle(inner, target, path).is_some())
+        })
+    }
+
+    /// A type annotation gives a class or enum as many type arguments as it has type
+    /// parameters, each implementing the bounds of its parameter, and no other
+    /// type any. Every `dyn Trait` the program names has to be of a trait
+    /// whose functions can all be called on a value of unknown type.
+    fn check_type_literals(&mut self, program: &Program) {
+        let mut type_literals = TypeLiterals(Vec::new());
+        type_literals.visit_program(program);
+        for type_literal in type_literals.0 {
+            let span = type_literal.span;
+            match (&type_literal.kind, self.resolution.get(type_literal.id)) {
+                (TypeLiteralKind::DynType { .. }, Some(Res::Trait(trait_path))) => self.check_object_safe(trait_path, span),
+                (TypeLiteralKind::CustomType { name, .. }, Some(_)) if name == "Self" => {}
+                (TypeLiteralKind::CustomType { name, type_args }, Some(res)) => {
+                    let params = match res {
+                        Res::Class(path) | Res::Enum(path) => self.item_params.get(path).cloned().unwrap_or_default(),
+                        _ => Vec::new(),
+                    };
+                    if params.len() != type_args.len() {
+                        self.errors.push(CheckErr::WrongTypeArgCount(name.clone(), params.len(), type_args.len(), span));
+                        continue;
+                    }
+                    for (param, type_arg) in params.iter().zip(type_args) {
+                        let ty = self.type_of(type_arg);
+                        self.check_bounds(&ty, param, type_arg.span);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Reports every bound of the type parameter `param` that `ty` does not implement.
+    fn check_bounds(&mut self, ty: &Type, param: &Type, span: Span) {
+        let Type::Param { bounds, .. } = param else { return };
+        if *ty == Type::Never {
+            return;
+        }
+        for bound in bounds {
+            if !self.implements(ty, bound) {
+                self.errors.push(CheckErr::NotImplemented(bound.clone(), ty.clone(), span));
+            }
+        }
+    }
+
+    fn check_object_safe(&mut self, trait_path: &str, span: Span) {
+        let unsafe_function = self.traits[trait_path].iter().find_map(|function| {
+            let function_type = &self.functions[&format!("{}::{}", trait_path, function.name)];
+            let has_self = function_type.has_self;
+            let mentions_self = function_type.params.iter().skip(1).any(|(ty, _)| mentions_self(ty)) || mentions_self(&function_type.return_type);
+            (!has_self || mentions_self).then(|| (function.name.clone(), has_self, function_type.span))
+        });
+        if let Some((name, has_self, signature)) = unsafe_function {
+            self.errors.push(CheckErr::NotObjectSafe(trait_path.to_string(), name, has_self, signature, span));
+        }
+    }
+
+    fn collect_function(&mut self, path: String, function: &FunctionDeclaration, class_params: &[Type]) {
+        let function_type = self.function_type(&function.signature, function.is_pub, class_params);
+        self.functions.insert(path, function_type);
+    }
+
+    /// The types of the type parameters, each bounded by the traits it names.
+    fn collect_type_params(&mut self, type_params: &[TypeParam]) -> Vec<Type> {
+        let mut types = Vec::new();
+        for type_param in type_params {
+            // a bound that is not a trait was reported by the resolver
+            let bounds = type_param
+                .bounds
+                .iter()
+                .filter_map(|bound| match self.resolution.get(bound.id) {
+                    Some(Res::Trait(path)) => Some(path.clone()),
+                    _ => None,
+                })
+                .collect();
+            let ty = Type::Param { name: type_param.name.clone(), bounds };
+            self.type_params.insert(type_param.id, ty.clone());
+            self.types.insert(type_param.id, ty.clone());
+            types.push(ty);
+        }
+        types
+    }
+
+    /// `class_params` are those of the class of a method.
+    fn function_type(&mut self, signature: &FunctionSignature, is_pub: bool, class_params: &[Type]) -> FunctionType {
+        let mut type_params = class_params.to_vec();
+        type_params.extend(self.collect_type_params(&signature.type_params));
+        let params = signature.params.iter().map(|param| (self.type_of(&param.type_annotation), param.type_annotation.span)).collect();
+        let return_type = signature.return_type.as_ref().map(|ty| self.type_of(ty)).unwrap_or_else(Type::unit);
+        FunctionType {
+            params,
+            return_type,
+            span: signature.span,
+            has_self: signature.has_self(),
+            is_pub,
+            trait_path: None,
+            type_params,
+            class_params: class_params.len(),
+        }
+    }
+
+    /// Which function of an impl a call runs is only decided by the type
+    /// `Self` is, so the functions of traits and impls can not be generic.
+    fn check_not_generic(&mut self, signature: &FunctionSignature) {
+        if let (Some(first), Some(last)) = (signature.type_params.first(), signature.type_params.last()) {
+            self.errors.push(CheckErr::GenericTraitFunction(signature.name.clone(), Span::new(first.span.start, last.span.end)));
+        }
+    }
+
+    /// Checks the value of every static before any function, so a function
+    /// can read a static without annotation declared after it.
+    fn check_statics(&mut self, program: &Program) {
+        for declaration in &program.declarations {
+            match declaration {
+                Declaration::StaticVariable(variable) => {
+                    let found = self.check_expression(&variable.value);
+                    if !is_constant(&variable.value) {
+                        self.errors.push(CheckErr::NonConstantStatic(variable.name.clone(), variable.value.span));
+                    }
+                    let path = self.full_path(&variable.name);
+                    match self.statics.get(&path) {
+                        Some(expected) => {
+                            let expected = expected.clone();
+                            let annotation = variable.type_annotation.as_ref().map(|annotation| annotation.span);
+                            self.expect_annotated(&expected, &found, annotation, variable.value.span);
+                            self.finish_body();
+                        }
+                        None => {
+                            self.finish_body();
+                            let found = self.apply(&found);
+                            self.statics.insert(path, found);
+                        }
+                    }
+                }
+                Declaration::Namespace(namespace) => {
+                    self.namespace.push(namespace.name.clone());
+                    self.check_statics(&namespace.inner);
+                    self.namespace.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn check_program(&mut self, program: &Program) {
+        for declaration in &program.declarations {
+            match declaration {
+                Declaration::Function(function) => self.check_function(function),
+                Declaration::Namespace(namespace) => {
+                    self.namespace.push(namespace.name.clone());
+                    self.check_program(&namespace.inner);
+                    self.namespace.pop();
+                }
+                Declaration::Class(class) => {
+                    self.class = Some(self.full_path(&class.name));
+                    for method in &class.methods {
+                        self.check_function(method);
+                    }
+                    self.class = None;
+                }
+                Declaration::Trait(declaration) => {
+                    // the defaults are checked once, for any type implementing the trait
+                    self.self_type = self.self_types.get(&declaration.id).cloned();
+                    for function in &declaration.functions {
+                        self.check_function(function);
+                    }
+                    self.self_type = None;
+                }
+                Declaration::Impl(declaration) => self.check_impl(declaration),
+                Declaration::StaticVariable(_) | Declaration::Enum(_) | Declaration::Import(_) => {}
+            }
+        }
+    }
+
+    /// The functions of an impl are those of its trait, with the signatures
+    /// the trait declares once `Self` is the type of the impl. Inside an impl
+    /// for a class they can read its private fields like its methods.
+    fn check_impl(&mut self, declaration: &ImplDeclaration) {
+        let self_type = self.self_types[&declaration.id].clone();
+        for function in &declaration.functions {
+            self.check_not_generic(&function.signature);
+        }
+        if let (Some(Res::Trait(trait_path)), false) = (self.resolution.get(declaration.trait_type.id), self_type == Type::Never) {
+            self.check_impl_signatures(trait_path, &self_type, declaration);
+        }
+        if let Type::Custom { name, .. } = &self_type {
+            self.class = Some(name.clone());
+        }
+        self.self_type = Some(self_type);
+        for function in &declaration.functions {
+            self.check_function(function);
+        }
+        self.class = None;
+        self.self_type = None;
+    }
+
+    fn check_impl_signatures(&mut self, trait_path: &str, self_type: &Type, declaration: &ImplDeclaration) {
+        let args = HashMap::from([("Self".to_string(), self_type.clone())]);
+        for function in &declaration.functions {
+            let Some(expected) = self.functions.get(&format!("{}::{}", trait_path, function.name)) else {
+                let names = self.traits[trait_path].iter().map(|function| function.name.as_str());
+                let similar = similar_name(&function.name, names).map(str::to_string);
+                self.errors.push(CheckErr::NotInTrait(function.name.clone(), trait_path.to_string(), similar, function.signature.span));
+                continue;
+            };
+            let params: Vec<(Type, Span)> = expected.params.iter().map(|(ty, span)| (ty.substitute(&args), *span)).collect();
+            let (return_type, trait_signature) = (expected.return_type.substitute(&args), expected.span);
+            let found = self.function_type(&function.signature, false, &[]);
+            if found.params.len() != params.len() {
+                let error = CheckErr::WrongImplParamCount(function.name.clone(), trait_path.to_string(), params.len(), found.params.len(), trait_signature, function.signature.span);
+                self.errors.push(error);
+                continue;
+            }
+            for ((expected, annotation), (found, span)) in params.iter().zip(&found.params) {
+                self.expect_annotated(expected, found, Some(*annotation), *span);
+            }
+            let span = function.signature.return_type.as_ref().map_or(function.signature.span, |return_type| return_type.span);
+            self.expect_annotated(&return_type, &found.return_type, Some(trait_signature), span);
+        }
+        let missing: Vec<Identifier> = self.traits[trait_path]
+            .iter()
+            .filter(|required| !required.has_default && declaration.functions.iter().all(|function| function.name != required.name))
+            .map(|required| required.name.clone())
+            .collect();
+        if !missing.is_empty() {
+            self.errors.push(CheckErr::MissingTraitFunctions(trait_path.to_string(), self_type.clone(), missing, impl_header(declaration)));
+        }
+    }
+
+    /// Whether `ty` implements the trait, by an impl or by a bound. A
+    /// `dyn Trait` implements its trait.
+    fn implements(&self, ty: &Type, trait_path: &str) -> bool {
+        match ty {
+            Type::Param { bounds, .. } => bounds.iter().any(|bound| bound == trait_path),
+            Type::Dyn(dyn_trait) if dyn_trait == trait_path => true,
+            ty => self.impls.get(trait_path).is_some_and(|impls| impls.iter().any(|other| other.self_type == *ty)),
+        }
+    }
+
+    /// The traits that `ty` implements and that have a method `name`.
+    fn trait_methods(&self, ty: &Type, name: &str) -> Vec<String> {
+        let mut traits: Vec<String> = self
+            .traits
+            .keys()
+            .filter(|trait_path| self.functions.get(&format!("{}::{}", trait_path, name)).is_some_and(|function| function.has_self))
+            .filter(|trait_path| self.implements(ty, trait_path))
+            .cloned()
+            .collect();
+        traits.sort();
+        traits
+    }
+
+    fn check_function(&mut self, function: &FunctionDeclaration) {
+        let signature = &function.signature;
+        self.return_type = signature.return_type.as_ref().map(|ty| self.type_of(ty)).unwrap_or_else(Type::unit);
+        for param in &signature.params {
+            let ty = self.type_of(&param.type_annotation);
+            self.bind_pattern(&param.pattern, ty, false);
+            self.check_irrefutable(&param.pattern);
+        }
+        let found = self.check_block(&function.inner);
+        let expected = self.return_type.clone();
+        let annotation = signature.return_type.as_ref().map(|annotation| annotation.span);
+        match &function.inner.value {
+            Some(value) => self.expect_coerced(&expected, &found, annotation, value),
+            None => self.expect_annotated(&expected, &found, annotation, tail_span(&function.inner)),
+        }
+        self.finish_body();
+    }
+
+    /// Gives number literals nothing fixed the type of their default type,
+    /// then checks what needed every type of the body to be known.
+    fn finish_body(&mut self) {
+        let literals = std::mem::take(&mut self.literals);
+        for literal in &literals {
+            let default = if literal.value.is_some() { NumericType::I32 } else { NumericType::F32 };
+            if let Type::Var(_, VarKind::Integer | VarKind::Float) = self.shallow(&literal.ty) {
+                self.unify(&literal.ty, &Type::Numeric(default));
+            }
+        }
+
+        // locals sharing one unknown type are reported once, at the first of them
+        let mut unknown = Vec::new();
+        for (name, span, ty) in std::mem::take(&mut self.locals) {
+            let ty = self.apply(&ty);
+            if ty.has_vars() && !unknown.contains(&ty) {
+                self.errors.push(CheckErr::AnnotationNeeded(name, span));
+                unknown.push(ty);
+            }
+        }
+
+        for literal in literals {
+            let (Some(value), Type::Numeric(numeric)) = (literal.value, self.shallow(&literal.ty)) else { continue };
+            let Some((min, max)) = numeric.range() else { continue };
+            let value = if literal.negative { -i128::from(value) } else { i128::from(value) };
+            if value < min || value > max {
+                self.errors.push(CheckErr::LiteralOutOfRange(numeric, value, literal.span));
+            }
+        }
+        for (ty, span) in std::mem::take(&mut self.negated) {
+            if let Type::Numeric(numeric) = self.shallow(&ty) {
+                if !numeric.is_signed() {
+                    self.errors.push(CheckErr::InvalidUnaryOperand(UnaryOpe::Minus, Type::Numeric(numeric), span));
+                }
+            }
+        }
+        for (ope, ty, span) in std::mem::take(&mut self.compared) {
+            let ty = self.apply(&ty);
+            if !self.equatable(&ty) {
+                self.errors.push(CheckErr::InvalidBinaryOperand(ope, ty.clone(), ty, span));
+            }
+        }
+
+        // a call whose type arguments are unknown is reported once
+        let mut unknown = Vec::new();
+        for (ty, param, span) in std::mem::take(&mut self.instances) {
+            let ty = self.apply(&ty);
+            if !ty.has_vars() {
+                self.check_bounds(&ty, &param, span);
+            } else if !unknown.contains(&span) {
+                self.errors.push(CheckErr::UnknownType(span));
+                unknown.push(span);
+            }
+        }
+    }
+
+    fn fresh_var(&mut self) -> Type {
+        self.fresh_var_of(VarKind::Any)
+    }
+
+    fn fresh_var_of(&mut self, kind: VarKind) -> Type {
+        self.substitution.push(None);
+        Type::Var(self.substitution.len() - 1, kind)
+    }
+
+    /// Makes `ty` a type of `kind`, and returns whether it could be.
+    fn constrain(&mut self, ty: &Type, kind: VarKind) -> bool {
+        let var = self.fresh_var_of(kind);
+        self.unify(&var, ty)
+    }
+
+    fn literal_type(&mut self, literal: &Literal, span: Span) -> Type {
+        let (value, kind) = match literal {
+            Literal::String(_) => return Type::Str,
+            Literal::Bool(_) => return Type::Bool,
+            Literal::Int(value) => (Some(*value), VarKind::Integer),
+            Literal::Float(_) => (None, VarKind::Float),
+        };
+        let ty = self.fresh_var_of(kind);
+        self.literals.push(NumberLiteral { value, negative: false, ty: ty.clone(), span });
+        ty
+    }
+
+    /// `ty` with its type variables applied, and a number literal whose type
+    /// is not fixed yet made `int` or `float`, like it would be at the end of
+    /// the body. A method can only be looked up on a known type.
+    fn default_number(&mut self, ty: &Type) -> Type {
+        let default = match self.shallow(ty) {
+            Type::Var(_, VarKind::Integer) => NumericType::I32,
+            Type::Var(_, VarKind::Float) => NumericType::F32,
+            _ => return self.apply(ty),
+        };
+        self.unify(ty, &Type::Numeric(default));
+        Type::Numeric(default)
+    }
+
+    /// `ty` with the outermost type variables replaced by what they were inferred to be.
+    fn shallow(&self, ty: &Type) -> Type {
+        match ty {
+            Type::Var(var, _) => match &self.substitution[*var] {
+                Some(inferred) => self.shallow(inferred),
+                None => ty.clone(),
+            },
+            ty => ty.clone(),
+        }
+    }
+
+    /// `ty` with every type variable that was inferred replaced by its type.
+    fn apply(&self, ty: &Type) -> Type {
+        match self.shallow(ty) {
+            Type::Tuple(inner) => Type::Tuple(inner.iter().map(|ty| self.apply(ty)).collect()),
+            Type::Custom { name, type_args } => Type::Custom { name, type_args: type_args.iter().map(|ty| self.apply(ty)).collect() },
+            ty => ty,
+        }
+    }
+
+    /// Makes `expected` and `found` the same type by inferring their type
+    /// variables, and returns whether they could be. A `Never` fits anything.
+    fn unify(&mut self, expected: &Type, found: &Type) -> bool {
+        match (self.shallow(expected), self.shallow(found)) {
+            (Type::Never, _) | (_, Type::Never) => true,
+            (Type::Var(expected, _), Type::Var(found, _)) if expected == found => true,
+            (Type::Var(expected, expected_kind), Type::Var(found, found_kind)) => {
+                // both become one variable that only types fitting both kinds can be
+                let Some(kind) = expected_kind.meet(found_kind) else { return false };
+                let var = self.fresh_var_of(kind);
+                self.substitution[expected] = Some(var.clone());
+                self.substitution[found] = Some(var);
+                true
+            }
+            (Type::Var(var, kind), ty) | (ty, Type::Var(var, kind)) => {
+                // a type can not contain itself, like `x` in `x = (x, 1);`
+                if !kind.fits(&ty) || occurs(var, &self.apply(&ty)) {
+                    return false;
+                }
+                self.substitution[var] = Some(ty);
+                true
+            }
+            (Type::Tuple(expected), Type::Tuple(found)) => {
+                expected.len() == found.len() && expected.iter().zip(&found).all(|(expected, found)| self.unify(expected, found))
+            }
+            (Type::Custom { name: expected_name, type_args: expected }, Type::Custom { name: found_name, type_args: found }) => {
+                expected_name == found_name && expected.len() == found.len() && expected.iter().zip(&found).all(|(expected, found)| self.unify(expected, found))
+            }
+            (expected, found) => expected == found,
+        }
+    }
+
+    fn expect_type(&mut self, expected: &Type, found: &Type, span: Span) {
+        self.expect_annotated(expected, found, None, span)
+    }
+
+    /// Like `expect_type`, for a type that the annotation at `annotation` asks for.
+    fn expect_annotated(&mut self, expected: &Type, found: &Type, annotation: Option<Span>, span: Span) {
+        if !self.unify(expected, found) {
+            self.errors.push(CheckErr::TypeMismatch(self.apply(expected), self.apply(found), annotation, span));
+        }
+    }
+
+    /// Like `expect_annotated` for the value of `expression`, which may also
+    /// be of a type implementing the trait of an expected `dyn Trait`. The
+    /// value is then turned into a `dyn Trait`.
+    fn expect_coerced(&mut self, expected: &Type, found: &Type, annotation: Option<Span>, expression: &Expression) {
+        if let Type::Dyn(trait_path) = self.shallow(expected) {
+            match self.default_number(found) {
+                // a value of unknown type becomes the `dyn Trait` itself
+                Type::Dyn(_) | Type::Never | Type::Var(..) => {}
+                found if self.implements(&found, &trait_path) => {
+                    self.types.insert_coercion(expression.id, Type::Dyn(trait_path));
+                    return;
+                }
+                found => {
+                    self.errors.push(CheckErr::NotImplemented(trait_path, found, expression.span));
+                    return;
+                }
+            }
+        }
+        self.expect_annotated(expected, found, annotation, expression.span)
+    }
+
+    fn bind_pattern(&mut self, pattern: &Pattern, ty: Type, is_const: bool) {
+        let ty = self.shallow(&ty);
+        self.types.insert(pattern.id, ty.clone());
+        match (&pattern.kind, ty) {
+            (PatternKind::Identifier(name), ty) if matches!(self.resolution.get(pattern.id), Some(Res::Variant(_))) => {
+                let accesser = Accesser { segments: vec![PathSegment { name: name.clone(), type_args: Vec::new() }] };
+                self.bind_variant_pattern(pattern, &accesser, &PatternFields::Unit, ty, is_const);
+            }
+            (PatternKind::Identifier(name), ty) => {
+                self.locals.push((name.clone(), pattern.span, ty.clone()));
+                self.variables.insert(pattern.id, Variable { ty, is_const });
+            }
+            (PatternKind::Wildcard, _) => {}
+            (PatternKind::Literal(literal), ty) => {
+                let found = self.literal_type(literal, pattern.span);
+                self.expect_type(&ty, &found, pattern.span);
+            }
+            (PatternKind::Tuple(patterns), Type::Tuple(types)) if patterns.len() == types.len() => {
+                for (pattern, ty) in patterns.iter().zip(types) {
+                    self.bind_pattern(pattern, ty, is_const);
+                }
+            }
+            (PatternKind::Tuple(patterns), var @ Type::Var(..)) => {
+                let types: Vec<Type> = patterns.iter().map(|_| self.fresh_var()).collect();
+                self.unify(&var, &Type::Tuple(types.clone()));
+                for (pattern, ty) in patterns.iter().zip(types) {
+                    self.bind_pattern(pattern, ty, is_const);
+                }
+            }
+            (PatternKind::Tuple(patterns), Type::Never) => {
+                for pattern in patterns {
+                    self.bind_pattern(pattern, Type::Never, is_const);
+                }
+            }
+            (PatternKind::Tuple(patterns), ty) => {
+                let expected = Type::Tuple(patterns.iter().map(|_| Type::Never).collect());
+                self.errors.push(CheckErr::TypeMismatch(expected, ty, None, pattern.span));
+                self.bind_pattern(pattern, Type::Never, is_const);
+            }
+            (PatternKind::Variant { path, fields }, ty) => self.bind_variant_pattern(pattern, path, fields, ty, is_const),
+        }
+    }
+
+    /// Binds the fields of an enum pattern, whose variant has to be of the
+    /// enum of `ty` and written like it is declared. A struct pattern may
+    /// leave out fields.
+    fn bind_variant_pattern(&mut self, pattern: &Pattern, accesser: &Accesser, fields: &PatternFields, ty: Type, is_const: bool) {
+        let inner: Vec<&Pattern> = match fields {
+            PatternFields::Unit => Vec::new(),
+            PatternFields::Tuple(inner) => inner.iter().collect(),
+            PatternFields::Struct(fields) => fields.iter().map(|field| &field.pattern).collect(),
+        };
+        let bind_never = |checker: &mut Self| {
+            for pattern in &inner {
+                checker.bind_pattern(pattern, Type::Never, is_const);
+            }
+        };
+        // a path that did not name a variant was reported by the resolver
+        let Some(Res::Variant(path)) = self.resolution.get(pattern.id) else { return bind_never(self) };
+        if !self.check_variant_style(path, VariantStyle::of(fields), pattern.span) {
+            return bind_never(self);
+        }
+        let (enum_type, args_of) = self.variant_type(path, accesser, false, pattern.span);
+        if !self.unify(&ty, &enum_type) {
+            self.errors.push(CheckErr::TypeMismatch(self.apply(&ty), self.apply(&enum_type), None, pattern.span));
+            return bind_never(self);
+        }
+        let variant = self.variant(path).unwrap();
+        let variant_span = variant.span;
+        let types: Vec<(Identifier, Type)> = variant.fields.iter().map(|field| (field.name.clone(), field.ty.substitute(&args_of))).collect();
+        match fields {
+            PatternFields::Unit => {}
+            PatternFields::Tuple(inner) if inner.len() != types.len() => {
+                self.errors.push(CheckErr::WrongArgCount(path.clone(), types.len(), inner.len(), Some(variant_span), pattern.span));
+                bind_never(self);
+            }
+            PatternFields::Tuple(inner) => {
+                for (pattern, (_, ty)) in inner.iter().zip(types) {
+                    self.bind_pattern(pattern, ty, is_const);
+                }
+            }
+            PatternFields::Struct(fields) => {
+                let mut given: HashMap<&Identifier, Span> = HashMap::new();
+                for field in fields {
+                    let name_span = Span::new(field.span.start, field.span.start + field.name.len());
+                    let ty = match types.iter().find(|(name, _)| *name == field.name) {
+                        _ if given.contains_key(&field.name) => {
+                            self.errors.push(CheckErr::RepeatedField(field.name.clone(), given[&field.name], name_span));
+                            Type::Never
+                        }
+                        Some((_, ty)) => ty.clone(),
+                        None => {
+                            let similar = similar_name(&field.name, types.iter().map(|(name, _)| name.as_str())).map(str::to_string);
+                            self.errors.push(CheckErr::NoField(self.apply(&enum_type), field.name.clone(), similar, name_span));
+                            Type::Never
+                        }
+                    };
+                    given.entry(&field.name).or_insert(name_span);
+                    self.bind_pattern(&field.pattern, ty, is_const);
+                }
+            }
+        }
+    }
+
+    /// Whether `pattern` matches every value of its type. A variant only does
+    /// when it is the one variant of its enum. A pattern whose variant did not
+    /// resolve is taken to, as it was reported already.
+    fn is_irrefutable(&self, pattern: &Pattern) -> bool {
+        let variants = match self.resolution.get(pattern.id) {
+            Some(Res::Variant(path)) => path.rsplit_once("::").and_then(|(enum_path, _)| self.enums.get(enum_path)),
+            _ => None,
+        };
+        match &pattern.kind {
+            PatternKind::Identifier(_) => variants.is_none_or(|variants| variants.len() == 1),
+            PatternKind::Wildcard => true,
+            PatternKind::Literal(_) => false,
+            PatternKind::Tuple(inner) => inner.iter().all(|pattern| self.is_irrefutable(pattern)),
+            PatternKind::Variant { fields, .. } => variants.is_none_or(|variants| variants.len() == 1) && self.are_irrefutable(fields),
+        }
+    }
+
+    fn are_irrefutable(&self, fields: &PatternFields) -> bool {
+        match fields {
+            PatternFields::Unit => true,
+            PatternFields::Tuple(inner) => inner.iter().all(|pattern| self.is_irrefutable(pattern)),
+            PatternFields::Struct(fields) => fields.iter().all(|field| self.is_irrefutable(&field.pattern)),
+        }
+    }
+
+    /// A `let` or a parameter has nothing to run when its pattern does not match.
+    fn check_irrefutable(&mut self, pattern: &Pattern) {
+        if !self.is_irrefutable(pattern) {
+            self.errors.push(CheckErr::RefutablePattern(pattern.span));
+        }
+    }
+
+    /// The local that the accesser expression `id` resolved to.
+    fn variable(&self, id: NodeId) -> Option<&Variable> {
+        match self.resolution.get(id) {
+            Some(Res::Local(binding)) => self.variables.get(binding),
+            _ => None,
+        }
+    }
+
+    fn check_block(&mut self, block: &Block) -> Type {
+        let mut diverges = false;
+        for statement in &block.statements {
+            diverges |= self.check_statement(statement);
+        }
+        match &block.value {
+            Some(value) => self.check_expression(value),
+            None if diverges => Type::Never,
+            None => Type::unit(),
+        }
+    }
+
+    /// Returns whether control flow never continues after the statement.
+    fn check_statement(&mut self, statement: &Statement) -> bool {
+        match &statement.kind {
+            StatementKind::Expression(expression) => {
+                let ty = self.check_expression(expression);
+                self.shallow(&ty) == Type::Never
+            }
+            StatementKind::Let { is_const, pattern, type_annotation, value } => {
+                let found = value.as_ref().map(|value| (self.check_expression(value), value));
+                let ty = match (type_annotation.as_ref().map(|annotation| self.type_of(annotation)), found) {
+                    (Some(expected), Some((found, value))) => {
+                        self.expect_coerced(&expected, &found, type_annotation.as_ref().map(|annotation| annotation.span), value);
+                        expected
+                    }
+                    (Some(expected), None) => expected,
+                    (None, Some((found, _))) => found,
+                    // inferred from the uses of the binding
+                    (None, None) => self.fresh_var(),
+                };
+                self.bind_pattern(pattern, ty, *is_const);
+                self.check_irrefutable(pattern);
+                false
+            }
+            StatementKind::Assign { target, value } => {
+                let found = self.check_expression(value);
+                // a field of a const is as constant as the const itself
+                let mut root = target;
+                while let ExpressionKind::Field { target, .. } = &root.kind {
+                    root = target;
+                }
+                if let ExpressionKind::Accesser(accesser) = &root.kind {
+                    if let Some(Variable { is_const: true, .. }) = self.variable(root.id) {
+                        self.errors.push(CheckErr::AssignToConst(accesser.path(), target.span));
+                    }
+                }
+                let expected = self.check_expression(target);
+                self.expect_coerced(&expected, &found, None, value);
+                false
+            }
+            StatementKind::Return(value) => {
+                let expected = self.return_type.clone();
+                match value {
+                    Some(value) => {
+                        let found = self.check_expression(value);
+                        self.expect_coerced(&expected, &found, None, value);
+                    }
+                    None => self.expect_type(&expected, &Type::unit(), statement.span),
+                }
+                true
+            }
+            StatementKind::For { pattern, iterable, body } => {
+                let ty = self.check_expression(iterable);
+                if self.shallow(&ty) != Type::Never {
+                    self.errors.push(CheckErr::NotIterable(self.apply(&ty), iterable.span));
+                }
+                self.bind_pattern(pattern, Type::Never, true);
+                let found = self.check_block(body);
+                self.expect_type(&Type::unit(), &found, tail_span(body));
+                false
+            }
+            StatementKind::While { condition, body } => {
+                let found = self.check_expression(condition);
+                self.expect_type(&Type::Bool, &found, condition.span);
+                let found = self.check_block(body);
+                self.expect_type(&Type::unit(), &found, tail_span(body));
+                false
+            }
+        }
+    }
+
+    fn check_expression(&mut self, expression: &Expression) -> Type {
+        let ty = self.check_expression_kind(expression);
+        self.types.insert(expression.id, ty.clone());
+        ty
+    }
+
+    fn check_expression_kind(&mut self, expression: &Expression) -> Type {
+        match &expression.kind {
+            ExpressionKind::Literal(literal) => self.literal_type(literal, expression.span),
+            ExpressionKind::Accesser(accesser) => {
+                if let Some(Res::Variant(path)) = self.resolution.get(expression.id) {
+                    return self.check_variant(path, accesser, None, expression.span);
+                }
+                self.check_no_type_args(accesser, expression.span);
+                match self.resolution.get(expression.id) {
+                    Some(Res::Local(_)) => self.variable(expression.id).map_or(Type::Never, |variable| variable.ty.clone()),
+                    Some(Res::Static(path)) => match self.statics.get(path) {
+                        Some(ty) => ty.clone(),
+                        // a static without annotation is only known once its value is checked
+                        None => {
+                            self.errors.push(CheckErr::AnnotationNeeded(accesser.path(), expression.span));
+                            Type::Never
+                        }
+                    },
+                    Some(Res::Function(_) | Res::Intrinsic(_)) => {
+                        self.errors.push(CheckErr::NotCallable(accesser.path(), expression.span));
+                        Type::Never
+                    }
+                    // anything else was reported by the resolver
+                    _ => Type::Never,
+                }
+            }
+            ExpressionKind::Unary { ope, operand } => {
+                let ty = self.check_expression(operand);
+                match (ope, self.shallow(&ty)) {
+                    (UnaryOpe::Minus, Type::Never) => ty,
+                    (UnaryOpe::Minus, numeric) if self.constrain(&numeric, VarKind::Number) => {
+                        match &operand.kind {
+                            // `-128` is one literal, which may fit where `128` does not
+                            ExpressionKind::Literal(Literal::Int(_)) => {
+                                let literal = self.literals.last_mut().unwrap();
+                                literal.negative = true;
+                                literal.span = expression.span;
+                            }
+                            _ => self.negated.push((ty.clone(), expression.span)),
+                        }
+                        ty
+                    }
+                    (ope, ty) => {
+                        self.errors.push(CheckErr::InvalidUnaryOperand(*ope, self.apply(&ty), expression.span));
+                        Type::Never
+                    }
+                }
+            }
+            ExpressionKind::Binary { ope, left, right } => {
+                let left = self.check_expression(left);
+                let right = self.check_expression(right);
+                let (left, right) = (self.shallow(&left), self.shallow(&right));
+                self.check_binary(*ope, left, right, expression.span)
+            }
+            ExpressionKind::Call { callee, args } => {
+                if let ExpressionKind::Field { target, name } = &callee.kind {
+                    return self.check_method_call(expression.id, target, name, args, callee.span, expression.span);
+                }
+                let found: Vec<Type> = args.iter().map(|arg| self.check_expression(arg)).collect();
+                let ExpressionKind::Accesser(accesser) = &callee.kind else {
+                    self.check_expression(callee);
+                    self.errors.push(CheckErr::NotCallable("expression".into(), callee.span));
+                    return Type::Never;
+                };
+                match self.resolution.get(callee.id) {
+                    Some(Res::Function(path)) => {
+                        let Some(function) = self.functions.get(path) else { return Type::Never };
+                        if let Some(trait_path) = function.trait_path.clone() {
+                            self.check_no_type_args(accesser, callee.span);
+                            return self.check_trait_function_call(expression.id, &trait_path, accesser, found, args, expression.span);
+                        }
+                        let args_of = self.instantiate(expression.id, path, Some(accesser), callee.span);
+                        let function = &self.functions[path];
+                        let params: Vec<(Type, Span)> = function.params.iter().map(|(ty, span)| (ty.substitute(&args_of), *span)).collect();
+                        let return_type = function.return_type.substitute(&args_of);
+                        self.check_args(&accesser.path(), &params, found, args, expression.span);
+                        return_type
+                    }
+                    Some(Res::Variant(path)) => self.check_variant(path, accesser, Some((found, args)), expression.span),
+                    // `wrapping_add` and the like take two integers of one type and return
+                    // that type, `checked_add` and the like an `Option` of it
+                    Some(Res::Intrinsic(intrinsic)) => {
+                        let checked = intrinsic.overflow == Overflow::Checked;
+                        self.check_no_type_args(accesser, callee.span);
+                        let ty = self.fresh_var_of(VarKind::Integer);
+                        if args.len() != 2 {
+                            self.errors.push(CheckErr::WrongArgCount(accesser.path(), 2, args.len(), None, expression.span));
+                            return Type::Never;
+                        }
+                        let errors = self.errors.len();
+                        for (found, arg) in found.iter().zip(args) {
+                            self.expect_annotated(&ty, found, None, arg.span);
+                        }
+                        match self.errors.len() == errors {
+                            true if checked => Type::Custom { name: prelude::OPTION.into(), type_args: vec![ty] },
+                            true => ty,
+                            false => Type::Never,
+                        }
+                    }
+                    Some(Res::Local(_) | Res::Static(_)) => {
+                        self.check_no_type_args(accesser, callee.span);
+                        let ty = self.check_expression(callee);
+                        if self.shallow(&ty) != Type::Never {
+                            self.errors.push(CheckErr::NotAFunction(accesser.path(), self.apply(&ty), callee.span));
+                        }
+                        Type::Never
+                    }
+                    _ => Type::Never,
+                }
+            }
+            ExpressionKind::Cast { value, ty } => {
+                let found = self.check_expression(value);
+                let target = self.type_of(ty);
+                self.check_cast(found, target, expression.span)
+            }
+            ExpressionKind::Field { target, name } => {
+                let ty = self.check_expression(target);
+                self.check_field(ty, name, expression.span)
+            }
+            ExpressionKind::Try(value) => self.check_try(value, expression.span),
+            ExpressionKind::Tuple(inner) => Type::Tuple(inner.iter().map(|expression| self.check_expression(expression)).collect()),
+            ExpressionKind::Struct { class, fields } => self.check_struct(expression, class, fields),
+            ExpressionKind::If(if_expression) => self.check_if(if_expression),
+            ExpressionKind::Match(match_expression) => self.check_match(match_expression),
+            ExpressionKind::Block(block) => self.check_block(block),
+        }
+    }
+
+    /// Checks the arguments of a call of `path` against its parameters.
+    fn check_args(&mut self, path: &str, params: &[(Type, Span)], found: Vec<Type>, args: &[Expression], span: Span) {
+        if params.len() != args.len() {
+            let signature = self.functions.get(path).map(|function| function.span);
+            self.errors.push(CheckErr::WrongArgCount(path.to_string(), params.len(), args.len(), signature, span));
+            return;
+        }
+        for (((expected, annotation), found), arg) in params.iter().zip(found).zip(args) {
+            self.expect_coerced(expected, &found, Some(*annotation), arg);
+        }
+    }
+
+    /// `target.name(args)` calls the method `name` of the class of `target`
+    /// with `target` as `self`, or else the method of a trait that the type of
+    /// `target` implements. `callee` is `target.name`.
+    fn check_method_call(&mut self, id: NodeId, target: &Expression, name: &Identifier, args: &[Expression], callee: Span, span: Span) -> Type {
+        let ty = self.check_expression(target);
+        let found: Vec<Type> = args.iter().map(|arg| self.check_expression(arg)).collect();
+        let ty = self.default_number(&ty);
+        let class = match &ty {
+            Type::Never => return Type::Never,
+            Type::Var(_, VarKind::Any) => {
+                self.errors.push(CheckErr::UnknownType(target.span));
+                return Type::Never;
+            }
+            Type::Custom { name: class, .. } => class.clone(),
+            _ => String::new(),
+        };
+        let path = format!("{}::{}", class, name);
+        let mut args_of = HashMap::new();
+        let path = match self.functions.get(&path).filter(|function| function.has_self) {
+            Some(method) => {
+                if !method.is_pub && self.class.as_ref() != Some(&class) {
+                    self.errors.push(ResolveErr::Private(path.clone(), method.span, callee).into());
+                }
+                // the receiver gives the parameters of a generic class their types
+                args_of = self.instantiate(id, &path, None, callee);
+                path
+            }
+            None => match self.trait_methods(&ty, name).as_slice() {
+                [trait_path] => {
+                    self.types.insert_trait_call(id, TraitCall { trait_path: trait_path.clone(), name: name.clone(), self_type: ty.clone() });
+                    args_of.insert("Self".to_string(), ty.clone());
+                    format!("{}::{}", trait_path, name)
+                }
+                [] => {
+                    let associated = self.functions.get(&path).map(|_| path.clone());
+                    let prefix = format!("{}::", class);
+                    let mut methods: Vec<&str> = self.functions.iter().filter(|(_, function)| function.has_self).filter_map(|(path, _)| path.strip_prefix(&prefix)).collect();
+                    for (trait_path, functions) in &self.traits {
+                        if self.implements(&ty, trait_path) {
+                            let has_self = |function: &&TraitFunction| self.functions[&format!("{}::{}", trait_path, function.name)].has_self;
+                            methods.extend(functions.iter().filter(has_self).map(|function| function.name.as_str()));
+                        }
+                    }
+                    let similar = similar_name(name, methods).map(str::to_string);
+                    self.errors.push(CheckErr::NoMethod(ty, name.clone(), similar, associated, callee));
+                    return Type::Never;
+                }
+                traits => {
+                    self.errors.push(CheckErr::AmbiguousMethod(ty.clone(), name.clone(), traits.to_vec(), callee));
+                    return Type::Never;
+                }
+            },
+        };
+        let method = &self.functions[&path];
+        let mut params = method.params.iter().map(|(ty, span)| (ty.substitute(&args_of), *span));
+        let receiver = params.next().unwrap().0;
+        let params: Vec<(Type, Span)> = params.collect();
+        let return_type = method.return_type.substitute(&args_of);
+        self.expect_type(&receiver, &ty, target.span);
+        self.check_args(&path, &params, found, args, span);
+        return_type
+    }
+
+    /// `Trait::name(args)` calls the function of the impl of the trait for
+    /// the type `Self` is inferred to be from the arguments. Inside a trait,
+    /// `Self::name(args)` calls it for `Self`.
+    fn check_trait_function_call(&mut self, id: NodeId, trait_path: &str, accesser: &Accesser, found: Vec<Type>, args: &[Expression], span: Span) -> Type {
+        let self_type = match &self.self_type {
+            Some(self_type) if accesser.segments[0].name == "Self" => self_type.clone(),
+            _ => self.fresh_var(),
+        };
+        let args_of = HashMap::from([("Self".to_string(), self_type.clone())]);
+        let name = &accesser.segments.last().unwrap().name;
+        let path = format!("{}::{}", trait_path, name);
+        let function = &self.functions[&path];
+        let params: Vec<(Type, Span)> = function.params.iter().map(|(ty, span)| (ty.substitute(&args_of), *span)).collect();
+        let return_type = function.return_type.substitute(&args_of);
+        self.check_args(&path, &params, found, args, span);
+        match self.default_number(&self_type) {
+            Type::Never => {}
+            self_type if self_type.has_vars() => {
+                self.errors.push(CheckErr::UnknownType(span));
+                return Type::Never;
+            }
+            self_type if !self.implements(&self_type, trait_path) => {
+                self.errors.push(CheckErr::NotImplemented(trait_path.to_string(), self_type, span));
+                return Type::Never;
+            }
+            self_type => self.types.insert_trait_call(id, TraitCall { trait_path: trait_path.to_string(), name: name.clone(), self_type }),
+        }
+        return_type
+    }
+
+    /// A unit variant like `Shape::Empty`, or with `args` the call of a tuple
+    /// variant like `Shape::Circle(1.0)`, which builds a value of its enum.
+    fn check_variant(&mut self, path: &str, accesser: &Accesser, args: Option<(Vec<Type>, &[Expression])>, span: Span) -> Type {
+        let style = if args.is_some() { VariantStyle::Tuple } else { VariantStyle::Unit };
+        if !self.check_variant_style(path, style, span) {
+            return Type::Never;
+        }
+        let (ty, args_of) = self.variant_type(path, accesser, true, span);
+        let Some((found, args)) = args else { return ty };
+        let variant = self.variant(path).unwrap();
+        let variant_span = variant.span;
+        let params: Vec<(Type, Span)> = variant.fields.iter().map(|field| (field.ty.substitute(&args_of), field.span)).collect();
+        if params.len() != args.len() {
+            self.errors.push(CheckErr::WrongArgCount(path.to_string(), params.len(), args.len(), Some(variant_span), span));
+            return Type::Never;
+        }
+        self.check_args(path, &params, found, args, span);
+        ty
+    }
+
+    /// Reports a variant that is used otherwise than it is declared, like
+    /// calling a unit variant, and returns whether it is used right.
+    fn check_variant_style(&mut self, path: &str, used: VariantStyle, span: Span) -> bool {
+        let Some(variant) = self.variant(path) else { return false };
+        if variant.style != used {
+            self.errors.push(CheckErr::InvalidVariantUse(path.to_string(), variant.style.name(), used.name(), variant.span, span));
+            return false;
+        }
+        true
+    }
+
+    /// The type of the enum of the variant `path` with fresh type arguments,
+    /// and the type arguments by the name of their parameter. A turbofish on
+    /// the enum fixes them, like `Option::<int>::None`. The bounds of the
+    /// arguments of a value being built are checked at the end of the body;
+    /// those of a value being matched already hold.
+    fn variant_type(&mut self, path: &str, accesser: &Accesser, is_built: bool, span: Span) -> (Type, HashMap<Identifier, Type>) {
+        let enum_path = path.rsplit_once("::").map_or("", |(enum_path, _)| enum_path);
+        let params = self.item_params.get(enum_path).cloned().unwrap_or_default();
+        let segments = &accesser.segments;
+        let mut given: &[TypeLiteral] = &[];
+        // a variant named without its enum, like `None::<int>`, takes them on its name
+        let enum_index = if segments.len() == 1 { 0 } else { segments.len().wrapping_sub(2) };
+        for (index, segment) in segments.iter().enumerate().filter(|(_, segment)| !segment.type_args.is_empty()) {
+            if index == enum_index && !params.is_empty() {
+                given = &segment.type_args;
+            } else {
+                self.errors.push(CheckErr::UnexpectedTypeArgs(accesser.path(), span));
+            }
+        }
+        let type_args = if is_built {
+            self.instantiate_params(&params, given, enum_path, span)
+        } else {
+            let type_args: Vec<Type> = params.iter().map(|_| self.fresh_var()).collect();
+            if given.len() == params.len() {
+                for (ty, type_literal) in type_args.iter().zip(given) {
+                    let found = self.type_of(type_literal);
+                    self.unify(ty, &found);
+                }
+            } else if !given.is_empty() {
+                self.errors.push(CheckErr::WrongTypeArgCount(enum_path.to_string(), params.len(), given.len(), span));
+            }
+            type_args
+        };
+        let args_of = param_args(&params, &type_args);
+        (Type::Custom { name: enum_path.to_string(), type_args }, args_of)
+    }
+
+    /// `Class { field: value, ... }` gives every field of the class a value
+    /// once. The type arguments of a generic class are inferred from the
+    /// values, unless `Class::<...>` gives them. A struct variant of an enum
+    /// is built the same way, like `Shape::Rect { w: 1.0, h: 2.0 }`.
+    fn check_struct(&mut self, expression: &Expression, accesser: &Accesser, inits: &[FieldInit]) -> Type {
+        let found: Vec<Type> = inits.iter().map(|init| self.check_expression(&init.value)).collect();
+        let (class, ty, args_of) = match self.resolution.get(expression.id) {
+            Some(Res::Class(class)) => {
+                let (segments, last) = accesser.segments.split_at(accesser.segments.len() - 1);
+                if segments.iter().any(|segment| !segment.type_args.is_empty()) {
+                    self.errors.push(CheckErr::UnexpectedTypeArgs(accesser.path(), expression.span));
+                }
+                let params = self.item_params.get(class).cloned().unwrap_or_default();
+                let type_args = self.instantiate_params(&params, &last[0].type_args, &accesser.path(), expression.span);
+                let args_of = param_args(&params, &type_args);
+                (class, Type::Custom { name: class.clone(), type_args }, args_of)
+            }
+            Some(Res::Variant(path)) => {
+                if !self.check_variant_style(path, VariantStyle::Struct, expression.span) {
+                    return Type::Never;
+                }
+                let (ty, args_of) = self.variant_type(path, accesser, true, expression.span);
+                (path, ty, args_of)
+            }
+            // a path that did not name a class or variant was reported by the resolver
+            _ => return Type::Never,
+        };
+        let fields = match (self.classes.get(class), self.variant(class)) {
+            (Some(fields), _) => fields,
+            (None, Some(variant)) => &variant.fields,
+            (None, None) => return Type::Never,
+        };
+        let mut errors = Vec::new();
+        let mut expected = Vec::new();
+        let mut given: HashMap<&Identifier, Span> = HashMap::new();
+        for (init, found) in inits.iter().zip(found) {
+            // the name alone, which a suggestion replaces
+            let name_span = Span::new(init.span.start, init.span.start + init.name.len());
+            if let Some(first) = given.insert(&init.name, name_span) {
+                errors.push(CheckErr::RepeatedField(init.name.clone(), first, name_span));
+                continue;
+            }
+            let Some(field) = fields.iter().find(|field| field.name == init.name) else {
+                // a typo is of a field the literal leaves out
+                let unused = fields.iter().filter(|field| inits.iter().all(|init| init.name != field.name));
+                let similar = similar_name(&init.name, unused.map(|field| field.name.as_str())).map(str::to_string);
+                errors.push(CheckErr::NoField(ty.clone(), init.name.clone(), similar, name_span));
+                continue;
+            };
+            if !field.is_pub && self.class.as_ref() != Some(class) {
+                errors.push(CheckErr::PrivateField(class.clone(), init.name.clone(), field.span, name_span));
+            }
+            expected.push((field.ty.substitute(&args_of), field.span, found, &init.value));
+        }
+        let missing: Vec<Identifier> = fields.iter().filter(|field| !given.contains_key(&field.name)).map(|field| field.name.clone()).collect();
+        if !missing.is_empty() {
+            errors.push(CheckErr::MissingFields(class.clone(), missing, expression.span));
+        }
+        self.errors.extend(errors);
+        for (expected, annotation, found, value) in expected {
+            self.expect_coerced(&expected, &found, Some(annotation), value);
+        }
+        ty
+    }
+
+    /// Both operands have to be of one type, and operands of unknown type take
+    /// the type of the other operand.
+    fn check_binary(&mut self, ope: BinaryOpe, left: Type, right: Type, span: Span) -> Type {
+        let valid = match (ope, &left, &right) {
+            (_, Type::Never, _) | (_, _, Type::Never) => true,
+            (BinaryOpe::Add | BinaryOpe::Sub | BinaryOpe::Mul | BinaryOpe::Div, left, right) => {
+                self.unify(left, right) && self.constrain(left, VarKind::Number)
+            }
+            (BinaryOpe::Equal | BinaryOpe::NotEqual, left, right) => self.unify(left, right) && match self.shallow(left) {
+                Type::Var(_, VarKind::Any) => {
+                    self.compared.push((ope, left.clone(), span));
+                    true
+                }
+                ty => self.equatable(&ty),
+            },
+            (BinaryOpe::Lesser | BinaryOpe::Greater | BinaryOpe::LesserEqual | BinaryOpe::GreaterEqual, left, right) => {
+                self.unify(left, right) && (self.shallow(left) == Type::Char || self.constrain(left, VarKind::Number))
+            }
+        };
+        if !valid {
+            self.errors.push(CheckErr::InvalidBinaryOperand(ope, self.apply(&left), self.apply(&right), span));
+        }
+        match ope {
+            BinaryOpe::Add | BinaryOpe::Sub | BinaryOpe::Mul | BinaryOpe::Div if !valid => Type::Never,
+            BinaryOpe::Add | BinaryOpe::Sub | BinaryOpe::Mul | BinaryOpe::Div => match left {
+                Type::Never => right,
+                left => self.shallow(&left),
+            },
+            _ => Type::Bool,
+        }
+    }
+
+    /// Whether `==` and `!=` compare values of `ty`: numbers, `bool`, `char`
+    /// and enums whose variants hold nothing, which are only their tag.
+    fn equatable(&self, ty: &Type) -> bool {
+        match ty {
+            Type::Numeric(_) | Type::Bool | Type::Char | Type::Never | Type::Var(..) => true,
+            Type::Custom { name, .. } => self.enums.get(name).is_some_and(|variants| variants.iter().all(|variant| variant.fields.is_empty())),
+            _ => false,
+        }
+    }
+
+    /// `as` converts numbers into each other, `bool` and `char` into integers,
+    /// and `u8` into `char`.
+    fn check_cast(&mut self, found: Type, target: Type, span: Span) -> Type {
+        // a value of unknown type can only be cast if it is a number
+        if let Type::Var(_, VarKind::Any) = self.shallow(&found) {
+            self.constrain(&found, VarKind::Number);
+        }
+        let valid = match (self.shallow(&found), &target) {
+            (Type::Never, _) | (_, Type::Never) => true,
+            (Type::Numeric(_) | Type::Var(..), Type::Numeric(_)) => true,
+            (Type::Bool | Type::Char, Type::Numeric(numeric)) => !numeric.is_float(),
+            (Type::Numeric(NumericType::U8) | Type::Char, Type::Char) => true,
+            (Type::Var(..), Type::Char) => self.unify(&found, &Type::Numeric(NumericType::U8)),
+            (found, target) => found == *target,
+        };
+        if !valid {
+            self.errors.push(CheckErr::InvalidCast(self.apply(&found), target.clone(), span));
+        }
+        target
+    }
+
+    /// The type of the field `name` of a value of type `ty`.
+    fn check_field(&mut self, ty: Type, name: &Identifier, span: Span) -> Type {
+        let ty = self.apply(&ty);
+        let fields = match &ty {
+            Type::Never => return Type::Never,
+            // a literal's type is not decided yet, but it is a number either way
+            Type::Var(_, VarKind::Any) => {
+                self.errors.push(CheckErr::UnknownType(span));
+                return Type::Never;
+            }
+            Type::Custom { name: class, .. } => self.classes.get(class).map_or(&[][..], Vec::as_slice),
+            _ => &[],
+        };
+        let Some(field) = fields.iter().find(|field| field.name == *name) else {
+            let similar = similar_name(name, fields.iter().map(|field| field.name.as_str())).map(str::to_string);
+            self.errors.push(CheckErr::NoField(ty, name.clone(), similar, span));
+            return Type::Never;
+        };
+        let Type::Custom { name: class, type_args } = &ty else { unreachable!() };
+        if !field.is_pub && self.class.as_ref() != Some(class) {
+            self.errors.push(CheckErr::PrivateField(class.clone(), name.clone(), field.span, span));
+        }
+        let params = self.item_params.get(class).map_or(&[][..], Vec::as_slice);
+        field.ty.substitute(&param_args(params, type_args))
+    }
+
+    /// Gives every type parameter of the function `path` a fresh type, and
+    /// records them as the types the call `id` gives them. The turbofish of
+    /// `accesser` fixes them: that of the class for its parameters, like
+    /// `Box::<int>::new`, and that of the function for its own.
+    fn instantiate(&mut self, id: NodeId, path: &str, accesser: Option<&Accesser>, span: Span) -> HashMap<Identifier, Type> {
+        let function = &self.functions[path];
+        let (class_params, own_params) = function.type_params.split_at(function.class_params);
+        let (class_params, own_params) = (class_params.to_vec(), own_params.to_vec());
+        let mut given: [&[TypeLiteral]; 2] = [&[], &[]];
+        if let Some(accesser) = accesser {
+            let segments = &accesser.segments;
+            for (index, segment) in segments.iter().enumerate().filter(|(_, segment)| !segment.type_args.is_empty()) {
+                match segments.len() - index {
+                    1 if !own_params.is_empty() => given[1] = &segment.type_args,
+                    2 if !class_params.is_empty() => given[0] = &segment.type_args,
+                    _ => self.errors.push(CheckErr::UnexpectedTypeArgs(accesser.path(), span)),
+                }
+            }
+        }
+        let class = path.rsplit_once("::").map_or("", |(class, _)| class);
+        let mut type_args = self.instantiate_params(&class_params, given[0], class, span);
+        type_args.extend(self.instantiate_params(&own_params, given[1], path, span));
+        if type_args.is_empty() {
+            return HashMap::new();
+        }
+        let function = &self.functions[path];
+        let args_of = param_args(&function.type_params, &type_args);
+        self.types.insert_instance(id, type_args);
+        args_of
+    }
+
+    /// Fresh types for the type parameters `params` of `path`, fixed to
+    /// those `given` in a turbofish. Their bounds are checked at the end of
+    /// the body.
+    fn instantiate_params(&mut self, params: &[Type], given: &[TypeLiteral], path: &str, span: Span) -> Vec<Type> {
+        let type_args: Vec<Type> = params.iter().map(|_| self.fresh_var()).collect();
+        for (param, ty) in params.iter().zip(&type_args) {
+            self.instances.push((ty.clone(), param.clone(), span));
+        }
+        if given.is_empty() {
+            return type_args;
+        }
+        if given.len() != params.len() {
+            self.errors.push(CheckErr::WrongTypeArgCount(path.to_string(), params.len(), given.len(), span));
+            return type_args;
+        }
+        for (ty, type_literal) in type_args.iter().zip(given) {
+            let found = self.type_of(type_literal);
+            self.unify(ty, &found);
+        }
+        type_args
+    }
+
+    /// Only generic functions and classes take type arguments, so a
+    /// turbofish is an error on anything else.
+    fn check_no_type_args(&mut self, accesser: &Accesser, span: Span) {
+        if accesser.has_type_args() {
+            self.errors.push(CheckErr::UnexpectedTypeArgs(accesser.path(), span));
+        }
+    }
+
+    /// `value?` is the value inside the `Some` or `Ok` that `value` is. The
+    /// `None` or `Err` is returned as it is, so the function has to return an
+    /// `Option`, or a `Result` with the same error type.
+    fn check_try(&mut self, value: &Expression, span: Span) -> Type {
+        let found = self.check_expression(value);
+        let (name, type_args) = match self.apply(&found) {
+            Type::Never => return Type::Never,
+            Type::Custom { name, type_args } if name == prelude::OPTION || name == prelude::RESULT => (name, type_args),
+            Type::Var(_, VarKind::Any) => {
+                self.errors.push(CheckErr::UnknownType(value.span));
+                return Type::Never;
+            }
+            found => {
+                self.errors.push(CheckErr::InvalidTry(found, value.span));
+                return Type::Never;
+            }
+        };
+        match self.apply(&self.return_type.clone()) {
+            Type::Custom { name: returned, type_args: returned_args } if returned == name => {
+                if let ([_, expected], [_, error]) = (&returned_args[..], &type_args[..]) {
+                    if !self.unify(expected, error) {
+                        self.errors.push(CheckErr::TypeMismatch(self.apply(expected), self.apply(error), None, value.span));
+                    }
+                }
+            }
+            return_type => self.errors.push(CheckErr::TryInWrongFunction(name, return_type, span)),
+        }
+        type_args.into_iter().next().unwrap_or(Type::Never)
+    }
+
+    /// The arms have to agree on their type like the branches of an `if`, and
+    /// together match every value of the type of the scrutinee.
+    fn check_match(&mut self, match_expression: &MatchExpression) -> Type {
+        let ty = self.check_expression(&match_expression.scrutinee);
+        let mut first: Option<(Type, Span)> = None;
+        for arm in &match_expression.arms {
+            self.bind_pattern(&arm.pattern, ty.clone(), false);
+            let found = self.check_expression(&arm.value);
+            let span = match &arm.value.kind {
+                ExpressionKind::Block(block) => tail_span(block),
+                _ => arm.value.span,
+            };
+            match (self.shallow(&found), &first) {
+                (Type::Never, _) => {}
+                (found, None) => first = Some((found, span)),
+                (found, Some((expected, first_span))) => {
+                    if !self.unify(expected, &found) {
+                        self.errors.push(CheckErr::TypeMismatch(self.apply(expected), self.apply(&found), Some(*first_span), span));
+                    }
+                }
+            }
+        }
+        self.check_exhaustive(&ty, &match_expression.arms, match_expression.scrutinee.span);
+        first.map_or(Type::Never, |(ty, _)| ty)
+    }
+
+    /// Reports the values of the scrutinee that no arm matches, as patterns
+    /// matching them.
+    fn check_exhaustive(&mut self, ty: &Type, arms: &[MatchArm], span: Span) {
+        let ty = self.apply(ty);
+        let rows: Vec<Vec<Option<&Pattern>>> = arms.iter().map(|arm| vec![Some(&arm.pattern)]).collect();
+        let missing: Vec<String> = self.witnesses(&rows, std::slice::from_ref(&ty)).into_iter().map(|mut witness| witness.remove(0)).collect();
+        if !missing.is_empty() {
+            self.errors.push(CheckErr::NonExhaustive(ty, missing, span));
+        }
+    }
+
+    /// Values of the columns `types` that no row of patterns matches, written
+    /// as patterns, at most `MAX_WITNESSES` of them. `None` in a row matches
+    /// anything. Enums, `bool` and tuples are split into the values of each of
+    /// their variants; other types have too many values, so only a row that
+    /// matches anything covers them.
+    fn witnesses(&self, rows: &[Vec<Option<&Pattern>>], types: &[Type]) -> Vec<Vec<String>> {
+        let Some((ty, rest)) = types.split_first() else {
+            return if rows.is_empty() { vec![Vec::new()] } else { Vec::new() };
+        };
+        let ty = self.apply(ty);
+        let constructors = match &ty {
+            // the value already failed to check
+            Type::Never => return Vec::new(),
+            Type::Custom { name, type_args } if self.enums.contains_key(name) => {
+                let params = self.item_params.get(name).map_or(&[][..], Vec::as_slice);
+                let args_of = param_args(params, type_args);
+                let constructors = self.enums[name].iter().map(|variant| {
+                    let fields = variant.fields.iter().map(|field| (field.name.clone(), field.ty.substitute(&args_of))).collect();
+                    let path = format!("{}::{}", name, variant.name);
+                    let written = if prelude::contains(variant.span) { variant.name.clone() } else { path.clone() };
+                    (Constructor::Variant { path, style: variant.style, written }, fields)
+                });
+                constructors.collect()
+            }
+            Type::Bool => vec![(Constructor::Bool(true), Vec::new()), (Constructor::Bool(false), Vec::new())],
+            Type::Tuple(inner) => vec![(Constructor::Tuple, inner.iter().enumerate().map(|(index, ty)| (index.to_string(), ty.clone())).collect())],
+            _ => {
+                let rows: Vec<Vec<Option<&Pattern>>> = rows.iter().filter(|row| self.matches_anything(row[0])).map(|row| row[1..].to_vec()).collect();
+                let mut witnesses = self.witnesses(&rows, rest);
+                for witness in &mut witnesses {
+                    witness.insert(0, "_".to_string());
+                }
+                return witnesses;
+            }
+        };
+        let mut witnesses = Vec::new();
+        for (constructor, fields) in constructors {
+            let rows: Vec<Vec<Option<&Pattern>>> = rows
+                .iter()
+                .filter_map(|row| {
+                    let mut inner = self.specialize(row[0], &constructor, &fields)?;
+                    inner.extend_from_slice(&row[1..]);
+                    Some(inner)
+                })
+                .collect();
+            let types: Vec<Type> = fields.iter().map(|(_, ty)| ty.clone()).chain(rest.iter().cloned()).collect();
+            for mut witness in self.witnesses(&rows, &types) {
+                let inner: Vec<String> = witness.drain(..fields.len()).collect();
+                witness.insert(0, constructor.write(&fields, inner));
+                witnesses.push(witness);
+            }
+            if witnesses.len() >= MAX_WITNESSES {
+                witnesses.truncate(MAX_WITNESSES);
+                break;
+            }
+        }
+        witnesses
+    }
+
+    /// Whether `pattern` matches any value in a column of `witnesses`. A
+    /// variant that did not resolve was reported already and is taken to.
+    fn matches_anything(&self, pattern: Option<&Pattern>) -> bool {
+        match pattern.map(|pattern| (&pattern.kind, self.resolution.get(pattern.id))) {
+            None | Some((PatternKind::Wildcard, _)) => true,
+            // an identifier only resolves when it names a variant
+            Some((PatternKind::Identifier(_) | PatternKind::Variant { .. }, resolved)) => !matches!(resolved, Some(Res::Variant(_))),
+            Some(_) => false,
+        }
+    }
+
+    /// The patterns of the fields of `pattern` if it matches values built by
+    /// `constructor`, which has `fields`.
+    fn specialize<'p>(&self, pattern: Option<&'p Pattern>, constructor: &Constructor, fields: &[(Identifier, Type)]) -> Option<Vec<Option<&'p Pattern>>> {
+        let anything = vec![None; fields.len()];
+        if self.matches_anything(pattern) {
+            return Some(anything);
+        }
+        let pattern = pattern?;
+        match (&pattern.kind, constructor) {
+            (PatternKind::Tuple(inner), Constructor::Tuple) if inner.len() == fields.len() => Some(inner.iter().map(Some).collect()),
+            (PatternKind::Literal(Literal::Bool(literal)), Constructor::Bool(value)) => (literal == value).then_some(Vec::new()),
+            (PatternKind::Identifier(_), Constructor::Variant { path, .. }) => (self.resolution.get(pattern.id) == Some(&Res::Variant(path.clone()))).then_some(anything),
+            (PatternKind::Variant { fields: pattern_fields, .. }, Constructor::Variant { path, style, .. }) => {
+                if self.resolution.get(pattern.id) != Some(&Res::Variant(path.clone())) {
+                    return None;
+                }
+                // a variant used otherwise than declared was reported already
+                let inner = match pattern_fields {
+                    PatternFields::Tuple(inner) if *style == VariantStyle::Tuple && inner.len() == fields.len() => inner.iter().map(Some).collect(),
+                    PatternFields::Struct(given) if *style == VariantStyle::Struct => fields
+                        .iter()
+                        .map(|(name, _)| given.iter().find(|field| field.name == *name).map(|field| &field.pattern))
+                        .collect(),
+                    _ => anything,
+                };
+                Some(inner)
+            }
+            // a literal of a type with too many values to split
+            _ => None,
+        }
+    }
+
+    fn check_if(&mut self, if_expression: &IfExpression) -> Type {
+        let condition = self.check_expression(&if_expression.condition);
+        self.expect_type(&Type::Bool, &condition, if_expression.condition.span);
+        let then_type = self.check_block(&if_expression.then_branch);
+        let Some(else_branch) = &if_expression.else_branch else {
+            if !self.unify(&Type::unit(), &then_type) {
+                self.errors.push(CheckErr::MissingElse(self.apply(&then_type), tail_span(&if_expression.then_branch)));
+            }
+            return Type::unit();
+        };
+        let else_type = self.check_expression(else_branch);
+        match (self.shallow(&then_type), self.shallow(&else_type)) {
+            (Type::Never, ty) | (ty, Type::Never) => ty,
+            (then_type, else_type) if self.unify(&then_type, &else_type) => then_type,
+            (then_type, else_type) => {
+                let (then_type, else_type) = (self.apply(&then_type), self.apply(&else_type));
+                let else_span = match &else_branch.kind {
+                    ExpressionKind::Block(block) => tail_span(block),
+                    _ => else_branch.span,
+                };
+                self.errors.push(CheckErr::BranchMismatch(then_type, else_type, tail_span(&if_expression.then_branch), else_span));
+                Type::Never
+            }
+        }
+    }
+}
+
+/// Where the value of a block comes from: its trailing expression, or the
+/// whole block when it has none.
+fn tail_span(block: &Block) -> Span {
+    block.value.as_ref().map_or(block.span, |value| value.span)
+}
+
+/// Whether `expression` can initialize a static: a number or `bool` literal,
+/// or the negation of one.
+fn is_constant(expression: &Expression) -> bool {
+    match &expression.kind {
+        ExpressionKind::Literal(Literal::Int(_) | Literal::Float(_) | Literal::Bool(_)) => true,
+        ExpressionKind::Unary { ope: UnaryOpe::Minus, operand } => is_constant(operand),
+        _ => false,
+    }
+}
+
+/// The `impl Trait for Type` of an impl, without its functions.
+fn impl_header(declaration: &ImplDeclaration) -> Span {
+    Span::new(declaration.span.start, declaration.self_type.span.end)
+}
+
+/// Whether `Self` is part of `ty`.
+fn mentions_self(ty: &Type) -> bool {
+    match ty {
+        Type::Param { name, .. } => name == "Self",
+        Type::Tuple(inner) | Type::Custom { type_args: inner, .. } => inner.iter().any(mentions_self),
+        _ => false,
+    }
+}
+
+/// The type arguments `type_args` by the name of the type parameter of
+/// `params` they are given for.
+fn param_args(params: &[Type], type_args: &[Type]) -> HashMap<Identifier, Type> {
+    params
+        .iter()
+        .zip(type_args)
+        .filter_map(|(param, ty)| match param {
+            Type::Param { name, .. } => Some((name.clone(), ty.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Every custom and `dyn Trait` type literal of a program, also those
+/// inside other type literals.
+struct TypeLiterals(Vec<TypeLiteral>);
+
+impl Visitor for TypeLiterals {
+    fn visit_type_literal(&mut self, type_literal: &TypeLiteral) {
+        if let TypeLiteralKind::CustomType { .. } | TypeLiteralKind::DynType { .. } = type_literal.kind {
+            self.0.push(type_literal.clone());
+        }
+        visit::walk_type_literal(self, type_literal)
+    }
+}
+
+/// The classes a value of type `ty` holds in place. A `dyn Trait` is held
+/// behind a pointer, and a generic class is taken to hold its type
+/// arguments in place.
+fn contained_classes<'t>(ty: &'t Type, classes: &mut Vec<&'t String>) {
+    match ty {
+        Type::Custom { name, type_args } => {
+            classes.push(name);
+            for ty in type_args {
+                contained_classes(ty, classes);
+            }
+        }
+        Type::Tuple(inner) => {
+            for ty in inner {
+                contained_classes(ty, classes);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn occurs(var: usize, ty: &Type) -> bool {
+    match ty {
+        Type::Var(other, _) => *other == var,
+        Type::Tuple(inner) | Type::Custom { type_args: inner, .. } => inner.iter().any(|ty| occurs(var, ty)),
+        _ => false,
+    }
+}
@@ -0,0 +1,335 @@
+use std::fmt;
+
+use crate::checker::Type;
+use crate::numeric::NumericType;
+use crate::diagnostic::{Applicability, Diagnostic};
+use crate::parser::{BinaryOpe, Identifier, UnaryOpe};
+use crate::resolver::error::ResolveErr;
+use crate::span::Span;
+
+/// The last `Span` of every variant is where the error is reported.
+pub enum CheckErr {
+    /// a name that did not resolve, reported along with the type errors
+    Resolve(ResolveErr),
+    /// expected, found, and the annotation that expects it if there is one
+    TypeMismatch(Type, Type, Option<Span>, Span),
+    /// type of the `if` branch, type of the `else` branch, and the value of each
+    BranchMismatch(Type, Type, Span, Span),
+    /// `if` without `else` whose branch has a value of this type
+    MissingElse(Type, Span),
+    InvalidBinaryOperand(BinaryOpe, Type, Type, Span),
+    InvalidUnaryOperand(UnaryOpe, Type, Span),
+    NotCallable(String, Span),
+    /// a call of something that is not a function, and its type
+    NotAFunction(String, Type, Span),
+    AssignToConst(Identifier, Span),
+    AnnotationNeeded(Identifier, Span),
+    /// a value whose type has to be known where it is used, but is not yet
+    UnknownType(Span),
+    /// the type of an integer literal and its value, with its sign
+    LiteralOutOfRange(NumericType, i128, Span),
+    /// the type of the value and the type it is cast to
+    InvalidCast(Type, Type, Span),
+    NotIterable(Type, Span),
+    /// the type, the field, and a field of the class it may be a typo of
+    NoField(Type, Identifier, Option<Identifier>, Span),
+    /// the class, the field, its declaration and the access
+    PrivateField(String, Identifier, Span, Span),
+    /// the type, the method, a method of the class it may be a typo of, and
+    /// the associated function of that name if the class has one
+    NoMethod(Type, Identifier, Option<Identifier>, Option<String>, Span),
+    /// the class and the fields a struct literal leaves out
+    MissingFields(String, Vec<Identifier>, Span),
+    /// the field, and where a struct literal first and again gives it a value
+    RepeatedField(Identifier, Span, Span),
+    /// the class or enum, which of the two it is, and the field through
+    /// which it contains itself
+    RecursiveClass(String, &'static str, Identifier, Span),
+    /// the function of an impl, the trait, and a function of the trait it may be a typo of
+    NotInTrait(Identifier, String, Option<Identifier>, Span),
+    /// the trait, the type of the impl, and the functions without default it leaves out
+    MissingTraitFunctions(String, Type, Vec<Identifier>, Span),
+    /// the function, the trait, the expected and found number of parameters,
+    /// and the signature in the trait
+    WrongImplParamCount(Identifier, String, usize, usize, Span, Span),
+    /// the trait, the type, and the first and second impl of the trait for the type
+    ConflictingImpls(String, Type, Span, Span),
+    /// the type, the method, and the traits that all give it a method of that name
+    AmbiguousMethod(Type, Identifier, Vec<String>, Span),
+    /// the trait, and the type that does not implement it
+    NotImplemented(String, Type, Span),
+    /// the trait of a `dyn Trait`, its first function that can not be called
+    /// on one, whether that function takes `self`, and its signature
+    NotObjectSafe(String, Identifier, bool, Span, Span),
+    /// the function, the expected and found number of arguments, where the
+    /// function is declared unless it is an intrinsic, and the call
+    WrongArgCount(String, usize, usize, Option<Span>, Span),
+    UnexpectedTypeArgs(String, Span),
+    /// the generic function or class, and the expected and found number of type arguments
+    WrongTypeArgCount(String, usize, usize, Span),
+    /// a function of a trait or impl, and its type parameters
+    GenericTraitFunction(Identifier, Span),
+    /// the variant, how it is declared and how it is used, like `tuple` and
+    /// `unit`, and its declaration
+    InvalidVariantUse(String, &'static str, &'static str, Span, Span),
+    /// the type of the scrutinee, and the variants or values no arm matches
+    NonExhaustive(Type, Vec<String>, Span),
+    /// a pattern of a `let` or parameter that does not match every value
+    RefutablePattern(Span),
+    /// the type of a value `?` is applied to that is neither `Option` nor `Result`
+    InvalidTry(Type, Span),
+    /// `Option` or `Result` that `?` is applied to, and the return type of
+    /// the function, which is not the same enum
+    TryInWrongFunction(String, Type, Span),
+    /// a static and its initial value, which is not a literal
+    NonConstantStatic(Identifier, Span),
+}
+
+impl CheckErr {
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            Self::Resolve(error) => error.to_diagnostic(),
+            Self::TypeMismatch(expected, found, annotation, span) => {
+                let diagnostic = Diagnostic::error("mismatched types")
+                    .with_code("E0301")
+                    .with_label(*span, format!("expected {}, found {}", expected, found));
+                match annotation {
+                    Some(annotation) => diagnostic.with_secondary(*annotation, "expected because of this"),
+                    None => diagnostic,
+                }
+            }
+            Self::BranchMismatch(then_type, else_type, then_span, else_span) => Diagnostic::error("`if` and `else` have different types")
+                .with_code("E0302")
+                .with_secondary(*then_span, format!("this is {}", then_type))
+                .with_label(*else_span, format!("this is {}", else_type))
+                .with_help("make both branches evaluate to the same type"),
+            Self::MissingElse(then_type, span) => Diagnostic::error("`if` without `else` has a value")
+                .with_code("E0303")
+                .with_label(*span, format!("this is {}, but without `else` the `if` evaluates to ()", then_type))
+                .with_help("add an `else` branch, or end the value with `;`"),
+            Self::InvalidBinaryOperand(ope, left, right, span) => Diagnostic::error(format!("can not apply `{}` to {} and {}", ope.symbol(), left, right))
+                .with_code("E0304")
+                .with_label(*span, ""),
+            Self::InvalidUnaryOperand(ope, operand, span) => Diagnostic::error(format!("can not apply `{}` to {}", ope.symbol(), operand))
+                .with_code("E0305")
+                .with_label(*span, ""),
+            Self::NotCallable(name, span) => Diagnostic::error(format!("`{}` is used as a value", name))
+                .with_code("E0307")
+                .with_label(*span, "functions can only be called"),
+            Self::AssignToConst(name, span) => Diagnostic::error(format!("can not assign twice to const `{}`", name))
+                .with_code("E0308")
+                .with_label(*span, "can not assign to a const")
+                .with_help(format!("declare `{}` with `let` to make it mutable", name)),
+            Self::AnnotationNeeded(name, span) => Diagnostic::error(format!("type of `{}` can not be known", name))
+                .with_code("E0309")
+                .with_label(*span, "needs a type annotation or an initial value"),
+            Self::UnknownType(span) => Diagnostic::error("type annotations needed")
+                .with_code("E0321")
+                .with_label(*span, "the type must be known at this point")
+                .with_help("annotate the binding this value comes from with its type"),
+            Self::LiteralOutOfRange(numeric, value, span) => {
+                let (min, max) = numeric.range().unwrap();
+                Diagnostic::error(format!("literal out of range for `{}`", numeric))
+                    .with_code("E0322")
+                    .with_label(*span, format!("`{}` does not fit into `{}`", value, numeric))
+                    .with_note(format!("`{}` holds values from {} to {}", numeric, min, max))
+            }
+            Self::InvalidCast(found, target, span) => Diagnostic::error(format!("can not cast {} as {}", found, target))
+                .with_code("E0323")
+                .with_label(*span, "invalid cast")
+                .with_note("`as` converts numbers into each other, `bool` and `char` into integers, and `u8` into `char`"),
+            Self::NotIterable(ty, span) => Diagnostic::error(format!("type {} can not be iterated", ty))
+                .with_code("E0310")
+                .with_label(*span, ""),
+            Self::NoField(ty, name, similar, span) => {
+                let diagnostic = Diagnostic::error(format!("type {} has no field `{}`", ty, name))
+                    .with_code("E0311")
+                    .with_label(*span, "unknown field");
+                match similar {
+                    // the suggestion replaces only the name after the `.`
+                    Some(similar) => diagnostic.with_suggestion(
+                        format!("a field with a similar name exists: `{}`", similar),
+                        Span::new(span.end - name.len(), span.end),
+                        similar.clone(),
+                        Applicability::MaybeIncorrect,
+                    ),
+                    None => diagnostic,
+                }
+            }
+            Self::PrivateField(class, name, definition, span) => Diagnostic::error(format!("field `{}` of class `{}` is private", name, class))
+                .with_code("E0320")
+                .with_secondary(*definition, "declared here without `pub`")
+                .with_label(*span, "private field")
+                .with_suggestion("make it public", Span::new(definition.start, definition.start), "pub ", Applicability::MaybeIncorrect),
+            Self::NoMethod(ty, name, similar, associated, span) => {
+                let diagnostic = Diagnostic::error(format!("type {} has no method `{}`", ty, name))
+                    .with_code("E0324")
+                    .with_label(*span, "method not found");
+                let diagnostic = match associated {
+                    Some(path) => diagnostic
+                        .with_note(format!("`{}` is an associated function, it does not take `self`", path))
+                        .with_help(format!("call it as `{}(...)`", path)),
+                    None => diagnostic,
+                };
+                match similar {
+                    Some(similar) => diagnostic.with_suggestion(
+                        format!("a method with a similar name exists: `{}`", similar),
+                        Span::new(span.end - name.len(), span.end),
+                        similar.clone(),
+                        Applicability::MaybeIncorrect,
+                    ),
+                    None => diagnostic,
+                }
+            }
+            Self::MissingFields(class, fields, span) => {
+                let names: Vec<String> = fields.iter().map(|field| format!("`{}`", field)).collect();
+                let plural = if fields.len() == 1 { "field" } else { "fields" };
+                Diagnostic::error(format!("missing {} {} in struct literal of `{}`", plural, names.join(", "), class))
+                    .with_code("E0325")
+                    .with_label(*span, format!("missing {}", names.join(", ")))
+                    .with_help("every field of a class needs a value")
+            }
+            Self::RepeatedField(name, first, span) => Diagnostic::error(format!("field `{}` is given a value more than once", name))
+                .with_code("E0326")
+                .with_secondary(*first, "first given here")
+                .with_label(*span, "given again here"),
+            Self::RecursiveClass(class, kind, field, span) => Diagnostic::error(format!("{} `{}` contains itself", kind, class))
+                .with_code("E0327")
+                .with_label(*span, format!("`{}` holds a `{}` again", field, class))
+                .with_note(format!("a value of the {} would be infinitely large", kind)),
+            Self::NotInTrait(name, trait_path, similar, span) => {
+                let diagnostic = Diagnostic::error(format!("function `{}` is not a member of trait `{}`", name, trait_path))
+                    .with_code("E0328")
+                    .with_label(*span, format!("not a function of `{}`", trait_path));
+                match similar {
+                    Some(similar) => diagnostic.with_help(format!("a function with a similar name exists: `{}`", similar)),
+                    None => diagnostic.with_help("an impl can only give the functions its trait declares"),
+                }
+            }
+            Self::MissingTraitFunctions(trait_path, ty, names, span) => {
+                let names: Vec<String> = names.iter().map(|name| format!("`{}`", name)).collect();
+                Diagnostic::error(format!("not all functions of trait `{}` are implemented for {}", trait_path, ty))
+                    .with_code("E0329")
+                    .with_label(*span, format!("missing {}", names.join(", ")))
+                    .with_help("every function the trait declares without a body needs one in the impl")
+            }
+            Self::WrongImplParamCount(name, trait_path, expected, found, signature, span) => {
+                let plural = |count: usize| if count == 1 { "parameter" } else { "parameters" };
+                Diagnostic::error(format!("function `{}` has {} {} but the declaration in trait `{}` has {}", name, found, plural(*found), trait_path, expected))
+                    .with_code("E0330")
+                    .with_secondary(*signature, format!("trait requires {} {}", expected, plural(*expected)))
+                    .with_label(*span, format!("expected {} {}, found {}", expected, plural(*expected), found))
+            }
+            Self::ConflictingImpls(trait_path, ty, first, span) => Diagnostic::error(format!("conflicting impls of trait `{}` for {}", trait_path, ty))
+                .with_code("E0331")
+                .with_secondary(*first, "first impl here")
+                .with_label(*span, format!("conflicting impl for {}", ty)),
+            Self::AmbiguousMethod(ty, name, traits, span) => {
+                let mut diagnostic = Diagnostic::error(format!("multiple methods named `{}` found for {}", name, ty))
+                    .with_code("E0332")
+                    .with_label(*span, "multiple traits have this method");
+                for trait_path in traits {
+                    diagnostic = diagnostic.with_note(format!("trait `{}` has a method `{}`", trait_path, name));
+                }
+                diagnostic.with_help(format!("call it by its trait to pick one, like `{}::{}(...)`", traits[0], name))
+            }
+            Self::NotImplemented(trait_path, ty, span) => Diagnostic::error(format!("trait `{}` is not implemented for {}", trait_path, ty))
+                .with_code("E0333")
+                .with_label(*span, format!("{} does not implement `{}`", ty, trait_path))
+                .with_help(format!("implement it with `impl {} for {} {{ ... }}`", trait_path, ty)),
+            Self::NotObjectSafe(trait_path, name, has_self, signature, span) => {
+                let reason = if *has_self {
+                    format!("...because `{}` has `Self` in its signature besides `self`", name)
+                } else {
+                    format!("...because `{}` does not take `self`", name)
+                };
+                Diagnostic::error(format!("trait `{}` can not be used as `dyn {}`", trait_path, trait_path))
+                    .with_code("E0334")
+                    .with_label(*span, format!("`{}` can not be `dyn`", trait_path))
+                    .with_secondary(*signature, reason)
+                    .with_note("the type behind a `dyn` value is only known when the program runs")
+            }
+            Self::WrongArgCount(path, expected, found, signature, span) => {
+                let plural = |count: usize| if count == 1 { "argument" } else { "arguments" };
+                let diagnostic = Diagnostic::error(format!("`{}` takes {} {} but {} {} supplied", path, expected, plural(*expected), found, if *found == 1 { "was" } else { "were" }))
+                    .with_code("E0319")
+                    .with_label(*span, format!("expected {} {}", expected, plural(*expected)));
+                match signature {
+                    Some(signature) => diagnostic.with_secondary(*signature, "defined here"),
+                    None => diagnostic,
+                }
+            }
+            Self::NotAFunction(path, ty, span) => Diagnostic::error(format!("`{}` is not a function", path))
+                .with_code("E0317")
+                .with_label(*span, format!("this is {}, it can not be called", ty)),
+            Self::UnexpectedTypeArgs(path, span) => Diagnostic::error(format!("`{}` does not take type arguments", path))
+                .with_code("E0312")
+                .with_label(*span, "")
+                .with_help("remove the `::<...>`"),
+            Self::WrongTypeArgCount(path, expected, found, span) => {
+                let plural = |count: usize| if count == 1 { "type argument" } else { "type arguments" };
+                Diagnostic::error(format!("`{}` takes {} {} but {} {} supplied", path, expected, plural(*expected), found, if *found == 1 { "was" } else { "were" }))
+                    .with_code("E0335")
+                    .with_label(*span, format!("expected {} {}", expected, plural(*expected)))
+            }
+            Self::GenericTraitFunction(name, span) => Diagnostic::error(format!("function `{}` of a trait or impl can not be generic", name))
+                .with_code("E0336")
+                .with_label(*span, "type parameters are not allowed here")
+                .with_note("which impl a call runs is only decided by the type `Self` is"),
+            Self::InvalidVariantUse(path, declared, used, definition, span) => {
+                let usage = |style: &str| match style {
+                    "unit" => format!("`{}`", path),
+                    "tuple" => format!("`{}(...)`", path),
+                    _ => format!("`{} {{ ... }}`", path),
+                };
+                Diagnostic::error(format!("{} variant `{}` is used as a {} variant", declared, path, used))
+                    .with_code("E0337")
+                    .with_secondary(*definition, format!("declared as a {} variant here", declared))
+                    .with_label(*span, format!("expected {}", usage(declared)))
+            }
+            Self::NonExhaustive(ty, missing, span) => {
+                let names: Vec<String> = missing.iter().map(|name| format!("`{}`", name)).collect();
+                let plural = if missing.len() == 1 { "pattern" } else { "patterns" };
+                Diagnostic::error(format!("non-exhaustive patterns: {} not covered", names.join(", ")))
+                    .with_code("E0338")
+                    .with_label(*span, format!("{} {} not covered", plural, names.join(", ")))
+                    .with_note(format!("the matched value is of type {}", ty))
+                    .with_help("add an arm for each of them, or a `_` arm matching anything")
+            }
+            Self::RefutablePattern(span) => Diagnostic::error("refutable pattern in `let` or parameter")
+                .with_code("E0339")
+                .with_label(*span, "does not match every value of its type")
+                .with_help("use a `match` to handle the values the pattern does not match"),
+            Self::InvalidTry(ty, span) => Diagnostic::error("the `?` operator can only be applied to an `Option` or a `Result`")
+                .with_code("E0340")
+                .with_label(*span, format!("this is {}", ty)),
+            Self::TryInWrongFunction(name, return_type, span) => {
+                let article = if name.starts_with(['A', 'E', 'I', 'O', 'U']) { "an" } else { "a" };
+                Diagnostic::error(format!("the `?` operator on {} `{}` can only be used in a function that returns `{}`", article, name, name))
+                    .with_code("E0341")
+                    .with_label(*span, format!("the function returns {}", return_type))
+                    .with_help(format!("make the function return {} `{}`, or use a `match` on the value", article, name))
+            }
+            Self::NonConstantStatic(name, span) => Diagnostic::error(format!("initial value of static `{}` is not a constant", name))
+                .with_code("E0342")
+                .with_label(*span, "this is computed when the program runs")
+                .with_help("initialize the static with a number or `bool` literal"),
+        }
+    }
+}
+
+impl From<ResolveErr> for CheckErr {
+    fn from(error: ResolveErr) -> Self {
+        CheckErr::Resolve(error)
+    }
+}
+
+impl fmt::Debug for CheckErr {
+    fn fmt (&self, formatter: &mut fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        if let Self::Resolve(error) = self {
+            return write!(formatter, "{:?}", error);
+        }
+        writeln!(formatter, "failed to type check program because...")?;
+        write!(formatter, "{}", self.to_diagnostic())
+    }
+}
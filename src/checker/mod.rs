@@ -0,0 +1,276 @@
+//! Type checking of a parsed `Program`.
+//!
+//! Every expression gets a `Type`; `if` branches have to agree, blocks take the
+//! type of their trailing expression and function bodies have to match the
+//! declared return type. Calls are checked against the parameters of the
+//! function they call, and fields and methods against the class of the value
+//! they are used on. A struct literal gives every field of its class a value
+//! once.
+//!
+//! A variant of an enum is built and matched the way it is declared: a unit
+//! variant by its path, a tuple variant like a call and a struct variant like
+//! a struct literal. The arms of a `match` have to agree on their type and
+//! together match every value of the scrutinee, while the patterns of `let`
+//! and parameters have to match every value on their own.
+//!
+//! An impl has to give every function of its trait that has no default, with
+//! the signature the trait declares for it. A method of a trait can be called
+//! on any type implementing the trait, and inside the trait on `Self`, which
+//! is a `Type::Param` bounded by the trait. Which impl a call runs is decided
+//! here, by the type `Self` is at the call.
+//!
+//! A value of a type implementing a trait can be given where a `dyn Trait` is
+//! expected: as the value of a `let`, an assignment, a return, an argument or
+//! a field. The call of a method on a `dyn Trait` runs the impl of the type
+//! the value had, which is only known when the program runs, so a trait can
+//! only be `dyn` if every function of it takes `self` and has no other `Self`
+//! in its signature.
+//!
+//! A generic function or class is checked once, with its type parameters as
+//! `Type::Param`s that can only be used through the traits bounding them.
+//! Every call gives the parameters fresh type variables, inferred from the
+//! arguments or given by a turbofish like `max::<u8>(a, b)`, which have to
+//! implement the bounds once the body is checked.
+//!
+//! The `?` operator takes an `Option` or a `Result` of the `prelude` and has
+//! the type of its `Some` or `Ok`. The function it is used in has to return
+//! the same enum, and for a `Result` with the same error type.
+//!
+//! Names, also those in type annotations, are looked up in the `Resolution`
+//! of the `resolver` rather than by the checker itself.
+//!
+//! Locals without annotation are inferred by unification: `let x;` gets a
+//! type variable that the first use fixing its type binds, like `x = 1.5;` or
+//! passing `x` to a function. A binding whose type is still unknown at the end
+//! of its function needs an annotation. The `TypeTable` holds the inferred
+//! type of every expression and binding once checking is done.
+//!
+//! Number literals are inferred the same way, from a variable that only
+//! numbers of their kind fit: `let x: u8 = 1;` makes the `1` a `u8`. Literals
+//! whose type nothing fixes are `int` or `float`, and every integer literal
+//! has to fit its type.
+
+pub mod checker;
+pub mod error;
+mod test;
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::numeric::NumericType;
+use crate::parser::{Identifier, NodeId};
+use crate::resolver::Resolution;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    /// `int` and `float` are `i32` and `f32`
+    Numeric(NumericType),
+    Char,
+    Bool,
+    Str,
+    /// type of expressions that never produce a value, like a block ending in `return`.
+    /// It is also given to expressions that already failed to check, so one mistake
+    /// is reported only once.
+    Never,
+    /// `()` is the empty tuple
+    Tuple(Vec<Type>),
+    /// a class, enum or trait by its full path like `shapes::Point`
+    Custom {
+        name: String,
+        type_args: Vec<Type>,
+    },
+    /// a type parameter, which is any type implementing the traits it is
+    /// bounded by. `Self` in a trait is the parameter bounded by the trait.
+    Param {
+        name: Identifier,
+        bounds: Vec<String>,
+    },
+    /// `dyn Trait` by the full path of the trait, a value of any type
+    /// implementing it whose functions are looked up when it is called
+    Dyn(String),
+    /// a type still to be inferred from how the value is used, by its index in
+    /// the substitution of the checker, and which types it can become
+    Var(usize, VarKind),
+}
+
+/// Which types a type variable can still become.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VarKind {
+    Any,
+    /// the operand of arithmetic, any numeric type
+    Number,
+    /// an integer literal, any integer type
+    Integer,
+    /// a float literal, `f32` or `f64`
+    Float,
+}
+
+impl VarKind {
+    /// Whether a variable of this kind can be inferred to be `ty`.
+    pub fn fits(self, ty: &Type) -> bool {
+        match (self, ty) {
+            (VarKind::Any, _) => true,
+            (VarKind::Number, Type::Numeric(_)) => true,
+            (VarKind::Integer, Type::Numeric(numeric)) => !numeric.is_float(),
+            (VarKind::Float, Type::Numeric(numeric)) => numeric.is_float(),
+            _ => false,
+        }
+    }
+
+    /// The kind of a variable that both a variable of `self` and one of
+    /// `other` can be, if there is one.
+    pub fn meet(self, other: VarKind) -> Option<VarKind> {
+        match (self, other) {
+            (VarKind::Any, kind) | (kind, VarKind::Any) => Some(kind),
+            (VarKind::Number, kind) | (kind, VarKind::Number) => Some(kind),
+            (kind, other) if kind == other => Some(kind),
+            _ => None,
+        }
+    }
+}
+
+impl Type {
+    pub fn unit() -> Self {
+        Type::Tuple(Vec::new())
+    }
+
+    pub fn is_unit(&self) -> bool {
+        matches!(self, Type::Tuple(inner) if inner.is_empty())
+    }
+
+    /// `self` with the type parameters named in `args` replaced by their types.
+    pub fn substitute(&self, args: &HashMap<Identifier, Type>) -> Type {
+        match self {
+            Type::Param { name, .. } => args.get(name).cloned().unwrap_or_else(|| self.clone()),
+            Type::Tuple(inner) => Type::Tuple(inner.iter().map(|ty| ty.substitute(args)).collect()),
+            Type::Custom { name, type_args } => Type::Custom { name: name.clone(), type_args: type_args.iter().map(|ty| ty.substitute(args)).collect() },
+            ty => ty.clone(),
+        }
+    }
+
+    /// Whether a type variable is left in the type, so it is not fully known.
+    pub fn has_vars(&self) -> bool {
+        match self {
+            Type::Var(..) => true,
+            Type::Tuple(inner) | Type::Custom { type_args: inner, .. } => inner.iter().any(Type::has_vars),
+            _ => false,
+        }
+    }
+}
+
+/// A call of a function that a trait declares. It runs the function of the
+/// impl of the trait for `self_type`, or the default of the trait when the
+/// impl leaves the function out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraitCall {
+    pub trait_path: String,
+    pub name: Identifier,
+    pub self_type: Type,
+}
+
+/// The type of every expression, of every pattern binding a local and of the
+/// type every impl is for, by the `NodeId` of the node. Calls of trait
+/// functions also get the `TraitCall` they make, calls of generic functions
+/// the types of their type parameters, and expressions whose value is turned
+/// into a `dyn Trait` the `Type::Dyn` it is turned into.
+#[derive(Debug, Default)]
+pub struct TypeTable {
+    types: HashMap<NodeId, Type>,
+    trait_calls: HashMap<NodeId, TraitCall>,
+    coercions: HashMap<NodeId, Type>,
+    instances: HashMap<NodeId, Vec<Type>>,
+}
+
+impl TypeTable {
+    pub fn get(&self, id: NodeId) -> Option<&Type> {
+        self.types.get(&id)
+    }
+
+    pub fn insert(&mut self, id: NodeId, ty: Type) {
+        self.types.insert(id, ty);
+    }
+
+    pub fn trait_call(&self, id: NodeId) -> Option<&TraitCall> {
+        self.trait_calls.get(&id)
+    }
+
+    pub fn insert_trait_call(&mut self, id: NodeId, call: TraitCall) {
+        self.trait_calls.insert(id, call);
+    }
+
+    pub fn coercion(&self, id: NodeId) -> Option<&Type> {
+        self.coercions.get(&id)
+    }
+
+    pub fn insert_coercion(&mut self, id: NodeId, ty: Type) {
+        self.coercions.insert(id, ty);
+    }
+
+    /// The types a call of a generic function gives its type parameters,
+    /// first those of the class of a method.
+    pub fn instance(&self, id: NodeId) -> Option<&Vec<Type>> {
+        self.instances.get(&id)
+    }
+
+    pub fn insert_instance(&mut self, id: NodeId, type_args: Vec<Type>) {
+        self.instances.insert(id, type_args);
+    }
+
+    fn values_mut(&mut self) -> impl Iterator<Item = &mut Type> {
+        let trait_calls = self.trait_calls.values_mut().map(|call| &mut call.self_type);
+        self.types.values_mut().chain(trait_calls).chain(self.instances.values_mut().flatten())
+    }
+}
+
+/// What the front end found out about a program that passed it, which the
+/// backend lowers the program with.
+#[derive(Debug)]
+pub struct Checked {
+    pub resolution: Resolution,
+    pub types: TypeTable,
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Numeric(numeric) => write!(formatter, "{}", numeric),
+            Type::Char => write!(formatter, "char"),
+            Type::Bool => write!(formatter, "bool"),
+            Type::Str => write!(formatter, "string"),
+            Type::Never => write!(formatter, "!"),
+            Type::Tuple(inner) => {
+                write!(formatter, "(")?;
+                for (index, ty) in inner.iter().enumerate() {
+                    if index != 0 {
+                        write!(formatter, ", ")?;
+                    }
+                    write!(formatter, "{}", ty)?;
+                }
+                if inner.len() == 1 {
+                    write!(formatter, ",")?;
+                }
+                write!(formatter, ")")
+            }
+            Type::Custom { name, type_args } => {
+                write!(formatter, "{}", name)?;
+                if !type_args.is_empty() {
+                    write!(formatter, "<")?;
+                    for (index, ty) in type_args.iter().enumerate() {
+                        if index != 0 {
+                            write!(formatter, ", ")?;
+                        }
+                        write!(formatter, "{}", ty)?;
+                    }
+                    write!(formatter, ">")?;
+                }
+                Ok(())
+            }
+            Type::Param { name, .. } => write!(formatter, "{}", name),
+            Type::Dyn(trait_path) => write!(formatter, "dyn {}", trait_path),
+            Type::Var(_, VarKind::Any) => write!(formatter, "_"),
+            Type::Var(_, VarKind::Number) => write!(formatter, "{{number}}"),
+            Type::Var(_, VarKind::Integer) => write!(formatter, "{{integer}}"),
+            Type::Var(_, VarKind::Float) => write!(formatter, "{{float}}"),
+        }
+    }
+}
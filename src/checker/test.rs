@@ -0,0 +1,436 @@
+#[cfg(test)]
+mod tests {
+    use crate::checker::checker::Checker;
+    use crate::checker::error::CheckErr;
+    use crate::checker::{TraitCall, Type, VarKind};
+    use crate::numeric::NumericType;
+    use crate::parser::parser::Parser;
+    use crate::parser::*;
+    use crate::resolver::resolver::Resolver;
+    use crate::tokenizer::tokenizer::Tokenizer;
+
+    fn parse(program: &str) -> Program {
+        let mut tokenizer = Tokenizer::new(program);
+        let tokens = tokenizer.tokenize().unwrap().clone();
+        Parser::new(tokens, tokenizer.spans().clone()).parse().unwrap()
+    }
+
+    fn check(program: &str) -> Result<(), Vec<CheckErr>> {
+        let program = parse(program);
+        let (resolution, errors) = Resolver::new().resolve(&program);
+        assert!(errors.is_empty(), "{:?}", errors);
+        Checker::new(&resolution).check(&program)
+    }
+
+    fn check_with_prelude(program: &str) -> Result<(), Vec<CheckErr>> {
+        let mut program = parse(program);
+        crate::prelude::add(&mut program);
+        let (resolution, errors) = Resolver::new().resolve(&program);
+        assert!(errors.is_empty(), "{:?}", errors);
+        Checker::new(&resolution).check(&program)
+    }
+
+    #[test]
+    fn test_check_if_else_expression() {
+        check("fn pick(c: bool): int { let x: int = if c { 1 } else { 2 }; x }").unwrap();
+        check("fn pick(c: bool): float { if c { 1.5 } else if c == false { 2.0 } else { 0.0 } }").unwrap();
+    }
+
+    #[test]
+    fn test_check_block_expression() {
+        check("fn f(): int { let y = { let a = 1; a * 2 }; y + 1 }").unwrap();
+        check("fn f(): int { { 1 } }").unwrap();
+    }
+
+    #[test]
+    fn test_check_branch_mismatch() {
+        let errors = check("fn pick(c: bool): int { if c { 1 } else { 2.0 } }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::BranchMismatch(Type::Var(_, VarKind::Integer), Type::Var(_, VarKind::Float), ..)]));
+    }
+
+    #[test]
+    fn test_check_missing_else() {
+        let errors = check("fn f(c: bool): int { let x = if c { 1 }; 0 }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::MissingElse(Type::Var(_, VarKind::Integer), _)]));
+    }
+
+    #[test]
+    fn test_check_returning_branch() {
+        check("fn f(c: bool): int { let x = if c { return 0; } else { 2 }; x }").unwrap();
+    }
+
+    #[test]
+    fn test_check_condition_is_bool() {
+        let errors = check("fn f(): int { if 1 { 1 } else { 2 } }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::TypeMismatch(Type::Bool, Type::Var(_, VarKind::Integer), None, _)]));
+    }
+
+    #[test]
+    fn test_check_comparison() {
+        check("fn f(a: int, b: float): bool { a < 2 == (b >= 1.0) }").unwrap();
+        let errors = check("fn f(a: int): bool { a < 1.0 }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::InvalidBinaryOperand(_, Type::Numeric(NumericType::I32), Type::Var(_, VarKind::Float), _)]));
+    }
+
+    #[test]
+    fn test_check_equality() {
+        check("enum Color { Red, Green } fn f(a: char, b: bool, c: Color, d: u8): bool { a == a == (b != false) == (c == Color::Red) == (d != 1) }").unwrap();
+        // only numbers, `bool`, `char` and enums of tags alone are compared
+        let errors = check("class P { x: int; } enum E { A, B(int) } trait T { fn f(self): int; } \
+                            fn f(p: P, q: P, t: dyn T, u: dyn T): bool { p == q; \"a\" == \"b\"; (1, 2) == (1, 2); E::A == E::B(1); t != u }")
+        .unwrap_err();
+        let [
+            CheckErr::InvalidBinaryOperand(BinaryOpe::Equal, Type::Custom { .. }, _, _),
+            CheckErr::InvalidBinaryOperand(BinaryOpe::Equal, Type::Str, Type::Str, _),
+            CheckErr::InvalidBinaryOperand(BinaryOpe::Equal, Type::Tuple(_), _, _),
+            CheckErr::InvalidBinaryOperand(BinaryOpe::Equal, Type::Custom { .. }, _, _),
+            CheckErr::InvalidBinaryOperand(BinaryOpe::NotEqual, Type::Dyn(_), Type::Dyn(_), _),
+        ] = &errors[..] else {
+            panic!("{:?}", errors)
+        };
+        let errors = check_with_prelude("fn f(): bool { Some(1) != None }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::InvalidBinaryOperand(BinaryOpe::NotEqual, ..)]), "{:?}", errors);
+        // operands of a type inferred later are checked once it is known
+        let errors = check("fn f(): bool { let x; let y; let same = x == y; x = \"a\"; y = \"b\"; same }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::InvalidBinaryOperand(BinaryOpe::Equal, Type::Str, Type::Str, _)]), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_check_turbofish_on_non_generic() {
+        let errors = check("fn id(a: int): int { a } fn f(): int { id::<int>(1) }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::UnexpectedTypeArgs(..)]));
+    }
+
+    #[test]
+    fn test_check_call_arguments() {
+        check("fn add(a: int, b: float): float { b } fn f(): float { add(1, 2.0) }").unwrap();
+        let errors = check("fn add(a: int, b: float): float { b } fn f(): float { add(1) + add(1, 2.0, 3) }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::WrongArgCount(_, 2, 1, ..), CheckErr::WrongArgCount(_, 2, 3, ..)]));
+        let errors = check("fn add(a: int, b: float): float { b } fn f(): float { add(1.0, 2.0) }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::TypeMismatch(Type::Numeric(NumericType::I32), Type::Var(_, VarKind::Float), Some(_), _)]));
+        let errors = check("namespace math { pub fn one(): int { 1 } } fn f(): bool { math::one() }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::TypeMismatch(Type::Bool, Type::Numeric(NumericType::I32), Some(_), _)]));
+    }
+
+    #[test]
+    fn test_check_annotations() {
+        let errors = check("static LIMIT: int = 1.5; fn f(): int { let x: bool = 1; LIMIT }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::TypeMismatch(Type::Numeric(NumericType::I32), Type::Var(_, VarKind::Float), Some(_), _), CheckErr::TypeMismatch(Type::Bool, Type::Var(_, VarKind::Integer), Some(_), _)]));
+    }
+
+    #[test]
+    fn test_check_static_values() {
+        check("static A: int = -1; static B: f64 = 2.5; static C = true; fn f(): int { A }").unwrap();
+        // the values of statics are checked before the functions that read them
+        check("fn f(): bool { C } namespace n { fn g(): f32 { D } static D = 1.5; } static C = true;").unwrap();
+        let errors = check("fn one(): int { 1 } static A: int = one() + 2; static B = 1; static C: int = B; static D = \"d\";").unwrap_err();
+        let [CheckErr::NonConstantStatic(a, _), CheckErr::NonConstantStatic(c, _), CheckErr::NonConstantStatic(d, _)] = &errors[..] else {
+            panic!("{:?}", errors)
+        };
+        assert_eq!((a.as_str(), c.as_str(), d.as_str()), ("A", "C", "D"));
+    }
+
+    #[test]
+    fn test_check_class_fields() {
+        let program = "
+            namespace shapes {
+                pub class Point { pub x: int; y: float; fn y(p: Point): float { p.y } }
+                pub fn x(p: Point): int { p.x }
+            }
+        ";
+        check(program).unwrap();
+        let errors = check("class Point { pub x: int; y: int; } fn f(p: Point): int { p.y + p.width + p.xx }").unwrap_err();
+        let [CheckErr::PrivateField(class, name, ..), CheckErr::NoField(_, _, None, _), CheckErr::NoField(Type::Custom { .. }, _, Some(similar), _)] = errors.as_slice() else {
+            panic!("{:?}", errors)
+        };
+        assert_eq!((class.as_str(), name.as_str(), similar.as_str()), ("Point", "y", "x"));
+        let errors = check("fn f(p: (int, int)): int { p.x }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::NoField(Type::Tuple(_), _, None, _)]));
+    }
+
+    #[test]
+    fn test_infer_from_later_use() {
+        check("fn f(): float { let x; x = 1.5; x }").unwrap();
+        check("fn f(): int { let x; let y = x; y + 1 }").unwrap();
+        check("fn f(): int { let (a, b); a = 1; b = true; if b { a } else { 0 } }").unwrap();
+        check("fn take(flag: bool): int { 0 } fn f(c: bool): bool { let x; let y = if c { x } else { take(x) == 0 }; y }").unwrap();
+        check("fn f(): bool { let x; let y; let same = x == y; y < 2 == same }").unwrap();
+    }
+
+    #[test]
+    fn test_infer_errors_show_inferred_types() {
+        let errors = check("fn f(): int { let x; x = 1; x = 1.0; let b: bool = x; 0 }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::TypeMismatch(Type::Var(_, VarKind::Integer), Type::Var(_, VarKind::Float), None, _), CheckErr::TypeMismatch(Type::Bool, Type::Var(_, VarKind::Integer), Some(_), _)]));
+        let errors = check("fn f(): int { let x; x = (x, 1); 0 }").unwrap_err();
+        // a type can not contain itself
+        assert!(matches!(errors.as_slice(), [CheckErr::TypeMismatch(Type::Var(..), Type::Tuple(_), None, _), CheckErr::AnnotationNeeded(..)]));
+    }
+
+    #[test]
+    fn test_infer_needs_annotation() {
+        // locals sharing one unknown type are reported once
+        let errors = check("fn f(): int { let x; let y = x; let z = -y; 0 }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::AnnotationNeeded(name, _)] if name == "x"));
+        let errors = check("class Point { pub x: int; } fn f(q: Point): int { let p; let x = p.x; p = q; x }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::UnknownType(_)]));
+    }
+
+    #[test]
+    fn test_check_numeric_literals() {
+        check("fn f(a: u8, b: f64): i64 { let c = a + 255; let d = b * 0.5; let e: i8 = -128; 9223372036854775807 }").unwrap();
+        let errors = check("fn f(a: u8): u8 { let b: i8 = 128; a + 256 }").unwrap_err();
+        let [CheckErr::LiteralOutOfRange(NumericType::I8, 128, _), CheckErr::LiteralOutOfRange(NumericType::U8, 256, _)] = errors.as_slice() else {
+            panic!("{:?}", errors)
+        };
+        // literals nothing else decides are `i32` and `f32`
+        let errors = check("fn f(): int { let big = 3000000000; let neg: u32 = -1; 0 }").unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [CheckErr::LiteralOutOfRange(NumericType::I32, 3000000000, _), CheckErr::LiteralOutOfRange(NumericType::U32, -1, _)]
+        ));
+        let errors = check("fn f(a: u32): u32 { let b = a; -b }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::InvalidUnaryOperand(_, Type::Numeric(NumericType::U32), _)]));
+        let errors = check("fn f(a: i64, b: u64): i64 { a + b }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::InvalidBinaryOperand(_, Type::Numeric(NumericType::I64), Type::Numeric(NumericType::U64), _)]));
+        let errors = check("fn f(a: u16): bool { a == 1.5 }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::InvalidBinaryOperand(_, Type::Numeric(NumericType::U16), Type::Var(_, VarKind::Float), _)]));
+    }
+
+    #[test]
+    fn test_check_casts() {
+        check("fn f(a: u8, b: f64, c: bool, d: char): char { let x = a as i64 + b as i64 + c as i64 + d as i64; let y = 300 as u8; 97 as char }").unwrap();
+        let errors = check("fn f(a: f32, b: u16, c: int): bool { let x = a as char; let y = b as char; c as bool }").unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [
+                CheckErr::InvalidCast(Type::Numeric(NumericType::F32), Type::Char, _),
+                CheckErr::InvalidCast(Type::Numeric(NumericType::U16), Type::Char, _),
+                CheckErr::InvalidCast(Type::Numeric(NumericType::I32), Type::Bool, _),
+            ]
+        ));
+        let errors = check("fn f(t: (int, int)): f64 { let x; let y = x as f64; t as f64 + y }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::InvalidCast(Type::Tuple(_), _, _), CheckErr::AnnotationNeeded(..)]));
+    }
+
+    #[test]
+    fn test_check_intrinsics() {
+        check_with_prelude("fn f(a: u8, b: i64): i64 { let c: u8 = wrapping_add(a, 200); saturating_mul(b, 2) + wrapping_sub(1, b) }").unwrap();
+        // `checked_*` give `None` on overflow, so `?` passes it on
+        check_with_prelude("fn f(a: u8, b: i64): Option<i64> { let c: Option<u8> = checked_add(a, 1); Some(checked_mul(b, 2)? + 1) }").unwrap();
+        let errors = check_with_prelude("fn f(b: i64): i64 { checked_sub(1, b) }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::TypeMismatch(Type::Numeric(NumericType::I64), Type::Custom { .. }, Some(_), _)]), "{:?}", errors);
+        let errors = check("fn f(a: f32, b: u8, c: u16): u8 { let d = wrapping_mul(a, a); let e = checked_add(b, c); saturating_add(b) }").unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [
+                CheckErr::TypeMismatch(Type::Var(_, VarKind::Integer), Type::Numeric(NumericType::F32), None, _),
+                CheckErr::TypeMismatch(Type::Var(_, VarKind::Integer), Type::Numeric(NumericType::F32), None, _),
+                CheckErr::TypeMismatch(Type::Numeric(NumericType::U8), Type::Numeric(NumericType::U16), None, _),
+                CheckErr::WrongArgCount(_, 2, 1, None, _),
+            ]
+        ));
+        let errors = check("fn f(): int { let add = wrapping_add; 0 }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::NotCallable(..), ..]));
+    }
+
+    #[test]
+    fn test_inferred_types_are_recorded() {
+        let program = parse("fn f(): (int, float) { let t; let (a, b) = t; a = 2; b = 0.5; t }");
+        let (resolution, _) = Resolver::new().resolve(&program);
+        let mut checker = Checker::new(&resolution);
+        checker.check(&program).unwrap();
+        let Declaration::Function(function) = &program.declarations[0] else { unreachable!() };
+        let StatementKind::Let { pattern, .. } = &function.inner.statements[0].kind else { unreachable!() };
+        assert_eq!(checker.types().get(pattern.id), Some(&Type::Tuple(vec![Type::Numeric(NumericType::I32), Type::Numeric(NumericType::F32)])));
+        let value = function.inner.value.as_ref().unwrap();
+        assert_eq!(checker.types().get(value.id).unwrap().to_string(), "(i32, f32)");
+    }
+
+    #[test]
+    fn test_check_struct_literals() {
+        check("class P { pub x: int; y: (u8, bool); pub fn new(): P { P { y: (1, true), x: 2 } } } fn f(): int { let p = P::new(); p.x = 3; p.x }").unwrap();
+        let errors = check("class P { pub x: int; y: int; pub z: int; } fn f() { let p = P { x: 1, x: 2, w: 3, y: true }; }").unwrap_err();
+        let [CheckErr::RepeatedField(repeated, ..), CheckErr::NoField(_, _, Some(similar), _), CheckErr::PrivateField(..), CheckErr::MissingFields(_, missing, _), CheckErr::TypeMismatch(Type::Numeric(_), Type::Bool, Some(_), _)] = &errors[..] else {
+            panic!("{:?}", errors)
+        };
+        assert_eq!((repeated.as_str(), similar.as_str(), missing.as_slice()), ("x", "z", &["z".to_string()][..]));
+        let errors = check("class P { pub x: int; } fn f() { const p = P { x: 1 }; p.x = 2; }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::AssignToConst(name, _)] if name == "p"));
+        let errors = check("class A { b: B; } class B { a: (A, int); } class C { a: A; }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::RecursiveClass(a, ..), CheckErr::RecursiveClass(b, ..)] if a == "A" && b == "B"));
+    }
+
+    #[test]
+    fn test_check_methods() {
+        check("class P { pub x: int; pub fn new(x: int): Self { Self { x: x } } pub fn add(self, n: int): int { self.x + n } } fn f(): int { P::new(1).add(2) + P::add(P::new(3), 4) }").unwrap();
+        let errors = check("class P { pub fn new(): P { P { } } fn get(self): int { 1 } pub fn set(self, n: int) { } } fn f(p: P) { p.new(); p.gett(); p.get(); p.set(); (1).get(); }").unwrap_err();
+        let [CheckErr::NoMethod(_, _, None, Some(associated), _), CheckErr::NoMethod(_, _, Some(similar), None, _), CheckErr::Resolve(_), CheckErr::WrongArgCount(path, 1, 0, Some(_), _), CheckErr::NoMethod(Type::Numeric(NumericType::I32), ..)] = &errors[..] else {
+            panic!("{:?}", errors)
+        };
+        assert_eq!((associated.as_str(), similar.as_str(), path.as_str()), ("P::new", "get", "P::set"));
+    }
+
+    #[test]
+    fn test_check_traits() {
+        let source = "trait Shape { fn area(self): int; fn twice(self): int { self.area() + Self::area(self) } } class P { x: int; pub fn new(): P { P { x: 2 } } } \
+                      impl Shape for P { fn area(self): int { self.x } } impl Shape for u8 { fn area(self): int { self as int } } \
+                      fn f(): int { let p = P::new(); p.twice() + Shape::area(p) + (3 as u8).area() }";
+        let program = parse(source);
+        let (resolution, _) = Resolver::new().resolve(&program);
+        let mut checker = Checker::new(&resolution);
+        checker.check(&program).unwrap();
+        let Declaration::Function(function) = &program.declarations[4] else { unreachable!() };
+        let ExpressionKind::Binary { left, right, .. } = &function.inner.value.as_ref().unwrap().kind else { unreachable!() };
+        let ExpressionKind::Binary { left, right: path_call, .. } = &left.kind else { unreachable!() };
+        let self_types: Vec<String> = [left.id, path_call.id, right.id].iter().map(|id| checker.types().trait_call(*id).unwrap().self_type.to_string()).collect();
+        assert_eq!(self_types, ["P", "P", "u8"]);
+        // inside the trait the calls are for `Self`, whichever type that is
+        let Declaration::Trait(declaration) = &program.declarations[0] else { unreachable!() };
+        let Some(ExpressionKind::Binary { left, .. }) = declaration.functions[0].inner.value.as_ref().map(|value| &value.kind) else { unreachable!() };
+        assert!(matches!(checker.types().trait_call(left.id), Some(TraitCall { self_type: Type::Param { .. }, .. })));
+
+        let errors = check("trait T { fn f(self): int; fn g(self, n: int): int; } class P { } impl T for P { fn f(self): bool { true } fn g(self): int { 1 } fn h() { } } impl T for P { fn f(self): int { 1 } fn g(self, n: int): int { n } } impl T for int { }").unwrap_err();
+        let [CheckErr::ConflictingImpls(..), CheckErr::TypeMismatch(Type::Numeric(_), Type::Bool, Some(_), _), CheckErr::WrongImplParamCount(_, _, 2, 1, ..), CheckErr::NotInTrait(..), CheckErr::MissingTraitFunctions(_, Type::Numeric(_), missing, _)] = &errors[..] else {
+            panic!("{:?}", errors)
+        };
+        assert_eq!(missing, &["f".to_string(), "g".to_string()]);
+        let errors = check("trait A { fn f(self): int; } trait B { fn f(self): int; fn new(): Self; } class P { } impl A for P { fn f(self): int { 1 } } impl B for P { fn f(self): int { 2 } fn new(): Self { P { } } } \
+                            fn g(p: P): int { p.f() + A::f(1) + B::new().f() + A::f(p) }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::AmbiguousMethod(_, _, traits, _), CheckErr::NotImplemented(..), CheckErr::UnknownType(_)] if traits.len() == 2));
+    }
+
+    #[test]
+    fn test_check_dyn_traits() {
+        let source = "trait Shape { fn area(self): int; fn twice(self): int { self.area() * 2 } } class P { pub x: int; } class Q { pub s: dyn Shape; } \
+                      impl Shape for P { fn area(self): int { self.x } } impl Shape for int { fn area(self): int { self } } \
+                      fn f(s: dyn Shape): dyn Shape { let t: dyn Shape = P { x: 1 }; t = 2; let q = Q { s: s }; q.s.twice() + Shape::area(t); g(t) } fn g(s: dyn Shape): dyn Shape { return s; }";
+        let program = parse(source);
+        let (resolution, _) = Resolver::new().resolve(&program);
+        let mut checker = Checker::new(&resolution);
+        checker.check(&program).unwrap();
+        let Declaration::Function(function) = &program.declarations[5] else { unreachable!() };
+        let [let_t, assign, let_q, StatementKind::Expression(sum)] = &function.inner.statements.iter().map(|statement| &statement.kind).collect::<Vec<_>>()[..] else { unreachable!() };
+        let (StatementKind::Let { value: Some(p), .. }, StatementKind::Assign { value: two, .. }) = (let_t, assign) else { unreachable!() };
+        let shape = Type::Dyn("Shape".into());
+        assert_eq!((checker.types().coercion(p.id), checker.types().coercion(two.id)), (Some(&shape), Some(&shape)));
+        let StatementKind::Let { value: Some(q), .. } = let_q else { unreachable!() };
+        let ExpressionKind::Struct { fields, .. } = &q.kind else { unreachable!() };
+        // a `dyn Shape` given as a `dyn Shape` stays as it is
+        assert_eq!(checker.types().coercion(fields[0].value.id), None);
+        let ExpressionKind::Binary { left, right, .. } = &sum.kind else { unreachable!() };
+        assert_eq!(checker.types().trait_call(left.id).unwrap().self_type, shape);
+        assert_eq!(checker.types().trait_call(right.id).unwrap().self_type, shape);
+
+        let errors = check("trait T { fn f(self): int; fn g(self, other: Self); } trait U { fn new(): int; } class P { } \
+                            fn f(t: dyn T, u: (int, dyn U)) { let x: dyn T = P { }; }").unwrap_err();
+        let [CheckErr::NotObjectSafe(_, g, true, ..), CheckErr::NotObjectSafe(_, new, false, ..), CheckErr::NotObjectSafe(..), CheckErr::NotImplemented(..)] = &errors[..] else {
+            panic!("{:?}", errors)
+        };
+        assert_eq!((g.as_str(), new.as_str()), ("g", "new"));
+    }
+
+    #[test]
+    fn test_check_generics() {
+        let source = "trait Ord { fn gt(self, other: Self): bool; } impl Ord for int { fn gt(self, other: Self): bool { self > other } } \
+                      fn max<T: Ord>(a: T, b: T): T { if a.gt(b) { a } else { b } } \
+                      class Box<T> { pub value: T; pub fn get(self): T { self.value } } \
+                      fn f(): int { let b = Box { value: max(1, 2) }; b.get() + Box::<int> { value: 3 }.value + max::<int>(4, 5) }";
+        let program = parse(source);
+        let (resolution, _) = Resolver::new().resolve(&program);
+        let mut checker = Checker::new(&resolution);
+        checker.check(&program).unwrap();
+        let Declaration::Function(function) = &program.declarations[4] else { unreachable!() };
+        let StatementKind::Let { value: Some(boxed), .. } = &function.inner.statements[0].kind else { unreachable!() };
+        let int = Type::Numeric(NumericType::I32);
+        assert_eq!(checker.types().get(boxed.id), Some(&Type::Custom { name: "Box".into(), type_args: vec![int.clone()] }));
+        let ExpressionKind::Struct { fields, .. } = &boxed.kind else { unreachable!() };
+        assert_eq!(checker.types().instance(fields[0].value.id), Some(&vec![int.clone()]));
+        let ExpressionKind::Binary { left, .. } = &function.inner.value.as_ref().unwrap().kind else { unreachable!() };
+        let ExpressionKind::Binary { left: get, .. } = &left.kind else { unreachable!() };
+        // the receiver gives the method of a generic class the types of the class
+        assert_eq!(checker.types().instance(get.id), Some(&vec![int]));
+
+        let errors = check("trait Ord { fn gt(self, other: Self): bool; } class Box<T: Ord> { value: T; } trait X { fn f<T>(self); } \
+                            fn max<T: Ord>(a: T, b: T): T { a } fn none<T>(): int { 0 } \
+                            fn f(b: Box, c: Box<bool>) { max(true, false); none(); max::<int, int>(1, 2); }").unwrap_err();
+        // the bounds of a call are checked at the end of the body
+        let [
+            CheckErr::GenericTraitFunction(..),
+            CheckErr::WrongTypeArgCount(_, 1, 0, _),
+            CheckErr::NotImplemented(_, Type::Bool, _),
+            CheckErr::WrongTypeArgCount(path, 1, 2, _),
+            CheckErr::NotImplemented(_, Type::Bool, _),
+            CheckErr::UnknownType(..),
+            CheckErr::NotImplemented(..),
+        ] = &errors[..] else {
+            panic!("{:?}", errors)
+        };
+        assert_eq!(path, "max");
+    }
+
+    #[test]
+    fn test_check_enums() {
+        let source = "enum Shape<T> { Circle(T), Rect { w: T, h: T }, Empty } \
+                      fn area(s: Shape<float>): float { match s { Shape::Circle(r) => r * r, Shape::Rect { w, h } => w * h, Shape::Empty => 0.0 } } \
+                      fn f(): float { let s = Shape::Circle(2.0); let t: Shape<float> = Shape::Empty; area(s) + area(t) + area(Shape::<float>::Rect { w: 1.0, h: 2.0 }) }";
+        let program = parse(source);
+        let (resolution, _) = Resolver::new().resolve(&program);
+        let mut checker = Checker::new(&resolution);
+        checker.check(&program).unwrap();
+        let Declaration::Function(function) = &program.declarations[2] else { unreachable!() };
+        let StatementKind::Let { value: Some(circle), .. } = &function.inner.statements[0].kind else { unreachable!() };
+        let float = Type::Numeric(NumericType::F32);
+        assert_eq!(checker.types().get(circle.id), Some(&Type::Custom { name: "Shape".into(), type_args: vec![float] }));
+        // a single variant matches every value, so it can be destructured by `let`
+        check("enum Pair { Of(int, int) } fn f(p: Pair): int { let Pair::Of(a, b) = p; a + b } fn g(Pair::Of(a, _): Pair): int { a }").unwrap();
+        check("fn f(b: bool): int { match b { true => 1, false => 0 } } fn g(x: int): int { match x { 1 => 2, n => n } }").unwrap();
+        // arms cover nested variants together, and name a value they all miss
+        check("enum O<T> { S(T), N } fn f(o: O<O<bool>>): int { match o { O::S(O::S(true)) => 1, O::S(O::S(false)) => 2, O::S(O::N) => 3, O::N => 4 } }").unwrap();
+        let errors = check("enum O<T> { S(T), N } fn f(o: (O<bool>, int)): int { match o { (O::S(true), _) => 1, (O::N, 1) => 2 } }").unwrap_err();
+        assert!(matches!(errors.as_slice(), [CheckErr::NonExhaustive(_, missing, _)] if missing == &["(O::S(false), _)".to_string(), "(O::N, _)".to_string()]), "{:?}", errors);
+
+        let errors = check("enum E { A(int), B { x: int }, C } enum L { Cons(int, L), Nil } \
+                            fn f(e: E): int { let E::A(a) = e; E::C(1); E::A; E::B { y: 1, x: 2 }; match e { E::A(1) => 1, E::B { x } => x, E::C => true } } \
+                            fn g(b: bool) { match b { true => { } } }").unwrap_err();
+        let [
+            CheckErr::RecursiveClass(list, "enum", ..),
+            CheckErr::RefutablePattern(..),
+            CheckErr::InvalidVariantUse(_, "unit", "tuple", ..),
+            CheckErr::InvalidVariantUse(_, "tuple", "unit", ..),
+            CheckErr::NoField(_, y, ..),
+            CheckErr::TypeMismatch(_, Type::Bool, Some(_), _),
+            CheckErr::NonExhaustive(_, missing, _),
+            CheckErr::NonExhaustive(Type::Bool, bools, _),
+        ] = &errors[..] else {
+            panic!("{:?}", errors)
+        };
+        assert_eq!((list.as_str(), y.as_str()), ("L", "y"));
+        assert_eq!((missing.as_slice(), bools.as_slice()), (&["E::A(_)".to_string()][..], &["false".to_string()][..]));
+    }
+
+    #[test]
+    fn test_check_try() {
+        check_with_prelude("fn parse(x: int): Result<int, bool> { if x < 0 { Err(false) } else { Ok(x) } } \
+                            fn twice(x: int): Result<int, bool> { Ok(parse(x)? * 2) } \
+                            fn first(o: Option<Option<u8>>): Option<u8> { let x = o??; Some(x + 1) }")
+        .unwrap();
+        let errors = check_with_prelude("fn f(x: int): int { x? } \
+                                         fn g(o: Option<int>): int { o? } \
+                                         fn h(r: Result<int, bool>): Option<int> { Some(r?) } \
+                                         fn k(r: Result<int, bool>): Result<int, int> { Ok(r?) } \
+                                         fn m(o: Option<int>): int { match o { Some(x) => x } }")
+        .unwrap_err();
+        let [
+            CheckErr::InvalidTry(Type::Numeric(NumericType::I32), _),
+            CheckErr::TryInWrongFunction(option, Type::Numeric(NumericType::I32), _),
+            CheckErr::TryInWrongFunction(result, Type::Custom { .. }, _),
+            CheckErr::TypeMismatch(_, Type::Bool, None, _),
+            CheckErr::NonExhaustive(_, missing, _),
+        ] = &errors[..] else {
+            panic!("{:?}", errors)
+        };
+        assert_eq!((option.as_str(), result.as_str()), ("Option", "Result"));
+        // witnesses name a variant of the prelude alone, as it is written
+        assert_eq!(missing.as_slice(), ["None".to_string()]);
+    }
+}
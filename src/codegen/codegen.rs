@@ -0,0 +1,1633 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use inkwell::attributes::{Attribute, AttributeLoc};
+use inkwell::basic_block::BasicBlock;
+use inkwell::builder::Builder;
+use inkwell::context::Context;
+use inkwell::intrinsics::Intrinsic as LlvmIntrinsic;
+use inkwell::module::{Linkage, Module};
+use inkwell::targets::{CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetMachine};
+use inkwell::types::{BasicMetadataTypeEnum, BasicType, BasicTypeEnum, IntType, PointerType, StructType};
+use inkwell::values::{BasicMetadataValueEnum, BasicValue, BasicValueEnum, FunctionValue, IntValue, PointerValue};
+use inkwell::{AddressSpace, FloatPredicate, IntPredicate, OptimizationLevel};
+
+use crate::checker::{Checked, TraitCall, Type, TypeTable};
+use crate::codegen::error::CodegenErr;
+use crate::diagnostic::SourceMap;
+use crate::numeric::{Intrinsic, NumericType, Operation, Overflow};
+use crate::parser::*;
+use crate::prelude;
+use crate::resolver::{Res, Resolution};
+use crate::span::Span;
+use crate::Profile;
+
+/// The function every runtime error calls. It is internal to each module, and
+/// `.` can not appear in the names of sonneko items.
+const TRAP: &str = "sonneko.trap";
+
+/// Exit code of a program stopped by a runtime error.
+pub const TRAP_EXIT_CODE: u64 = 101;
+
+struct Variable<'ctx> {
+    pointer: PointerValue<'ctx>,
+    ty: BasicTypeEnum<'ctx>,
+}
+
+/// The LLVM struct of a class, named by the full path of the class with its
+/// type arguments like `Box<i32>`, and its fields in declaration order, which
+/// is the order of the struct members.
+struct Class<'ctx> {
+    ty: StructType<'ctx>,
+    fields: Vec<Identifier>,
+}
+
+/// The layout of an enum, named like a class. An enum whose variants hold
+/// nothing is only its tag, the index of the variant; any other is a struct
+/// of the tag and an array big and aligned enough for the fields of every
+/// variant, which are read and written through a struct of their own.
+struct Enum<'ctx> {
+    ty: BasicTypeEnum<'ctx>,
+    tag: IntType<'ctx>,
+    variants: Vec<VariantLayout<'ctx>>,
+}
+
+/// A variant of an enum, and its fields in declaration order, which is the
+/// order of the members of `payload`. Those of a tuple variant are named by
+/// their index.
+struct VariantLayout<'ctx> {
+    name: Identifier,
+    fields: Vec<Identifier>,
+    payload: StructType<'ctx>,
+}
+
+/// A generic function, or a method of a generic class, which is compiled
+/// once for every list of types it is called with.
+struct Generic {
+    /// the type parameters of the class of a method
+    class_params: Vec<Identifier>,
+    function: FunctionDeclaration,
+}
+
+/// An instance of a generic function that is declared but not compiled yet.
+struct Instance {
+    name: String,
+    type_args: HashMap<Identifier, Type>,
+    function: FunctionDeclaration,
+}
+
+pub struct Codegen<'ctx> {
+    context: &'ctx Context,
+    module: Module<'ctx>,
+    builder: Builder<'ctx>,
+    namespace: Vec<Identifier>,
+    /// what the paths of the program name, which may be imported items of other modules
+    resolution: Resolution,
+    /// the types the checker gave every expression and pattern
+    types: TypeTable,
+    scopes: Vec<HashMap<Identifier, Variable<'ctx>>>,
+    /// the declaration of every class by its full path
+    class_declarations: HashMap<String, ClassDeclaration>,
+    /// the struct of every class and instance of a generic class by its name
+    classes: HashMap<String, Class<'ctx>>,
+    /// the declaration of every enum by its full path
+    enum_declarations: HashMap<String, EnumDeclaration>,
+    /// the layout of every enum and instance of a generic enum by its name
+    enums: HashMap<String, Enum<'ctx>>,
+    /// the generic functions and methods by their full path
+    generics: HashMap<String, Generic>,
+    /// the instances of generic functions still to be compiled
+    instances: Vec<Instance>,
+    /// the functions every trait gives a default for, by the full path of the trait
+    defaults: HashMap<String, Vec<FunctionDeclaration>>,
+    /// the signatures of the functions of every trait in the order of its vtables
+    trait_functions: HashMap<String, Vec<FunctionSignature>>,
+    /// what the type parameters are in the function being compiled, and
+    /// `Self` in the functions of an impl
+    type_args: HashMap<Identifier, Type>,
+    function: Option<FunctionValue<'ctx>>,
+    /// the return type of the function being compiled, which `?` builds its `None` or `Err` of
+    return_type: Type,
+    profile: Profile,
+    /// the files of the program, for the locations runtime errors print
+    sources: SourceMap,
+}
+
+type CodegenResult<T> = Result<T, CodegenErr>;
+
+/// `None` is the value of unit typed expressions and of expressions that never finish.
+type Value<'ctx> = Option<BasicValueEnum<'ctx>>;
+
+/// The values of the fields of a variant by name.
+type Fields<'ctx> = Vec<(Identifier, BasicValueEnum<'ctx>)>;
+
+impl<'ctx> Codegen<'ctx> {
+    pub fn new(context: &'ctx Context, module_name: &str) -> Self {
+        Codegen {
+            context,
+            module: context.create_module(module_name),
+            builder: context.create_builder(),
+            namespace: Vec::new(),
+            resolution: Resolution::default(),
+            types: TypeTable::default(),
+            scopes: Vec::new(),
+            class_declarations: HashMap::new(),
+            classes: HashMap::new(),
+            enum_declarations: HashMap::new(),
+            enums: HashMap::new(),
+            generics: HashMap::new(),
+            instances: Vec::new(),
+            defaults: HashMap::new(),
+            trait_functions: HashMap::new(),
+            type_args: HashMap::new(),
+            function: None,
+            return_type: Type::unit(),
+            profile: Profile::Debug,
+            sources: SourceMap::new(),
+        }
+    }
+
+    /// Compiles a debug build unless told otherwise.
+    pub fn with_profile(mut self, profile: Profile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    pub fn with_sources(mut self, sources: SourceMap) -> Self {
+        self.sources = sources;
+        self
+    }
+
+    pub fn module(&self) -> &Module<'ctx> {
+        &self.module
+    }
+
+    /// Lowers a program into this module with what `crate::check` returned
+    /// for it.
+    pub fn compile(&mut self, program: &Program, checked: Checked) -> CodegenResult<()> {
+        self.resolution = checked.resolution;
+        self.types = checked.types;
+        self.declare_classes(program);
+        self.define_classes(program)?;
+        self.collect_traits(program);
+        self.declare(program)?;
+        self.compile_program(program)?;
+        self.compile_instances()?;
+        self.module.verify().map_err(|message| CodegenErr::Verify(message.to_string()))
+    }
+
+    /// Writes the compiled module as an object file for the host machine.
+    pub fn write_object(&self, path: &Path) -> CodegenResult<()> {
+        Target::initialize_native(&InitializationConfig::default()).map_err(CodegenErr::Target)?;
+        let triple = TargetMachine::get_default_triple();
+        let target = Target::from_triple(&triple).map_err(|message| CodegenErr::Target(message.to_string()))?;
+        let machine = target
+            .create_target_machine(
+                &triple,
+                &TargetMachine::get_host_cpu_name().to_string(),
+                &TargetMachine::get_host_cpu_features().to_string(),
+                OptimizationLevel::Default,
+                RelocMode::PIC,
+                CodeModel::Default,
+            )
+            .ok_or_else(|| CodegenErr::Target(format!("no target machine for {}", triple)))?;
+        self.module.set_triple(&triple);
+        self.module.set_data_layout(&machine.get_target_data().get_data_layout());
+        machine
+            .write_to_file(&self.module, FileType::Object, path)
+            .map_err(|message| CodegenErr::Target(message.to_string()))
+    }
+
+    fn full_path(&self, name: &str) -> String {
+        let mut path = self.namespace.join("::");
+        if !path.is_empty() {
+            path.push_str("::");
+        }
+        path.push_str(name);
+        path
+    }
+
+    /// The full path of the item the accesser expression `id` names.
+    fn item_path(&self, id: NodeId, accesser: &Accesser) -> CodegenResult<&str> {
+        match self.resolution.get(id) {
+            Some(Res::Function(path) | Res::Static(path)) => Ok(path),
+            _ => Err(CodegenErr::Undefined(accesser.path())),
+        }
+    }
+
+    /// Integers of either sign are LLVM integers of the same width; the
+    /// operations on them pick signed or unsigned instructions.
+    fn numeric_type(&self, numeric: NumericType) -> BasicTypeEnum<'ctx> {
+        match numeric {
+            NumericType::F32 => self.context.f32_type().into(),
+            NumericType::F64 => self.context.f64_type().into(),
+            numeric => self.context.custom_width_int_type(numeric.bits()).into(),
+        }
+    }
+
+    /// The type the checker gave the expression `id`; literals it never saw are `i32` and `f32`.
+    fn type_at(&self, id: NodeId) -> &Type {
+        self.types.get(id).unwrap_or(&Type::Never)
+    }
+
+    /// `ty` with the type parameters replaced by what they are in the function being compiled.
+    fn concrete(&self, ty: &Type) -> Type {
+        ty.substitute(&self.type_args)
+    }
+
+    /// The function of the impl that the call `id` of a trait function runs, if it is one.
+    fn trait_call(&self, id: NodeId) -> Option<String> {
+        let call = self.types.trait_call(id)?;
+        Some(impl_function(&call.trait_path, &self.concrete(&call.self_type), &call.name))
+    }
+
+    /// The call `id` of a function of a trait on a `dyn` of that trait, which
+    /// goes through the vtable of the value.
+    fn dyn_call(&self, id: NodeId) -> Option<TraitCall> {
+        self.types.trait_call(id).filter(|call| self.concrete(&call.self_type) == Type::Dyn(call.trait_path.clone())).cloned()
+    }
+
+    /// Whether comparisons and divisions of values of type `ty` are signed.
+    fn is_signed(ty: &Type) -> bool {
+        matches!(ty, Type::Numeric(numeric) if numeric.is_signed())
+    }
+
+    /// The type `type_literal` names, with the type parameters replaced by
+    /// what they are in the function being compiled.
+    fn type_of(&self, type_literal: &TypeLiteral) -> CodegenResult<Type> {
+        let ty = match &type_literal.kind {
+            TypeLiteralKind::IntType => Type::Numeric(NumericType::I32),
+            TypeLiteralKind::FloatType => Type::Numeric(NumericType::F32),
+            TypeLiteralKind::NumericType(numeric) => Type::Numeric(*numeric),
+            TypeLiteralKind::CharType => Type::Char,
+            TypeLiteralKind::BoolType => Type::Bool,
+            TypeLiteralKind::TupleType { inner } => Type::Tuple(inner.iter().map(|ty| self.type_of(ty)).collect::<CodegenResult<_>>()?),
+            TypeLiteralKind::CustomType { name, type_args } => match self.resolution.get(type_literal.id) {
+                // inside a generic class or enum `Self` is the type with its own parameters
+                Some(Res::Class(path) | Res::Enum(path)) if name == "Self" => {
+                    let params = match (self.class_declarations.get(path), self.enum_declarations.get(path)) {
+                        (Some(class), _) => &class.type_params[..],
+                        (None, Some(declaration)) => &declaration.type_params[..],
+                        (None, None) => &[],
+                    };
+                    let type_args = params.iter().map(|param| self.type_args.get(&param.name).cloned().ok_or(CodegenErr::Unsupported("type parameters")));
+                    Type::Custom { name: path.clone(), type_args: type_args.collect::<CodegenResult<_>>()? }
+                }
+                Some(Res::Class(path) | Res::Enum(path) | Res::Trait(path)) => Type::Custom {
+                    name: path.clone(),
+                    type_args: type_args.iter().map(|ty| self.type_of(ty)).collect::<CodegenResult<_>>()?,
+                },
+                Some(Res::SelfType(_)) => self.type_args.get("Self").cloned().ok_or(CodegenErr::Unsupported("type parameters"))?,
+                Some(Res::TypeParam(id)) => self.concrete(self.type_at(*id)),
+                _ => return Err(CodegenErr::Undefined(name.clone())),
+            },
+            TypeLiteralKind::DynType { name } => match self.resolution.get(type_literal.id) {
+                Some(Res::Trait(path)) => Type::Dyn(path.clone()),
+                _ => return Err(CodegenErr::Undefined(name.clone())),
+            },
+        };
+        Ok(ty)
+    }
+
+    /// `None` stands for `()`, which has no LLVM representation.
+    fn basic_type(&mut self, type_literal: &TypeLiteral) -> CodegenResult<Option<BasicTypeEnum<'ctx>>> {
+        let ty = self.type_of(type_literal)?;
+        self.lower_type(&ty)
+    }
+
+    fn lower_type(&mut self, ty: &Type) -> CodegenResult<Option<BasicTypeEnum<'ctx>>> {
+        let ty = match ty {
+            Type::Char => self.context.i32_type().into(),
+            Type::Numeric(numeric) => self.numeric_type(*numeric),
+            Type::Bool => self.context.bool_type().into(),
+            Type::Tuple(inner) if inner.is_empty() => return Ok(None),
+            Type::Tuple(inner) => {
+                let mut fields = Vec::new();
+                for ty in inner {
+                    fields.push(self.lower_type(ty)?.ok_or(CodegenErr::Unsupported("unit tuple fields"))?);
+                }
+                self.context.struct_type(&fields, false).into()
+            }
+            Type::Str => return Err(CodegenErr::Unsupported("strings")),
+            Type::Custom { name, .. } if self.enum_declarations.contains_key(name) => self.enumeration(ty)?.ty,
+            Type::Custom { .. } => self.class(ty)?.ty.into(),
+            // `Self` of a trait is the type of the impl a default is compiled for
+            Type::Param { name, .. } => match self.type_args.get(name) {
+                Some(ty) => return self.lower_type(&ty.clone()),
+                None => return Err(CodegenErr::Unsupported("type parameters")),
+            },
+            Type::Dyn(_) => self.dyn_type().into(),
+            Type::Never | Type::Var(..) => return Err(CodegenErr::Unsupported("locals of unknown type")),
+        };
+        Ok(Some(ty))
+    }
+
+    fn pointer_type(&self) -> PointerType<'ctx> {
+        self.context.ptr_type(AddressSpace::default())
+    }
+
+    /// A `dyn Trait` is a pointer to the value and a pointer to the vtable of
+    /// its type for the trait.
+    fn dyn_type(&self) -> StructType<'ctx> {
+        let pointer = self.pointer_type();
+        self.context.struct_type(&[pointer.into(), pointer.into()], false)
+    }
+
+    /// The struct of the class `ty`, which for a generic class is defined
+    /// the first time an instance of it is used.
+    fn class(&mut self, ty: &Type) -> CodegenResult<&Class<'ctx>> {
+        let name = ty.to_string();
+        if !self.classes.contains_key(&name) {
+            let Type::Custom { name: path, type_args } = ty else { return Err(CodegenErr::Undefined(name)) };
+            let declaration = self.class_declarations.get(path).ok_or(CodegenErr::Unsupported("trait types"))?.clone();
+            let struct_type = self.context.opaque_struct_type(&name);
+            let field_names = declaration.fields.iter().map(|field| field.name.clone()).collect();
+            self.classes.insert(name.clone(), Class { ty: struct_type, fields: field_names });
+            let params = declaration.type_params.iter().map(|param| param.name.clone());
+            let type_args = std::mem::replace(&mut self.type_args, params.zip(type_args.iter().cloned()).collect());
+            let fields: CodegenResult<Vec<BasicTypeEnum>> = declaration
+                .fields
+                .iter()
+                .map(|field| self.basic_type(&field.type_annotation)?.ok_or(CodegenErr::Unsupported("unit fields")))
+                .collect();
+            self.type_args = type_args;
+            struct_type.set_body(&fields?, false);
+        }
+        Ok(&self.classes[&name])
+    }
+
+    /// The layout of the enum `ty`, which for a generic enum is defined the
+    /// first time an instance of it is used.
+    fn enumeration(&mut self, ty: &Type) -> CodegenResult<&Enum<'ctx>> {
+        let name = ty.to_string();
+        if !self.enums.contains_key(&name) {
+            let Type::Custom { name: path, type_args } = ty else { return Err(CodegenErr::Undefined(name)) };
+            let declaration = self.enum_declarations.get(path).ok_or_else(|| CodegenErr::Undefined(name.clone()))?.clone();
+            let params = declaration.type_params.iter().map(|param| param.name.clone());
+            let outer = std::mem::replace(&mut self.type_args, params.zip(type_args.iter().cloned()).collect());
+            let variants = self.variant_layouts(&declaration);
+            self.type_args = outer;
+            let variants = variants?;
+            let tag = if variants.len() <= 1 << 8 { self.context.i8_type() } else { self.context.i32_type() };
+            let mut size = 0;
+            let mut align = 1;
+            for variant in &variants {
+                let (variant_size, variant_align) = layout(variant.payload.into())?;
+                size = size.max(variant_size);
+                align = align.max(variant_align);
+            }
+            let ty = if size == 0 {
+                tag.into()
+            } else {
+                let unit = self.context.custom_width_int_type(align as u32 * 8);
+                let struct_type = self.context.opaque_struct_type(&name);
+                struct_type.set_body(&[tag.into(), unit.array_type(size.div_ceil(align) as u32).into()], false);
+                struct_type.into()
+            };
+            self.enums.insert(name.clone(), Enum { ty, tag, variants });
+        }
+        Ok(&self.enums[&name])
+    }
+
+    fn variant_layouts(&mut self, declaration: &EnumDeclaration) -> CodegenResult<Vec<VariantLayout<'ctx>>> {
+        let mut variants = Vec::new();
+        for variant in &declaration.variants {
+            let fields: Vec<(Identifier, &TypeLiteral)> = match &variant.kind {
+                VariantKind::Unit => Vec::new(),
+                VariantKind::Tuple(types) => types.iter().enumerate().map(|(index, ty)| (index.to_string(), ty)).collect(),
+                VariantKind::Struct(fields) => fields.iter().map(|field| (field.name.clone(), &field.type_annotation)).collect(),
+            };
+            let mut types = Vec::new();
+            for (_, type_literal) in &fields {
+                types.push(self.basic_type(type_literal)?.ok_or(CodegenErr::Unsupported("unit fields"))?);
+            }
+            let payload = self.context.struct_type(&types, false);
+            variants.push(VariantLayout { name: variant.name.clone(), fields: fields.into_iter().map(|(name, _)| name).collect(), payload });
+        }
+        Ok(variants)
+    }
+
+    /// The index of the variant `path` of the enum `ty` and the layout of the enum.
+    fn variant(&mut self, ty: &Type, path: &str) -> CodegenResult<(usize, &Enum<'ctx>)> {
+        let name = path.rsplit_once("::").map_or(path, |(_, name)| name);
+        let layout = self.enumeration(ty)?;
+        let index = layout.variants.iter().position(|variant| variant.name == name).ok_or_else(|| CodegenErr::Undefined(path.to_string()))?;
+        Ok((index, layout))
+    }
+
+    /// Builds a value of the variant `path` of the enum `ty` from the values
+    /// of its fields by their index.
+    fn build_variant(&mut self, ty: &Type, path: &str, values: Vec<(usize, BasicValueEnum<'ctx>)>) -> CodegenResult<BasicValueEnum<'ctx>> {
+        let (index, layout) = self.variant(ty, path)?;
+        let tag = layout.tag.const_int(index as u64, false);
+        let BasicTypeEnum::StructType(enum_type) = layout.ty else { return Ok(tag.into()) };
+        let payload_type = layout.variants[index].payload;
+        let mut payload = payload_type.get_undef();
+        for (index, value) in values {
+            payload = self.builder.build_insert_value(payload, value, index as u32, "field")?.into_struct_value();
+        }
+        let pointer = self.build_entry_alloca(enum_type.into(), "enum")?;
+        let tag_pointer = self.builder.build_struct_gep(enum_type, pointer, 0, "enum.tag")?;
+        self.builder.build_store(tag_pointer, tag)?;
+        let payload_pointer = self.builder.build_struct_gep(enum_type, pointer, 1, "enum.payload")?;
+        self.builder.build_store(payload_pointer, payload)?;
+        Ok(self.builder.build_load(enum_type, pointer, "enum")?)
+    }
+
+    /// The tag of `value` of the enum `ty`, and the fields it has if it is of
+    /// the variant `path`, by name. The fields mean nothing for a value of
+    /// another variant.
+    fn variant_fields(&mut self, value: BasicValueEnum<'ctx>, ty: &Type, path: &str) -> CodegenResult<(IntValue<'ctx>, IntValue<'ctx>, Fields<'ctx>)> {
+        let (index, layout) = self.variant(ty, path)?;
+        let expected = layout.tag.const_int(index as u64, false);
+        let BasicTypeEnum::StructType(enum_type) = layout.ty else { return Ok((value.into_int_value(), expected, Vec::new())) };
+        let variant = &layout.variants[index];
+        let (payload_type, names) = (variant.payload, variant.fields.clone());
+        let pointer = self.build_entry_alloca(enum_type.into(), "enum")?;
+        self.builder.build_store(pointer, value)?;
+        let tag = self.builder.build_extract_value(value.into_struct_value(), 0, "enum.tag")?.into_int_value();
+        let payload_pointer = self.builder.build_struct_gep(enum_type, pointer, 1, "enum.payload")?;
+        let payload = self.builder.build_load(payload_type, payload_pointer, "payload")?.into_struct_value();
+        let mut fields = Vec::new();
+        for (index, name) in names.into_iter().enumerate() {
+            fields.push((name.clone(), self.builder.build_extract_value(payload, index as u32, &name)?));
+        }
+        Ok((tag, expected, fields))
+    }
+
+    /// Keeps the declaration of every class and enum. A class holds itself at
+    /// most behind a pointer, so the struct of a class can be defined before
+    /// the structs of its fields.
+    fn declare_classes(&mut self, program: &Program) {
+        for declaration in &program.declarations {
+            match declaration {
+                Declaration::Class(class) => {
+                    self.class_declarations.insert(self.full_path(&class.name), class.clone());
+                }
+                Declaration::Enum(declaration) => {
+                    self.enum_declarations.insert(self.full_path(&declaration.name), declaration.clone());
+                }
+                Declaration::Namespace(namespace) => {
+                    self.namespace.push(namespace.name.clone());
+                    self.declare_classes(&namespace.inner);
+                    self.namespace.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Gives every class and enum that is not generic its struct; those of
+    /// generic ones are defined for every instance that is used.
+    fn define_classes(&mut self, program: &Program) -> CodegenResult<()> {
+        for declaration in &program.declarations {
+            match declaration {
+                Declaration::Class(class) if class.type_params.is_empty() => {
+                    self.class(&Type::Custom { name: self.full_path(&class.name), type_args: Vec::new() })?;
+                }
+                Declaration::Enum(declaration) if declaration.type_params.is_empty() => {
+                    self.enumeration(&Type::Custom { name: self.full_path(&declaration.name), type_args: Vec::new() })?;
+                }
+                Declaration::Namespace(namespace) => {
+                    self.namespace.push(namespace.name.clone());
+                    self.define_classes(&namespace.inner)?;
+                    self.namespace.pop();
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Keeps the defaults of every trait, which are compiled with the impls of
+    /// the trait, and the signatures of its functions for its vtables.
+    fn collect_traits(&mut self, program: &Program) {
+        for declaration in &program.declarations {
+            match declaration {
+                Declaration::Trait(declaration) => {
+                    let path = self.full_path(&declaration.name);
+                    let signatures = declaration.signatures.iter().chain(declaration.functions.iter().map(|function| &function.signature));
+                    self.trait_functions.insert(path.clone(), signatures.cloned().collect());
+                    self.defaults.insert(path, declaration.functions.clone());
+                }
+                Declaration::Namespace(namespace) => {
+                    self.namespace.push(namespace.name.clone());
+                    self.collect_traits(&namespace.inner);
+                    self.namespace.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The functions an impl is compiled to, with their names: its own, and
+    /// the defaults of its trait it leaves out. `Self` is set to the type of
+    /// the impl until the next impl.
+    fn impl_functions(&mut self, declaration: &ImplDeclaration) -> CodegenResult<Vec<(String, FunctionDeclaration)>> {
+        let Some(Res::Trait(trait_path)) = self.resolution.get(declaration.trait_type.id) else {
+            return Err(CodegenErr::Undefined(printer::print_type(&declaration.trait_type)));
+        };
+        let self_type = self.type_at(declaration.id).clone();
+        let mut functions: Vec<(String, FunctionDeclaration)> = declaration
+            .functions
+            .iter()
+            .map(|function| (impl_function(trait_path, &self_type, &function.name), function.clone()))
+            .collect();
+        for default in self.defaults.get(trait_path).into_iter().flatten() {
+            if declaration.functions.iter().all(|function| function.name != default.name) {
+                functions.push((impl_function(trait_path, &self_type, &default.name), default.clone()));
+            }
+        }
+        self.type_args = HashMap::from([("Self".to_string(), self_type)]);
+        Ok(functions)
+    }
+
+    fn declare_function(&mut self, path: &str, function: &FunctionDeclaration) -> CodegenResult<()> {
+        let mut params = Vec::<BasicMetadataTypeEnum>::new();
+        for param in &function.signature.params {
+            let ty = self.basic_type(&param.type_annotation)?.ok_or(CodegenErr::Unsupported("unit parameters"))?;
+            params.push(ty.into());
+        }
+        let return_type = match &function.signature.return_type {
+            Some(type_literal) => self.basic_type(type_literal)?,
+            None => None,
+        };
+        let fn_type = match return_type {
+            Some(ty) => ty.fn_type(&params, false),
+            None => self.context.void_type().fn_type(&params, false),
+        };
+        self.module.add_function(path, fn_type, None);
+        Ok(())
+    }
+
+    /// Adds every function and static first so bodies can refer to them in any order.
+    /// Methods are functions under the path of their class, like `Point::new`.
+    fn declare(&mut self, program: &Program) -> CodegenResult<()> {
+        for declaration in &program.declarations {
+            match declaration {
+                Declaration::Function(function) if !function.signature.type_params.is_empty() => {
+                    let generic = Generic { class_params: Vec::new(), function: function.clone() };
+                    self.generics.insert(self.full_path(&function.name), generic);
+                }
+                Declaration::Function(function) => self.declare_function(&self.full_path(&function.name), function)?,
+                Declaration::StaticVariable(variable) => {
+                    let value = self.const_value(&variable.value).ok_or_else(|| CodegenErr::NonConstantStatic(variable.name.clone()))?;
+                    let global = self.module.add_global(value.get_type(), None, &self.full_path(&variable.name));
+                    global.set_initializer(&value);
+                }
+                Declaration::Namespace(namespace) => {
+                    self.namespace.push(namespace.name.clone());
+                    self.declare(&namespace.inner)?;
+                    self.namespace.pop();
+                }
+                Declaration::Class(class) => {
+                    let class_params: Vec<Identifier> = class.type_params.iter().map(|param| param.name.clone()).collect();
+                    for method in &class.methods {
+                        let path = self.full_path(&format!("{}::{}", class.name, method.name));
+                        if class_params.is_empty() && method.signature.type_params.is_empty() {
+                            self.declare_function(&path, method)?;
+                        } else {
+                            self.generics.insert(path, Generic { class_params: class_params.clone(), function: method.clone() });
+                        }
+                    }
+                }
+                // a trait is compiled with each of its impls
+                Declaration::Trait(_) => {}
+                // an enum has no functions of its own
+                Declaration::Enum(_) => {}
+                Declaration::Impl(declaration) => {
+                    for (path, function) in self.impl_functions(declaration)? {
+                        self.declare_function(&path, &function)?;
+                    }
+                    self.type_args.clear();
+                }
+                // imported items are compiled with the namespace of their module
+                Declaration::Import(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn const_value(&self, expression: &Expression) -> Option<BasicValueEnum<'ctx>> {
+        match &expression.kind {
+            ExpressionKind::Literal(Literal::Int(value)) => {
+                let ty = match self.type_at(expression.id) {
+                    Type::Numeric(numeric) if !numeric.is_float() => self.numeric_type(*numeric).into_int_type(),
+                    _ => self.context.i32_type(),
+                };
+                Some(ty.const_int(*value, false).into())
+            }
+            ExpressionKind::Literal(Literal::Float(value)) => {
+                let ty = match self.type_at(expression.id) {
+                    Type::Numeric(numeric) if numeric.is_float() => self.numeric_type(*numeric).into_float_type(),
+                    _ => self.context.f32_type(),
+                };
+                Some(ty.const_float(*value).into())
+            }
+            ExpressionKind::Literal(Literal::Bool(value)) => Some(self.context.bool_type().const_int(*value as u64, false).into()),
+            ExpressionKind::Unary { ope: UnaryOpe::Minus, operand } => match self.const_value(operand)? {
+                BasicValueEnum::IntValue(value) => Some(value.const_neg().into()),
+                BasicValueEnum::FloatValue(value) => Some(value.get_type().const_float(-value.get_constant()?.0).into()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn compile_program(&mut self, program: &Program) -> CodegenResult<()> {
+        for declaration in &program.declarations {
+            match declaration {
+                Declaration::Function(function) if function.signature.type_params.is_empty() => {
+                    self.compile_function(&self.full_path(&function.name), function)?
+                }
+                Declaration::Namespace(namespace) => {
+                    self.namespace.push(namespace.name.clone());
+                    self.compile_program(&namespace.inner)?;
+                    self.namespace.pop();
+                }
+                Declaration::Class(class) if class.type_params.is_empty() => {
+                    for method in class.methods.iter().filter(|method| method.signature.type_params.is_empty()) {
+                        self.compile_function(&self.full_path(&format!("{}::{}", class.name, method.name)), method)?;
+                    }
+                }
+                Declaration::Impl(declaration) => {
+                    for (path, function) in self.impl_functions(declaration)? {
+                        self.compile_function(&path, &function)?;
+                    }
+                    self.type_args.clear();
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// The instance of the generic function `path` for the types the call
+    /// `id` gives its type parameters, named like `max<i32>` or
+    /// `Box<i32>::get`. It is declared the first time a call needs it, so
+    /// every instance is compiled once however many calls and modules use it.
+    fn instance(&mut self, path: &str, id: NodeId) -> CodegenResult<FunctionValue<'ctx>> {
+        let generic = self.generics.get(path).ok_or_else(|| CodegenErr::Undefined(path.to_string()))?;
+        let type_args: Vec<Type> = self.types.instance(id).ok_or_else(|| CodegenErr::Undefined(path.to_string()))?.iter().map(|ty| self.concrete(ty)).collect();
+        let (class_args, own_args) = type_args.split_at(generic.class_params.len());
+        let mut name = match (path.rsplit_once("::"), class_args) {
+            (Some((class, function)), [_, ..]) => format!("{}<{}>::{}", class, type_list(class_args), function),
+            _ => path.to_string(),
+        };
+        if !own_args.is_empty() {
+            name.push_str(&format!("<{}>", type_list(own_args)));
+        }
+        if let Some(function) = self.module.get_function(&name) {
+            return Ok(function);
+        }
+        let own_params = generic.function.signature.type_params.iter().map(|param| &param.name);
+        let params: Vec<Identifier> = generic.class_params.iter().chain(own_params).cloned().collect();
+        let function = generic.function.clone();
+        let outer = std::mem::replace(&mut self.type_args, params.into_iter().zip(type_args).collect());
+        let declared = self.declare_function(&name, &function);
+        let type_args = std::mem::replace(&mut self.type_args, outer);
+        declared?;
+        self.instances.push(Instance { name: name.clone(), type_args, function });
+        self.module.get_function(&name).ok_or(CodegenErr::Undefined(name))
+    }
+
+    /// Compiles the instances of generic functions that calls declared,
+    /// including those that the instances call in turn.
+    fn compile_instances(&mut self) -> CodegenResult<()> {
+        while let Some(instance) = self.instances.pop() {
+            self.type_args = instance.type_args;
+            self.compile_function(&instance.name, &instance.function)?;
+        }
+        self.type_args.clear();
+        Ok(())
+    }
+
+    fn compile_function(&mut self, path: &str, function: &FunctionDeclaration) -> CodegenResult<()> {
+        let fn_value = self.module.get_function(path).ok_or_else(|| CodegenErr::Undefined(path.to_string()))?;
+        let entry = self.context.append_basic_block(fn_value, "entry");
+        self.builder.position_at_end(entry);
+        self.function = Some(fn_value);
+        self.return_type = match &function.signature.return_type {
+            Some(type_literal) => self.type_of(type_literal)?,
+            None => Type::unit(),
+        };
+
+        self.scopes.push(HashMap::new());
+        for (param, value) in function.signature.params.iter().zip(fn_value.get_param_iter()) {
+            self.bind_pattern(&param.pattern, value)?;
+        }
+        let value = self.compile_block(&function.inner)?;
+        if self.current_block().get_terminator().is_none() {
+            match (fn_value.get_type().get_return_type(), value) {
+                (None, _) => self.builder.build_return(None)?,
+                (Some(_), Some(value)) => self.builder.build_return(Some(&value))?,
+                // the checker made sure a reachable end of the body has a value
+                (Some(_), None) => self.builder.build_unreachable()?,
+            };
+        }
+        self.scopes.pop();
+        Ok(())
+    }
+
+    fn current_block(&self) -> BasicBlock<'ctx> {
+        self.builder.get_insert_block().unwrap()
+    }
+
+    /// Whether code emitted now could never run, e.g. after `return` or after an
+    /// `if` whose branches both return.
+    fn is_unreachable(&self) -> bool {
+        let block = self.current_block();
+        let is_entry = block.get_parent().and_then(|function| function.get_first_basic_block()) == Some(block);
+        block.get_terminator().is_some() || (!is_entry && block.get_first_use().is_none())
+    }
+
+    /// Stack slots go to the top of the entry block so loops don't grow the stack.
+    fn build_entry_alloca(&self, ty: BasicTypeEnum<'ctx>, name: &str) -> CodegenResult<PointerValue<'ctx>> {
+        let builder = self.context.create_builder();
+        let entry = self.function.unwrap().get_first_basic_block().unwrap();
+        match entry.get_first_instruction() {
+            Some(instruction) => builder.position_before(&instruction),
+            None => builder.position_at_end(entry),
+        }
+        Ok(builder.build_alloca(ty, name)?)
+    }
+
+    fn declare_variable(&mut self, name: &Identifier, ty: BasicTypeEnum<'ctx>) -> CodegenResult<PointerValue<'ctx>> {
+        let pointer = self.build_entry_alloca(ty, name)?;
+        self.scopes.last_mut().unwrap().insert(name.clone(), Variable { pointer, ty });
+        Ok(pointer)
+    }
+
+    fn bind_pattern(&mut self, pattern: &Pattern, value: BasicValueEnum<'ctx>) -> CodegenResult<()> {
+        match &pattern.kind {
+            // an identifier that names a variant binds nothing
+            PatternKind::Identifier(_) if matches!(self.resolution.get(pattern.id), Some(Res::Variant(_))) => {}
+            PatternKind::Identifier(name) => {
+                let pointer = self.declare_variable(name, value.get_type())?;
+                self.builder.build_store(pointer, value)?;
+            }
+            PatternKind::Tuple(patterns) => {
+                let value = value.into_struct_value();
+                for (index, pattern) in patterns.iter().enumerate() {
+                    let field = self.builder.build_extract_value(value, index as u32, "field")?;
+                    self.bind_pattern(pattern, field)?;
+                }
+            }
+            PatternKind::Variant { path, fields } => {
+                let ty = self.concrete(self.type_at(pattern.id));
+                let Some(Res::Variant(variant)) = self.resolution.get(pattern.id) else { return Err(CodegenErr::Undefined(path.path())) };
+                let (_, _, values) = self.variant_fields(value, &ty, &variant.clone())?;
+                for (pattern, value) in field_patterns(fields, values)? {
+                    self.bind_pattern(pattern, value)?;
+                }
+            }
+            PatternKind::Wildcard | PatternKind::Literal(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Whether `value` matches `pattern`, or `None` if it always does. The
+    /// fields of a variant are compared whatever the tag is, and their
+    /// result only picked when the tag is that of the variant.
+    fn pattern_condition(&mut self, pattern: &Pattern, value: BasicValueEnum<'ctx>) -> CodegenResult<Option<IntValue<'ctx>>> {
+        let condition = match &pattern.kind {
+            PatternKind::Identifier(_) => {
+                let Some(Res::Variant(variant)) = self.resolution.get(pattern.id) else { return Ok(None) };
+                let ty = self.concrete(self.type_at(pattern.id));
+                let (tag, expected, _) = self.variant_fields(value, &ty, &variant.clone())?;
+                self.builder.build_int_compare(IntPredicate::EQ, tag, expected, "is_variant")?
+            }
+            PatternKind::Wildcard => return Ok(None),
+            PatternKind::Literal(Literal::String(_)) => return Err(CodegenErr::Unsupported("string patterns")),
+            PatternKind::Literal(literal) => {
+                let expected = match literal {
+                    Literal::Int(literal) => value.get_type().into_int_type().const_int(*literal, false).into(),
+                    Literal::Float(literal) => value.get_type().into_float_type().const_float(*literal).into(),
+                    Literal::Bool(literal) => self.context.bool_type().const_int(*literal as u64, false).into(),
+                    Literal::String(_) => unreachable!(),
+                };
+                let signed = Self::is_signed(self.type_at(pattern.id));
+                self.compile_binary(BinaryOpe::Equal, value, expected, signed, pattern.span)?.into_int_value()
+            }
+            PatternKind::Tuple(patterns) => {
+                let value = value.into_struct_value();
+                let mut condition = None;
+                for (index, pattern) in patterns.iter().enumerate() {
+                    let field = self.builder.build_extract_value(value, index as u32, "field")?;
+                    let inner = self.pattern_condition(pattern, field)?;
+                    condition = self.build_and(condition, inner)?;
+                }
+                return Ok(condition);
+            }
+            PatternKind::Variant { path, fields } => {
+                let ty = self.concrete(self.type_at(pattern.id));
+                let Some(Res::Variant(variant)) = self.resolution.get(pattern.id) else { return Err(CodegenErr::Undefined(path.path())) };
+                let (tag, expected, values) = self.variant_fields(value, &ty, &variant.clone())?;
+                let is_variant = self.builder.build_int_compare(IntPredicate::EQ, tag, expected, "is_variant")?;
+                let mut condition = Some(is_variant);
+                for (pattern, value) in field_patterns(fields, values)? {
+                    let inner = self.pattern_condition(pattern, value)?;
+                    condition = self.build_and(condition, inner)?;
+                }
+                condition.unwrap()
+            }
+        };
+        Ok(Some(condition))
+    }
+
+    /// Both conditions, where `None` always holds. `right` is only picked
+    /// when `left` holds, so what it is otherwise does not matter.
+    fn build_and(&self, left: Option<IntValue<'ctx>>, right: Option<IntValue<'ctx>>) -> CodegenResult<Option<IntValue<'ctx>>> {
+        match (left, right) {
+            (Some(left), Some(right)) => {
+                let false_value = self.context.bool_type().const_zero();
+                Ok(Some(self.builder.build_select(left, right, false_value, "and")?.into_int_value()))
+            }
+            (condition, None) | (None, condition) => Ok(condition),
+        }
+    }
+
+    fn expect_value(value: Value<'ctx>) -> CodegenResult<BasicValueEnum<'ctx>> {
+        value.ok_or(CodegenErr::MissingValue)
+    }
+
+    fn compile_block(&mut self, block: &Block) -> CodegenResult<Value<'ctx>> {
+        self.scopes.push(HashMap::new());
+        let mut value = None;
+        for statement in &block.statements {
+            self.compile_statement(statement)?;
+            if self.is_unreachable() {
+                self.scopes.pop();
+                return Ok(None);
+            }
+        }
+        if let Some(expression) = &block.value {
+            value = self.compile_expression(expression)?;
+        }
+        self.scopes.pop();
+        Ok(value)
+    }
+
+    fn compile_statement(&mut self, statement: &Statement) -> CodegenResult<()> {
+        match &statement.kind {
+            StatementKind::Expression(expression) => {
+                self.compile_expression(expression)?;
+            }
+            StatementKind::Let { pattern, type_annotation, value, .. } => match value {
+                Some(value) => {
+                    if let Some(value) = self.compile_expression(value)? {
+                        self.bind_pattern(pattern, value)?;
+                    }
+                }
+                None => {
+                    let PatternKind::Identifier(name) = &pattern.kind else {
+                        return Err(CodegenErr::Unsupported("uninitialized patterns"));
+                    };
+                    let ty = match type_annotation {
+                        Some(type_annotation) => self.basic_type(type_annotation)?,
+                        None => self.lower_type(&self.type_at(pattern.id).clone())?,
+                    };
+                    if let Some(ty) = ty {
+                        self.declare_variable(name, ty)?;
+                    }
+                }
+            },
+            StatementKind::Assign { target, value } => {
+                let value = Self::expect_value(self.compile_expression(value)?)?;
+                let (pointer, _) = self.place(target)?;
+                self.builder.build_store(pointer, value)?;
+            }
+            StatementKind::Return(value) => {
+                let value = match value {
+                    Some(value) => self.compile_expression(value)?,
+                    None => None,
+                };
+                if !self.is_unreachable() {
+                    self.builder.build_return(value.as_ref().map(|value| value as &dyn BasicValue))?;
+                }
+            }
+            StatementKind::While { condition, body } => {
+                let function = self.function.unwrap();
+                let condition_block = self.context.append_basic_block(function, "while.condition");
+                let body_block = self.context.append_basic_block(function, "while.body");
+                let end_block = self.context.append_basic_block(function, "while.end");
+                self.builder.build_unconditional_branch(condition_block)?;
+
+                self.builder.position_at_end(condition_block);
+                let condition = Self::expect_value(self.compile_expression(condition)?)?.into_int_value();
+                self.builder.build_conditional_branch(condition, body_block, end_block)?;
+
+                self.builder.position_at_end(body_block);
+                self.compile_block(body)?;
+                self.branch_to(condition_block)?;
+
+                self.builder.position_at_end(end_block);
+            }
+            StatementKind::For { .. } => return Err(CodegenErr::Unsupported("for loops")),
+        }
+        Ok(())
+    }
+
+    /// Branches to `target` unless the current block already ended, and returns
+    /// the block the branch comes from.
+    fn branch_to(&self, target: BasicBlock<'ctx>) -> CodegenResult<Option<BasicBlock<'ctx>>> {
+        let current = self.current_block();
+        if self.is_unreachable() {
+            return Ok(None);
+        }
+        self.builder.build_unconditional_branch(target)?;
+        Ok(Some(current))
+    }
+
+    /// Finds the stack slot of a local or the global of a static.
+    fn lookup_variable(&self, id: NodeId, accesser: &Accesser) -> CodegenResult<(PointerValue<'ctx>, BasicTypeEnum<'ctx>)> {
+        if let Some(name) = accesser.as_identifier() {
+            if let Some(variable) = self.scopes.iter().rev().find_map(|scope| scope.get(name)) {
+                return Ok((variable.pointer, variable.ty));
+            }
+        }
+        let path = self.item_path(id, accesser)?;
+        let global = self.module.get_global(path).ok_or_else(|| CodegenErr::Undefined(path.to_string()))?;
+        let ty = BasicTypeEnum::try_from(global.get_value_type()).map_err(|_| CodegenErr::Unsupported("non basic statics"))?;
+        Ok((global.as_pointer_value(), ty))
+    }
+
+    /// The index of the field `name` in the struct of the class of `target`.
+    fn field_index(&mut self, target: &Expression, name: &Identifier) -> CodegenResult<(StructType<'ctx>, u32)> {
+        let ty = self.concrete(self.type_at(target.id));
+        if !matches!(ty, Type::Custom { .. }) {
+            return Err(CodegenErr::Undefined(name.clone()));
+        }
+        let class = self.class(&ty)?;
+        let index = class.fields.iter().position(|field| field == name).ok_or_else(|| CodegenErr::Undefined(name.clone()))?;
+        Ok((class.ty, index as u32))
+    }
+
+    /// The memory an assignment to `target` writes, a variable or a field of
+    /// one. The field of any other value is one of a temporary copy.
+    fn place(&mut self, target: &Expression) -> CodegenResult<(PointerValue<'ctx>, BasicTypeEnum<'ctx>)> {
+        match &target.kind {
+            ExpressionKind::Accesser(accesser) => self.lookup_variable(target.id, accesser),
+            ExpressionKind::Field { target: inner, name } => {
+                let (pointer, _) = self.place(inner)?;
+                let (ty, index) = self.field_index(inner, name)?;
+                let field = self.builder.build_struct_gep(ty, pointer, index, name)?;
+                let field_type = ty.get_field_type_at_index(index).ok_or_else(|| CodegenErr::Undefined(name.clone()))?;
+                Ok((field, field_type))
+            }
+            _ => {
+                let value = Self::expect_value(self.compile_expression(target)?)?;
+                let pointer = self.build_entry_alloca(value.get_type(), "temporary")?;
+                self.builder.build_store(pointer, value)?;
+                Ok((pointer, value.get_type()))
+            }
+        }
+    }
+
+    /// Compiles an expression, and turns its value into a `dyn Trait` where
+    /// the checker says so.
+    fn compile_expression(&mut self, expression: &Expression) -> CodegenResult<Value<'ctx>> {
+        let value = self.compile_uncoerced(expression)?;
+        match (self.types.coercion(expression.id), value) {
+            (Some(Type::Dyn(trait_path)), Some(value)) => {
+                let trait_path = trait_path.clone();
+                let ty = self.concrete(self.type_at(expression.id));
+                Ok(Some(self.build_trait_object(value, &ty, &trait_path)?))
+            }
+            (_, value) => Ok(value),
+        }
+    }
+
+    fn compile_uncoerced(&mut self, expression: &Expression) -> CodegenResult<Value<'ctx>> {
+        let value: BasicValueEnum = match &expression.kind {
+            ExpressionKind::Literal(Literal::String(value)) => self.builder.build_global_string_ptr(value, "string")?.as_pointer_value().into(),
+            ExpressionKind::Literal(_) => self.const_value(expression).unwrap(),
+            // `-128` is one literal, which fits an `i8` while `128` does not
+            ExpressionKind::Unary { ope: UnaryOpe::Minus, operand } if matches!(operand.kind, ExpressionKind::Literal(_)) => {
+                self.const_value(expression).unwrap()
+            }
+            ExpressionKind::Accesser(_) if matches!(self.resolution.get(expression.id), Some(Res::Variant(_))) => {
+                let Some(Res::Variant(path)) = self.resolution.get(expression.id) else { unreachable!() };
+                let (ty, path) = (self.concrete(self.type_at(expression.id)), path.clone());
+                self.build_variant(&ty, &path, Vec::new())?
+            }
+            ExpressionKind::Accesser(accesser) => {
+                let (pointer, ty) = self.lookup_variable(expression.id, accesser)?;
+                self.builder.build_load(ty, pointer, &accesser.path())?
+            }
+            ExpressionKind::Unary { ope: UnaryOpe::Minus, operand } => match Self::expect_value(self.compile_expression(operand)?)? {
+                // only signed integers can be negated, and only `-MIN` overflows
+                BasicValueEnum::IntValue(value) if self.profile == Profile::Debug => {
+                    let zero = value.get_type().const_zero();
+                    let (value, overflowed) = self.build_with_overflow(Operation::Sub, zero, value, true)?;
+                    self.build_trap_if(overflowed, "attempt to negate with overflow", expression.span)?;
+                    value.into()
+                }
+                BasicValueEnum::IntValue(value) => self.builder.build_int_neg(value, "neg")?.into(),
+                BasicValueEnum::FloatValue(value) => self.builder.build_float_neg(value, "neg")?.into(),
+                _ => return Err(CodegenErr::Unsupported("negation of non numbers")),
+            },
+            ExpressionKind::Binary { ope, left, right } => {
+                let signed = Self::is_signed(self.type_at(left.id));
+                let left = Self::expect_value(self.compile_expression(left)?)?;
+                let right = Self::expect_value(self.compile_expression(right)?)?;
+                self.compile_binary(*ope, left, right, signed, expression.span)?
+            }
+            ExpressionKind::Cast { value, .. } => {
+                let from = self.type_at(value.id).clone();
+                let compiled = Self::expect_value(self.compile_expression(value)?)?;
+                let to = self.type_at(expression.id).clone();
+                self.compile_cast(compiled, &from, &to)?
+            }
+            // `target.name(args)` calls `Class::name` with `target` as the first argument
+            ExpressionKind::Call { callee, args } if matches!(callee.kind, ExpressionKind::Field { .. }) => {
+                let ExpressionKind::Field { target, name } = &callee.kind else { unreachable!() };
+                if let Some(call) = self.dyn_call(expression.id) {
+                    return self.build_dyn_call(&call, target, args);
+                }
+                let path = match (self.trait_call(expression.id), self.type_at(target.id)) {
+                    (Some(path), _) => path,
+                    (None, Type::Custom { name: class, .. }) => format!("{}::{}", class, name),
+                    (None, _) => return Err(CodegenErr::Undefined(name.clone())),
+                };
+                let function = match self.generics.contains_key(&path) {
+                    true => self.instance(&path, expression.id)?,
+                    false => self.module.get_function(&path).ok_or(CodegenErr::Undefined(path))?,
+                };
+                let mut values = Vec::<BasicMetadataValueEnum>::new();
+                for arg in std::iter::once(&**target).chain(args) {
+                    values.push(Self::expect_value(self.compile_expression(arg)?)?.into());
+                }
+                let call = self.builder.build_call(function, &values, "call")?;
+                return Ok(call.try_as_basic_value().left());
+            }
+            ExpressionKind::Call { callee, args } => {
+                let ExpressionKind::Accesser(accesser) = &callee.kind else {
+                    return Err(CodegenErr::Unsupported("calls of computed functions"));
+                };
+                if let (Some(Res::Intrinsic(intrinsic)), [left, right]) = (self.resolution.get(callee.id), args.as_slice()) {
+                    let intrinsic = *intrinsic;
+                    let signed = Self::is_signed(self.type_at(left.id));
+                    let left = Self::expect_value(self.compile_expression(left)?)?.into_int_value();
+                    let right = Self::expect_value(self.compile_expression(right)?)?.into_int_value();
+                    let ty = self.concrete(self.type_at(expression.id));
+                    return Ok(Some(self.build_intrinsic(intrinsic, left, right, signed, &ty)?));
+                }
+                if let Some(Res::Variant(path)) = self.resolution.get(callee.id) {
+                    let (ty, path) = (self.concrete(self.type_at(expression.id)), path.clone());
+                    let mut values = Vec::new();
+                    for (index, arg) in args.iter().enumerate() {
+                        values.push((index, Self::expect_value(self.compile_expression(arg)?)?));
+                    }
+                    return Ok(Some(self.build_variant(&ty, &path, values)?));
+                }
+                // the `self` of a function of a trait is its first parameter
+                if let (Some(call), [receiver, args @ ..]) = (self.dyn_call(expression.id), args.as_slice()) {
+                    return self.build_dyn_call(&call, receiver, args);
+                }
+                let path = match self.trait_call(expression.id) {
+                    Some(path) => path,
+                    None => self.item_path(callee.id, accesser)?.to_string(),
+                };
+                let function = match self.generics.contains_key(&path) {
+                    true => self.instance(&path, expression.id)?,
+                    false => self.module.get_function(&path).ok_or(CodegenErr::Undefined(path))?,
+                };
+                let mut values = Vec::<BasicMetadataValueEnum>::new();
+                for arg in args {
+                    values.push(Self::expect_value(self.compile_expression(arg)?)?.into());
+                }
+                let call = self.builder.build_call(function, &values, "call")?;
+                return Ok(call.try_as_basic_value().left());
+            }
+            ExpressionKind::Field { target, name } => {
+                let (_, index) = self.field_index(target, name)?;
+                let value = Self::expect_value(self.compile_expression(target)?)?.into_struct_value();
+                self.builder.build_extract_value(value, index, name)?
+            }
+            ExpressionKind::Struct { fields, .. } if matches!(self.resolution.get(expression.id), Some(Res::Variant(_))) => {
+                let Some(Res::Variant(path)) = self.resolution.get(expression.id) else { unreachable!() };
+                let (ty, path) = (self.concrete(self.type_at(expression.id)), path.clone());
+                let (index, layout) = self.variant(&ty, &path)?;
+                let names = layout.variants[index].fields.clone();
+                let mut values = Vec::new();
+                for field in fields {
+                    let index = names.iter().position(|name| *name == field.name).ok_or_else(|| CodegenErr::Undefined(field.name.clone()))?;
+                    values.push((index, Self::expect_value(self.compile_expression(&field.value)?)?));
+                }
+                self.build_variant(&ty, &path, values)?
+            }
+            // fields are evaluated in the order they are written, and stored in declaration order
+            ExpressionKind::Struct { class, fields } => {
+                let ty = self.concrete(self.type_at(expression.id));
+                if !matches!(ty, Type::Custom { .. }) {
+                    return Err(CodegenErr::Undefined(class.path()));
+                }
+                let class = self.class(&ty)?;
+                let (ty, names) = (class.ty, class.fields.clone());
+                let mut value = ty.get_undef();
+                for field in fields {
+                    let index = names.iter().position(|name| *name == field.name).ok_or_else(|| CodegenErr::Undefined(field.name.clone()))?;
+                    let field_value = Self::expect_value(self.compile_expression(&field.value)?)?;
+                    value = self.builder.build_insert_value(value, field_value, index as u32, &field.name)?.into_struct_value();
+                }
+                value.into()
+            }
+            ExpressionKind::Tuple(inner) if inner.is_empty() => return Ok(None),
+            ExpressionKind::Tuple(inner) => {
+                let mut values = Vec::new();
+                for expression in inner {
+                    values.push(Self::expect_value(self.compile_expression(expression)?)?);
+                }
+                let types: Vec<BasicTypeEnum> = values.iter().map(|value| value.get_type()).collect();
+                let mut tuple = self.context.struct_type(&types, false).get_undef();
+                for (index, value) in values.into_iter().enumerate() {
+                    tuple = self.builder.build_insert_value(tuple, value, index as u32, "tuple")?.into_struct_value();
+                }
+                tuple.into()
+            }
+            ExpressionKind::If(if_expression) => return self.compile_if(if_expression),
+            ExpressionKind::Match(match_expression) => return self.compile_match(match_expression),
+            ExpressionKind::Try(value) => return self.compile_try(value),
+            ExpressionKind::Block(block) => return self.compile_block(block),
+        };
+        Ok(Some(value))
+    }
+
+    /// `signed` picks the integer division and comparisons; `char` and unsigned integers compare unsigned.
+    /// A runtime error in the operation points at `span`.
+    fn compile_binary(&self, ope: BinaryOpe, left: BasicValueEnum<'ctx>, right: BasicValueEnum<'ctx>, signed: bool, span: Span) -> CodegenResult<BasicValueEnum<'ctx>> {
+        let predicate = |signed_predicate, unsigned_predicate| if signed { signed_predicate } else { unsigned_predicate };
+        let value = match (left, right) {
+            (BasicValueEnum::IntValue(left), BasicValueEnum::IntValue(right)) => match ope {
+                BinaryOpe::Add => self.build_arithmetic(Operation::Add, left, right, signed, span)?,
+                BinaryOpe::Sub => self.build_arithmetic(Operation::Sub, left, right, signed, span)?,
+                BinaryOpe::Mul => self.build_arithmetic(Operation::Mul, left, right, signed, span)?,
+                BinaryOpe::Div => self.build_division(left, right, signed, span)?,
+                BinaryOpe::Equal => self.builder.build_int_compare(IntPredicate::EQ, left, right, "eq")?,
+                BinaryOpe::NotEqual => self.builder.build_int_compare(IntPredicate::NE, left, right, "ne")?,
+                BinaryOpe::Lesser => self.builder.build_int_compare(predicate(IntPredicate::SLT, IntPredicate::ULT), left, right, "lt")?,
+                BinaryOpe::Greater => self.builder.build_int_compare(predicate(IntPredicate::SGT, IntPredicate::UGT), left, right, "gt")?,
+                BinaryOpe::LesserEqual => self.builder.build_int_compare(predicate(IntPredicate::SLE, IntPredicate::ULE), left, right, "le")?,
+                BinaryOpe::GreaterEqual => self.builder.build_int_compare(predicate(IntPredicate::SGE, IntPredicate::UGE), left, right, "ge")?,
+            }.into(),
+            (BasicValueEnum::FloatValue(left), BasicValueEnum::FloatValue(right)) => match ope {
+                BinaryOpe::Add => self.builder.build_float_add(left, right, "add")?.into(),
+                BinaryOpe::Sub => self.builder.build_float_sub(left, right, "sub")?.into(),
+                BinaryOpe::Mul => self.builder.build_float_mul(left, right, "mul")?.into(),
+                BinaryOpe::Div => self.builder.build_float_div(left, right, "div")?.into(),
+                BinaryOpe::Equal => self.builder.build_float_compare(FloatPredicate::OEQ, left, right, "eq")?.into(),
+                BinaryOpe::NotEqual => self.builder.build_float_compare(FloatPredicate::UNE, left, right, "ne")?.into(),
+                BinaryOpe::Lesser => self.builder.build_float_compare(FloatPredicate::OLT, left, right, "lt")?.into(),
+                BinaryOpe::Greater => self.builder.build_float_compare(FloatPredicate::OGT, left, right, "gt")?.into(),
+                BinaryOpe::LesserEqual => self.builder.build_float_compare(FloatPredicate::OLE, left, right, "le")?.into(),
+                BinaryOpe::GreaterEqual => self.builder.build_float_compare(FloatPredicate::OGE, left, right, "ge")?.into(),
+            },
+            _ => return Err(CodegenErr::Unsupported("operators on non numbers")),
+        };
+        Ok(value)
+    }
+
+    /// Lowers `as` with the semantics documented in [`crate::numeric`]. Floats
+    /// become integers through the saturating `llvm.fpto[su]i.sat` intrinsics,
+    /// as the plain instructions are undefined for values out of range.
+    fn compile_cast(&mut self, value: BasicValueEnum<'ctx>, from: &Type, to: &Type) -> CodegenResult<BasicValueEnum<'ctx>> {
+        let target = self.lower_type(to)?.ok_or(CodegenErr::Unsupported("casts to unit"))?;
+        let value = match (value, target) {
+            (BasicValueEnum::IntValue(value), BasicTypeEnum::IntType(target)) => {
+                self.builder.build_int_cast_sign_flag(value, target, Self::is_signed(from), "cast")?.into()
+            }
+            (BasicValueEnum::IntValue(value), BasicTypeEnum::FloatType(target)) if Self::is_signed(from) => {
+                self.builder.build_signed_int_to_float(value, target, "cast")?.into()
+            }
+            (BasicValueEnum::IntValue(value), BasicTypeEnum::FloatType(target)) => self.builder.build_unsigned_int_to_float(value, target, "cast")?.into(),
+            (BasicValueEnum::FloatValue(value), BasicTypeEnum::FloatType(target)) => self.builder.build_float_cast(value, target, "cast")?.into(),
+            (BasicValueEnum::FloatValue(value), BasicTypeEnum::IntType(target)) => {
+                let name = if Self::is_signed(to) { "llvm.fptosi.sat" } else { "llvm.fptoui.sat" };
+                self.call_intrinsic(name, &[target.into(), value.get_type().into()], &[value.into()])?
+            }
+            _ => return Err(CodegenErr::Unsupported("casts of non numbers")),
+        };
+        Ok(value)
+    }
+
+    fn call_intrinsic(&self, name: &str, types: &[BasicTypeEnum<'ctx>], args: &[BasicMetadataValueEnum<'ctx>]) -> CodegenResult<BasicValueEnum<'ctx>> {
+        let function = LlvmIntrinsic::find(name)
+            .and_then(|intrinsic| intrinsic.get_declaration(&self.module, types))
+            .ok_or_else(|| CodegenErr::Undefined(name.to_string()))?;
+        let call = self.builder.build_call(function, args, "intrinsic")?;
+        call.try_as_basic_value().left().ok_or(CodegenErr::MissingValue)
+    }
+
+    /// `+`, `-` or `*` on integers, which traps on overflow in debug builds and wraps in release builds.
+    fn build_arithmetic(&self, operation: Operation, left: IntValue<'ctx>, right: IntValue<'ctx>, signed: bool, span: Span) -> CodegenResult<IntValue<'ctx>> {
+        match self.profile {
+            Profile::Debug => self.build_checked(operation, left, right, signed, span),
+            Profile::Release => self.build_wrapping(operation, left, right),
+        }
+    }
+
+    fn build_wrapping(&self, operation: Operation, left: IntValue<'ctx>, right: IntValue<'ctx>) -> CodegenResult<IntValue<'ctx>> {
+        let value = match operation {
+            Operation::Add => self.builder.build_int_add(left, right, "add")?,
+            Operation::Sub => self.builder.build_int_sub(left, right, "sub")?,
+            Operation::Mul => self.builder.build_int_mul(left, right, "mul")?,
+        };
+        Ok(value)
+    }
+
+    fn build_checked(&self, operation: Operation, left: IntValue<'ctx>, right: IntValue<'ctx>, signed: bool, span: Span) -> CodegenResult<IntValue<'ctx>> {
+        let (value, overflowed) = self.build_with_overflow(operation, left, right, signed)?;
+        self.build_trap_if(overflowed, &format!("attempt to {} with overflow", operation.verb()), span)?;
+        Ok(value)
+    }
+
+    /// The wrapped result of the operation and whether it overflowed, from `llvm.[su]{add,sub,mul}.with.overflow`.
+    fn build_with_overflow(&self, operation: Operation, left: IntValue<'ctx>, right: IntValue<'ctx>, signed: bool) -> CodegenResult<(IntValue<'ctx>, IntValue<'ctx>)> {
+        let name = format!("llvm.{}{}.with.overflow", if signed { "s" } else { "u" }, Self::operation_name(operation));
+        let result = self.call_intrinsic(&name, &[left.get_type().into()], &[left.into(), right.into()])?.into_struct_value();
+        let value = self.builder.build_extract_value(result, 0, "value")?.into_int_value();
+        let overflowed = self.builder.build_extract_value(result, 1, "overflowed")?.into_int_value();
+        Ok((value, overflowed))
+    }
+
+    fn operation_name(operation: Operation) -> &'static str {
+        match operation {
+            Operation::Add => "add",
+            Operation::Sub => "sub",
+            Operation::Mul => "mul",
+        }
+    }
+
+    /// Lowers a call of `intrinsic`, whose result is of type `ty`.
+    fn build_intrinsic(&mut self, intrinsic: Intrinsic, left: IntValue<'ctx>, right: IntValue<'ctx>, signed: bool, ty: &Type) -> CodegenResult<BasicValueEnum<'ctx>> {
+        let sign = if signed { "s" } else { "u" };
+        let value = match (intrinsic.overflow, intrinsic.operation) {
+            (Overflow::Wrapping, operation) => self.build_wrapping(operation, left, right)?,
+            (Overflow::Checked, operation) => {
+                let (value, overflowed) = self.build_with_overflow(operation, left, right, signed)?;
+                let some = self.build_variant(ty, &format!("{}::Some", prelude::OPTION), vec![(0, value.into())])?;
+                let none = self.build_variant(ty, &format!("{}::None", prelude::OPTION), Vec::new())?;
+                return Ok(self.builder.build_select(overflowed, none, some, "checked")?);
+            }
+            (Overflow::Saturating, Operation::Mul) => {
+                // LLVM has no saturating multiplication: the bound is picked by the sign the result should have
+                let (value, overflowed) = self.build_with_overflow(Operation::Mul, left, right, signed)?;
+                let int_type = left.get_type();
+                let bound = if signed {
+                    let min_bit = 1 << (int_type.get_bit_width() - 1);
+                    let signs_differ = self.builder.build_xor(left, right, "signs")?;
+                    let negative = self.builder.build_int_compare(IntPredicate::SLT, signs_differ, int_type.const_zero(), "negative")?;
+                    self.builder.build_select(negative, int_type.const_int(min_bit, false), int_type.const_int(min_bit - 1, false), "bound")?.into_int_value()
+                } else {
+                    int_type.const_all_ones()
+                };
+                self.builder.build_select(overflowed, bound, value, "saturated")?.into_int_value()
+            }
+            (Overflow::Saturating, operation) => {
+                let name = format!("llvm.{}{}.sat", sign, Self::operation_name(operation));
+                self.call_intrinsic(&name, &[left.get_type().into()], &[left.into(), right.into()])?.into_int_value()
+            }
+        };
+        Ok(value.into())
+    }
+
+    /// Integer division traps on a zero divisor in every build, as LLVM
+    /// leaves it undefined, and so does the one signed division that
+    /// overflows, `MIN / -1`.
+    fn build_division(&self, left: IntValue<'ctx>, right: IntValue<'ctx>, signed: bool, span: Span) -> CodegenResult<IntValue<'ctx>> {
+        let ty = right.get_type();
+        let is_zero = self.builder.build_int_compare(IntPredicate::EQ, right, ty.const_zero(), "is_zero")?;
+        self.build_trap_if(is_zero, "attempt to divide by zero", span)?;
+        if !signed {
+            return Ok(self.builder.build_int_unsigned_div(left, right, "div")?);
+        }
+        let is_min = self.builder.build_int_compare(IntPredicate::EQ, left, ty.const_int(1 << (ty.get_bit_width() - 1), false), "is_min")?;
+        let is_minus_one = self.builder.build_int_compare(IntPredicate::EQ, right, ty.const_all_ones(), "is_minus_one")?;
+        let overflows = self.builder.build_and(is_min, is_minus_one, "overflows")?;
+        self.build_trap_if(overflows, "attempt to divide with overflow", span)?;
+        Ok(self.builder.build_int_signed_div(left, right, "div")?)
+    }
+
+    /// `file:line:column` of `span`, for runtime errors.
+    fn location(&self, span: Span) -> String {
+        match self.sources.file(span.start) {
+            Some(file) => {
+                let (line, column) = file.line_col(span.start);
+                format!("{}:{}:{}", file.name, line, column)
+            }
+            None => format!("offset {}", span.start),
+        }
+    }
+
+    /// Stops the program with `message` and the location of `span` when `condition` holds.
+    fn build_trap_if(&self, condition: IntValue<'ctx>, message: &str, span: Span) -> CodegenResult<()> {
+        let function = self.function.unwrap();
+        let trap_block = self.context.append_basic_block(function, "trap");
+        let continue_block = self.context.append_basic_block(function, "trap.skip");
+        self.builder.build_conditional_branch(condition, trap_block, continue_block)?;
+
+        self.builder.position_at_end(trap_block);
+        let text = format!("{}: {}\n", self.location(span), message);
+        let message = self.builder.build_global_string_ptr(&text, "trap.message")?.as_pointer_value();
+        let length = self.context.i64_type().const_int(text.len() as u64, false);
+        self.builder.build_call(self.trap_function(message.get_type())?, &[message.into(), length.into()], "")?;
+        self.builder.build_unreachable()?;
+
+        self.builder.position_at_end(continue_block);
+        Ok(())
+    }
+
+    /// Defines `sonneko.trap(message, length)` on first use. It writes the
+    /// message to stderr and exits with `TRAP_EXIT_CODE`, through the C library
+    /// the program is linked with.
+    fn trap_function(&self, pointer_type: PointerType<'ctx>) -> CodegenResult<FunctionValue<'ctx>> {
+        if let Some(trap) = self.module.get_function(TRAP) {
+            return Ok(trap);
+        }
+        let (i32_type, i64_type, void_type) = (self.context.i32_type(), self.context.i64_type(), self.context.void_type());
+        let write = self.module.add_function("write", i64_type.fn_type(&[i32_type.into(), pointer_type.into(), i64_type.into()], false), Some(Linkage::External));
+        let exit = self.module.add_function("exit", void_type.fn_type(&[i32_type.into()], false), Some(Linkage::External));
+        let trap = self.module.add_function(TRAP, void_type.fn_type(&[pointer_type.into(), i64_type.into()], false), Some(Linkage::Internal));
+        for attribute in ["noreturn", "cold", "noinline"] {
+            trap.add_attribute(AttributeLoc::Function, self.context.create_enum_attribute(Attribute::get_named_enum_kind_id(attribute), 0));
+        }
+
+        let builder = self.context.create_builder();
+        builder.position_at_end(self.context.append_basic_block(trap, "entry"));
+        let (message, length) = (trap.get_nth_param(0).unwrap(), trap.get_nth_param(1).unwrap());
+        let stderr = i32_type.const_int(2, false);
+        builder.build_call(write, &[stderr.into(), message.into(), length.into()], "")?;
+        builder.build_call(exit, &[i32_type.const_int(TRAP_EXIT_CODE, false).into()], "")?;
+        builder.build_unreachable()?;
+        Ok(trap)
+    }
+
+    /// Moves `value` of type `ty` to the heap and pairs it with the vtable of
+    /// `ty` for the trait. Nothing tells when a `dyn` value is no longer used,
+    /// so its memory is never freed.
+    fn build_trait_object(&mut self, value: BasicValueEnum<'ctx>, ty: &Type, trait_path: &str) -> CodegenResult<BasicValueEnum<'ctx>> {
+        let size = value.get_type().size_of().ok_or(CodegenErr::Unsupported("unsized values"))?;
+        let data = self.builder.build_call(self.malloc_function(), &[size.into()], "dyn.data")?;
+        let data = data.try_as_basic_value().left().ok_or(CodegenErr::MissingValue)?.into_pointer_value();
+        self.builder.build_store(data, value)?;
+        let vtable = self.vtable(trait_path, ty)?;
+        let object = self.builder.build_insert_value(self.dyn_type().get_undef(), data, 0, "dyn")?;
+        Ok(self.builder.build_insert_value(object, vtable, 1, "dyn")?.as_basic_value_enum())
+    }
+
+    /// Calls the function of a trait on a `dyn` of the trait: the function
+    /// is taken from the vtable of `receiver`, and takes its value by pointer.
+    fn build_dyn_call(&mut self, call: &TraitCall, receiver: &Expression, args: &[Expression]) -> CodegenResult<Value<'ctx>> {
+        let signatures = self.trait_functions.get(&call.trait_path).ok_or_else(|| CodegenErr::Undefined(call.trait_path.clone()))?;
+        let index = signatures.iter().position(|signature| signature.name == call.name).ok_or_else(|| CodegenErr::Undefined(call.name.clone()))?;
+        let (count, signature) = (signatures.len() as u32, signatures[index].clone());
+        let pointer = self.pointer_type();
+        let mut params = vec![BasicMetadataTypeEnum::from(pointer)];
+        for param in signature.params.iter().skip(1) {
+            params.push(self.basic_type(&param.type_annotation)?.ok_or(CodegenErr::Unsupported("unit parameters"))?.into());
+        }
+        let fn_type = match &signature.return_type {
+            Some(return_type) => match self.basic_type(return_type)? {
+                Some(ty) => ty.fn_type(&params, false),
+                None => self.context.void_type().fn_type(&params, false),
+            },
+            None => self.context.void_type().fn_type(&params, false),
+        };
+
+        let object = Self::expect_value(self.compile_expression(receiver)?)?.into_struct_value();
+        let data = self.builder.build_extract_value(object, 0, "dyn.data")?;
+        let vtable = self.builder.build_extract_value(object, 1, "dyn.vtable")?.into_pointer_value();
+        let functions = self.builder.build_load(pointer.array_type(count), vtable, "vtable")?.into_array_value();
+        let function = self.builder.build_extract_value(functions, index as u32, &call.name)?.into_pointer_value();
+        let mut values = vec![BasicMetadataValueEnum::from(data)];
+        for arg in args {
+            values.push(Self::expect_value(self.compile_expression(arg)?)?.into());
+        }
+        let call = self.builder.build_indirect_call(fn_type, function, &values, "call")?;
+        Ok(call.try_as_basic_value().left())
+    }
+
+    /// The vtable of `ty` for the trait: an array with a function for every
+    /// function of the trait, in the order the trait declares them. It is
+    /// emitted the first time a value of `ty` becomes a `dyn` of the trait.
+    fn vtable(&self, trait_path: &str, ty: &Type) -> CodegenResult<PointerValue<'ctx>> {
+        let name = format!("sonneko.vtable.<{} as {}>", ty, trait_path);
+        if let Some(global) = self.module.get_global(&name) {
+            return Ok(global.as_pointer_value());
+        }
+        let signatures = self.trait_functions.get(trait_path).ok_or_else(|| CodegenErr::Undefined(trait_path.to_string()))?;
+        let mut functions = Vec::new();
+        for signature in signatures {
+            functions.push(self.dyn_function(trait_path, ty, &signature.name)?.as_global_value().as_pointer_value());
+        }
+        let table = self.pointer_type().const_array(&functions);
+        let global = self.module.add_global(table.get_type(), None, &name);
+        global.set_initializer(&table);
+        global.set_constant(true);
+        global.set_linkage(Linkage::Internal);
+        Ok(global.as_pointer_value())
+    }
+
+    /// The function of the vtable of `ty` for `name`: it loads `self` from
+    /// the pointer it is given and calls the function of the impl.
+    fn dyn_function(&self, trait_path: &str, ty: &Type, name: &str) -> CodegenResult<FunctionValue<'ctx>> {
+        let path = impl_function(trait_path, ty, name);
+        let dyn_path = format!("{}.dyn", path);
+        if let Some(function) = self.module.get_function(&dyn_path) {
+            return Ok(function);
+        }
+        let target = self.module.get_function(&path).ok_or(CodegenErr::Undefined(path))?;
+        let target_type = target.get_type();
+        let mut params = target_type.get_param_types();
+        let self_type = BasicTypeEnum::try_from(params[0]).map_err(|_| CodegenErr::Unsupported("unit receivers"))?;
+        params[0] = self.pointer_type().into();
+        let fn_type = match target_type.get_return_type() {
+            Some(ty) => ty.fn_type(&params, false),
+            None => self.context.void_type().fn_type(&params, false),
+        };
+        let function = self.module.add_function(&dyn_path, fn_type, Some(Linkage::Internal));
+
+        let builder = self.context.create_builder();
+        builder.position_at_end(self.context.append_basic_block(function, "entry"));
+        let mut args: Vec<BasicMetadataValueEnum> = function.get_param_iter().map(Into::into).collect();
+        args[0] = builder.build_load(self_type, function.get_first_param().unwrap().into_pointer_value(), "self")?.into();
+        match builder.build_call(target, &args, "call")?.try_as_basic_value().left() {
+            Some(value) => builder.build_return(Some(&value))?,
+            None => builder.build_return(None)?,
+        };
+        Ok(function)
+    }
+
+    /// Declares `malloc` of the C library on first use.
+    fn malloc_function(&self) -> FunctionValue<'ctx> {
+        self.module.get_function("malloc").unwrap_or_else(|| {
+            let fn_type = self.pointer_type().fn_type(&[self.context.i64_type().into()], false);
+            self.module.add_function("malloc", fn_type, Some(Linkage::External))
+        })
+    }
+
+    /// Lowers `if` to a diamond. When both branches reach the end with a value
+    /// they are merged with a phi node; a branch that returns contributes nothing.
+    fn compile_if(&mut self, if_expression: &IfExpression) -> CodegenResult<Value<'ctx>> {
+        let condition = Self::expect_value(self.compile_expression(&if_expression.condition)?)?.into_int_value();
+        let function = self.function.unwrap();
+        let then_block = self.context.append_basic_block(function, "if.then");
+        let else_block = self.context.append_basic_block(function, "if.else");
+        let merge_block = self.context.append_basic_block(function, "if.merge");
+        self.builder.build_conditional_branch(condition, then_block, else_block)?;
+
+        self.builder.position_at_end(then_block);
+        let then_value = self.compile_block(&if_expression.then_branch)?;
+        let then_end = self.branch_to(merge_block)?;
+
+        self.builder.position_at_end(else_block);
+        let else_value = match &if_expression.else_branch {
+            Some(else_branch) => self.compile_expression(else_branch)?,
+            None => None,
+        };
+        let else_end = self.branch_to(merge_block)?;
+
+        self.builder.position_at_end(merge_block);
+        let value = match (then_value.zip(then_end), else_value.zip(else_end)) {
+            (Some((then_value, then_end)), Some((else_value, else_end))) => {
+                let phi = self.builder.build_phi(then_value.get_type(), "if.value")?;
+                phi.add_incoming(&[(&then_value, then_end), (&else_value, else_end)]);
+                Some(phi.as_basic_value())
+            }
+            (Some((value, _)), None) if else_end.is_none() => Some(value),
+            (None, Some((value, _))) if then_end.is_none() => Some(value),
+            _ => None,
+        };
+        Ok(value)
+    }
+
+    /// Returns a `None` or an `Err` of the return type of the function when
+    /// `value` is one, with the error of `value`. Otherwise the value of the
+    /// expression is the one inside the `Some` or `Ok`.
+    fn compile_try(&mut self, value: &Expression) -> CodegenResult<Value<'ctx>> {
+        let ty = self.concrete(self.type_at(value.id));
+        let Type::Custom { name, .. } = &ty else { return Err(CodegenErr::Undefined(ty.to_string())) };
+        let (kept, returned) = if name == prelude::OPTION { ("Some", "None") } else { ("Ok", "Err") };
+        let (kept, returned) = (format!("{}::{}", name, kept), format!("{}::{}", name, returned));
+        let operand = Self::expect_value(self.compile_expression(value)?)?;
+        let (tag, expected, mut fields) = self.variant_fields(operand, &ty, &kept)?;
+
+        let function = self.function.unwrap();
+        let return_block = self.context.append_basic_block(function, "try.return");
+        let continue_block = self.context.append_basic_block(function, "try.continue");
+        let is_kept = self.builder.build_int_compare(IntPredicate::EQ, tag, expected, "is_kept")?;
+        self.builder.build_conditional_branch(is_kept, continue_block, return_block)?;
+
+        self.builder.position_at_end(return_block);
+        let (_, _, errors) = self.variant_fields(operand, &ty, &returned)?;
+        let return_type = self.return_type.clone();
+        let early = self.build_variant(&return_type, &returned, errors.into_iter().map(|(_, error)| error).enumerate().collect())?;
+        self.builder.build_return(Some(&early))?;
+
+        self.builder.position_at_end(continue_block);
+        Ok(fields.pop().map(|(_, value)| value))
+    }
+
+    /// Lowers `match` to a chain of tests, one arm after the other. The arms
+    /// that reach the end with a value are merged with a phi node, and the
+    /// end of the chain is unreachable as the arms cover every value.
+    fn compile_match(&mut self, match_expression: &MatchExpression) -> CodegenResult<Value<'ctx>> {
+        let scrutinee = self.compile_expression(&match_expression.scrutinee)?;
+        let function = self.function.unwrap();
+        let end_block = self.context.append_basic_block(function, "match.end");
+        let mut incoming = Vec::new();
+        for arm in &match_expression.arms {
+            let arm_block = self.context.append_basic_block(function, "match.arm");
+            let next_block = self.context.append_basic_block(function, "match.next");
+            let condition = match scrutinee {
+                Some(scrutinee) => self.pattern_condition(&arm.pattern, scrutinee)?,
+                // `()` only matches patterns that match anything
+                None => None,
+            };
+            match condition {
+                Some(condition) => self.builder.build_conditional_branch(condition, arm_block, next_block)?,
+                None => self.builder.build_unconditional_branch(arm_block)?,
+            };
+
+            self.builder.position_at_end(arm_block);
+            self.scopes.push(HashMap::new());
+            if let Some(scrutinee) = scrutinee {
+                self.bind_pattern(&arm.pattern, scrutinee)?;
+            }
+            let value = self.compile_expression(&arm.value)?;
+            self.scopes.pop();
+            if let (Some(end), Some(value)) = (self.branch_to(end_block)?, value) {
+                incoming.push((value, end));
+            }
+            self.builder.position_at_end(next_block);
+        }
+        self.builder.build_unreachable()?;
+
+        self.builder.position_at_end(end_block);
+        let Some((first, _)) = incoming.first() else { return Ok(None) };
+        let phi = self.builder.build_phi(first.get_type(), "match.value")?;
+        for (value, block) in &incoming {
+            phi.add_incoming(&[(value, *block)]);
+        }
+        Ok(Some(phi.as_basic_value()))
+    }
+}
+
+/// The subpatterns of the fields of an enum pattern with the values of the
+/// fields they match.
+fn field_patterns<'p, 'ctx>(fields: &'p PatternFields, values: Fields<'ctx>) -> CodegenResult<Vec<(&'p Pattern, BasicValueEnum<'ctx>)>> {
+    match fields {
+        PatternFields::Unit => Ok(Vec::new()),
+        PatternFields::Tuple(patterns) => Ok(patterns.iter().zip(values.into_iter().map(|(_, value)| value)).collect()),
+        PatternFields::Struct(fields) => fields
+            .iter()
+            .map(|field| match values.iter().find(|(name, _)| *name == field.name) {
+                Some((_, value)) => Ok((&field.pattern, *value)),
+                None => Err(CodegenErr::Undefined(field.name.clone())),
+            })
+            .collect(),
+    }
+}
+
+/// The size and alignment in bytes of a value of type `ty`, by the rules of C
+/// for structs. They are at least those LLVM gives the type on any target.
+fn layout(ty: BasicTypeEnum) -> CodegenResult<(u64, u64)> {
+    let layout = match ty {
+        BasicTypeEnum::IntType(ty) => {
+            let size = u64::from(ty.get_bit_width()).div_ceil(8).next_power_of_two();
+            (size, size)
+        }
+        BasicTypeEnum::FloatType(ty) => {
+            let size = if ty == ty.get_context().f32_type() { 4 } else { 8 };
+            (size, size)
+        }
+        BasicTypeEnum::PointerType(_) => (8, 8),
+        BasicTypeEnum::ArrayType(ty) => {
+            let (size, align) = layout(ty.get_element_type())?;
+            (size * u64::from(ty.len()), align)
+        }
+        BasicTypeEnum::StructType(ty) => {
+            let (mut size, mut align) = (0u64, 1);
+            for field in ty.get_field_types() {
+                let (field_size, field_align) = layout(field)?;
+                size = size.next_multiple_of(field_align) + field_size;
+                align = align.max(field_align);
+            }
+            (size.next_multiple_of(align), align)
+        }
+        _ => return Err(CodegenErr::Unsupported("vector fields")),
+    };
+    Ok(layout)
+}
+
+/// `types` as they are listed in the name of an instance, like `i32, bool`.
+fn type_list(types: &[Type]) -> String {
+    types.iter().map(Type::to_string).collect::<Vec<_>>().join(", ")
+}
+
+/// The name of the function `name` of the impl of a trait for `self_type`,
+/// like `<shapes::Square as Shape>::area`.
+fn impl_function(trait_path: &str, self_type: &Type, name: &str) -> String {
+    format!("<{} as {}>::{}", self_type, trait_path, name)
+}
@@ -0,0 +1,47 @@
+use std::fmt;
+
+use inkwell::builder::BuilderError;
+
+use crate::diagnostic::Diagnostic;
+
+pub enum CodegenErr {
+    /// the checker let through a language feature the backend can not lower yet
+    Unsupported(&'static str),
+    /// the checker let through a static whose value is not a literal
+    NonConstantStatic(String),
+    Undefined(String),
+    /// a unit typed expression was used where a value is needed
+    MissingValue,
+    Builder(BuilderError),
+    Verify(String),
+    /// the host target could not be set up or the object file not written
+    Target(String),
+}
+
+impl From<BuilderError> for CodegenErr {
+    fn from(error: BuilderError) -> Self {
+        CodegenErr::Builder(error)
+    }
+}
+
+impl CodegenErr {
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let diagnostic = match self {
+            Self::Unsupported(feature) => return Diagnostic::error(format!("{} are not supported by the LLVM backend yet", feature)),
+            Self::NonConstantStatic(name) => Diagnostic::error(format!("initial value of static `{}` is not a constant", name)),
+            Self::Undefined(name) => Diagnostic::error(format!("`{}` is not defined", name)),
+            Self::MissingValue => Diagnostic::error("expression has no value"),
+            Self::Builder(error) => Diagnostic::error(format!("LLVM builder failed: {}", error)),
+            Self::Verify(message) => Diagnostic::error(format!("generated module is invalid: {}", message)),
+            Self::Target(message) => return Diagnostic::error(format!("can not emit code for the host: {}", message)),
+        };
+        diagnostic.with_note("the checker should have rejected this program; this is a bug in the compiler")
+    }
+}
+
+impl fmt::Debug for CodegenErr {
+    fn fmt (&self, formatter: &mut fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        writeln!(formatter, "failed to generate code because...")?;
+        write!(formatter, "{}", self.to_diagnostic())
+    }
+}
@@ -0,0 +1,35 @@
+//! LLVM code generation for a type checked `Program`.
+//!
+//! Expressions are lowered to SSA values; `if` expressions that produce a value
+//! merge their branches with a phi node and local variables live in stack slots
+//! allocated in the entry block of their function.
+//!
+//! Every class is a named LLVM struct with its fields in declaration order,
+//! and values of it are passed around by value. A method is a function under
+//! the path of its class that takes `self` as its first argument.
+//!
+//! An enum whose variants hold nothing is only its tag, the index of the
+//! variant. Any other enum is a named struct of the tag and an array big and
+//! aligned enough for the fields of its largest variant, which are written
+//! and read through a struct of the fields of the variant. A `match` tests
+//! its arms one after the other and merges their values with a phi node.
+//!
+//! A `dyn Trait` is a pair of pointers: to its value, which is moved to the
+//! heap, and to the vtable of the type of the value for the trait. A vtable
+//! holds a function for every function of the trait, which takes `self` by
+//! pointer and calls the function of the impl; calls on a `dyn Trait` call
+//! the function from the vtable.
+//!
+//! Generic functions are monomorphized: every list of types a call gives the
+//! type parameters gets its own instance, like `max<i32>` or
+//! `Box<i32>::get`, which is compiled once however many calls and modules
+//! use it. A generic class likewise gets a struct for every instance, like
+//! `Box<i32>`.
+//!
+//! The `?` operator branches on the tag of its operand: a `Some` or an `Ok`
+//! gives its field, while a `None` or an `Err` is built again as a value of
+//! the return type of the function and returned.
+
+pub mod codegen;
+pub mod error;
+mod test;
@@ -0,0 +1,183 @@
+#[cfg(test)]
+mod tests {
+    use inkwell::context::Context;
+
+    use crate::codegen::codegen::Codegen;
+    use crate::diagnostic::SourceFile;
+    use crate::{check_str, Profile};
+
+    fn compile(program: &str) -> String {
+        compile_as(program, Profile::Debug)
+    }
+
+    fn compile_as(source: &str, profile: Profile) -> String {
+        let (program, checked) = check_str(source).unwrap();
+        let context = Context::create();
+        let mut codegen = Codegen::new(&context, "test").with_profile(profile).with_sources(SourceFile::new("test.sn", source).into());
+        codegen.compile(&program, checked).unwrap();
+        codegen.module().print_to_string().to_string()
+    }
+
+    #[test]
+    fn test_codegen_if_else_phi() {
+        let ir = compile("fn pick(c: bool): int { let x = if c { 1 } else { 2 }; x }");
+        assert!(ir.contains("phi i32"));
+    }
+
+    #[test]
+    fn test_codegen_returning_branch() {
+        let ir = compile("fn f(c: bool): int { let x = if c { return 0; } else { 2 }; x + 1 }");
+        assert!(!ir.contains("phi"));
+    }
+
+    #[test]
+    fn test_codegen_block_expression() {
+        let ir = compile("fn f(): float { let y = { let a = 1.5; a * 2.0 }; y }");
+        assert!(ir.contains("fmul float"));
+    }
+
+    #[test]
+    fn test_codegen_statement_if() {
+        let ir = compile("fn f(n: int): int { let x = n; if n == 0 { x = 1; } x }");
+        assert!(ir.contains("if.merge"));
+    }
+
+    #[test]
+    fn test_codegen_comparison() {
+        let ir = compile("fn max(a: int, b: int): int { if a < b { b } else { a } }");
+        assert!(ir.contains("icmp slt"));
+    }
+
+    #[test]
+    fn test_codegen_inferred_local() {
+        let ir = compile("fn f(): float { let x; x = 1.5; x * 2.0 }");
+        assert!(ir.contains("alloca float"));
+    }
+
+    #[test]
+    fn test_codegen_sized_numbers() {
+        let ir = compile("fn f(a: u8, b: i64, c: f64): bool { let d = a / 2; let e = -128; let f: i8 = e; b < 3 == (c > 0.5) == (d < a) }");
+        assert!(ir.contains("udiv i8"));
+        assert!(ir.contains("store i8 -128"));
+        assert!(ir.contains("icmp slt i64"));
+        assert!(ir.contains("fcmp ogt double"));
+        assert!(ir.contains("icmp ult i8"));
+    }
+
+    #[test]
+    fn test_codegen_casts() {
+        let ir = compile("fn f(a: u8, b: i16, c: f64, d: bool): i64 { let x = a as i64 + b as i64 + d as i64; let y = c as u16; let z = b as f32; x }");
+        assert!(ir.contains("zext i8"));
+        assert!(ir.contains("sext i16"));
+        assert!(ir.contains("zext i1"));
+        assert!(ir.contains("call i16 @llvm.fptoui.sat.i16.f64"));
+        assert!(ir.contains("sitofp i16"));
+    }
+
+    #[test]
+    fn test_codegen_overflow_checks() {
+        let source = "fn f(a: int, b: u8): int {\n    let c = b * 2;\n    a + -a\n}";
+        let debug = compile_as(source, Profile::Debug);
+        assert!(debug.contains("@llvm.umul.with.overflow.i8"));
+        assert!(debug.contains("@llvm.sadd.with.overflow.i32"));
+        assert!(debug.contains("test.sn:3:5: attempt to add with overflow"));
+        assert!(debug.contains("test.sn:3:9: attempt to negate with overflow"));
+        let release = compile_as(source, Profile::Release);
+        assert!(release.contains("mul i8"));
+        assert!(!release.contains("with.overflow"));
+    }
+
+    #[test]
+    fn test_codegen_division_traps() {
+        let ir = compile_as("fn f(a: u8, b: i64): i64 { let c = a / 3; b / 2 }", Profile::Release);
+        assert!(ir.contains("udiv i8"));
+        assert!(ir.contains("sdiv i64"));
+        assert!(ir.contains("attempt to divide by zero"));
+        assert!(ir.contains("attempt to divide with overflow"));
+    }
+
+    #[test]
+    fn test_codegen_intrinsics() {
+        let ir = compile("fn f(a: u8, b: i16): i16 { let c = saturating_add(a, 1); let d = wrapping_mul(b, 3); saturating_mul(b, match checked_sub(d, 1) { Some(e) => e, None => 0 }) }");
+        assert!(ir.contains("@llvm.uadd.sat.i8"));
+        assert!(ir.contains("mul i16"));
+        assert!(ir.contains("@llvm.ssub.with.overflow.i16"));
+        // an overflow gives `None` rather than a trap
+        assert!(ir.contains("select i1 %overflowed, %\"Option<i16>\""));
+        assert!(!ir.contains("attempt to subtract with overflow"));
+    }
+
+    #[test]
+    fn test_codegen_classes() {
+        let ir = compile("class Point { pub x: int; y: (u8, Point2); pub fn new(x: int): Point { Point { y: (1, Point2 {}), x: x } } pub fn get(self): int { self.x } } class Point2 {} fn f(): int { let p = Point::new(1); p.x = 2; p.get() }");
+        assert!(ir.contains("%Point = type { i32, { i8, %Point2 } }"));
+        assert!(ir.contains("%Point2 = type {}"));
+        assert!(ir.contains("define i32 @\"Point::get\"(%Point"));
+        assert!(ir.contains("insertvalue %Point"));
+        assert!(ir.contains("getelementptr inbounds %Point"));
+        assert!(ir.contains("extractvalue %Point"));
+        assert!(ir.contains("call i32 @\"Point::get\"(%Point"));
+    }
+
+    #[test]
+    fn test_codegen_impls() {
+        let ir = compile("trait Shape { fn area(self): int; fn twice(self): int { self.area() * 2 } } class P { pub x: int; } impl Shape for P { fn area(self): int { self.x } } fn f(): int { let p = P { x: 1 }; p.twice() + Shape::area(p) }");
+        assert!(ir.contains("define i32 @\"<P as Shape>::area\"(%P"));
+        assert!(ir.contains("define i32 @\"<P as Shape>::twice\"(%P"));
+        assert!(ir.contains("call i32 @\"<P as Shape>::twice\"(%P"));
+        assert!(ir.contains("call i32 @\"<P as Shape>::area\"(%P"));
+    }
+
+    #[test]
+    fn test_codegen_dyn_traits() {
+        let ir = compile("trait Shape { fn area(self): int; } class P { pub x: int; } impl Shape for P { fn area(self): int { self.x } } fn f(s: dyn Shape): int { s.area() } fn g(): int { f(P { x: 1 }) + f(P { x: 2 }) }");
+        assert!(ir.contains("@\"sonneko.vtable.<P as Shape>\" = internal constant [1 x ptr] [ptr @\"<P as Shape>::area.dyn\"]"));
+        assert_eq!(ir.matches("sonneko.vtable.<P as Shape>\" =").count(), 1);
+        assert!(ir.contains("define internal i32 @\"<P as Shape>::area.dyn\"(ptr"));
+        assert!(ir.contains("call ptr @malloc(i64"));
+        assert!(ir.contains("define i32 @f({ ptr, ptr }"));
+        assert!(ir.contains("call i32 %area(ptr"));
+    }
+
+    #[test]
+    fn test_codegen_generics() {
+        let ir = compile("trait Ord { fn gt(self, other: Self): bool; } impl Ord for int { fn gt(self, other: Self): bool { self > other } } \
+                          fn max<T: Ord>(a: T, b: T): T { if a.gt(b) { a } else { b } } class Box<T> { pub value: T; pub fn get(self): T { self.value } } \
+                          namespace a { pub fn f(): int { max(1, 2) } } namespace b { pub fn f(): int { max(3, 4) + Box { value: 5 }.get() } } \
+                          fn g(): (bool, int) { (Box { value: true }.get(), max(5, 6)) }");
+        // one instance for every list of types, however many namespaces call it
+        assert_eq!(ir.matches("define i32 @\"max<i32>\"(i32").count(), 1);
+        assert!(ir.contains("call i1 @\"<i32 as Ord>::gt\""));
+        assert!(ir.contains("%\"Box<i32>\" = type { i32 }"));
+        assert!(ir.contains("%\"Box<bool>\" = type { i1 }"));
+        assert!(ir.contains("define i1 @\"Box<bool>::get\"(%\"Box<bool>\""));
+        assert!(!ir.contains("@max("));
+    }
+
+    #[test]
+    fn test_codegen_enums() {
+        let ir = compile("enum Color { Red, Green } enum Shape { Circle(f64), Rect { w: u8, h: u8 }, Empty } enum O<T> { S(T), N } \
+                          fn f(c: Color, s: Shape): int { match s { Shape::Circle(_) => 1, Shape::Rect { w, h: 2 } => w as int, _ => if c == Color::Red { 3 } else { 4 } } } \
+                          fn g(o: O<i16>): i16 { match o { O::S(x) => x, O::N => 0 } } \
+                          fn h(): int { g(O::S(1)); f(Color::Green, Shape::Rect { h: 2, w: 1 }) }");
+        // an enum without payloads is only its tag
+        assert!(ir.contains("define i32 @f(i8 %0, %Shape %1)"));
+        assert!(ir.contains("%Shape = type { i8, [1 x i64] }"));
+        assert!(ir.contains("%\"O<i16>\" = type { i8, [1 x i16] }"));
+        assert!(ir.contains("store { i8, i8 } "));
+        assert!(ir.contains("load { i16 }"));
+        assert!(ir.contains("phi i32"));
+        assert!(ir.contains("unreachable"));
+    }
+
+    #[test]
+    fn test_codegen_try() {
+        let ir = compile("fn parse(x: int): Result<int, u8> { if x < 0 { Err(1) } else { Ok(x) } } \
+                          fn twice(x: int): Result<int, u8> { Ok(parse(x)? * 2) } \
+                          fn h(): int { match twice(2) { Ok(x) => x, Err(_) => 0 } }");
+        assert!(ir.contains("%\"Result<i32, u8>\" = type { i8, [1 x i32] }"));
+        // an `Err` leaves the function through a block of its own
+        assert!(ir.contains("try.return:"));
+        assert!(ir.contains("try.continue:"));
+    }
+}
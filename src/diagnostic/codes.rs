@@ -0,0 +1,73 @@
+//! Long explanations of the error codes, shown by `sonneko --explain`. Each
+//! one lives in `explanations/<code>.md` and is built into the binary.
+//!
+//! An explanation has an erroneous example in a ```` ```compile_fail,<code> ````
+//! block and a corrected one in a plain ```` ``` ```` block; the tests check that
+//! the first fails with the code and the second compiles. An example of
+//! several modules starts each file with a `// <name>.sn` line, the entry
+//! file first.
+//!
+//! Codes are grouped by the pass that reports them: `E01xx` the tokenizer,
+//! `E02xx` the parser, `E03xx` the resolver and the checker, `E04xx`
+//! building an executable and `E05xx` loading modules.
+
+pub const EXPLANATIONS: &[(&str, &str)] = &[
+    ("E0101", include_str!("explanations/E0101.md")),
+    ("E0102", include_str!("explanations/E0102.md")),
+    ("E0103", include_str!("explanations/E0103.md")),
+    ("E0201", include_str!("explanations/E0201.md")),
+    ("E0202", include_str!("explanations/E0202.md")),
+    ("E0203", include_str!("explanations/E0203.md")),
+    ("E0204", include_str!("explanations/E0204.md")),
+    ("E0301", include_str!("explanations/E0301.md")),
+    ("E0302", include_str!("explanations/E0302.md")),
+    ("E0303", include_str!("explanations/E0303.md")),
+    ("E0304", include_str!("explanations/E0304.md")),
+    ("E0305", include_str!("explanations/E0305.md")),
+    ("E0306", include_str!("explanations/E0306.md")),
+    ("E0307", include_str!("explanations/E0307.md")),
+    ("E0308", include_str!("explanations/E0308.md")),
+    ("E0309", include_str!("explanations/E0309.md")),
+    ("E0310", include_str!("explanations/E0310.md")),
+    ("E0311", include_str!("explanations/E0311.md")),
+    ("E0312", include_str!("explanations/E0312.md")),
+    ("E0313", include_str!("explanations/E0313.md")),
+    ("E0314", include_str!("explanations/E0314.md")),
+    ("E0315", include_str!("explanations/E0315.md")),
+    ("E0316", include_str!("explanations/E0316.md")),
+    ("E0317", include_str!("explanations/E0317.md")),
+    ("E0318", include_str!("explanations/E0318.md")),
+    ("E0319", include_str!("explanations/E0319.md")),
+    ("E0320", include_str!("explanations/E0320.md")),
+    ("E0321", include_str!("explanations/E0321.md")),
+    ("E0322", include_str!("explanations/E0322.md")),
+    ("E0323", include_str!("explanations/E0323.md")),
+    ("E0324", include_str!("explanations/E0324.md")),
+    ("E0325", include_str!("explanations/E0325.md")),
+    ("E0326", include_str!("explanations/E0326.md")),
+    ("E0327", include_str!("explanations/E0327.md")),
+    ("E0328", include_str!("explanations/E0328.md")),
+    ("E0329", include_str!("explanations/E0329.md")),
+    ("E0330", include_str!("explanations/E0330.md")),
+    ("E0331", include_str!("explanations/E0331.md")),
+    ("E0332", include_str!("explanations/E0332.md")),
+    ("E0333", include_str!("explanations/E0333.md")),
+    ("E0334", include_str!("explanations/E0334.md")),
+    ("E0335", include_str!("explanations/E0335.md")),
+    ("E0336", include_str!("explanations/E0336.md")),
+    ("E0337", include_str!("explanations/E0337.md")),
+    ("E0338", include_str!("explanations/E0338.md")),
+    ("E0339", include_str!("explanations/E0339.md")),
+    ("E0340", include_str!("explanations/E0340.md")),
+    ("E0341", include_str!("explanations/E0341.md")),
+    ("E0342", include_str!("explanations/E0342.md")),
+    ("E0401", include_str!("explanations/E0401.md")),
+    ("E0501", include_str!("explanations/E0501.md")),
+    ("E0502", include_str!("explanations/E0502.md")),
+];
+
+/// The explanation of `code`, which may also be given without the `E` like `0101`.
+pub fn explain(code: &str) -> Option<&'static str> {
+    let code = code.strip_prefix('E').unwrap_or(code);
+    EXPLANATIONS.iter().find(|(known, _)| known[1..] == *code).map(|(_, explanation)| *explanation)
+}
@@ -0,0 +1,35 @@
+//! Applying suggestions to source text, for `sonneko fix`.
+
+use crate::diagnostic::{Applicability, Diagnostic, Suggestion};
+
+/// Applies the machine-applicable suggestions of `diagnostics` to `text` and
+/// returns the patched text with the number of suggestions applied.
+///
+/// A suggestion overlapping one that was already accepted is skipped; running
+/// the checker again and applying what is left picks it up if still relevant.
+pub fn apply(text: &str, diagnostics: &[Diagnostic]) -> (String, usize) {
+    let mut suggestions: Vec<&Suggestion> = diagnostics
+        .iter()
+        .flat_map(|diagnostic| &diagnostic.suggestions)
+        .filter(|suggestion| suggestion.applicability == Applicability::MachineApplicable)
+        .filter(|suggestion| suggestion.span.end <= text.len())
+        .collect();
+    suggestions.sort_by_key(|suggestion| (suggestion.span.start, suggestion.span.end));
+
+    let mut accepted: Vec<&Suggestion> = Vec::new();
+    for suggestion in suggestions {
+        if let Some(last) = accepted.last() {
+            // two insertions at the same place would apply in an arbitrary order
+            if suggestion.span.start < last.span.end || suggestion.span.start == last.span.start {
+                continue;
+            }
+        }
+        accepted.push(suggestion);
+    }
+
+    let mut patched = text.to_string();
+    for suggestion in accepted.iter().rev() {
+        patched.replace_range(suggestion.span.start..suggestion.span.end, &suggestion.replacement);
+    }
+    (patched, accepted.len())
+}
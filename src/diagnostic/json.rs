@@ -0,0 +1,103 @@
+//! One line JSON objects for `--error-format=json`, for CI annotators and
+//! editor plugins. Written by hand rather than with `serde_json` so the format
+//! is there without the `serde` feature.
+//!
+//! ```json
+//! {"file":"main.sn","severity":"error","code":null,"message":"mismatched types",
+//!  "spans":[{"file":"main.sn","byte_start":40,"byte_end":43,"line_start":2,"column_start":18,"line_end":2,"column_end":21,
+//!            "is_primary":true,"label":"expected int, found float"}],
+//!  "notes":[],"help":[],"suggestions":[]}
+//! ```
+//!
+//! The top level `file` is the one the diagnostic is reported in; each span
+//! names its own file, which differs for spans into imported modules. Byte
+//! offsets count from the start of that file.
+//!
+//! A suggestion has the same range fields as a span, with `message`,
+//! `replacement` and `applicability` (`"machine-applicable"` or
+//! `"maybe-incorrect"`) in place of `is_primary` and `label`.
+//!
+//! Lines and columns are one based, columns count characters and `*_end` is
+//! just past the span. `file` is `null` and `spans` empty for errors that are
+//! not about a source file.
+
+use std::fmt::Write;
+
+use crate::diagnostic::{Diagnostic, SourceFile, SourceMap};
+use crate::span::Span;
+
+pub fn to_json(diagnostic: &Diagnostic, sources: Option<&SourceMap>) -> String {
+    let source = sources.and_then(|sources| match diagnostic.primary_span() {
+        Some(span) => sources.file(span.start),
+        None => sources.files().first(),
+    });
+    let file_of = |span: Span| sources.and_then(|sources| sources.file(span.start));
+    let mut output = String::from("{");
+    write!(output, "\"file\":{}", source.map_or("null".to_string(), |source| string(&source.name))).unwrap();
+    write!(output, ",\"severity\":{}", string(&diagnostic.severity.to_string())).unwrap();
+    write!(output, ",\"code\":{}", diagnostic.code.map_or("null".to_string(), string)).unwrap();
+    write!(output, ",\"message\":{}", string(&diagnostic.message)).unwrap();
+
+    output.push_str(",\"spans\":[");
+    let labels = diagnostic.labels.iter().filter_map(|label| Some((file_of(label.span)?, label)));
+    for (index, (file, label)) in labels.enumerate() {
+        if index > 0 {
+            output.push(',');
+        }
+        write!(output, "{{{},\"is_primary\":{},\"label\":{}}}", range(label.span, file), label.is_primary, string(&label.message)).unwrap();
+    }
+    output.push(']');
+    write!(output, ",\"notes\":{}", strings(&diagnostic.notes)).unwrap();
+    write!(output, ",\"help\":{}", strings(&diagnostic.help)).unwrap();
+
+    output.push_str(",\"suggestions\":[");
+    let suggestions = diagnostic.suggestions.iter().filter_map(|suggestion| Some((file_of(suggestion.span)?, suggestion)));
+    for (index, (file, suggestion)) in suggestions.enumerate() {
+        if index > 0 {
+            output.push(',');
+        }
+        write!(
+            output,
+            "{{\"message\":{},{},\"replacement\":{},\"applicability\":{}}}",
+            string(&suggestion.message),
+            range(suggestion.span, file),
+            string(&suggestion.replacement),
+            string(&suggestion.applicability.to_string()),
+        )
+        .unwrap();
+    }
+    output.push(']');
+    output.push('}');
+    output
+}
+
+/// The `file`, `byte_*`, `line_*` and `column_*` fields of `span`.
+fn range(span: Span, source: &SourceFile) -> String {
+    let (line_start, column_start) = source.line_col(span.start);
+    let (line_end, column_end) = source.line_col(span.end);
+    format!(
+        "\"file\":{},\"byte_start\":{},\"byte_end\":{},\"line_start\":{},\"column_start\":{},\"line_end\":{},\"column_end\":{}",
+        string(&source.name), span.start - source.start, span.end - source.start, line_start, column_start, line_end, column_end,
+    )
+}
+
+fn string(text: &str) -> String {
+    let mut output = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(output, "\\u{:04x}", c as u32).unwrap(),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+
+fn strings(texts: &[String]) -> String {
+    format!("[{}]", texts.iter().map(|text| string(text)).collect::<Vec<_>>().join(","))
+}
@@ -0,0 +1,203 @@
+//! Errors and warnings of every pass in one shape, so the driver can render
+//! them the same way no matter where they come from.
+//!
+//! A `Diagnostic` has a severity, an optional code, a message and labels that
+//! point into the source. The primary label marks where the problem is, the
+//! secondary ones add context like "expected because of this". `render`
+//! prints it like rustc does:
+//!
+//! ```text
+//! error: `if` and `else` have different types
+//!  --> main.sn:2:23
+//!   |
+//! 2 |     if c { 1 } else { 2.0 }
+//!   |            - this is int
+//!   |                       ^^^ this is float
+//!   |
+//!   = help: make both branches evaluate to the same type
+//! ```
+
+pub mod source;
+pub mod render;
+pub mod json;
+pub mod codes;
+pub mod fix;
+mod test;
+
+use std::fmt;
+
+use crate::span::Span;
+
+pub use crate::diagnostic::json::to_json;
+pub use crate::diagnostic::render::{render, render_all};
+pub use crate::diagnostic::source::{SourceFile, SourceMap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(formatter, "error"),
+            Severity::Warning => write!(formatter, "warning"),
+            Severity::Note => write!(formatter, "note"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub span: Span,
+    /// may be empty, then only the span is underlined
+    pub message: String,
+    pub is_primary: bool,
+}
+
+/// How sure a suggestion is to be what the user meant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Applicability {
+    /// certainly right; `sonneko fix` applies it
+    MachineApplicable,
+    /// probably right, but it may change what the program means
+    MaybeIncorrect,
+}
+
+impl fmt::Display for Applicability {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Applicability::MachineApplicable => write!(formatter, "machine-applicable"),
+            Applicability::MaybeIncorrect => write!(formatter, "maybe-incorrect"),
+        }
+    }
+}
+
+/// A fix for a diagnostic: replace the text at `span` with `replacement`. An
+/// empty span inserts, an empty replacement deletes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub message: String,
+    pub span: Span,
+    pub replacement: String,
+    pub applicability: Applicability,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: Option<&'static str>,
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub help: Vec<String>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity,
+            code: None,
+            message: message.into(),
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Marks `span` as where the problem is.
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label { span, message: message.into(), is_primary: true });
+        self
+    }
+
+    /// Points at `span` as context for the primary label.
+    pub fn with_secondary(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.labels.push(Label { span, message: message.into(), is_primary: false });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help.push(help.into());
+        self
+    }
+
+    pub fn with_suggestion(mut self, message: impl Into<String>, span: Span, replacement: impl Into<String>, applicability: Applicability) -> Self {
+        self.suggestions.push(Suggestion { message: message.into(), span, replacement: replacement.into(), applicability });
+        self
+    }
+
+    /// The span of the first primary label, or of the first label if none is primary.
+    pub fn primary_span(&self) -> Option<Span> {
+        self.labels.iter().find(|label| label.is_primary).or(self.labels.first()).map(|label| label.span)
+    }
+}
+
+/// A one line summary without source, e.g. for `{:?}` of the error types.
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}", self.message)?;
+        if let Some(span) = self.primary_span() {
+            write!(formatter, " at {}..{}", span.start, span.end)?;
+        }
+        Ok(())
+    }
+}
+
+/// The candidate closest to the misspelled `name`, if one is close enough to
+/// be a likely typo. Ties go to the alphabetically first candidate.
+pub fn similar_name<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let max_distance = (name.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// Edit distance counted in characters, where swapping two neighbouring
+/// characters is a single edit like in `mian` for `main`.
+fn edit_distance(from: &str, to: &str) -> usize {
+    let from: Vec<char> = from.chars().collect();
+    let to: Vec<char> = to.chars().collect();
+    let mut before: Vec<usize> = Vec::new();
+    let mut previous: Vec<usize> = (0..=to.len()).collect();
+    for i in 0..from.len() {
+        let mut current = vec![i + 1];
+        for j in 0..to.len() {
+            let substitution = previous[j] + usize::from(from[i] != to[j]);
+            let mut distance = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+            if i > 0 && j > 0 && from[i] == to[j - 1] && from[i - 1] == to[j] {
+                distance = distance.min(before[j - 1] + 1);
+            }
+            current.push(distance);
+        }
+        before = std::mem::replace(&mut previous, current);
+    }
+    previous[to.len()]
+}
+
@@ -0,0 +1,219 @@
+use std::fmt::Write;
+
+use crate::diagnostic::{Diagnostic, Label, Severity, SourceFile, SourceMap, Suggestion};
+use crate::span::Span;
+
+const TAB_WIDTH: usize = 4;
+
+const BOLD: &str = "1";
+const RED: &str = "1;31";
+const YELLOW: &str = "1;33";
+const GREEN: &str = "1;32";
+const BLUE: &str = "1;34";
+const CYAN: &str = "1;36";
+
+/// Wraps text in ANSI escape codes when color is on.
+struct Painter {
+    color: bool,
+}
+
+impl Painter {
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.color && !text.is_empty() {
+            format!("\x1b[{}m{}\x1b[0m", style, text)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+fn severity_style(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => RED,
+        Severity::Warning => YELLOW,
+        Severity::Note => GREEN,
+    }
+}
+
+/// Renders `diagnostic` with the lines of `sources` its labels point at.
+/// Labels in another file than the primary one follow under a `:::` line
+/// naming that file. Without sources only the message, the notes and the help
+/// are rendered.
+pub fn render(diagnostic: &Diagnostic, sources: Option<&SourceMap>, color: bool) -> String {
+    let painter = Painter { color };
+    let mut output = String::new();
+
+    let mut header = diagnostic.severity.to_string();
+    if let Some(code) = diagnostic.code {
+        write!(header, "[{}]", code).unwrap();
+    }
+    writeln!(output, "{}{}", painter.paint(severity_style(diagnostic.severity), &header), painter.paint(BOLD, &format!(": {}", diagnostic.message))).unwrap();
+
+    // the file the diagnostic is reported in
+    let source = sources.and_then(|sources| match diagnostic.primary_span() {
+        Some(span) => sources.file(span.start),
+        None => sources.files().first(),
+    });
+    let file_of = |span: Span| sources.and_then(|sources| sources.file(span.start));
+    let mut labels: Vec<(&SourceFile, &Label)> = diagnostic.labels.iter().filter_map(|label| Some((file_of(label.span)?, label))).collect();
+    let patches: Vec<(&SourceFile, &Suggestion)> = diagnostic
+        .suggestions
+        .iter()
+        .filter_map(|suggestion| Some((file_of(suggestion.span)?, suggestion)))
+        .filter(|(file, suggestion)| shows_patch(suggestion, file))
+        .collect();
+    let lines = labels.iter().map(|(file, label)| file.line_index(label.span.start)).chain(patches.iter().map(|(file, suggestion)| file.line_index(suggestion.span.start)));
+    let gutter = match source {
+        Some(_) => lines.map(|line| line + 1).max().unwrap_or(0).to_string().len(),
+        None => 0,
+    };
+    let pad = " ".repeat(gutter);
+    let bar = painter.paint(BLUE, "|");
+
+    if let Some(source) = source {
+        let location = match diagnostic.primary_span() {
+            Some(span) => location(source, span),
+            None => source.name.clone(),
+        };
+        writeln!(output, "{}{} {}", pad, painter.paint(BLUE, "-->"), location).unwrap();
+    }
+
+    if let Some(source) = source.filter(|_| !labels.is_empty()) {
+        writeln!(output, "{} {}", pad, bar).unwrap();
+        labels.sort_by_key(|(file, label)| (file.start != source.start, file.start, label.span.start, !label.is_primary));
+        // the start of the file and the line of the last label written
+        let mut previous: Option<(usize, usize)> = None;
+        for (file, label) in &labels {
+            let line = file.line_index(label.span.start);
+            let same_file = previous.is_some_and(|(start, _)| start == file.start);
+            if !same_file && file.start != source.start {
+                if previous.is_some() {
+                    writeln!(output, "{} {}", pad, bar).unwrap();
+                }
+                writeln!(output, "{}{} {}", pad, painter.paint(BLUE, ":::"), location(file, label.span)).unwrap();
+                writeln!(output, "{} {}", pad, bar).unwrap();
+            }
+            let previous_line = previous.filter(|_| same_file).map(|(_, line)| line);
+            if previous_line != Some(line) {
+                match previous_line {
+                    // a single line in between is shown rather than elided
+                    Some(previous) if line == previous + 2 => write_line(&mut output, &painter, file, previous + 1, gutter, &bar),
+                    Some(previous) if line > previous + 2 => writeln!(output, "{}", painter.paint(BLUE, "...")).unwrap(),
+                    _ => {}
+                }
+                write_line(&mut output, &painter, file, line, gutter, &bar);
+                previous = Some((file.start, line));
+            }
+            write_underline(&mut output, &painter, file, label, diagnostic.severity, &pad, &bar);
+        }
+    }
+
+    if !diagnostic.notes.is_empty() || !diagnostic.help.is_empty() {
+        if source.is_some() {
+            writeln!(output, "{} {}", pad, bar).unwrap();
+        }
+        for note in &diagnostic.notes {
+            writeln!(output, "{} {} {}: {}", pad, painter.paint(BLUE, "="), painter.paint(BOLD, "note"), note).unwrap();
+        }
+        for help in &diagnostic.help {
+            writeln!(output, "{} {} {}: {}", pad, painter.paint(BLUE, "="), painter.paint(CYAN, "help"), help).unwrap();
+        }
+    }
+
+    for suggestion in &diagnostic.suggestions {
+        match patches.iter().find(|(_, patch)| std::ptr::eq(*patch, suggestion)) {
+            Some((file, _)) => {
+                let foreign = source.is_some_and(|source| source.start != file.start);
+                write_suggestion(&mut output, &painter, file, suggestion, foreign, gutter, &bar);
+            }
+            None => {
+                let help = format!("{}: `{}`", suggestion.message, suggestion.replacement);
+                writeln!(output, "{} {} {}: {}", pad, painter.paint(BLUE, "="), painter.paint(CYAN, "help"), help).unwrap();
+            }
+        }
+    }
+    output
+}
+
+fn location(source: &SourceFile, span: Span) -> String {
+    let (line, column) = source.line_col(span.start);
+    format!("{}:{}:{}", source.name, line, column)
+}
+
+/// Whether `suggestion` can be shown as its line with the replacement
+/// spliced in, which needs both to stay on one line.
+fn shows_patch(suggestion: &Suggestion, source: &SourceFile) -> bool {
+    suggestion.span.end <= source.end()
+        && source.line_index(suggestion.span.start) == source.line_index(suggestion.span.end)
+        && !suggestion.replacement.contains('\n')
+}
+
+/// Writes `help: message` followed by the line of `suggestion` as it would
+/// read after the fix, marking inserted text with `+`, replaced text with `~`
+/// and removed text with `-`. A suggestion in a `foreign` file, another one
+/// than the diagnostic is reported in, also names that file.
+fn write_suggestion(output: &mut String, painter: &Painter, source: &SourceFile, suggestion: &Suggestion, foreign: bool, gutter: usize, bar: &str) {
+    let line = source.line_index(suggestion.span.start);
+    let line_start = source.line_start(line);
+    let text = source.line(line);
+    let start = (suggestion.span.start - line_start).min(text.len());
+    let end = (suggestion.span.end - line_start).clamp(start, text.len());
+
+    let (patched, marked, mark) = if suggestion.replacement.is_empty() {
+        // a removal shows the line before the fix, with the removed text marked
+        (text.to_string(), &text[start..end], "-")
+    } else {
+        let patched = format!("{}{}{}", &text[..start], suggestion.replacement, &text[end..]);
+        (patched, suggestion.replacement.as_str(), if start == end { "+" } else { "~" })
+    };
+    let pad = " ".repeat(gutter);
+    let number = painter.paint(BLUE, &format!("{:>width$}", line + 1, width = gutter));
+    let before = display_width(&text[..start]);
+    let width = display_width(marked).max(1);
+
+    writeln!(output, "{}: {}", painter.paint(CYAN, "help"), suggestion.message).unwrap();
+    if foreign {
+        writeln!(output, "{}{} {}", pad, painter.paint(BLUE, ":::"), location(source, suggestion.span)).unwrap();
+    }
+    writeln!(output, "{} {}", pad, bar).unwrap();
+    writeln!(output, "{} {} {}", number, bar, expand_tabs(&patched).trim_end()).unwrap();
+    writeln!(output, "{} {} {}{}", pad, bar, " ".repeat(before), painter.paint(GREEN, &mark.repeat(width))).unwrap();
+}
+
+fn write_line(output: &mut String, painter: &Painter, source: &SourceFile, line: usize, gutter: usize, bar: &str) {
+    let number = painter.paint(BLUE, &format!("{:>width$}", line + 1, width = gutter));
+    writeln!(output, "{} {} {}", number, bar, expand_tabs(source.line(line)).trim_end()).unwrap();
+}
+
+/// Writes the `^^^` or `---` row under the line of `label`. A span running
+/// over several lines is underlined up to the end of its first line.
+fn write_underline(output: &mut String, painter: &Painter, source: &SourceFile, label: &Label, severity: Severity, pad: &str, bar: &str) {
+    let line = source.line_index(label.span.start);
+    let line_start = source.line_start(line);
+    let text = source.line(line);
+    let start = (label.span.start - line_start).min(text.len());
+    let end = label.span.end.saturating_sub(line_start).clamp(start, text.len());
+    let before = display_width(text.get(..start).unwrap_or(""));
+    let width = display_width(text.get(start..end).unwrap_or("")).max(1);
+
+    let (mark, style) = if label.is_primary { ("^", severity_style(severity)) } else { ("-", BLUE) };
+    let mut underline = mark.repeat(width);
+    if !label.message.is_empty() {
+        underline.push(' ');
+        underline.push_str(&label.message);
+    }
+    writeln!(output, "{} {} {}{}", pad, bar, " ".repeat(before), painter.paint(style, &underline)).unwrap();
+}
+
+fn display_width(text: &str) -> usize {
+    text.chars().map(|c| if c == '\t' { TAB_WIDTH } else { 1 }).sum()
+}
+
+fn expand_tabs(text: &str) -> String {
+    text.replace('\t', &" ".repeat(TAB_WIDTH))
+}
+
+/// Renders every diagnostic and separates them with a blank line.
+pub fn render_all(diagnostics: &[Diagnostic], sources: Option<&SourceMap>, color: bool) -> String {
+    diagnostics.iter().map(|diagnostic| render(diagnostic, sources, color)).collect::<Vec<_>>().join("\n")
+}
@@ -0,0 +1,113 @@
+use std::path::Path;
+
+use crate::error::CompileErr;
+use crate::CompileResult;
+
+/// The text of one source file, indexed by line so byte offsets of spans can
+/// be turned into line and column numbers.
+///
+/// Offsets are those of the spans pointing into the file. They start at
+/// `start`, which is 0 unless the file is one of several in a `SourceMap`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceFile {
+    /// how the file is referred to in diagnostics, usually its path
+    pub name: String,
+    pub text: String,
+    /// offset of the first byte of `text`
+    pub start: usize,
+    /// byte offset where each line starts
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    pub fn new(name: impl Into<String>, text: impl Into<String>) -> Self {
+        Self::starting_at(name, text, 0)
+    }
+
+    pub fn starting_at(name: impl Into<String>, text: impl Into<String>, start: usize) -> Self {
+        let text = text.into();
+        let line_starts = std::iter::once(start).chain(text.match_indices('\n').map(|(index, _)| start + index + 1)).collect();
+        SourceFile { name: name.into(), text, start, line_starts }
+    }
+
+    /// Offset just past the last byte of `text`.
+    pub fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> CompileResult<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|error| CompileErr::Read(path.to_path_buf(), error))?;
+        Ok(Self::new(path.display().to_string(), text))
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Zero based index of the line `offset` is on.
+    pub fn line_index(&self, offset: usize) -> usize {
+        let offset = offset.clamp(self.start, self.end());
+        match self.line_starts.binary_search(&offset) {
+            Ok(index) => index,
+            Err(index) => index - 1,
+        }
+    }
+
+    /// Text of the line at `index` without its line break.
+    pub fn line(&self, index: usize) -> &str {
+        let start = self.line_starts[index] - self.start;
+        let end = self.line_starts.get(index + 1).map_or(self.text.len(), |next| next - 1 - self.start);
+        self.text[start..end].trim_end_matches('\r')
+    }
+
+    pub fn line_start(&self, index: usize) -> usize {
+        self.line_starts[index]
+    }
+
+    /// One based line and column of `offset`; columns count characters.
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.clamp(self.start, self.end());
+        let index = self.line_index(offset);
+        let line_start = self.line_starts[index];
+        let prefix = self.text.get(line_start - self.start..offset - self.start).map_or(offset - line_start, |prefix| prefix.chars().count());
+        (index + 1, prefix + 1)
+    }
+}
+
+/// Every file of a program. Each file gets its own range of offsets, so a
+/// span alone tells which file it points into.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file after the last one. One offset is left between files so a
+    /// span at the very end of a file still belongs to it.
+    pub fn add(&mut self, name: impl Into<String>, text: impl Into<String>) -> &SourceFile {
+        let start = self.files.last().map_or(0, |file| file.end() + 1);
+        self.files.push(SourceFile::starting_at(name, text, start));
+        self.files.last().unwrap()
+    }
+
+    pub fn files(&self) -> &[SourceFile] {
+        &self.files
+    }
+
+    /// The file `offset` points into, if any; none covers the spans of the prelude.
+    pub fn file(&self, offset: usize) -> Option<&SourceFile> {
+        let index = self.files.partition_point(|file| file.start <= offset);
+        self.files.get(index.checked_sub(1)?).filter(|file| offset <= file.end())
+    }
+}
+
+impl From<SourceFile> for SourceMap {
+    fn from(file: SourceFile) -> Self {
+        SourceMap { files: vec![file] }
+    }
+}
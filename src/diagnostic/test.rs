@@ -0,0 +1,299 @@
+#[cfg(test)]
+mod tests {
+    use crate::diagnostic::codes::{explain, EXPLANATIONS};
+    use crate::diagnostic::fix::apply;
+    use crate::diagnostic::{render, similar_name, to_json, Applicability, Diagnostic, SourceFile, SourceMap};
+    use crate::parser::Program;
+    use crate::span::Span;
+    use crate::{check_file, check_main, check_str, fix_str, CompileResult};
+
+    fn span_of(source: &str, text: &str) -> Span {
+        let start = source.find(text).unwrap();
+        Span::new(start, start + text.len())
+    }
+
+    #[test]
+    fn test_line_col() {
+        let source = SourceFile::new("a.sn", "fn f() {\n\tlet é = 1;\r\n}");
+        assert_eq!(source.line_count(), 3);
+        assert_eq!(source.line_col(0), (1, 1));
+        assert_eq!(source.line_col(9), (2, 1));
+        assert_eq!(source.line_col(source.text.find('=').unwrap()), (2, 8));
+        assert_eq!(source.line(1), "\tlet é = 1;");
+        assert_eq!(source.line_col(source.text.len()), (3, 2));
+    }
+
+    #[test]
+    fn test_render_labels() {
+        let text = "fn pick(c: bool): int {\n    if c { 1 } else { 2.0 }\n}\n";
+        let source = SourceMap::from(SourceFile::new("main.sn", text));
+        let diagnostic = Diagnostic::error("`if` and `else` have different types")
+            .with_secondary(Span::new(span_of(text, "1 }").start, span_of(text, "1 }").start + 1), "this is int")
+            .with_label(span_of(text, "2.0"), "this is float")
+            .with_help("make both branches evaluate to the same type");
+        assert_eq!(
+            render(&diagnostic, Some(&source), false),
+            "\
+error: `if` and `else` have different types
+ --> main.sn:2:23
+  |
+2 |     if c { 1 } else { 2.0 }
+  |            - this is int
+  |                       ^^^ this is float
+  |
+  = help: make both branches evaluate to the same type
+"
+        );
+    }
+
+    #[test]
+    fn test_render_several_files() {
+        let mut sources = SourceMap::new();
+        let main = sources.add("main.sn", "import { f } from \"util\";\n").clone();
+        let util = sources.add("util.sn", "\nfn f() { }\n").clone();
+        assert_eq!(util.start, main.end() + 1);
+        assert_eq!(sources.file(main.end()), Some(&main));
+        assert_eq!(sources.file(crate::prelude::START), None);
+        assert_eq!(util.line_col(util.start + 1), (2, 1));
+
+        let definition = Span::new(util.start + 1, util.start + 7);
+        let diagnostic = Diagnostic::error("`util.sn::f` is private")
+            .with_label(Span::new(9, 10), "private item")
+            .with_secondary(definition, "declared here without `pub`")
+            .with_suggestion("make it public", Span::new(definition.start, definition.start), "pub ", Applicability::MaybeIncorrect);
+        assert_eq!(
+            render(&diagnostic, Some(&sources), false),
+            "\
+error: `util.sn::f` is private
+ --> main.sn:1:10
+  |
+1 | import { f } from \"util\";
+  |          ^ private item
+  |
+ ::: util.sn:2:1
+  |
+2 | fn f() { }
+  | ------ declared here without `pub`
+help: make it public
+ ::: util.sn:2:1
+  |
+2 | pub fn f() { }
+  | ++++
+"
+        );
+        assert!(to_json(&diagnostic, Some(&sources)).contains(r#"{"file":"util.sn","byte_start":1,"byte_end":7,"line_start":2,"#));
+    }
+
+    #[test]
+    fn test_render_distant_lines_and_tabs() {
+        let text = "a\n\tb\nc\nd\ne\nf\ng\nh\ni\nj\nkk\n";
+        let source = SourceMap::from(SourceFile::new("x.sn", text));
+        let diagnostic = Diagnostic::warning("two places")
+            .with_code("E9999")
+            .with_secondary(span_of(text, "b"), "")
+            .with_label(span_of(text, "kk"), "here")
+            .with_note("a note");
+        assert_eq!(
+            render(&diagnostic, Some(&source), false),
+            "\
+warning[E9999]: two places
+  --> x.sn:11:1
+   |
+ 2 |     b
+   |     -
+...
+11 | kk
+   | ^^ here
+   |
+   = note: a note
+"
+        );
+    }
+
+    #[test]
+    fn test_render_without_source_and_color() {
+        let diagnostic = Diagnostic::error("no input").with_label(Span::new(0, 1), "ignored").with_help("pass a file");
+        assert_eq!(render(&diagnostic, None, false), "error: no input\n = help: pass a file\n");
+        let colored = render(&Diagnostic::error("bad"), None, true);
+        assert_eq!(colored, "\x1b[1;31merror\x1b[0m\x1b[1m: bad\x1b[0m\n");
+    }
+
+    #[test]
+    fn test_render_check_errors() {
+        let text = "fn main(): int {\n    let x: int = 1.5;\n    y\n}\n";
+        let source = SourceMap::from(SourceFile::new("main.sn", text));
+        let rendered: Vec<String> = check_str(text).unwrap_err().diagnostics().iter().map(|diagnostic| render(diagnostic, Some(&source), false)).collect();
+        assert_eq!(rendered.len(), 2);
+        assert!(rendered[0].contains(" --> main.sn:2:18\n"), "{}", rendered[0]);
+        assert!(rendered[0].contains("  |                  ^^^ expected i32, found {float}\n"), "{}", rendered[0]);
+        assert!(rendered[1].starts_with("error[E0306]: `y` is not defined\n --> main.sn:3:5\n"), "{}", rendered[1]);
+    }
+
+    #[test]
+    fn test_to_json() {
+        let text = "fn main(): int {\n    \"é\\\"\n}\n";
+        let source = SourceMap::from(SourceFile::new("dir/main.sn", text));
+        let diagnostic = Diagnostic::error("mismatched types")
+            .with_label(span_of(text, "\"é\\\""), "expected int, found \"string\"")
+            .with_secondary(span_of(text, "int"), "")
+            .with_note("line\nbreak");
+        assert_eq!(
+            to_json(&diagnostic, Some(&source)),
+            concat!(
+                r#"{"file":"dir/main.sn","severity":"error","code":null,"message":"mismatched types","spans":["#,
+                r#"{"file":"dir/main.sn","byte_start":21,"byte_end":26,"line_start":2,"column_start":5,"line_end":2,"column_end":9,"is_primary":true,"label":"expected int, found \"string\""},"#,
+                r#"{"file":"dir/main.sn","byte_start":11,"byte_end":14,"line_start":1,"column_start":12,"line_end":1,"column_end":15,"is_primary":false,"label":""}],"#,
+                r#""notes":["line\nbreak"],"help":[],"suggestions":[]}"#,
+            )
+        );
+        let diagnostic = Diagnostic::warning("tab\there").with_code("E0001");
+        assert_eq!(
+            to_json(&diagnostic, None),
+            r#"{"file":null,"severity":"warning","code":"E0001","message":"tab\there","spans":[],"notes":[],"help":[],"suggestions":[]}"#
+        );
+    }
+
+    #[test]
+    fn test_render_suggestions() {
+        let text = "fn main(): int {\n    let x = 1\n    lenght\n}\n";
+        let source = SourceMap::from(SourceFile::new("main.sn", text));
+        let end = span_of(text, "1\n").start + 1;
+        let diagnostic = Diagnostic::error("expected `;`, found identifier `lenght`")
+            .with_label(span_of(text, "lenght"), "expected `;`")
+            .with_suggestion("add `;` here", Span::new(end, end), ";", Applicability::MachineApplicable)
+            .with_suggestion("a similar name exists: `length`", span_of(text, "lenght"), "length", Applicability::MaybeIncorrect)
+            .with_suggestion("remove this", span_of(text, "let "), "", Applicability::MaybeIncorrect);
+        assert_eq!(
+            render(&diagnostic, Some(&source), false),
+            "error: expected `;`, found identifier `lenght`
+ --> main.sn:3:5
+  |
+3 |     lenght
+  |     ^^^^^^ expected `;`
+help: add `;` here
+  |
+2 |     let x = 1;
+  |              +
+help: a similar name exists: `length`
+  |
+3 |     length
+  |     ~~~~~~
+help: remove this
+  |
+2 |     let x = 1
+  |     ----
+"
+        );
+        assert_eq!(render(&diagnostic, None, false).lines().nth(1), Some(" = help: add `;` here: `;`"));
+        assert!(to_json(&diagnostic, Some(&source)).contains(concat!(
+            r#""suggestions":[{"message":"add `;` here","file":"main.sn","byte_start":30,"byte_end":30,"line_start":2,"column_start":14,"#,
+            r#""line_end":2,"column_end":14,"replacement":";","applicability":"machine-applicable"},"#,
+        )));
+    }
+
+    #[test]
+    fn test_apply_suggestions() {
+        let text = "let a = 1 let b = c";
+        let suggest = |start: usize, end: usize, replacement: &str, applicability| {
+            Diagnostic::error("").with_suggestion("", Span::new(start, end), replacement, applicability)
+        };
+        let diagnostics = [
+            suggest(16, 19, "= d", Applicability::MachineApplicable),
+            suggest(9, 9, ";", Applicability::MachineApplicable),
+            // overlaps the one before, and a second insertion at the same place
+            suggest(18, 19, "x", Applicability::MachineApplicable),
+            suggest(9, 9, ",", Applicability::MachineApplicable),
+            suggest(4, 5, "z", Applicability::MaybeIncorrect),
+        ];
+        assert_eq!(apply(text, &diagnostics), ("let a = 1; let b = d".to_string(), 2));
+        assert_eq!(apply(text, &[]), (text.to_string(), 0));
+    }
+
+    #[test]
+    fn test_similar_name() {
+        let names = ["length", "main", "count", "counter"];
+        assert_eq!(similar_name("lenght", names), Some("length"));
+        assert_eq!(similar_name("mian", names), Some("main"));
+        assert_eq!(similar_name("counte", names), Some("count"));
+        assert_eq!(similar_name("x", names), None);
+        assert_eq!(similar_name("main", names), None);
+    }
+
+    #[test]
+    fn test_fix_str() {
+        let (fixed, applied) = fix_str("fn main(): int {\n    let x = 1\n    let y = 2\n    if x = y { x } else { y }\n}\n");
+        assert_eq!(fixed, "fn main(): int {\n    let x = 1;\n    let y = 2;\n    if x == y { x } else { y }\n}\n");
+        assert_eq!(applied, 3);
+        assert!(check_str(&fixed).is_ok());
+        // only machine-applicable suggestions are applied
+        assert_eq!(fix_str("fn main(): int { lenght }"), ("fn main(): int { lenght }".to_string(), 0));
+        // a `;` is only added before a token on a later line, which could not have continued the statement
+        for source in ["fn main(): int { let x = (1 + 2)); x }", "fn main(): int { 1", "fn main(): int { let x = 1 2 3 4; x }"] {
+            assert_eq!(fix_str(source), (source.to_string(), 0));
+        }
+    }
+
+    /// The ```` ``` ```` blocks of an explanation with the text after the opening fence.
+    fn code_blocks(explanation: &str) -> Vec<(&str, String)> {
+        let mut blocks = Vec::new();
+        let mut lines = explanation.lines();
+        while let Some(line) = lines.next() {
+            if let Some(info) = line.strip_prefix("```") {
+                let code = lines.by_ref().take_while(|line| *line != "```").collect::<Vec<_>>().join("\n");
+                blocks.push((info, code));
+            }
+        }
+        blocks
+    }
+
+    /// Checks an example of the explanation of `code`. Examples of several
+    /// modules are written to a directory and checked from their first file.
+    fn check_example(code: &str, source: &str) -> CompileResult<Program> {
+        if !source.starts_with("// ") {
+            return check_str(source).map(|(program, _)| program);
+        }
+        let mut files: Vec<(&str, String)> = Vec::new();
+        for line in source.lines() {
+            match line.strip_prefix("// ") {
+                Some(name) => files.push((name, String::new())),
+                None => files.last_mut().unwrap().1.push_str(&format!("{}\n", line)),
+            }
+        }
+        let directory = std::env::temp_dir().join(format!("sonneko-{}-{}", code, std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        for (name, text) in &files {
+            std::fs::write(directory.join(name), text).unwrap();
+        }
+        let result = check_file(directory.join(files[0].0), &[]).map(|(program, _)| program);
+        std::fs::remove_dir_all(&directory).unwrap();
+        result
+    }
+
+    #[test]
+    fn test_explanation_examples() {
+        for (code, explanation) in EXPLANATIONS {
+            let blocks = code_blocks(explanation);
+            assert_eq!(blocks.len(), 2, "{}", code);
+            for (info, source) in blocks {
+                if info.is_empty() {
+                    check_example(code, &source).unwrap_or_else(|error| panic!("corrected example of {} fails: {:?}", code, error));
+                } else {
+                    let result = check_example(code, &source).and_then(|program| check_main(&program));
+                    assert_eq!(info, format!("compile_fail,{}", code));
+                    let codes: Vec<_> = result.unwrap_err().diagnostics().iter().map(|diagnostic| diagnostic.code).collect();
+                    assert_eq!(codes, vec![Some(*code)], "erroneous example of {}", code);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_explain() {
+        assert!(explain("E0301").unwrap().starts_with("A value has a different type than expected."));
+        assert_eq!(explain("0301"), explain("E0301"));
+        assert_eq!(explain("E9999"), None);
+        let mut codes: Vec<_> = EXPLANATIONS.iter().map(|(code, _)| *code).collect();
+        codes.dedup();
+        assert_eq!(codes.len(), EXPLANATIONS.len());
+    }
+}
@@ -0,0 +1,177 @@
+use std::path::PathBuf;
+
+use crate::driver::error::DriverErr;
+use crate::Profile;
+
+pub const HELP: &str = "\
+sonneko - compiler for the sonneko language
+
+USAGE:
+    sonneko <COMMAND> [OPTIONS] <FILE>
+
+COMMANDS:
+    build <FILE> [-o <OUT>]          compile FILE to the executable OUT (default: FILE without extension);
+                                     this and `run` need the `llvm` feature, which is on by default
+    run <FILE> [-- <ARGS>...]        compile FILE and run it with ARGS, exiting with its exit code
+    check <FILE>                     only tokenize, parse and type check FILE
+    fix <FILE> [--dry-run]           apply the machine-applicable suggestions of the errors in FILE to it;
+                                     with `--dry-run` print them as a diff instead
+    lex <FILE> [--format <FORMAT>]   print the tokens of FILE
+    parse <FILE> [--format <FORMAT>] print the AST of FILE
+
+    `build`, `run`, `check` and `fix` also compile every module FILE imports.
+
+OPTIONS:
+    -o, --output <OUT>     where `build` writes the executable
+    --release              make `build` and `run` wrap on integer overflow; without it the
+                           program stops with the location of the overflow and exit code 101
+    -I, --import-path <DIR>
+                           look for imported modules in DIR when they are not next to the
+                           importing file; may be given more than once and is searched in order
+    --format <FORMAT>      `text` (default), `json` or `sexp`; the last two need the `serde` feature
+    --error-format <FMT>   `human` (default) or `json`, one JSON object per line on stderr
+    --dry-run              make `fix` print a diff rather than change the file
+    --explain <CODE>       print the explanation of an error code like E0301
+    -h, --help             print this help
+    -V, --version          print the version
+
+EXIT STATUS:
+    0    success
+    1    the program or the command line has errors
+    101  the compiler itself failed
+";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Sexp,
+}
+
+/// How errors are reported on stderr.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Build { input: PathBuf, output: Option<PathBuf>, import_paths: Vec<PathBuf>, profile: Profile },
+    Run { input: PathBuf, args: Vec<String>, import_paths: Vec<PathBuf>, profile: Profile },
+    Check { input: PathBuf, import_paths: Vec<PathBuf> },
+    Fix { input: PathBuf, dry_run: bool, import_paths: Vec<PathBuf> },
+    Lex { input: PathBuf, format: OutputFormat },
+    Parse { input: PathBuf, format: OutputFormat },
+    Explain { code: String },
+    Help,
+    Version,
+}
+
+fn usage<T>(message: impl Into<String>) -> Result<T, DriverErr> {
+    Err(DriverErr::Usage(message.into()))
+}
+
+/// Splits `--error-format` off the arguments. It may stand anywhere before a
+/// `--`, as `--error-format=json` or `--error-format json`.
+fn split_error_format(args: &[String]) -> Result<(ErrorFormat, Vec<String>), DriverErr> {
+    let mut error_format = ErrorFormat::Human;
+    let mut rest = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = match arg.as_str() {
+            "--" => {
+                rest.push(arg.clone());
+                rest.extend(args.by_ref().cloned());
+                break;
+            }
+            "--error-format" => args.next().map(String::as_str),
+            arg => match arg.strip_prefix("--error-format=") {
+                Some(value) => Some(value),
+                None => {
+                    rest.push(arg.to_string());
+                    continue;
+                }
+            },
+        };
+        error_format = match value {
+            Some("human") => ErrorFormat::Human,
+            Some("json") => ErrorFormat::Json,
+            Some(other) => return usage(format!("unknown error format `{}`, expected `human` or `json`", other)),
+            None => return usage("`--error-format` needs a format"),
+        };
+    }
+    Ok((error_format, rest))
+}
+
+/// The `--error-format` the arguments ask for, `Human` when it is missing or
+/// invalid. Lets errors of `parse_args` itself be reported in that format.
+pub fn error_format(args: &[String]) -> ErrorFormat {
+    split_error_format(args).map_or(ErrorFormat::Human, |(error_format, _)| error_format)
+}
+
+/// Parses the arguments after the program name. `--error-format` is accepted
+/// but left to `error_format`.
+pub fn parse_args(args: &[String]) -> Result<Command, DriverErr> {
+    let (_, args) = split_error_format(args)?;
+    let Some((command, rest)) = args.split_first() else {
+        return Ok(Command::Help);
+    };
+    let mut input = None;
+    let mut output = None;
+    let mut format = OutputFormat::Text;
+    let mut dry_run = false;
+    let mut profile = Profile::Debug;
+    let mut import_paths = Vec::new();
+    let mut program_args = Vec::new();
+    let mut rest = rest.iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-o" | "--output" if command == "build" => match rest.next() {
+                Some(path) => output = Some(PathBuf::from(path)),
+                None => return usage(format!("`{}` needs a path", arg)),
+            },
+            "--format" if command == "lex" || command == "parse" => {
+                format = match rest.next().map(String::as_str) {
+                    Some("text") => OutputFormat::Text,
+                    Some("json") => OutputFormat::Json,
+                    Some("sexp") => OutputFormat::Sexp,
+                    Some(other) => return usage(format!("unknown format `{}`, expected `text`, `json` or `sexp`", other)),
+                    None => return usage("`--format` needs a format"),
+                }
+            }
+            "--dry-run" if command == "fix" => dry_run = true,
+            "--release" if command == "build" || command == "run" => profile = Profile::Release,
+            "-I" | "--import-path" if matches!(command.as_str(), "build" | "run" | "check" | "fix") => match rest.next() {
+                Some(path) => import_paths.push(PathBuf::from(path)),
+                None => return usage(format!("`{}` needs a directory", arg)),
+            },
+            "--" if command == "run" => {
+                program_args.extend(rest.by_ref().cloned());
+            }
+            option if option.starts_with('-') && option != "-" => return usage(format!("unknown option `{}` for `{}`", option, command)),
+            path if input.is_none() => input = Some(PathBuf::from(path)),
+            extra => return usage(format!("unexpected argument `{}`", extra)),
+        }
+    }
+
+    let input = match (command.as_str(), input) {
+        ("-h" | "--help" | "help", _) => return Ok(Command::Help),
+        ("-V" | "--version", _) => return Ok(Command::Version),
+        ("--explain", None) => return usage("`--explain` needs an error code like E0301"),
+        ("--explain", Some(code)) => return Ok(Command::Explain { code: code.to_string_lossy().into_owned() }),
+        ("build" | "run" | "check" | "fix" | "lex" | "parse", None) => return usage(format!("`{}` needs an input file", command)),
+        (_, Some(input)) => input,
+        (_, None) => return usage(format!("unknown command `{}`", command)),
+    };
+    match command.as_str() {
+        "build" => Ok(Command::Build { input, output, import_paths, profile }),
+        "run" => Ok(Command::Run { input, args: program_args, import_paths, profile }),
+        "check" => Ok(Command::Check { input, import_paths }),
+        "fix" => Ok(Command::Fix { input, dry_run, import_paths }),
+        "lex" => Ok(Command::Lex { input, format }),
+        "parse" => Ok(Command::Parse { input, format }),
+        _ => usage(format!("unknown command `{}`", command)),
+    }
+}
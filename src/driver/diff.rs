@@ -0,0 +1,121 @@
+//! Line based unified diffs, for `sonneko fix --dry-run`.
+
+use std::fmt::Write;
+
+/// Unchanged lines shown around each change.
+const CONTEXT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Edit {
+    Keep,
+    Delete,
+    Insert,
+}
+
+/// One step of an edit script with the line of `old` and of `new` it is at.
+/// A deletion is at the line it deletes and before the line of `new` that
+/// comes next, an insertion the other way round.
+#[derive(Debug, Clone, Copy)]
+struct Step {
+    edit: Edit,
+    old: usize,
+    new: usize,
+}
+
+/// The shortest edit script turning `old` into `new`, found with Myers'
+/// O(ND) algorithm.
+fn edit_script(old: &[&str], new: &[&str]) -> Vec<Step> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let offset = n + m + 1;
+    let index = |k: isize| (k + offset) as usize;
+    let mut furthest = vec![0isize; 2 * offset as usize + 1];
+    // `trace[d]` is how far each diagonal got with fewer than `d` edits
+    let mut trace = Vec::new();
+
+    'search: for d in 0..=n + m {
+        trace.push(furthest.clone());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && furthest[index(k - 1)] < furthest[index(k + 1)]) {
+                furthest[index(k + 1)]
+            } else {
+                furthest[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            furthest[index(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut steps = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, furthest) in trace.iter().enumerate().rev() {
+        let (d, k) = (d as isize, x - y);
+        let previous_k = if k == -d || (k != d && furthest[index(k - 1)] < furthest[index(k + 1)]) { k + 1 } else { k - 1 };
+        let previous_x = furthest[index(previous_k)];
+        let previous_y = previous_x - previous_k;
+        while x > previous_x && y > previous_y {
+            x -= 1;
+            y -= 1;
+            steps.push(Step { edit: Edit::Keep, old: x as usize, new: y as usize });
+        }
+        if d > 0 {
+            if x == previous_x {
+                steps.push(Step { edit: Edit::Insert, old: x as usize, new: previous_y as usize });
+            } else {
+                steps.push(Step { edit: Edit::Delete, old: previous_x as usize, new: y as usize });
+            }
+        }
+        x = previous_x;
+        y = previous_y;
+    }
+    steps.reverse();
+    steps
+}
+
+/// The unified diff from `old` to `new`, both the text of the file `name`.
+/// Empty when they have the same lines.
+pub fn unified_diff(name: &str, old: &str, new: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let steps = edit_script(&old_lines, &new_lines);
+    let changes: Vec<usize> = (0..steps.len()).filter(|&index| steps[index].edit != Edit::Keep).collect();
+    let Some(&first) = changes.first() else {
+        return String::new();
+    };
+
+    // changes closer than twice the context share a hunk
+    let mut hunks = vec![(first, first)];
+    for &change in &changes[1..] {
+        let last = hunks.last_mut().unwrap();
+        if change - last.1 <= 2 * CONTEXT {
+            last.1 = change;
+        } else {
+            hunks.push((change, change));
+        }
+    }
+
+    let mut output = format!("--- {}\n+++ {} (fixed)\n", name, name);
+    for (first, last) in hunks {
+        let hunk = &steps[first.saturating_sub(CONTEXT)..(last + CONTEXT + 1).min(steps.len())];
+        let old_count = hunk.iter().filter(|step| step.edit != Edit::Insert).count();
+        let new_count = hunk.iter().filter(|step| step.edit != Edit::Delete).count();
+        // an empty range starts at the line before it
+        let old_start = hunk[0].old + usize::from(old_count > 0);
+        let new_start = hunk[0].new + usize::from(new_count > 0);
+        writeln!(output, "@@ -{},{} +{},{} @@", old_start, old_count, new_start, new_count).unwrap();
+        for step in hunk {
+            match step.edit {
+                Edit::Keep => writeln!(output, " {}", old_lines[step.old]).unwrap(),
+                Edit::Delete => writeln!(output, "-{}", old_lines[step.old]).unwrap(),
+                Edit::Insert => writeln!(output, "+{}", new_lines[step.new]).unwrap(),
+            }
+        }
+    }
+    output
+}
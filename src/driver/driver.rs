@@ -0,0 +1,167 @@
+use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::checker::Checked;
+use crate::diagnostic::codes::explain;
+use crate::diagnostic::{SourceFile, SourceMap};
+use crate::driver::cli::{Command, OutputFormat, HELP};
+use crate::driver::diff::unified_diff;
+use crate::driver::error::DriverErr;
+use crate::driver::{EXIT_INTERNAL_ERROR, EXIT_SUCCESS};
+use crate::loader::loader::Loader;
+use crate::parser::Program;
+use crate::span::Span;
+use crate::tokenizer::Token;
+use crate::{check, fix_file, parse_str, tokenize, CompileResult, Profile};
+
+type DriverResult<T> = Result<T, DriverErr>;
+
+/// Whether diagnostics on stderr should be colored: only for a terminal, and
+/// not when the `NO_COLOR` environment variable is set.
+pub fn use_color() -> bool {
+    std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
+
+/// Attaches `sources` to a compile error so it is rendered with its lines.
+fn in_sources<T>(result: CompileResult<T>, sources: impl Into<SourceMap>) -> DriverResult<T> {
+    result.map_err(|error| DriverErr::Compile(error, Some(Box::new(sources.into()))))
+}
+
+/// Loads `input` with the modules it imports and checks the whole program.
+/// Returns what the checker found out about it and the files it is made of
+/// along with it.
+fn load_and_check(input: &Path, import_paths: &[PathBuf]) -> DriverResult<(Program, Checked, SourceMap)> {
+    let mut loader = Loader::new(import_paths.to_vec());
+    let result = loader.load(input).and_then(|program| check(&program).map(|checked| (program, checked)));
+    let sources = loader.into_sources();
+    match result {
+        Ok((program, checked)) => Ok((program, checked, sources)),
+        Err(error) => in_sources(Err(error), sources),
+    }
+}
+
+/// Runs `command` and returns the exit code of the process.
+pub fn run(command: Command) -> DriverResult<i32> {
+    match command {
+        Command::Help => print!("{}", HELP),
+        Command::Version => println!("sonneko {}", env!("CARGO_PKG_VERSION")),
+        Command::Explain { code } => match explain(&code) {
+            Some(explanation) => print!("{}", explanation),
+            None => return Err(DriverErr::Usage(format!("`{}` is not an error code", code))),
+        },
+        Command::Check { input, import_paths } => {
+            load_and_check(&input, &import_paths)?;
+        }
+        Command::Fix { input, dry_run, import_paths } => return fix(&input, dry_run, &import_paths),
+        Command::Lex { input, format } => {
+            let source = SourceFile::read(&input)?;
+            let (tokens, spans) = in_sources(tokenize(&source.text), source)?;
+            print_tokens(&tokens, &spans, format)?;
+        }
+        Command::Parse { input, format } => {
+            let source = SourceFile::read(&input)?;
+            let program = in_sources(parse_str(&source.text), source)?;
+            print_program(&program, format)?;
+        }
+        Command::Build { input, output, import_paths, profile } => {
+            let output = output.unwrap_or_else(|| input.with_extension(""));
+            build(&input, &output, &import_paths, profile)?;
+        }
+        Command::Run { input, args, import_paths, profile } => {
+            let directory = std::env::temp_dir().join(format!("sonneko-{}", process::id()));
+            fs::create_dir_all(&directory).map_err(|error| DriverErr::Execute(directory.clone(), error))?;
+            let executable = directory.join(input.file_stem().unwrap_or(input.as_os_str()));
+            let result = build(&input, &executable, &import_paths, profile).and_then(|()| {
+                process::Command::new(&executable)
+                    .args(args)
+                    .status()
+                    .map_err(|error| DriverErr::Execute(executable.clone(), error))
+            });
+            let _ = fs::remove_dir_all(&directory);
+            // a program killed by a signal has no exit code
+            return Ok(result?.code().unwrap_or(EXIT_INTERNAL_ERROR));
+        }
+    }
+    Ok(EXIT_SUCCESS)
+}
+
+/// Applies the machine-applicable suggestions to `input`, or prints them as a
+/// diff with `dry_run`. Only `input` itself is changed, not the modules it
+/// imports. Errors that are left after fixing are reported against the
+/// fixed file.
+fn fix(input: &Path, dry_run: bool, import_paths: &[PathBuf]) -> DriverResult<i32> {
+    let source = SourceFile::read(input)?;
+    let (fixed, applied) = fix_file(input, import_paths)?;
+    if dry_run {
+        print!("{}", unified_diff(&source.name, &source.text, &fixed));
+        return Ok(EXIT_SUCCESS);
+    }
+    if applied > 0 {
+        fs::write(input, &fixed).map_err(|error| DriverErr::Write(input.to_path_buf(), error))?;
+        eprintln!("fixed {} problem{} in {}", applied, if applied == 1 { "" } else { "s" }, source.name);
+    }
+    load_and_check(input, import_paths)?;
+    Ok(EXIT_SUCCESS)
+}
+
+#[cfg(feature = "llvm")]
+fn build(input: &Path, output: &Path, import_paths: &[PathBuf], profile: Profile) -> DriverResult<()> {
+    let (program, checked, sources) = load_and_check(input, import_paths)?;
+    let module_name = input.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let result = crate::build_program(&program, checked, &sources, &module_name, output, profile);
+    in_sources(result, sources)
+}
+
+#[cfg(not(feature = "llvm"))]
+fn build(_input: &Path, _output: &Path, _import_paths: &[PathBuf], _profile: Profile) -> DriverResult<()> {
+    Err(DriverErr::Usage("`build` and `run` need the `llvm` feature: cargo build --features llvm".into()))
+}
+
+fn print_tokens(tokens: &[Token], spans: &[Span], format: OutputFormat) -> DriverResult<()> {
+    match format {
+        OutputFormat::Text => {
+            for (token, span) in tokens.iter().zip(spans) {
+                println!("{}..{} {:?}", span.start, span.end, token);
+            }
+            Ok(())
+        }
+        format => print_dump(Dumped::Tokens(tokens, spans), format),
+    }
+}
+
+fn print_program(program: &Program, format: OutputFormat) -> DriverResult<()> {
+    match format {
+        OutputFormat::Text => {
+            println!("{:#?}", program);
+            Ok(())
+        }
+        format => print_dump(Dumped::Program(program), format),
+    }
+}
+
+/// What `lex` and `parse` print with `--format json` or `--format sexp`.
+#[cfg_attr(not(feature = "serde"), allow(dead_code))]
+enum Dumped<'a> {
+    Tokens(&'a [Token], &'a [Span]),
+    Program(&'a Program),
+}
+
+#[cfg(feature = "serde")]
+fn print_dump(dumped: Dumped, format: OutputFormat) -> DriverResult<()> {
+    use crate::dump::{dump, spanned_tokens, DumpFormat};
+
+    let format = if format == OutputFormat::Json { DumpFormat::Json } else { DumpFormat::Sexp };
+    let text = match dumped {
+        Dumped::Tokens(tokens, spans) => dump(&spanned_tokens(tokens, spans), format),
+        Dumped::Program(program) => dump(program, format),
+    };
+    println!("{}", text.map_err(|error| DriverErr::Dump(format!("{:?}", error)))?);
+    Ok(())
+}
+
+#[cfg(not(feature = "serde"))]
+fn print_dump(_dumped: Dumped, _format: OutputFormat) -> DriverResult<()> {
+    Err(DriverErr::Usage("`json` and `sexp` output need the `serde` feature: cargo build --features serde".into()))
+}
@@ -0,0 +1,110 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use crate::diagnostic::codes::explain;
+use crate::diagnostic::{render_all, to_json, Diagnostic, SourceMap};
+use crate::driver::{EXIT_INTERNAL_ERROR, EXIT_USER_ERROR};
+use crate::error::CompileErr;
+
+pub enum DriverErr {
+    /// the command line could not be understood
+    Usage(String),
+    /// and the files of the program, if it is about one
+    Compile(CompileErr, Option<Box<SourceMap>>),
+    /// the compiled program could not be started
+    Execute(PathBuf, io::Error),
+    /// `fix` could not write the fixed file back
+    Write(PathBuf, io::Error),
+    /// the dump of tokens or the AST failed
+    #[cfg(feature = "serde")]
+    Dump(String),
+}
+
+impl From<CompileErr> for DriverErr {
+    fn from(error: CompileErr) -> Self {
+        DriverErr::Compile(error, None)
+    }
+}
+
+impl DriverErr {
+    /// Whether the compiler itself is at fault rather than its input.
+    pub fn is_internal(&self) -> bool {
+        match self {
+            Self::Usage(_) => false,
+            Self::Compile(error, _) => error.is_internal(),
+            Self::Execute(..) => true,
+            Self::Write(..) => false,
+            #[cfg(feature = "serde")]
+            Self::Dump(_) => true,
+        }
+    }
+
+    /// Renders the error the way it is shown to the user, with the source
+    /// lines it is about when it is a compile error in the program.
+    /// Ends with a pointer to `--explain` when the diagnostics have codes.
+    pub fn render(&self, color: bool) -> String {
+        let diagnostics = self.diagnostics();
+        let mut output = render_all(&diagnostics, self.sources(), color);
+        let mut codes: Vec<&str> = Vec::new();
+        for code in diagnostics.iter().filter_map(|diagnostic| diagnostic.code) {
+            if !codes.contains(&code) && explain(code).is_some() {
+                codes.push(code);
+            }
+        }
+        match codes.as_slice() {
+            [] => {}
+            [code] => output.push_str(&format!("\nFor more information about this error, try `sonneko --explain {}`.\n", code)),
+            [first, ..] => output.push_str(&format!(
+                "\nSome errors have detailed explanations: {}.\nFor more information about an error, try `sonneko --explain {}`.\n",
+                codes.join(", "),
+                first
+            )),
+        }
+        output
+    }
+
+    /// Renders the error as one JSON object per line, for `--error-format=json`.
+    pub fn render_json(&self) -> String {
+        self.diagnostics().iter().map(|diagnostic| to_json(diagnostic, self.sources()) + "\n").collect()
+    }
+
+    fn diagnostics(&self) -> Vec<Diagnostic> {
+        match self {
+            Self::Compile(error, _) => error.diagnostics(),
+            Self::Usage(message) => vec![Diagnostic::error(message.clone()).with_help("see `sonneko --help`")],
+            Self::Execute(path, error) => vec![Diagnostic::error(format!("failed to run `{}`: {}", path.display(), error))],
+            Self::Write(path, error) => vec![Diagnostic::error(format!("can not write `{}`: {}", path.display(), error))],
+            #[cfg(feature = "serde")]
+            Self::Dump(message) => vec![Diagnostic::error(format!("failed to dump: {}", message))],
+        }
+    }
+
+    fn sources(&self) -> Option<&SourceMap> {
+        match self {
+            Self::Compile(_, sources) => sources.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        if self.is_internal() {
+            EXIT_INTERNAL_ERROR
+        } else {
+            EXIT_USER_ERROR
+        }
+    }
+}
+
+impl fmt::Debug for DriverErr {
+    fn fmt (&self, formatter: &mut fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            Self::Usage(message) => write!(formatter, "{}\nsee `sonneko --help`", message),
+            Self::Compile(error, _) => write!(formatter, "{:?}", error),
+            Self::Execute(path, error) => write!(formatter, "failed to run {} because...\n{}", path.display(), error),
+            Self::Write(path, error) => write!(formatter, "failed to write {} because...\n{}", path.display(), error),
+            #[cfg(feature = "serde")]
+            Self::Dump(message) => write!(formatter, "failed to dump because...\n{}", message),
+        }
+    }
+}
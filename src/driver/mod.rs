@@ -0,0 +1,17 @@
+//! The command line driver: parses the arguments into a `Command` and runs the
+//! passes it needs.
+//!
+//! The process exits with `EXIT_SUCCESS` when everything went fine, with
+//! `EXIT_USER_ERROR` when the input program or the command line is wrong, and
+//! with `EXIT_INTERNAL_ERROR` when the compiler itself failed. `run` exits with
+//! the exit code of the compiled program instead.
+
+pub mod cli;
+pub mod diff;
+pub mod driver;
+pub mod error;
+mod test;
+
+pub const EXIT_SUCCESS: i32 = 0;
+pub const EXIT_USER_ERROR: i32 = 1;
+pub const EXIT_INTERNAL_ERROR: i32 = 101;
@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::driver::cli::{error_format, parse_args, Command, ErrorFormat, OutputFormat};
+    use crate::check_str;
+    use crate::driver::diff::unified_diff;
+    use crate::driver::error::DriverErr;
+    use crate::driver::{EXIT_INTERNAL_ERROR, EXIT_USER_ERROR};
+    use crate::error::CompileErr;
+    use crate::Profile;
+
+    fn parse(args: &[&str]) -> Result<Command, DriverErr> {
+        parse_args(&args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_parse_args_commands() {
+        assert_eq!(parse(&["build", "a.sn", "-o", "out"]).unwrap(), Command::Build { input: "a.sn".into(), output: Some("out".into()), import_paths: vec![], profile: Profile::Debug });
+        assert_eq!(parse(&["build", "a.sn"]).unwrap(), Command::Build { input: "a.sn".into(), output: None, import_paths: vec![], profile: Profile::Debug });
+        assert_eq!(
+            parse(&["run", "a.sn", "--", "x", "-y"]).unwrap(),
+            Command::Run { input: "a.sn".into(), args: vec!["x".into(), "-y".into()], import_paths: vec![], profile: Profile::Debug }
+        );
+        assert_eq!(
+            parse(&["run", "--release", "a.sn"]).unwrap(),
+            Command::Run { input: "a.sn".into(), args: vec![], import_paths: vec![], profile: Profile::Release }
+        );
+        assert_eq!(parse(&["check", "a.sn"]).unwrap(), Command::Check { input: PathBuf::from("a.sn"), import_paths: vec![] });
+        assert_eq!(parse(&["fix", "a.sn"]).unwrap(), Command::Fix { input: "a.sn".into(), dry_run: false, import_paths: vec![] });
+        assert_eq!(parse(&["fix", "--dry-run", "a.sn"]).unwrap(), Command::Fix { input: "a.sn".into(), dry_run: true, import_paths: vec![] });
+        assert_eq!(parse(&["parse", "--format", "sexp", "a.sn"]).unwrap(), Command::Parse { input: "a.sn".into(), format: OutputFormat::Sexp });
+        assert_eq!(parse(&["lex", "a.sn"]).unwrap(), Command::Lex { input: "a.sn".into(), format: OutputFormat::Text });
+        assert_eq!(
+            parse(&["check", "-I", "lib", "a.sn", "--import-path", "/usr/share/sonneko"]).unwrap(),
+            Command::Check { input: "a.sn".into(), import_paths: vec!["lib".into(), "/usr/share/sonneko".into()] }
+        );
+    }
+
+    #[test]
+    fn test_parse_args_help_and_version() {
+        assert_eq!(parse(&[]).unwrap(), Command::Help);
+        assert_eq!(parse(&["--help"]).unwrap(), Command::Help);
+        assert_eq!(parse(&["build", "a.sn", "-h"]).unwrap(), Command::Help);
+        assert_eq!(parse(&["-V"]).unwrap(), Command::Version);
+        assert_eq!(parse(&["--explain", "E0301"]).unwrap(), Command::Explain { code: "E0301".into() });
+    }
+
+    #[test]
+    fn test_parse_args_usage_errors() {
+        for args in [&["frobnicate", "a.sn"][..], &["build"], &["check", "a.sn", "b.sn"], &["check", "-o", "x", "a.sn"], &["lex", "--format", "yaml", "a.sn"], &["--explain"], &["check", "--dry-run", "a.sn"], &["check", "a.sn", "-I"], &["lex", "-I", "lib", "a.sn"], &["check", "--release", "a.sn"]] {
+            let error = parse(args).unwrap_err();
+            assert!(matches!(error, DriverErr::Usage(_)), "{:?}", args);
+            assert_eq!(error.exit_code(), EXIT_USER_ERROR);
+        }
+    }
+
+    #[test]
+    fn test_parse_args_error_format() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(error_format(&args(&["check", "a.sn"])), ErrorFormat::Human);
+        assert_eq!(error_format(&args(&["--error-format=json", "check", "a.sn"])), ErrorFormat::Json);
+        assert_eq!(error_format(&args(&["check", "--error-format", "json", "a.sn"])), ErrorFormat::Json);
+        assert_eq!(error_format(&args(&["run", "a.sn", "--", "--error-format=json"])), ErrorFormat::Human);
+        assert_eq!(parse(&["--error-format=json", "check", "a.sn"]).unwrap(), Command::Check { input: "a.sn".into(), import_paths: vec![] });
+        assert_eq!(
+            parse(&["run", "a.sn", "--error-format", "human", "--", "--error-format=json"]).unwrap(),
+            Command::Run { input: "a.sn".into(), args: vec!["--error-format=json".into()], import_paths: vec![], profile: Profile::Debug }
+        );
+        assert!(matches!(parse(&["check", "--error-format=xml", "a.sn"]), Err(DriverErr::Usage(_))));
+        assert!(matches!(parse(&["check", "a.sn", "--error-format"]), Err(DriverErr::Usage(_))));
+    }
+
+    #[test]
+    fn test_front_end_errors_are_user_errors() {
+        assert!(check_str("fn main(): int { 0 }").is_ok());
+        for source in ["fn main(): int { $ }", "fn main(): int { 0 ", "fn main(): int { true }"] {
+            let error = DriverErr::from(check_str(source).unwrap_err());
+            assert!(!error.is_internal(), "{}", source);
+            assert_eq!(error.exit_code(), EXIT_USER_ERROR);
+        }
+        assert_eq!(DriverErr::from(CompileErr::Link("cc not found".into())).exit_code(), EXIT_INTERNAL_ERROR);
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        assert_eq!(unified_diff("a.sn", "a\nb\n", "a\nb\n"), "");
+        let old: String = (1..=12).map(|line| format!("{}\n", line)).collect();
+        let new = old.replacen("2\n", "two\n", 1).replace("11\n", "") + "13\n";
+        assert_eq!(
+            unified_diff("a.sn", &old, &new),
+            "--- a.sn\n+++ a.sn (fixed)\n@@ -1,5 +1,5 @@\n 1\n-2\n+two\n 3\n 4\n 5\n@@ -8,5 +8,5 @@\n 8\n 9\n 10\n-11\n 12\n+13\n"
+        );
+        assert_eq!(unified_diff("a.sn", "", "x\n"), "--- a.sn\n+++ a.sn (fixed)\n@@ -0,0 +1,1 @@\n+x\n");
+    }
+}
@@ -0,0 +1,31 @@
+use std::fmt;
+
+pub enum DumpErr {
+    Json(String),
+    /// raised by a `Serialize` impl through `serde::ser::Error::custom`
+    Custom(String),
+}
+
+impl fmt::Debug for DumpErr {
+    fn fmt (&self, formatter: &mut fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        writeln!(formatter, "failed to dump because...")?;
+        match self {
+            Self::Json(message) => write!(formatter, "JSON serialization failed: {}", message),
+            Self::Custom(message) => write!(formatter, "{}", message),
+        }
+    }
+}
+
+impl fmt::Display for DumpErr {
+    fn fmt (&self, formatter: &mut fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        fmt::Debug::fmt(self, formatter)
+    }
+}
+
+impl std::error::Error for DumpErr {}
+
+impl serde::ser::Error for DumpErr {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        DumpErr::Custom(message.to_string())
+    }
+}
@@ -0,0 +1,43 @@
+//! Dumps of the token stream and the AST for debugging and for tools written
+//! in other languages, built on the `serde::Serialize` impls behind the
+//! `serde` feature.
+//!
+//! Two formats are supported: JSON, and a compact S-expression form where a
+//! struct `Span { start: 0, end: 3 }` is written `(Span :start 0 :end 3)`, an
+//! enum variant `Int(1)` is written `(Int 1)`, a unit variant is its bare
+//! name, a sequence is `(a b c)` and `None` is `nil`.
+
+pub mod sexp;
+pub mod error;
+mod test;
+
+use serde::Serialize;
+
+use crate::dump::error::DumpErr;
+use crate::span::Span;
+use crate::tokenizer::Token;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DumpFormat {
+    Json,
+    Sexp,
+}
+
+/// A token together with where it is in the source.
+#[derive(Serialize)]
+pub struct SpannedToken<'a> {
+    pub token: &'a Token,
+    pub span: Span,
+}
+
+/// Pairs the tokens from `Tokenizer::tokenize` with `Tokenizer::spans`.
+pub fn spanned_tokens<'a>(tokens: &'a [Token], spans: &[Span]) -> Vec<SpannedToken<'a>> {
+    tokens.iter().zip(spans).map(|(token, span)| SpannedToken { token, span: *span }).collect()
+}
+
+pub fn dump<T: Serialize>(value: &T, format: DumpFormat) -> Result<String, DumpErr> {
+    match format {
+        DumpFormat::Json => serde_json::to_string_pretty(value).map_err(|error| DumpErr::Json(error.to_string())),
+        DumpFormat::Sexp => sexp::to_string(value),
+    }
+}
@@ -0,0 +1,324 @@
+//! A `serde::Serializer` writing the compact S-expression form described in
+//! the module documentation of `dump`.
+
+use serde::ser::{self, Serialize};
+
+use crate::dump::error::DumpErr;
+
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, DumpErr> {
+    let mut serializer = Serializer { output: String::new(), needs_space: false };
+    value.serialize(&mut serializer)?;
+    Ok(serializer.output)
+}
+
+pub struct Serializer {
+    output: String,
+    /// whether the next atom has to be separated from the previous one
+    needs_space: bool,
+}
+
+type SerializeResult = Result<(), DumpErr>;
+
+impl Serializer {
+    fn atom(&mut self, text: &str) {
+        if self.needs_space {
+            self.output.push(' ');
+        }
+        self.output.push_str(text);
+        self.needs_space = true;
+    }
+
+    /// Opens a list, optionally headed by a name like a struct or variant name.
+    fn open(&mut self, head: Option<&str>) {
+        if self.needs_space {
+            self.output.push(' ');
+        }
+        self.output.push('(');
+        self.needs_space = false;
+        if let Some(head) = head {
+            self.atom(head);
+        }
+    }
+
+    fn close(&mut self) {
+        self.output.push(')');
+        self.needs_space = true;
+    }
+
+    fn string(&mut self, value: &str) {
+        let mut text = String::with_capacity(value.len() + 2);
+        text.push('"');
+        for c in value.chars() {
+            match c {
+                '"' => text.push_str("\\\""),
+                '\\' => text.push_str("\\\\"),
+                '\n' => text.push_str("\\n"),
+                '\t' => text.push_str("\\t"),
+                c => text.push(c),
+            }
+        }
+        text.push('"');
+        self.atom(&text);
+    }
+}
+
+impl ser::Serializer for &mut Serializer {
+    type Ok = ();
+    type Error = DumpErr;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, value: bool) -> SerializeResult {
+        self.atom(if value { "true" } else { "false" });
+        Ok(())
+    }
+
+    fn serialize_i8(self, value: i8) -> SerializeResult {
+        self.serialize_i64(value as i64)
+    }
+
+    fn serialize_i16(self, value: i16) -> SerializeResult {
+        self.serialize_i64(value as i64)
+    }
+
+    fn serialize_i32(self, value: i32) -> SerializeResult {
+        self.serialize_i64(value as i64)
+    }
+
+    fn serialize_i64(self, value: i64) -> SerializeResult {
+        self.atom(&value.to_string());
+        Ok(())
+    }
+
+    fn serialize_u8(self, value: u8) -> SerializeResult {
+        self.serialize_u64(value as u64)
+    }
+
+    fn serialize_u16(self, value: u16) -> SerializeResult {
+        self.serialize_u64(value as u64)
+    }
+
+    fn serialize_u32(self, value: u32) -> SerializeResult {
+        self.serialize_u64(value as u64)
+    }
+
+    fn serialize_u64(self, value: u64) -> SerializeResult {
+        self.atom(&value.to_string());
+        Ok(())
+    }
+
+    fn serialize_f32(self, value: f32) -> SerializeResult {
+        // `Debug` keeps the `.0` that tells a float from an integer
+        self.atom(&format!("{:?}", value));
+        Ok(())
+    }
+
+    fn serialize_f64(self, value: f64) -> SerializeResult {
+        self.atom(&format!("{:?}", value));
+        Ok(())
+    }
+
+    fn serialize_char(self, value: char) -> SerializeResult {
+        self.string(value.encode_utf8(&mut [0; 4]));
+        Ok(())
+    }
+
+    fn serialize_str(self, value: &str) -> SerializeResult {
+        self.string(value);
+        Ok(())
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> SerializeResult {
+        self.open(None);
+        for byte in value {
+            self.atom(&byte.to_string());
+        }
+        self.close();
+        Ok(())
+    }
+
+    fn serialize_none(self) -> SerializeResult {
+        self.atom("nil");
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> SerializeResult {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> SerializeResult {
+        self.open(None);
+        self.close();
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> SerializeResult {
+        self.atom(name);
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> SerializeResult {
+        self.atom(variant);
+        Ok(())
+    }
+
+    /// Newtype structs like `NodeId` are written as their content.
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> SerializeResult {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _index: u32, variant: &'static str, value: &T) -> SerializeResult {
+        self.open(Some(variant));
+        value.serialize(&mut *self)?;
+        self.close();
+        Ok(())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self, DumpErr> {
+        self.open(None);
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, DumpErr> {
+        self.open(None);
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, name: &'static str, _len: usize) -> Result<Self, DumpErr> {
+        self.open(Some(name));
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(self, _name: &'static str, _index: u32, variant: &'static str, _len: usize) -> Result<Self, DumpErr> {
+        self.open(Some(variant));
+        Ok(self)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self, DumpErr> {
+        self.open(None);
+        Ok(self)
+    }
+
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<Self, DumpErr> {
+        self.open(Some(name));
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(self, _name: &'static str, _index: u32, variant: &'static str, _len: usize) -> Result<Self, DumpErr> {
+        self.open(Some(variant));
+        Ok(self)
+    }
+}
+
+impl ser::SerializeSeq for &mut Serializer {
+    type Ok = ();
+    type Error = DumpErr;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> SerializeResult {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> SerializeResult {
+        self.close();
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for &mut Serializer {
+    type Ok = ();
+    type Error = DumpErr;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> SerializeResult {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> SerializeResult {
+        self.close();
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut Serializer {
+    type Ok = ();
+    type Error = DumpErr;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> SerializeResult {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> SerializeResult {
+        self.close();
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut Serializer {
+    type Ok = ();
+    type Error = DumpErr;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> SerializeResult {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> SerializeResult {
+        self.close();
+        Ok(())
+    }
+}
+
+/// Each entry of a map is written as a `(key value)` pair.
+impl ser::SerializeMap for &mut Serializer {
+    type Ok = ();
+    type Error = DumpErr;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> SerializeResult {
+        self.open(None);
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> SerializeResult {
+        value.serialize(&mut **self)?;
+        self.close();
+        Ok(())
+    }
+
+    fn end(self) -> SerializeResult {
+        self.close();
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for &mut Serializer {
+    type Ok = ();
+    type Error = DumpErr;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> SerializeResult {
+        self.atom(&format!(":{}", key));
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> SerializeResult {
+        self.close();
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for &mut Serializer {
+    type Ok = ();
+    type Error = DumpErr;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> SerializeResult {
+        self.atom(&format!(":{}", key));
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> SerializeResult {
+        self.close();
+        Ok(())
+    }
+}
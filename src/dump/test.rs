@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod tests {
+    use crate::dump::{dump, spanned_tokens, DumpFormat};
+    use crate::dump::sexp;
+    use crate::parser::parser::Parser;
+    use crate::tokenizer::tokenizer::Tokenizer;
+
+    fn dump_source(program: &str, format: DumpFormat) -> String {
+        let mut tokenizer = Tokenizer::new(program);
+        let tokens = tokenizer.tokenize().unwrap().clone();
+        let program = Parser::new(tokens, tokenizer.spans().clone()).parse().unwrap();
+        dump(&program, format).unwrap()
+    }
+
+    #[test]
+    fn test_dump_sexp_program() {
+        assert_eq!(
+            dump_source("static x = -1;", DumpFormat::Sexp),
+            "(Program :id 3 :span (Span :start 0 :end 14) :declarations ((StaticVariable (StaticVariableDeclaration \
+             :id 2 :span (Span :start 0 :end 14) :is_pub false :name \"x\" :type_annotation nil :value (Expression \
+             :id 1 :span (Span :start 11 :end 13) :kind (Unary :ope Minus :operand (Expression :id 0 \
+             :span (Span :start 12 :end 13) :kind (Literal (Int 1)))))))))"
+        );
+    }
+
+    #[test]
+    fn test_dump_sexp_tokens() {
+        let mut tokenizer = Tokenizer::new("let s = \"a\\b\";");
+        let tokens = tokenizer.tokenize().unwrap().clone();
+        let spanned = spanned_tokens(&tokens, tokenizer.spans());
+        assert_eq!(sexp::to_string(&tokens[..4]).unwrap(), "(Let (Identifier \"s\") EqualOpe (StringLiteral \"a\\\\b\"))");
+        assert_eq!(sexp::to_string(&spanned[0]).unwrap(), "(SpannedToken :token Let :span (Span :start 0 :end 3))");
+    }
+
+    #[test]
+    fn test_dump_json_program() {
+        let json = dump_source("fn f(): float { 1.5 }", DumpFormat::Json);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let function = &value["declarations"][0]["Function"];
+        assert_eq!(function["name"], "f");
+        assert_eq!(function["signature"]["return_type"]["kind"], "FloatType");
+        assert_eq!(function["inner"]["value"]["kind"]["Literal"]["Float"], 1.5);
+        assert_eq!(function["inner"]["value"]["span"]["start"], 16);
+    }
+}
@@ -0,0 +1,115 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use crate::checker::error::CheckErr;
+#[cfg(feature = "llvm")]
+use crate::codegen::error::CodegenErr;
+use crate::diagnostic::Diagnostic;
+use crate::loader::error::LoadErr;
+use crate::parser::error::ParseErr;
+use crate::tokenizer::error::TokenizeErr;
+
+/// Any error of the passes behind the functions in the crate root.
+pub enum CompileErr {
+    Read(PathBuf, io::Error),
+    Tokenize(TokenizeErr),
+    Parse(ParseErr),
+    Load(LoadErr),
+    Check(Vec<CheckErr>),
+    NoMain,
+    #[cfg(feature = "llvm")]
+    Codegen(CodegenErr),
+    /// the system linker could not be started or failed
+    Link(String),
+}
+
+impl CompileErr {
+    /// Whether the compiler itself is at fault rather than its input.
+    pub fn is_internal(&self) -> bool {
+        match self {
+            Self::Read(..) | Self::Tokenize(_) | Self::Parse(_) | Self::Load(_) | Self::Check(_) | Self::NoMain => false,
+            #[cfg(feature = "llvm")]
+            Self::Codegen(CodegenErr::Unsupported(_)) => false,
+            #[cfg(feature = "llvm")]
+            Self::Codegen(_) => true,
+            Self::Link(_) => true,
+        }
+    }
+
+    /// What to report for the error, one diagnostic per problem found.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        match self {
+            Self::Read(path, error) => vec![Diagnostic::error(format!("can not read `{}`: {}", path.display(), error))],
+            Self::Tokenize(error) => vec![error.to_diagnostic()],
+            Self::Parse(error) => vec![error.to_diagnostic()],
+            Self::Load(error) => vec![error.to_diagnostic()],
+            Self::Check(errors) => {
+                // errors of name resolution come first, but are shown in source order
+                let mut diagnostics: Vec<Diagnostic> = errors.iter().map(CheckErr::to_diagnostic).collect();
+                diagnostics.sort_by_key(|diagnostic| diagnostic.primary_span().map(|span| span.start));
+                diagnostics
+            }
+            Self::NoMain => vec![Diagnostic::error("there is no `main` function").with_code("E0401").with_help("add `fn main(): int { 0 }` to the top level")],
+            #[cfg(feature = "llvm")]
+            Self::Codegen(error) => vec![error.to_diagnostic()],
+            Self::Link(message) => vec![Diagnostic::error(format!("failed to link program: {}", message))],
+        }
+    }
+}
+
+impl From<TokenizeErr> for CompileErr {
+    fn from(error: TokenizeErr) -> Self {
+        CompileErr::Tokenize(error)
+    }
+}
+
+impl From<ParseErr> for CompileErr {
+    fn from(error: ParseErr) -> Self {
+        CompileErr::Parse(error)
+    }
+}
+
+impl From<LoadErr> for CompileErr {
+    fn from(error: LoadErr) -> Self {
+        CompileErr::Load(error)
+    }
+}
+
+impl From<Vec<CheckErr>> for CompileErr {
+    fn from(errors: Vec<CheckErr>) -> Self {
+        CompileErr::Check(errors)
+    }
+}
+
+#[cfg(feature = "llvm")]
+impl From<CodegenErr> for CompileErr {
+    fn from(error: CodegenErr) -> Self {
+        CompileErr::Codegen(error)
+    }
+}
+
+impl fmt::Debug for CompileErr {
+    fn fmt (&self, formatter: &mut fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            Self::Read(path, error) => write!(formatter, "failed to read {} because...\n{}", path.display(), error),
+            Self::Tokenize(error) => write!(formatter, "{:?}", error),
+            Self::Parse(error) => write!(formatter, "{:?}", error),
+            Self::Load(error) => write!(formatter, "{:?}", error),
+            Self::Check(errors) => {
+                for (index, error) in errors.iter().enumerate() {
+                    if index > 0 {
+                        writeln!(formatter)?;
+                    }
+                    write!(formatter, "{:?}", error)?;
+                }
+                Ok(())
+            }
+            Self::NoMain => write!(formatter, "failed to build program because...\nthere is no `main` function"),
+            #[cfg(feature = "llvm")]
+            Self::Codegen(error) => write!(formatter, "{:?}", error),
+            Self::Link(message) => write!(formatter, "failed to link program because...\n{}", message),
+        }
+    }
+}
+
@@ -0,0 +1,226 @@
+//! Compiler for the sonneko language.
+//!
+//! The passes live in their own modules (`tokenizer`, `parser`, `resolver`,
+//! `checker`, `codegen`) and can be driven one by one. The functions here
+//! chain them for the common cases and are what the `sonneko` binary is built
+//! on. The `*_str` functions compile a single source without reading any
+//! file, so only the `*_file` ones follow imports, through the `loader`. Both
+//! add the `prelude` to the program, which the passes then see like any other
+//! declarations.
+//!
+//! `codegen`, `compile_str`, `build_str`, `build_file` and `build_program`
+//! need the `llvm` feature, which is on by default. Without it the crate
+//! builds without an LLVM install.
+
+// every pass is a directory whose main file shares its name, e.g. `parser::parser`
+#![allow(clippy::module_inception)]
+
+pub mod span;
+pub mod numeric;
+pub mod diagnostic;
+pub mod tokenizer;
+pub mod parser;
+pub mod prelude;
+pub mod loader;
+pub mod resolver;
+pub mod checker;
+#[cfg(feature = "llvm")]
+pub mod codegen;
+pub mod driver;
+pub mod error;
+#[cfg(feature = "serde")]
+pub mod dump;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::checker::checker::Checker;
+use crate::checker::error::CheckErr;
+use crate::checker::Checked;
+use crate::error::CompileErr;
+use crate::loader::loader::Loader;
+use crate::parser::parser::Parser;
+use crate::parser::{Declaration, Program};
+use crate::resolver::resolver::Resolver;
+use crate::span::Span;
+use crate::tokenizer::tokenizer::Tokenizer;
+use crate::tokenizer::Token;
+
+#[cfg(feature = "llvm")]
+use {
+    crate::codegen::codegen::Codegen,
+    crate::diagnostic::{SourceFile, SourceMap},
+    inkwell::context::Context,
+    std::process,
+};
+
+pub type CompileResult<T> = Result<T, CompileErr>;
+
+/// How a program is built. Integer `+`, `-` and `*` trap with the location
+/// of the operation when they overflow in debug builds, and wrap in release
+/// builds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Profile {
+    #[default]
+    Debug,
+    Release,
+}
+
+pub fn read_file(path: impl AsRef<Path>) -> CompileResult<String> {
+    let path = path.as_ref();
+    fs::read_to_string(path).map_err(|error| CompileErr::Read(path.to_path_buf(), error))
+}
+
+/// Returns the tokens of `source` and the span of each of them.
+pub fn tokenize(source: &str) -> CompileResult<(Vec<Token>, Vec<Span>)> {
+    let mut tokenizer = Tokenizer::new(source);
+    let tokens = tokenizer.tokenize()?.clone();
+    Ok((tokens, tokenizer.spans().clone()))
+}
+
+pub fn parse_str(source: &str) -> CompileResult<Program> {
+    let mut tokenizer = Tokenizer::new(source);
+    let tokens = tokenizer.tokenize()?.clone();
+    Ok(Parser::new(tokens, tokenizer.spans().clone()).with_line_starts(tokenizer.line_starts().clone()).parse()?)
+}
+
+pub fn parse_file(path: impl AsRef<Path>) -> CompileResult<Program> {
+    parse_str(&read_file(path)?)
+}
+
+/// Resolves the names of `program` and type checks it, returning what the
+/// backend needs to lower it.
+pub fn check(program: &Program) -> CompileResult<Checked> {
+    let (resolution, errors) = Resolver::new().resolve(program);
+    let mut errors: Vec<CheckErr> = errors.into_iter().map(CheckErr::from).collect();
+    let mut checker = Checker::new(&resolution);
+    if let Err(check_errors) = checker.check(program) {
+        errors.extend(check_errors);
+    }
+    let types = checker.into_types();
+    if errors.is_empty() {
+        Ok(Checked { resolution, types })
+    } else {
+        Err(CompileErr::Check(errors))
+    }
+}
+
+/// Runs the front end: tokenizes, parses, resolves and type checks `source`.
+pub fn check_str(source: &str) -> CompileResult<(Program, Checked)> {
+    let mut program = parse_str(source)?;
+    prelude::add(&mut program);
+    let checked = check(&program)?;
+    Ok((program, checked))
+}
+
+/// Loads the program at `input` with every module it imports, looking for
+/// them next to the importing file and then in `search_paths`, and checks it.
+pub fn check_file(input: impl AsRef<Path>, search_paths: &[PathBuf]) -> CompileResult<(Program, Checked)> {
+    let program = Loader::new(search_paths.to_vec()).load(input.as_ref())?;
+    let checked = check(&program)?;
+    Ok((program, checked))
+}
+
+/// Checks that `program` has the top level `main` an executable starts at,
+/// which can not be generic.
+pub fn check_main(program: &Program) -> CompileResult<()> {
+    let has_main = program.declarations.iter().any(|declaration| {
+        matches!(declaration, Declaration::Function(function) if function.name == "main" && function.signature.type_params.is_empty())
+    });
+    if has_main {
+        Ok(())
+    } else {
+        Err(CompileErr::NoMain)
+    }
+}
+
+/// Applies the machine-applicable suggestions of the front end to `source`
+/// and returns the fixed text with the number of suggestions applied.
+pub fn fix_str(source: &str) -> (String, usize) {
+    fix_with(source, |text| check_str(text).map(drop))
+}
+
+/// Like `fix_str` for the program at `input`, checked with every module it
+/// imports like `check_file` does. Returns the fixed text of `input` without
+/// writing it; the modules it imports are left as they are.
+pub fn fix_file(input: impl AsRef<Path>, search_paths: &[PathBuf]) -> CompileResult<(String, usize)> {
+    let input = input.as_ref();
+    let source = read_file(input)?;
+    Ok(fix_with(&source, |text| {
+        let program = Loader::new(search_paths.to_vec()).with_entry_text(text.to_string()).load(input)?;
+        check(&program).map(drop)
+    }))
+}
+
+/// The parser stops at its first error, so this checks again after every
+/// round of fixes until nothing more applies. The entry file comes first
+/// in the source map, so suggestions for the files it imports lie past the
+/// end of `source` and are never applied.
+fn fix_with(source: &str, check_text: impl Fn(&str) -> CompileResult<()>) -> (String, usize) {
+    const MAX_ROUNDS: usize = 32;
+    let mut text = source.to_string();
+    let mut applied = 0;
+    for _ in 0..MAX_ROUNDS {
+        let Err(error) = check_text(&text) else { break };
+        let (fixed, count) = diagnostic::fix::apply(&text, &error.diagnostics());
+        if count == 0 {
+            break;
+        }
+        text = fixed;
+        applied += count;
+    }
+    (text, applied)
+}
+
+/// Compiles `source` to the text of an LLVM module named `module_name`, as a debug build.
+#[cfg(feature = "llvm")]
+pub fn compile_str(source: &str, module_name: &str) -> CompileResult<String> {
+    let (program, checked) = check_str(source)?;
+    let context = Context::create();
+    let mut codegen = Codegen::new(&context, module_name).with_sources(SourceFile::new(module_name, source).into());
+    codegen.compile(&program, checked)?;
+    Ok(codegen.module().print_to_string().to_string())
+}
+
+/// Compiles the program at `input` and the modules it imports to an
+/// executable at `output`, linking with the system C compiler `cc`. The
+/// program needs a top level `main`.
+#[cfg(feature = "llvm")]
+pub fn build_file(input: impl AsRef<Path>, output: impl AsRef<Path>, profile: Profile) -> CompileResult<()> {
+    let input = input.as_ref();
+    let module_name = input.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let mut loader = Loader::new(Vec::new());
+    let program = loader.load(input)?;
+    let checked = check(&program)?;
+    build_program(&program, checked, &loader.into_sources(), &module_name, output, profile)
+}
+
+/// Compiles `source` to an executable at `output` like `build_file`.
+#[cfg(feature = "llvm")]
+pub fn build_str(source: &str, module_name: &str, output: impl AsRef<Path>, profile: Profile) -> CompileResult<()> {
+    let sources = SourceFile::new(module_name, source).into();
+    let (program, checked) = check_str(source)?;
+    build_program(&program, checked, &sources, module_name, output, profile)
+}
+
+/// Compiles `program` to an executable at `output` like `build_file`, with
+/// what `check` returned for it. `sources` are the files the program was
+/// read from, which runtime errors point into.
+#[cfg(feature = "llvm")]
+pub fn build_program(program: &Program, checked: Checked, sources: &SourceMap, module_name: &str, output: impl AsRef<Path>, profile: Profile) -> CompileResult<()> {
+    let output = output.as_ref();
+    check_main(program)?;
+
+    let context = Context::create();
+    let mut codegen = Codegen::new(&context, module_name).with_profile(profile).with_sources(sources.clone());
+    codegen.compile(program, checked)?;
+    let object = output.with_file_name(format!("{}.o", output.file_name().unwrap_or_default().to_string_lossy()));
+    codegen.write_object(&object)?;
+    let status = process::Command::new("cc").arg(&object).arg("-o").arg(output).status();
+    let _ = fs::remove_file(&object);
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(CompileErr::Link(format!("`cc` exited with {}", status))),
+        Err(error) => Err(CompileErr::Link(format!("can not start `cc`: {}", error))),
+    }
+}
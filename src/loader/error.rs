@@ -0,0 +1,39 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::diagnostic::Diagnostic;
+use crate::span::Span;
+
+/// The last `Span` of every variant is the import the error is reported at.
+pub enum LoadErr {
+    /// the module as written and the files that were tried
+    NotFound(String, Vec<PathBuf>, Span),
+    /// the modules of the cycle from the first one importing to the one imported again
+    Cycle(Vec<String>, Span),
+}
+
+impl LoadErr {
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            Self::NotFound(path, tried, span) => {
+                let tried: Vec<String> = tried.iter().map(|path| format!("`{}`", path.display())).collect();
+                Diagnostic::error(format!("can not find module `{}`", path))
+                    .with_code("E0501")
+                    .with_label(*span, "imported here")
+                    .with_note(format!("looked for {}", tried.join(", ")))
+                    .with_help("add the directory of the module with `--import-path`")
+            }
+            Self::Cycle(modules, span) => Diagnostic::error(format!("`{}` imports itself", modules[0]))
+                .with_code("E0502")
+                .with_label(*span, format!("`{}` is imported again here", modules.last().unwrap()))
+                .with_note(format!("the import cycle is {}", modules.join(" -> "))),
+        }
+    }
+}
+
+impl fmt::Debug for LoadErr {
+    fn fmt (&self, formatter: &mut fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        writeln!(formatter, "failed to load modules because...")?;
+        write!(formatter, "{}", self.to_diagnostic())
+    }
+}
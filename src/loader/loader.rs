@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::diagnostic::SourceMap;
+use crate::error::CompileErr;
+use crate::loader::error::LoadErr;
+use crate::loader::EXTENSION;
+use crate::parser::parser::Parser;
+use crate::parser::*;
+use crate::prelude;
+use crate::tokenizer::tokenizer::Tokenizer;
+use crate::CompileResult;
+
+pub struct Loader {
+    /// directories searched for a module after the one of the importing file
+    search_paths: Vec<PathBuf>,
+    sources: SourceMap,
+    /// the namespace every module read so far is loaded into, by canonical path
+    modules: HashMap<PathBuf, String>,
+    /// canonical paths of the modules whose imports are being loaded, entry first
+    loading: Vec<PathBuf>,
+    /// a namespace per imported module, each after the modules it imports
+    namespaces: Vec<Declaration>,
+    /// the directory of the entry file, which module names are relative to
+    root: PathBuf,
+    /// read instead of the entry file when set
+    entry_text: Option<String>,
+    next_id: NodeId,
+}
+
+impl Loader {
+    pub fn new(search_paths: Vec<PathBuf>) -> Self {
+        Loader {
+            search_paths,
+            sources: SourceMap::new(),
+            modules: HashMap::new(),
+            loading: Vec::new(),
+            namespaces: Vec::new(),
+            root: PathBuf::new(),
+            entry_text: None,
+            next_id: NodeId(0),
+        }
+    }
+
+    /// Loads `text` as the entry file rather than what is on disk, like the
+    /// fixed text `sonneko fix` has not written yet. Imports are still read
+    /// relative to the path passed to `load`.
+    pub fn with_entry_text(mut self, text: String) -> Self {
+        self.entry_text = Some(text);
+        self
+    }
+
+    /// Every file read so far, also after an error in one of them.
+    pub fn sources(&self) -> &SourceMap {
+        &self.sources
+    }
+
+    pub fn into_sources(self) -> SourceMap {
+        self.sources
+    }
+
+    /// Reads `entry` and every module it imports into one program, with the prelude.
+    pub fn load(&mut self, entry: &Path) -> CompileResult<Program> {
+        let canonical = fs::canonicalize(entry).map_err(|error| CompileErr::Read(entry.to_path_buf(), error))?;
+        self.root = canonical.parent().map(Path::to_path_buf).unwrap_or_default();
+        let mut program = self.load_module(entry, canonical)?;
+        program.declarations.append(&mut self.namespaces);
+        prelude::add(&mut program);
+        Ok(program)
+    }
+
+    fn load_module(&mut self, path: &Path, canonical: PathBuf) -> CompileResult<Program> {
+        let text = match self.entry_text.take() {
+            Some(text) => text,
+            None => fs::read_to_string(path).map_err(|error| CompileErr::Read(path.to_path_buf(), error))?,
+        };
+        let file = self.sources.add(path.display().to_string(), text);
+        let mut tokenizer = Tokenizer::starting_at(&file.text, file.start);
+        let tokens = tokenizer.tokenize()?.clone();
+        let mut parser = Parser::new(tokens, tokenizer.spans().clone()).with_first_id(self.next_id).with_line_starts(tokenizer.line_starts().clone());
+        let mut program = parser.parse()?;
+        self.next_id = parser.next_free_id();
+
+        let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        self.loading.push(canonical);
+        let result = self.load_imports(&mut program, &directory);
+        self.loading.pop();
+        result.map(|()| program)
+    }
+
+    fn load_imports(&mut self, program: &mut Program, directory: &Path) -> CompileResult<()> {
+        for declaration in &mut program.declarations {
+            match declaration {
+                Declaration::Import(import) => import.path = self.import(import, directory)?,
+                Declaration::Namespace(namespace) => self.load_imports(&mut namespace.inner, directory)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads the module `import` names unless it already is, and returns the
+    /// name of its namespace.
+    fn import(&mut self, import: &ImportDeclaration, directory: &Path) -> CompileResult<String> {
+        let (path, canonical) = self.find(&import.path, directory).map_err(|tried| LoadErr::NotFound(import.path.clone(), tried, import.span))?;
+        if let Some(index) = self.loading.iter().position(|loading| *loading == canonical) {
+            let cycle = self.loading[index..].iter().chain([&canonical]).map(|path| self.module_name(path)).collect();
+            return Err(LoadErr::Cycle(cycle, import.span).into());
+        }
+        if let Some(name) = self.modules.get(&canonical) {
+            return Ok(name.clone());
+        }
+
+        let mut name = self.module_name(&canonical);
+        if self.modules.values().any(|other| *other == name) {
+            // two files with the same path under different search paths
+            name = canonical.display().to_string();
+        }
+        self.modules.insert(canonical.clone(), name.clone());
+        let inner = self.load_module(&path, canonical)?;
+        let id = self.next_id;
+        self.next_id = NodeId(id.0 + 1);
+        self.namespaces.push(Declaration::Namespace(NamespaceDeclaration { id, span: inner.span, is_pub: false, name: name.clone(), inner }));
+        Ok(name)
+    }
+
+    /// The file of the module `written` next to the importing file or in a
+    /// search path, with its canonical path. Returns the files tried when
+    /// none exists.
+    fn find(&self, written: &str, directory: &Path) -> Result<(PathBuf, PathBuf), Vec<PathBuf>> {
+        let file = if written.ends_with(&format!(".{}", EXTENSION)) { written.to_string() } else { format!("{}.{}", written, EXTENSION) };
+        let candidates: Vec<PathBuf> = std::iter::once(directory).chain(self.search_paths.iter().map(PathBuf::as_path)).map(|directory| directory.join(&file)).collect();
+        for candidate in &candidates {
+            if let Some(canonical) = fs::canonicalize(candidate).ok().filter(|canonical| canonical.is_file()) {
+                return Ok((candidate.clone(), canonical));
+            }
+        }
+        Err(candidates)
+    }
+
+    /// Names a module by its path relative to the entry file or to the search
+    /// path it is in, like `util.sn` or `shapes/circle.sn`.
+    fn module_name(&self, canonical: &Path) -> String {
+        let bases = std::iter::once(self.root.clone()).chain(self.search_paths.iter().filter_map(|path| fs::canonicalize(path).ok()));
+        let relative = bases.filter_map(|base| canonical.strip_prefix(base).ok().map(Path::to_path_buf)).min_by_key(|relative| relative.components().count());
+        match relative {
+            Some(relative) => relative.components().map(|component| component.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/"),
+            None => canonical.display().to_string(),
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//! Loading a program that is split over several files.
+//!
+//! `import { a, b } from "util";` reads `util.sn` from the directory of the
+//! importing file, or else from the first search path that has it. Every
+//! module is read and parsed once however often it is imported, and modules
+//! may not import each other in a cycle.
+//!
+//! The files become one `Program`: the entry file at the top level and every
+//! imported module in a namespace of its own, named after its file like
+//! `util.sn` so that no identifier can clash with it. The `path` of each
+//! import is rewritten to that name, and the resolver binds the imported
+//! names to the `pub` items of the module. All files share one `SourceMap`,
+//! so the spans of a diagnostic tell which file they point into. The
+//! `prelude` comes first, in none of the files.
+
+pub mod loader;
+pub mod error;
+mod test;
+
+/// The extension of module files; `"util"` is read from `util.sn`.
+pub const EXTENSION: &str = "sn";
+
+/// Whether `name` is the namespace of a loaded module rather than one
+/// declared in a program, which only has identifiers for names.
+pub fn is_module(name: &str) -> bool {
+    name.strip_suffix(EXTENSION).is_some_and(|stem| stem.ends_with('.'))
+}
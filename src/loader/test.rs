@@ -0,0 +1,179 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use crate::error::CompileErr;
+    use crate::loader::error::LoadErr;
+    use crate::loader::is_module;
+    use crate::loader::loader::Loader;
+    use crate::parser::visit::{self, Visitor};
+    use crate::parser::*;
+    use crate::resolver::error::ResolveErr;
+    use crate::resolver::resolver::Resolver;
+    use crate::resolver::Res;
+
+    /// A directory of its own for each test, removed when dropped.
+    struct Files(PathBuf);
+
+    impl Files {
+        fn new(test: &str, files: &[(&str, &str)]) -> Self {
+            let directory = std::env::temp_dir().join(format!("sonneko-{}-{}", test, std::process::id()));
+            for (name, text) in files {
+                let path = directory.join(name);
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(path, text).unwrap();
+            }
+            Files(directory)
+        }
+
+        fn path(&self, name: &str) -> PathBuf {
+            self.0.join(name)
+        }
+    }
+
+    impl Drop for Files {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn load(entry: &Path, search_paths: Vec<PathBuf>) -> (Result<Program, CompileErr>, Loader) {
+        let mut loader = Loader::new(search_paths);
+        (loader.load(entry), loader)
+    }
+
+    fn namespaces(program: &Program) -> Vec<&str> {
+        program
+            .declarations
+            .iter()
+            .filter_map(|declaration| match declaration {
+                Declaration::Namespace(namespace) => Some(namespace.name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[derive(Default)]
+    struct Ids(Vec<NodeId>);
+
+    impl Visitor for Ids {
+        fn visit_expression(&mut self, expression: &Expression) {
+            self.0.push(expression.id);
+            visit::walk_expression(self, expression)
+        }
+
+        fn visit_function(&mut self, declaration: &FunctionDeclaration) {
+            self.0.push(declaration.id);
+            visit::walk_function(self, declaration)
+        }
+    }
+
+    #[test]
+    fn test_load_import_graph() {
+        let files = Files::new("load-graph", &[
+            ("main.sn", "import { area } from \"shapes/circle\";\nimport util from \"util\";\nfn main(): int { area(2) + util::double(1) }\n"),
+            ("shapes/circle.sn", "import { double } from \"../util\";\npub fn area(r: int): int { double(r * r) * 3 }\n"),
+            ("util.sn", "pub fn double(x: int): int { x * 2 }\n"),
+        ]);
+        let (program, loader) = load(&files.path("main.sn"), Vec::new());
+        let program = program.unwrap();
+        // `util.sn` is imported twice but loaded once, before the module importing it first
+        assert_eq!(namespaces(&program), ["util.sn", "shapes/circle.sn"]);
+        let imports: Vec<&str> = program
+            .declarations
+            .iter()
+            .filter_map(|declaration| match declaration {
+                Declaration::Import(import) => Some(import.path.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(imports, ["shapes/circle.sn", "util.sn"]);
+        assert!(namespaces(&program).iter().all(|name| is_module(name)));
+
+        let names: Vec<&str> = loader.sources().files().iter().map(|file| file.name.rsplit(['/', '\\']).next().unwrap()).collect();
+        assert_eq!(names, ["main.sn", "circle.sn", "util.sn"]);
+        // after the two enums of the prelude and the entry file
+        let Declaration::Namespace(util) = &program.declarations[5] else { unreachable!() };
+        assert!(loader.sources().file(util.span.start).unwrap().name.ends_with("util.sn"));
+
+        let mut ids = Ids::default();
+        ids.visit_program(&program);
+        let count = ids.0.len();
+        ids.0.sort();
+        ids.0.dedup();
+        assert_eq!(ids.0.len(), count, "node ids are unique across files");
+    }
+
+    #[test]
+    fn test_load_search_paths() {
+        let files = Files::new("load-search", &[
+            ("app/main.sn", "import { f } from \"lib\";\nfn main(): int { f() }\n"),
+            ("first/lib.sn", "pub fn f(): int { 1 }\n"),
+            ("second/lib.sn", "pub fn f(): int { 2 }\n"),
+        ]);
+        let (program, loader) = load(&files.path("app/main.sn"), vec![files.path("first"), files.path("second")]);
+        assert_eq!(namespaces(&program.unwrap()), ["lib.sn"]);
+        assert!(loader.sources().files()[1].name.ends_with("lib.sn") && loader.sources().files()[1].name.contains("first"));
+
+        let (error, _) = load(&files.path("app/main.sn"), Vec::new());
+        let Err(CompileErr::Load(LoadErr::NotFound(path, tried, _))) = error else { panic!("{:?}", error) };
+        assert_eq!(path, "lib");
+        assert_eq!(tried, [files.path("app/lib.sn")]);
+    }
+
+    #[test]
+    fn test_load_cycle() {
+        let files = Files::new("load-cycle", &[
+            ("main.sn", "import { a } from \"a\";\nfn main(): int { a() }\n"),
+            ("a.sn", "import { b } from \"b\";\npub fn a(): int { b() }\n"),
+            ("b.sn", "import { a } from \"a\";\npub fn b(): int { 1 }\n"),
+        ]);
+        let (error, loader) = load(&files.path("main.sn"), Vec::new());
+        let Err(CompileErr::Load(LoadErr::Cycle(modules, span))) = error else { panic!("{:?}", error) };
+        assert_eq!(modules, ["a.sn", "b.sn", "a.sn"]);
+        assert!(loader.sources().file(span.start).unwrap().name.ends_with("b.sn"));
+    }
+
+    #[test]
+    fn test_load_errors_point_into_their_file() {
+        let files = Files::new("load-errors", &[("main.sn", "import { f } from \"broken\";\n"), ("broken.sn", "pub fn f( {\n")]);
+        let (error, loader) = load(&files.path("main.sn"), Vec::new());
+        let Err(CompileErr::Parse(error)) = error else { panic!("{:?}", error) };
+        let span = error.to_diagnostic().primary_span().unwrap();
+        let file = loader.sources().file(span.start).unwrap();
+        assert!(file.name.ends_with("broken.sn"));
+        assert_eq!(file.line_col(span.start), (1, 11));
+    }
+
+    #[test]
+    fn test_resolve_imports() {
+        let files = Files::new("load-resolve", &[
+            ("main.sn", "import { square, hidden, sqare } from \"math\";\nimport m from \"math\";\nfn main(): int { square(2) + m::square(3) + m::hidden() }\n"),
+            ("math.sn", "pub fn square(x: int): int { x * x }\nfn hidden(): int { main() }\n"),
+        ]);
+        let (program, _) = load(&files.path("main.sn"), Vec::new());
+        let program = program.unwrap();
+        let (resolution, errors) = Resolver::new().resolve(&program);
+        let messages: Vec<String> = errors.iter().map(|error| error.to_diagnostic().message).collect();
+        assert_eq!(
+            messages,
+            [
+                "`math.sn::hidden` is private",
+                "`math.sn::sqare` is not defined",
+                "`m::hidden` is private",
+                // a module only sees what it declares or imports
+                "`main` is not defined",
+            ]
+        );
+        assert!(matches!(&errors[1], ResolveErr::Undefined(_, _, Some(similar)) if similar == "square"));
+
+        let Declaration::Function(main) = &program.declarations[4] else { unreachable!() };
+        let ExpressionKind::Binary { left, .. } = &main.inner.value.as_ref().unwrap().kind else { unreachable!() };
+        let ExpressionKind::Binary { left, right, .. } = &left.kind else { unreachable!() };
+        for call in [left, right] {
+            let ExpressionKind::Call { callee, .. } = &call.kind else { unreachable!() };
+            assert_eq!(resolution.get(callee.id), Some(&Res::Function("math.sn::square".into())));
+        }
+    }
+}
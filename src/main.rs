@@ -1,9 +1,19 @@
-mod tokenizer;
-mod parser;
-use tokenizer::tokenizer::Tokenizer;
+use std::process::exit;
+
+use sonneko_c_compiler::driver;
+use sonneko_c_compiler::driver::cli::ErrorFormat;
 
 fn main() {
-    let mut tokenizer = Tokenizer::new("");
-    tokenizer.tokenize().unwrap();
-    println!("Hello World!");
-}
\ No newline at end of file
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let code = match driver::cli::parse_args(&args).and_then(driver::driver::run) {
+        Ok(code) => code,
+        Err(error) => {
+            match driver::cli::error_format(&args) {
+                ErrorFormat::Human => eprint!("{}", error.render(driver::driver::use_color())),
+                ErrorFormat::Json => eprint!("{}", error.render_json()),
+            }
+            error.exit_code()
+        }
+    };
+    exit(code);
+}
@@ -0,0 +1,169 @@
+//! The sized numeric types shared by the tokenizer, the parser and later passes.
+//!
+//! `int` and `float` are other names for `i32` and `f32`. Numbers never
+//! convert implicitly; `as` converts between any two numeric types:
+//!
+//! - an integer to a narrower integer keeps the low bits, to a wider one it is
+//!   sign extended if it is signed and zero extended if not;
+//! - an integer to a float rounds to the nearest float;
+//! - a float to an integer rounds toward zero and saturates at the bounds of
+//!   the integer, with NaN becoming 0;
+//! - a float to another float rounds to the nearest value.
+//!
+//! `bool` and `char` also convert to integers, and `u8` to `char`.
+//!
+//! Integer `+`, `-` and `*` trap when the result overflows in debug builds and
+//! wrap in release builds. The `Intrinsic`s pick one behavior for every build:
+//! `wrapping_*` and `saturating_*` give a number of the type of their
+//! operands, and `checked_*` an `Option` of it, which is `None` when the
+//! result overflows. Division traps on a zero divisor in every build.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum NumericType {
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    /// pointer-sized unsigned integer
+    Usize,
+    F32,
+    F64,
+}
+
+impl NumericType {
+    pub const ALL: [NumericType; 11] = [
+        NumericType::I8,
+        NumericType::I16,
+        NumericType::I32,
+        NumericType::I64,
+        NumericType::U8,
+        NumericType::U16,
+        NumericType::U32,
+        NumericType::U64,
+        NumericType::Usize,
+        NumericType::F32,
+        NumericType::F64,
+    ];
+
+    /// The keyword naming the type.
+    pub fn name(self) -> &'static str {
+        match self {
+            NumericType::I8 => "i8",
+            NumericType::I16 => "i16",
+            NumericType::I32 => "i32",
+            NumericType::I64 => "i64",
+            NumericType::U8 => "u8",
+            NumericType::U16 => "u16",
+            NumericType::U32 => "u32",
+            NumericType::U64 => "u64",
+            NumericType::Usize => "usize",
+            NumericType::F32 => "f32",
+            NumericType::F64 => "f64",
+        }
+    }
+
+    pub fn is_float(self) -> bool {
+        matches!(self, NumericType::F32 | NumericType::F64)
+    }
+
+    pub fn is_signed(self) -> bool {
+        matches!(self, NumericType::I8 | NumericType::I16 | NumericType::I32 | NumericType::I64) || self.is_float()
+    }
+
+    /// Size in bits. `usize` is 64 bits wide, as on every target sonneko builds for.
+    pub fn bits(self) -> u32 {
+        match self {
+            NumericType::I8 | NumericType::U8 => 8,
+            NumericType::I16 | NumericType::U16 => 16,
+            NumericType::I32 | NumericType::U32 | NumericType::F32 => 32,
+            NumericType::I64 | NumericType::U64 | NumericType::Usize | NumericType::F64 => 64,
+        }
+    }
+
+    /// The smallest and largest value of an integer type.
+    pub fn range(self) -> Option<(i128, i128)> {
+        if self.is_float() {
+            return None;
+        }
+        let bits = self.bits();
+        Some(if self.is_signed() { (-(1 << (bits - 1)), (1 << (bits - 1)) - 1) } else { (0, (1 << bits) - 1) })
+    }
+}
+
+impl fmt::Display for NumericType {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}", self.name())
+    }
+}
+
+/// An integer operation with explicit overflow behavior, called like a
+/// function: `wrapping_add(a, b)`. Both operands have one integer type, which
+/// is the type of the result, or of the `Some` of it for `Overflow::Checked`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Intrinsic {
+    pub overflow: Overflow,
+    pub operation: Operation,
+}
+
+/// What an intrinsic does when the result does not fit its type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// keeps the low bits, like arithmetic in release builds
+    Wrapping,
+    /// gives `None`, and `Some` of the result when it fits
+    Checked,
+    /// gives the smallest or largest value of the type
+    Saturating,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Add,
+    Sub,
+    Mul,
+}
+
+impl Operation {
+    /// How a trap message names the operation, as in "attempt to add with overflow".
+    pub fn verb(self) -> &'static str {
+        match self {
+            Operation::Add => "add",
+            Operation::Sub => "subtract",
+            Operation::Mul => "multiply",
+        }
+    }
+}
+
+impl Intrinsic {
+    pub fn all() -> impl Iterator<Item = Intrinsic> {
+        [Overflow::Wrapping, Overflow::Checked, Overflow::Saturating]
+            .into_iter()
+            .flat_map(|overflow| [Operation::Add, Operation::Sub, Operation::Mul].map(|operation| Intrinsic { overflow, operation }))
+    }
+
+    /// The intrinsic called `name`, like `saturating_sub`.
+    pub fn find(name: &str) -> Option<Intrinsic> {
+        Self::all().find(|intrinsic| intrinsic.name() == name)
+    }
+
+    pub fn name(self) -> String {
+        let overflow = match self.overflow {
+            Overflow::Wrapping => "wrapping",
+            Overflow::Checked => "checked",
+            Overflow::Saturating => "saturating",
+        };
+        let operation = match self.operation {
+            Operation::Add => "add",
+            Operation::Sub => "sub",
+            Operation::Mul => "mul",
+        };
+        format!("{}_{}", overflow, operation)
+    }
+}
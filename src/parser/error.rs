@@ -1,3 +1,62 @@
+use std::fmt;
+
+use crate::diagnostic::{Applicability, Diagnostic};
+use crate::tokenizer::Token;
+use crate::span::Span;
+
 pub enum ParseErr {
-    
-}
\ No newline at end of file
+    /// found token, what was expected, where the token is, where the token
+    /// before it ends and whether the found token starts a later line than it
+    UnexpectedToken(Token, &'static str, Span, usize, bool),
+    /// what was expected, the end of the program
+    UnexpectedEOF(&'static str, Span),
+    InvalidAssignTarget(Span),
+    /// where the `=` is
+    AssignInCondition(Span),
+}
+
+impl ParseErr {
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            Self::UnexpectedToken(found, expected, span, previous_end, on_later_line) => {
+                let diagnostic = Diagnostic::error(format!("expected {}, found {}", expected, found))
+                    .with_code("E0201")
+                    .with_label(*span, format!("expected {}", expected));
+                // a token on the same line may as well be one too many, like the `)` of `(1 + 2))`
+                let applicability = if *on_later_line { Applicability::MachineApplicable } else { Applicability::MaybeIncorrect };
+                missing_semicolon(diagnostic, expected, *previous_end, applicability)
+            }
+            Self::UnexpectedEOF(expected, span) => {
+                let diagnostic = Diagnostic::error(format!("expected {}, found end of file", expected))
+                    .with_code("E0202")
+                    .with_label(*span, format!("expected {}", expected));
+                missing_semicolon(diagnostic, expected, span.start, Applicability::MachineApplicable)
+            }
+            Self::InvalidAssignTarget(span) => Diagnostic::error("invalid left hand side of assignment")
+                .with_code("E0203")
+                .with_label(*span, "can not assign to this")
+                .with_help("only variables and fields can be assigned to"),
+            Self::AssignInCondition(span) => Diagnostic::error("expected `{`, found `=`")
+                .with_code("E0204")
+                .with_label(*span, "assignment is not an expression")
+                .with_suggestion("to compare for equality, use `==`", *span, "==", Applicability::MachineApplicable),
+        }
+    }
+}
+
+/// A `;` may be missing when one is all that was expected; it goes right
+/// after the previous token, not before the token found on the next line.
+fn missing_semicolon(diagnostic: Diagnostic, expected: &str, previous_end: usize, applicability: Applicability) -> Diagnostic {
+    if expected == "`;`" {
+        diagnostic.with_suggestion("add `;` here", Span::new(previous_end, previous_end), ";", applicability)
+    } else {
+        diagnostic
+    }
+}
+
+impl fmt::Debug for ParseErr {
+    fn fmt (&self, formatter: &mut fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        writeln!(formatter, "failed to parse program because...")?;
+        write!(formatter, "{}", self.to_diagnostic())
+    }
+}
@@ -0,0 +1,351 @@
+//! Rewriting of the AST by value.
+//!
+//! Unlike `MutVisitor`, a `Fold` consumes each node and returns its
+//! replacement, so a desugaring can turn one kind of node into another (for
+//! example an `ExpressionKind::If` into an `ExpressionKind::Block`). The
+//! `walk_*` functions rebuild a node from its folded children.
+
+use crate::parser::*;
+
+pub trait Fold: Sized {
+    fn fold_program(&mut self, program: Program) -> Program {
+        walk_program(self, program)
+    }
+
+    fn fold_declaration(&mut self, declaration: Declaration) -> Declaration {
+        walk_declaration(self, declaration)
+    }
+
+    fn fold_static_variable(&mut self, declaration: StaticVariableDeclaration) -> StaticVariableDeclaration {
+        walk_static_variable(self, declaration)
+    }
+
+    fn fold_namespace(&mut self, declaration: NamespaceDeclaration) -> NamespaceDeclaration {
+        walk_namespace(self, declaration)
+    }
+
+    fn fold_function(&mut self, declaration: FunctionDeclaration) -> FunctionDeclaration {
+        walk_function(self, declaration)
+    }
+
+    fn fold_class(&mut self, declaration: ClassDeclaration) -> ClassDeclaration {
+        walk_class(self, declaration)
+    }
+
+    fn fold_field(&mut self, declaration: FieldDeclaration) -> FieldDeclaration {
+        walk_field(self, declaration)
+    }
+
+    fn fold_enum(&mut self, declaration: EnumDeclaration) -> EnumDeclaration {
+        walk_enum(self, declaration)
+    }
+
+    fn fold_variant(&mut self, variant: Variant) -> Variant {
+        walk_variant(self, variant)
+    }
+
+    fn fold_trait(&mut self, declaration: TraitDeclaration) -> TraitDeclaration {
+        walk_trait(self, declaration)
+    }
+
+    fn fold_impl(&mut self, declaration: ImplDeclaration) -> ImplDeclaration {
+        walk_impl(self, declaration)
+    }
+
+    fn fold_import(&mut self, declaration: ImportDeclaration) -> ImportDeclaration {
+        declaration
+    }
+
+    fn fold_function_signature(&mut self, signature: FunctionSignature) -> FunctionSignature {
+        walk_function_signature(self, signature)
+    }
+
+    fn fold_type_param(&mut self, type_param: TypeParam) -> TypeParam {
+        walk_type_param(self, type_param)
+    }
+
+    fn fold_parameter(&mut self, parameter: Parameter) -> Parameter {
+        walk_parameter(self, parameter)
+    }
+
+    fn fold_type_literal(&mut self, type_literal: TypeLiteral) -> TypeLiteral {
+        walk_type_literal(self, type_literal)
+    }
+
+    fn fold_block(&mut self, block: Block) -> Block {
+        walk_block(self, block)
+    }
+
+    fn fold_statement(&mut self, statement: Statement) -> Statement {
+        walk_statement(self, statement)
+    }
+
+    fn fold_expression(&mut self, expression: Expression) -> Expression {
+        walk_expression(self, expression)
+    }
+
+    fn fold_if_expression(&mut self, if_expression: IfExpression) -> IfExpression {
+        walk_if_expression(self, if_expression)
+    }
+
+    fn fold_match_expression(&mut self, match_expression: MatchExpression) -> MatchExpression {
+        walk_match_expression(self, match_expression)
+    }
+
+    fn fold_match_arm(&mut self, arm: MatchArm) -> MatchArm {
+        walk_match_arm(self, arm)
+    }
+
+    fn fold_accesser(&mut self, accesser: Accesser) -> Accesser {
+        walk_accesser(self, accesser)
+    }
+
+    fn fold_pattern(&mut self, pattern: Pattern) -> Pattern {
+        walk_pattern(self, pattern)
+    }
+}
+
+/// Folds the expression in place so the allocation of the box is reused.
+fn fold_boxed<F: Fold>(folder: &mut F, mut expression: Box<Expression>) -> Box<Expression> {
+    *expression = folder.fold_expression(*expression);
+    expression
+}
+
+pub fn walk_program<F: Fold>(folder: &mut F, program: Program) -> Program {
+    Program {
+        declarations: program.declarations.into_iter().map(|declaration| folder.fold_declaration(declaration)).collect(),
+        ..program
+    }
+}
+
+pub fn walk_declaration<F: Fold>(folder: &mut F, declaration: Declaration) -> Declaration {
+    match declaration {
+        Declaration::StaticVariable(declaration) => Declaration::StaticVariable(folder.fold_static_variable(declaration)),
+        Declaration::Namespace(declaration) => Declaration::Namespace(folder.fold_namespace(declaration)),
+        Declaration::Function(declaration) => Declaration::Function(folder.fold_function(declaration)),
+        Declaration::Class(declaration) => Declaration::Class(folder.fold_class(declaration)),
+        Declaration::Enum(declaration) => Declaration::Enum(folder.fold_enum(declaration)),
+        Declaration::Trait(declaration) => Declaration::Trait(folder.fold_trait(declaration)),
+        Declaration::Impl(declaration) => Declaration::Impl(folder.fold_impl(declaration)),
+        Declaration::Import(declaration) => Declaration::Import(folder.fold_import(declaration)),
+    }
+}
+
+pub fn walk_static_variable<F: Fold>(folder: &mut F, declaration: StaticVariableDeclaration) -> StaticVariableDeclaration {
+    StaticVariableDeclaration {
+        type_annotation: declaration.type_annotation.map(|type_annotation| folder.fold_type_literal(type_annotation)),
+        value: folder.fold_expression(declaration.value),
+        ..declaration
+    }
+}
+
+pub fn walk_namespace<F: Fold>(folder: &mut F, declaration: NamespaceDeclaration) -> NamespaceDeclaration {
+    NamespaceDeclaration {
+        inner: folder.fold_program(declaration.inner),
+        ..declaration
+    }
+}
+
+pub fn walk_function<F: Fold>(folder: &mut F, declaration: FunctionDeclaration) -> FunctionDeclaration {
+    FunctionDeclaration {
+        signature: folder.fold_function_signature(declaration.signature),
+        inner: folder.fold_block(declaration.inner),
+        ..declaration
+    }
+}
+
+pub fn walk_class<F: Fold>(folder: &mut F, declaration: ClassDeclaration) -> ClassDeclaration {
+    ClassDeclaration {
+        type_params: declaration.type_params.into_iter().map(|type_param| folder.fold_type_param(type_param)).collect(),
+        fields: declaration.fields.into_iter().map(|field| folder.fold_field(field)).collect(),
+        methods: declaration.methods.into_iter().map(|method| folder.fold_function(method)).collect(),
+        ..declaration
+    }
+}
+
+pub fn walk_field<F: Fold>(folder: &mut F, declaration: FieldDeclaration) -> FieldDeclaration {
+    FieldDeclaration {
+        type_annotation: folder.fold_type_literal(declaration.type_annotation),
+        ..declaration
+    }
+}
+
+pub fn walk_enum<F: Fold>(folder: &mut F, declaration: EnumDeclaration) -> EnumDeclaration {
+    EnumDeclaration {
+        type_params: declaration.type_params.into_iter().map(|type_param| folder.fold_type_param(type_param)).collect(),
+        variants: declaration.variants.into_iter().map(|variant| folder.fold_variant(variant)).collect(),
+        ..declaration
+    }
+}
+
+pub fn walk_variant<F: Fold>(folder: &mut F, variant: Variant) -> Variant {
+    let kind = match variant.kind {
+        VariantKind::Unit => VariantKind::Unit,
+        VariantKind::Tuple(types) => VariantKind::Tuple(types.into_iter().map(|type_literal| folder.fold_type_literal(type_literal)).collect()),
+        VariantKind::Struct(fields) => VariantKind::Struct(fields.into_iter().map(|field| folder.fold_field(field)).collect()),
+    };
+    Variant { kind, ..variant }
+}
+
+pub fn walk_trait<F: Fold>(folder: &mut F, declaration: TraitDeclaration) -> TraitDeclaration {
+    TraitDeclaration {
+        signatures: declaration.signatures.into_iter().map(|signature| folder.fold_function_signature(signature)).collect(),
+        functions: declaration.functions.into_iter().map(|function| folder.fold_function(function)).collect(),
+        ..declaration
+    }
+}
+
+pub fn walk_impl<F: Fold>(folder: &mut F, declaration: ImplDeclaration) -> ImplDeclaration {
+    ImplDeclaration {
+        trait_type: folder.fold_type_literal(declaration.trait_type),
+        self_type: folder.fold_type_literal(declaration.self_type),
+        functions: declaration.functions.into_iter().map(|function| folder.fold_function(function)).collect(),
+        ..declaration
+    }
+}
+
+pub fn walk_function_signature<F: Fold>(folder: &mut F, signature: FunctionSignature) -> FunctionSignature {
+    FunctionSignature {
+        type_params: signature.type_params.into_iter().map(|type_param| folder.fold_type_param(type_param)).collect(),
+        params: signature.params.into_iter().map(|param| folder.fold_parameter(param)).collect(),
+        return_type: signature.return_type.map(|return_type| folder.fold_type_literal(return_type)),
+        ..signature
+    }
+}
+
+pub fn walk_type_param<F: Fold>(folder: &mut F, type_param: TypeParam) -> TypeParam {
+    TypeParam {
+        bounds: type_param.bounds.into_iter().map(|bound| folder.fold_type_literal(bound)).collect(),
+        ..type_param
+    }
+}
+
+pub fn walk_parameter<F: Fold>(folder: &mut F, parameter: Parameter) -> Parameter {
+    Parameter {
+        pattern: folder.fold_pattern(parameter.pattern),
+        type_annotation: folder.fold_type_literal(parameter.type_annotation),
+        ..parameter
+    }
+}
+
+pub fn walk_type_literal<F: Fold>(folder: &mut F, type_literal: TypeLiteral) -> TypeLiteral {
+    let mut fold_all = |types: Vec<TypeLiteral>| types.into_iter().map(|type_literal| folder.fold_type_literal(type_literal)).collect();
+    let kind = match type_literal.kind {
+        TypeLiteralKind::CustomType { name, type_args } => TypeLiteralKind::CustomType { name, type_args: fold_all(type_args) },
+        TypeLiteralKind::TupleType { inner } => TypeLiteralKind::TupleType { inner: fold_all(inner) },
+        kind => kind,
+    };
+    TypeLiteral { kind, ..type_literal }
+}
+
+pub fn walk_block<F: Fold>(folder: &mut F, block: Block) -> Block {
+    Block {
+        statements: block.statements.into_iter().map(|statement| folder.fold_statement(statement)).collect(),
+        value: block.value.map(|value| fold_boxed(folder, value)),
+        ..block
+    }
+}
+
+pub fn walk_statement<F: Fold>(folder: &mut F, statement: Statement) -> Statement {
+    let kind = match statement.kind {
+        StatementKind::Expression(expression) => StatementKind::Expression(folder.fold_expression(expression)),
+        StatementKind::Let { is_const, pattern, type_annotation, value } => StatementKind::Let {
+            is_const,
+            pattern: folder.fold_pattern(pattern),
+            type_annotation: type_annotation.map(|type_annotation| folder.fold_type_literal(type_annotation)),
+            value: value.map(|value| folder.fold_expression(value)),
+        },
+        StatementKind::Assign { target, value } => StatementKind::Assign {
+            target: folder.fold_expression(target),
+            value: folder.fold_expression(value),
+        },
+        StatementKind::Return(value) => StatementKind::Return(value.map(|value| folder.fold_expression(value))),
+        StatementKind::For { pattern, iterable, body } => StatementKind::For {
+            pattern: folder.fold_pattern(pattern),
+            iterable: folder.fold_expression(iterable),
+            body: folder.fold_block(body),
+        },
+        StatementKind::While { condition, body } => StatementKind::While {
+            condition: folder.fold_expression(condition),
+            body: folder.fold_block(body),
+        },
+    };
+    Statement { kind, ..statement }
+}
+
+pub fn walk_expression<F: Fold>(folder: &mut F, expression: Expression) -> Expression {
+    let kind = match expression.kind {
+        ExpressionKind::Literal(literal) => ExpressionKind::Literal(literal),
+        ExpressionKind::Accesser(accesser) => ExpressionKind::Accesser(folder.fold_accesser(accesser)),
+        ExpressionKind::Unary { ope, operand } => ExpressionKind::Unary { ope, operand: fold_boxed(folder, operand) },
+        ExpressionKind::Binary { ope, left, right } => ExpressionKind::Binary {
+            ope,
+            left: fold_boxed(folder, left),
+            right: fold_boxed(folder, right),
+        },
+        ExpressionKind::Call { callee, args } => ExpressionKind::Call {
+            callee: fold_boxed(folder, callee),
+            args: args.into_iter().map(|arg| folder.fold_expression(arg)).collect(),
+        },
+        ExpressionKind::Field { target, name } => ExpressionKind::Field { target: fold_boxed(folder, target), name },
+        ExpressionKind::Cast { value, ty } => ExpressionKind::Cast { value: fold_boxed(folder, value), ty: folder.fold_type_literal(ty) },
+        ExpressionKind::Try(value) => ExpressionKind::Try(fold_boxed(folder, value)),
+        ExpressionKind::Tuple(inner) => ExpressionKind::Tuple(inner.into_iter().map(|expression| folder.fold_expression(expression)).collect()),
+        ExpressionKind::Struct { class, fields } => ExpressionKind::Struct {
+            class: folder.fold_accesser(class),
+            fields: fields.into_iter().map(|field| FieldInit { value: folder.fold_expression(field.value), ..field }).collect(),
+        },
+        ExpressionKind::If(if_expression) => ExpressionKind::If(folder.fold_if_expression(if_expression)),
+        ExpressionKind::Match(match_expression) => ExpressionKind::Match(folder.fold_match_expression(match_expression)),
+        ExpressionKind::Block(block) => ExpressionKind::Block(folder.fold_block(block)),
+    };
+    Expression { kind, ..expression }
+}
+
+pub fn walk_if_expression<F: Fold>(folder: &mut F, if_expression: IfExpression) -> IfExpression {
+    IfExpression {
+        condition: fold_boxed(folder, if_expression.condition),
+        then_branch: folder.fold_block(if_expression.then_branch),
+        else_branch: if_expression.else_branch.map(|else_branch| fold_boxed(folder, else_branch)),
+    }
+}
+
+pub fn walk_match_expression<F: Fold>(folder: &mut F, match_expression: MatchExpression) -> MatchExpression {
+    MatchExpression {
+        scrutinee: fold_boxed(folder, match_expression.scrutinee),
+        arms: match_expression.arms.into_iter().map(|arm| folder.fold_match_arm(arm)).collect(),
+    }
+}
+
+pub fn walk_match_arm<F: Fold>(folder: &mut F, arm: MatchArm) -> MatchArm {
+    MatchArm {
+        pattern: folder.fold_pattern(arm.pattern),
+        value: folder.fold_expression(arm.value),
+        ..arm
+    }
+}
+
+pub fn walk_accesser<F: Fold>(folder: &mut F, accesser: Accesser) -> Accesser {
+    Accesser {
+        segments: accesser.segments.into_iter().map(|segment| PathSegment {
+            type_args: segment.type_args.into_iter().map(|type_arg| folder.fold_type_literal(type_arg)).collect(),
+            ..segment
+        }).collect(),
+    }
+}
+
+pub fn walk_pattern<F: Fold>(folder: &mut F, pattern: Pattern) -> Pattern {
+    let kind = match pattern.kind {
+        PatternKind::Tuple(inner) => PatternKind::Tuple(inner.into_iter().map(|pattern| folder.fold_pattern(pattern)).collect()),
+        PatternKind::Variant { path, fields } => {
+            let fields = match fields {
+                PatternFields::Unit => PatternFields::Unit,
+                PatternFields::Tuple(inner) => PatternFields::Tuple(inner.into_iter().map(|pattern| folder.fold_pattern(pattern)).collect()),
+                PatternFields::Struct(fields) => PatternFields::Struct(fields.into_iter().map(|field| FieldPattern { pattern: folder.fold_pattern(field.pattern), ..field }).collect()),
+            };
+            PatternKind::Variant { path: folder.fold_accesser(path), fields }
+        }
+        kind => kind,
+    };
+    Pattern { kind, ..pattern }
+}
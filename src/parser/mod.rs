@@ -8,48 +8,92 @@
 //!   | <namespace_declaration>
 //!   | <function_declaration>
 //!   | <class_declaration>
+//!   | <enum_declaration>
 //!   | <trait_declaration>
+//!   | <impl_declaration>
 //!   | <import_declaration>
 //! <static_variable_declaration> := "pub"? "static" <IDENTIFIER> ":" <type_literal> "=" <expression> ";"
 //! <namespace_declaration> := "pub"? "namespace" <IDENTIFIER> "{" <program> "}"
-//! <function_declaration> := "pub"? <function_signature> "{" <statement>* "}"
-//! <class_declaration> := "pub"? "class" <IDENTIFIER> "{" ( <function_declaration> | <field_declaration> )* "}"
+//! <function_declaration> := "pub"? <function_signature> <block_expression>
+//! <class_declaration> := "pub"? "class" <IDENTIFIER> <type_params>? "{" ( <function_declaration> | <field_declaration> )* "}"
 //! <field_declaration> := "pub"? <IDENTIFIER> ":" <type_literal> ";"
-//! <trait_declaration> := "pub"? "trait" <IDENTIFIER> "{" ( ( <function_signature> ) | <function_declaration> )* "}"
-//! <import_declaration> := "import" <IDENTIFIER> | ( "{" <IDENTIFIER>* "}" ) "from" <STRING_LITERAL> ";"
+//! <enum_declaration> := "pub"? "enum" <IDENTIFIER> <type_params>? "{" ( <variant> "," )* <variant>? "}"
+//! <variant> := <IDENTIFIER> ( "(" ( <type_literal> "," )* <type_literal>? ")" | "{" ( <IDENTIFIER> ":" <type_literal> "," )* ( <IDENTIFIER> ":" <type_literal> )? "}" )?
+//! <trait_declaration> := "pub"? "trait" <IDENTIFIER> "{" ( ( <function_signature> ";" ) | <function_declaration> )* "}"
+//! <impl_declaration> := "impl" <type_literal> "for" <type_literal> "{" <function_declaration>* "}"
+//! <import_declaration> := "import" ( <IDENTIFIER> | "{" ( <IDENTIFIER> "," )* <IDENTIFIER>? "}" ) "from" <STRING_LITERAL> ";"
 //! 
 //! # statement level
 //! <statement> := 
 //!     <expression> ";"
+//!   | <block_like_expression> ";"?
 //!   | ( "let" | "const" ) <pattern> ( ":" <type_literal> )? ( "=" <expression> )? ";"
-//!   | <accesser> "=" <expression> ";"
-//!   | <accesser> "(" ( <expression> "," )* <expression>? ")" ";"
+//!   | <expression> "=" <expression> ";"
+//!   | "return" <expression>? ";"
 //!   | <for_statement>
 //!   | <while_statement>
-//! <for_statement> := "for" <pattern> "in" <expression> "{" <statement>* "}"
-//! <while_statement> := "while" <expression> "{" <statement>* "}"
+//! <for_statement> := "for" <pattern> "in" <expression> <block_expression>
+//! <while_statement> := "while" <expression> <block_expression>
 //! 
 //! # expression level
 //! <expression> := 
 //!     <unary_result>
 //!   | <binary_result>
-//!   | <if_expression>
-//!   | <match_expression>
-//!   | <block_expression>
+//!   | <call_result>
+//!   | <field_result>
+//!   | <cast_result>
+//!   | <try_result>
+//!   | <struct_literal>
+//!   | <block_like_expression>
+//!   | <accesser>
 //!   | <literal>
+//!   | "(" <expression> ")"
+//!   | "(" ( <expression> "," )* <expression>? ")"
+//! <block_like_expression> := <if_expression> | <match_expression> | <block_expression>
 //! <unary_result> := <unary_ope> <expression>
 //! <binary_result> := <expression> <binary_ope> <expression>
+//! <call_result> := <expression> "(" ( <expression> "," )* <expression>? ")"
+//! <field_result> := <expression> "." <IDENTIFIER>
+//! <cast_result> := <expression> "as" <type_literal>
+//! <try_result> := <expression> "?"
+//! <struct_literal> := <accesser> "{" ( <IDENTIFIER> ":" <expression> "," )* ( <IDENTIFIER> ":" <expression> )? "}"
 //! <unary_ope> := "-"
-//! <binary_ope> := "+" | "-" | "/" | "*"
-//! <if_expression> := "if" <expression> <statement>* ( "else" <expression> )?
-//! <match_expression> := "match" <expression> "{" ( <pattern> "=>" <expression> "," )* "}"
-//! <block_expression> := "{" <statement>* "}"
+//! <binary_ope> := "+" | "-" | "/" | "*" | "==" | "!=" | "<" | ">" | "<=" | ">="
+//! <if_expression> := "if" <expression> <block_expression> ( "else" ( <if_expression> | <block_expression> ) )?
+//! <match_expression> := "match" <expression> "{" ( <match_arm> "," )* <match_arm>? "}"
+//! <match_arm> := <pattern> "=>" <expression>
+//! <block_expression> := "{" <statement>* <expression>? "}"
+//! 
+//! The trailing <expression> of a <block_expression> (the one without ";") is
+//! the value of the block; a block without one evaluates to `()`. Binary
+//! operators bind as `== != < > <= >=` < `+ -` < `* /` < `as` < unary `-` < call,
+//! field and `?`, so `-x as u8 * 2` is `((-x) as u8) * 2` and `-f()?` is `-(f()?)`.
+//!
+//! The condition of `if` and `while`, the iterable of `for` and the value a
+//! `match` matches can not be a struct literal outside of parentheses, as
+//! `if x { ... }` reads `{` as the start of the block: `if p == (Point { x: 1 }) { ... }`.
+//!
+//! The `,` after an arm of a `match` can be left out when its value is a
+//! block-like expression, like `Shape::Empty => { 0 }`.
 //! 
 //! # atomic level
-//! <type_literal> := "int" | "float" | "char" | "bool" | <IDENTIFY> ( "<" <type_literal>* ">" )? | "(" ( <type_literal> "," )* <type_literal>? ")"
-//! <function_signature> := "fn" <IDENTIFIER> "(" ( <pattern> ":" <type_literal> "," )* ( <pattern> ":" <type_literal> )? ")" ( ":" <type_literal> )?
-//! <accesser> := <IDENTIFIER> ( ( "." | "::" ) <accesser> )*
-//! <literal> := <STRING_LITERAL> | <INT_LITERAL> | <FLOAT_LITERAL> | <BOOL_LITERAL> | <struct_literal> | 
+//! <type_literal> := "int" | "float" | "char" | "bool" | <numeric_type> | <IDENTIFY> <type_args>? | "(" ( <type_literal> "," )* <type_literal>? ")" | "dyn" <IDENTIFIER>
+//! <type_args> := "<" ( <type_literal> "," )* <type_literal>? ">"
+//! <numeric_type> := "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64" | "usize" | "f32" | "f64"
+//! <function_signature> := "fn" <IDENTIFIER> <type_params>? "(" ( <parameter> "," )* <parameter>? ")" ( ":" <type_literal> )?
+//! <type_params> := "<" ( <type_param> "," )* <type_param>? ">"
+//! <type_param> := <IDENTIFIER> ( ":" <type_literal> ( "+" <type_literal> )* )?
+//! <parameter> := <pattern> ":" <type_literal> | "self"
+//! <accesser> := <IDENTIFIER> ( "::" <type_args> )? ( "::" <IDENTIFIER> ( "::" <type_args> )? )*
+//! 
+//! Inside expressions `<` is always a comparison, so type arguments of a path
+//! have to be written with the turbofish: `a < b` compares while `f::<int>(a)`
+//! and `List::<int>::new()` pass type arguments. In types `<` always opens
+//! type arguments, and a `>=` directly after them is read as `>` `=`.
+//!
+//! `self` alone is only allowed as the first parameter, and is short for
+//! `self: Self`: the function is a method of its class.
+//! <literal> := <STRING_LITERAL> | <INT_LITERAL> | <FLOAT_LITERAL> | <BOOL_LITERAL>
 //! 
 //! # pattern
 //! <pattern> := 
@@ -69,21 +113,45 @@
 //! <wildcard-pattern> := "_"
 //! <tuple-pattern> := "(" ( <pattern> "," )* <pattern>? ")"
 //! <struct-pattern> := <accesser>
-//! <enum-pattern> := 
+//! <enum-pattern> := <accesser> ( "(" ( <pattern> "," )* <pattern>? ")" | "{" ( <field_pattern> "," )* <field_pattern>? "}" )?
+//! <field_pattern> := <IDENTIFIER> ( ":" <pattern> )?
 //! <reference-pattern> := 
 //! <slice-pattern> := 
 //! <range-pattern> := 
 //! <or-pattern> := 
+//!
+//! A single identifier is an <identifier-pattern>, so a variant without
+//! fields is matched by its path like `Shape::Empty`. A <field_pattern> of
+//! just a name like `w` is short for `w: w`.
 //! ```
 
 pub mod parser;
 pub mod error;
+pub mod visit;
+pub mod mut_visit;
+pub mod fold;
+pub mod printer;
+mod test;
+
+pub use crate::numeric::NumericType;
+pub use crate::span::Span;
+
+pub type Identifier = String;
 
-type Identifier = String;
+/// Identifies one node of a parsed `Program`. Ids are handed out in parse
+/// order, so parsing the same source twice gives the same ids. Later passes
+/// key their side tables (resolved names, types, ...) by it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NodeId(pub u32);
 
 /// <program> := <declaration>*
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Program {
-    declarations: Vec<Declaration>,
+    pub id: NodeId,
+    pub span: Span,
+    pub declarations: Vec<Declaration>,
 }
 
 
@@ -92,83 +160,576 @@ pub struct Program {
 ///   | <namespace_declaration>
 ///   | <function_declaration>
 ///   | <class_declaration>
+///   | <enum_declaration>
 ///   | <trait_declaration>
+///   | <impl_declaration>
 ///   | <import_declaration>
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Declaration {
     StaticVariable(StaticVariableDeclaration),
     Namespace(NamespaceDeclaration),
     Function(FunctionDeclaration),
     Class(ClassDeclaration),
+    Enum(EnumDeclaration),
     Trait(TraitDeclaration),
+    Impl(ImplDeclaration),
     Import(ImportDeclaration),
 }
 
+impl Declaration {
+    pub fn id(&self) -> NodeId {
+        match self {
+            Declaration::StaticVariable(declaration) => declaration.id,
+            Declaration::Namespace(declaration) => declaration.id,
+            Declaration::Function(declaration) => declaration.id,
+            Declaration::Class(declaration) => declaration.id,
+            Declaration::Enum(declaration) => declaration.id,
+            Declaration::Trait(declaration) => declaration.id,
+            Declaration::Impl(declaration) => declaration.id,
+            Declaration::Import(declaration) => declaration.id,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Declaration::StaticVariable(declaration) => declaration.span,
+            Declaration::Namespace(declaration) => declaration.span,
+            Declaration::Function(declaration) => declaration.span,
+            Declaration::Class(declaration) => declaration.span,
+            Declaration::Enum(declaration) => declaration.span,
+            Declaration::Trait(declaration) => declaration.span,
+            Declaration::Impl(declaration) => declaration.span,
+            Declaration::Import(declaration) => declaration.span,
+        }
+    }
+}
+
 /// <static_variable_declaration> := "pub"? "static" <IDENTIFIER> ":" <type_literal> "=" <expression> ";"
-struct StaticVariableDeclaration {
-    is_pub: bool,
-    name: Identifier,
-    type_annotation: Option<TypeLiteral>,
-    value: Expression,
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StaticVariableDeclaration {
+    pub id: NodeId,
+    pub span: Span,
+    pub is_pub: bool,
+    pub name: Identifier,
+    pub type_annotation: Option<TypeLiteral>,
+    pub value: Expression,
 }
 
 /// <namespace_declaration> := "pub"? "namespace" <IDENTIFIER> "{" <program> "}"
-struct NamespaceDeclaration {
-    name: Identifier,
-    inner: Program,
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NamespaceDeclaration {
+    pub id: NodeId,
+    pub span: Span,
+    pub is_pub: bool,
+    pub name: Identifier,
+    pub inner: Program,
 }
 
-/// <function_declaration> := "pub"? <function_signature> "{" <statement>* "}"
-struct FunctionDeclaration {
-    is_pub: bool,
-    name: Identifier,
-    signature: FunctionSignature,
-    inner: Vec<Statement>
+/// <function_declaration> := "pub"? <function_signature> <block_expression>
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FunctionDeclaration {
+    pub id: NodeId,
+    pub span: Span,
+    pub is_pub: bool,
+    pub name: Identifier,
+    pub signature: FunctionSignature,
+    pub inner: Block,
 }
 
-/// <class_declaration> := "pub"? "class" <IDENTIFIER> "{" ( <function_declaration> | <field_declaration> )* "}"
-struct ClassDeclaration {
-    is_pub: bool,
-    fields: Vec<FieldDeclaration>,
-    methods: Vec<FunctionDeclaration>,
+/// <class_declaration> := "pub"? "class" <IDENTIFIER> <type_params>? "{" ( <function_declaration> | <field_declaration> )* "}"
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ClassDeclaration {
+    pub id: NodeId,
+    pub span: Span,
+    pub is_pub: bool,
+    pub name: Identifier,
+    pub type_params: Vec<TypeParam>,
+    pub fields: Vec<FieldDeclaration>,
+    pub methods: Vec<FunctionDeclaration>,
 }
 
 /// <field_declaration> := "pub"? <IDENTIFIER> ":" <type_literal> ";"
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FieldDeclaration {
-    is_pub: bool,
-    name: Identifier,
-    type_annotation: TypeLiteral,
+    pub id: NodeId,
+    pub span: Span,
+    pub is_pub: bool,
+    pub name: Identifier,
+    pub type_annotation: TypeLiteral,
+}
+
+/// <enum_declaration> := "pub"? "enum" <IDENTIFIER> <type_params>? "{" ( <variant> "," )* <variant>? "}"
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EnumDeclaration {
+    pub id: NodeId,
+    pub span: Span,
+    pub is_pub: bool,
+    pub name: Identifier,
+    pub type_params: Vec<TypeParam>,
+    pub variants: Vec<Variant>,
+}
+
+/// <variant> := <IDENTIFIER> ( "(" ( <type_literal> "," )* <type_literal>? ")" | "{" ( <IDENTIFIER> ":" <type_literal> "," )* ( <IDENTIFIER> ":" <type_literal> )? "}" )?
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Variant {
+    pub id: NodeId,
+    pub span: Span,
+    pub name: Identifier,
+    pub kind: VariantKind,
 }
 
-/// <trait_declaration> := "pub"? "trait" <IDENTIFIER> "{" ( ( <function_signature> ) | <function_declaration> )* "}"
-struct TraitDeclaration {
-    is_pub: bool,
-    signatures: Vec<FunctionSignature>,
-    functions: Vec<FunctionDeclaration>,
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum VariantKind {
+    /// `Empty`, a variant without fields
+    Unit,
+    /// `Circle(float)`, whose fields are named by their index
+    Tuple(Vec<TypeLiteral>),
+    /// `Rect { w: float, h: float }`. The fields are as visible as the enum,
+    /// so they are all `pub`.
+    Struct(Vec<FieldDeclaration>),
 }
 
-/// <import_declaration> := "import" <IDENTIFIER> "from" <STRING_LITERAL> ";"
-struct ImportDeclaration {
-    name: Identifier,
-    path: String,
+/// <trait_declaration> := "pub"? "trait" <IDENTIFIER> "{" ( ( <function_signature> ";" ) | <function_declaration> )* "}"
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TraitDeclaration {
+    pub id: NodeId,
+    pub span: Span,
+    pub is_pub: bool,
+    pub name: Identifier,
+    pub signatures: Vec<FunctionSignature>,
+    pub functions: Vec<FunctionDeclaration>,
 }
 
-/// <type_literal> := "int" | "float" | "char" | "bool" | <IDENTIFY> ( "<" <type_literal>* ">" )? | "(" ( <type_literal> "," )* <type_literal>? ")"
-pub enum TypeLiteral {
+/// <impl_declaration> := "impl" <type_literal> "for" <type_literal> "{" <function_declaration>* "}"
+///
+/// Implements the trait `trait_type` for `self_type`, usually a class. The
+/// functions are the ones the trait declares, and can leave out those with a
+/// default.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ImplDeclaration {
+    pub id: NodeId,
+    pub span: Span,
+    pub trait_type: TypeLiteral,
+    pub self_type: TypeLiteral,
+    pub functions: Vec<FunctionDeclaration>,
+}
+
+/// <import_declaration> := "import" ( <IDENTIFIER> | "{" ( <IDENTIFIER> "," )* <IDENTIFIER>? "}" ) "from" <STRING_LITERAL> ";"
+///
+/// `path` is the module as written, like `"util"` for `util.sn`. Once the
+/// loader has read the module it is the name of the namespace the module was
+/// loaded into.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ImportDeclaration {
+    pub id: NodeId,
+    pub span: Span,
+    pub kind: ImportKind,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ImportKind {
+    /// `import util from "util";` names the whole module like a namespace
+    Module(Identifier),
+    /// `import { a, b } from "util";` brings in single items of the module
+    Items(Vec<ImportItem>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ImportItem {
+    pub id: NodeId,
+    pub span: Span,
+    pub name: Identifier,
+}
+
+/// <type_literal> := "int" | "float" | "char" | "bool" | <IDENTIFY> <type_args>? | "(" ( <type_literal> "," )* <type_literal>? ")" | "dyn" <IDENTIFIER>
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TypeLiteral {
+    pub id: NodeId,
+    pub span: Span,
+    pub kind: TypeLiteralKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TypeLiteralKind {
     IntType,
     FloatType,
     CharType,
     BoolType,
+    /// the sized numeric types; `int` and `float` stay `IntType` and `FloatType`
+    /// so they print as written
+    NumericType(NumericType),
     CustomType {
         name: Identifier,
         type_args: Vec<TypeLiteral>,
     },
     TupleType {
         inner: Vec<TypeLiteral>,
+    },
+    /// `dyn Trait`, a value of any type implementing the trait
+    DynType {
+        name: Identifier,
+    },
+}
+
+/// <function_signature> := "fn" <IDENTIFIER> <type_params>? "(" ( <parameter> "," )* <parameter>? ")" ( ":" <type_literal> )?
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FunctionSignature {
+    pub id: NodeId,
+    pub span: Span,
+    pub name: Identifier,
+    pub type_params: Vec<TypeParam>,
+    pub params: Vec<Parameter>,
+    pub return_type: Option<TypeLiteral>,
+}
+
+/// <type_param> := <IDENTIFIER> ( ":" <type_literal> ( "+" <type_literal> )* )?
+///
+/// A type parameter of a generic function or class, and the traits it is
+/// bounded by.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TypeParam {
+    pub id: NodeId,
+    pub span: Span,
+    pub name: Identifier,
+    pub bounds: Vec<TypeLiteral>,
+}
+
+/// <parameter> := <pattern> ":" <type_literal> | "self"
+///
+/// `self` is kept as the pattern `self` of type `Self`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Parameter {
+    pub id: NodeId,
+    pub span: Span,
+    pub pattern: Pattern,
+    pub type_annotation: TypeLiteral,
+}
+
+impl Parameter {
+    /// Whether this is the `self` parameter that makes a function a method.
+    pub fn is_self(&self) -> bool {
+        matches!(&self.pattern.kind, PatternKind::Identifier(name) if name == "self")
+    }
+}
+
+impl FunctionSignature {
+    /// Whether the function is a method, taking `self` as its first parameter.
+    pub fn has_self(&self) -> bool {
+        self.params.first().is_some_and(Parameter::is_self)
     }
 }
 
-pub struct Expression;
+/// <statement> := 
+///     <expression> ";"
+///   | <block_like_expression> ";"?
+///   | ( "let" | "const" ) <pattern> ( ":" <type_literal> )? ( "=" <expression> )? ";"
+///   | <expression> "=" <expression> ";"
+///   | "return" <expression>? ";"
+///   | <for_statement>
+///   | <while_statement>
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Statement {
+    pub id: NodeId,
+    pub span: Span,
+    pub kind: StatementKind,
+}
 
-/// <function_signature> := "fn" <IDENTIFIER> "(" ( <pattern> ":" <type_literal> "," )* ( <pattern> ":" <type_literal> )? ")" ( ":" <type_literal> )?
-pub struct FunctionSignature;
-pub struct Statement;
\ No newline at end of file
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum StatementKind {
+    Expression(Expression),
+    Let {
+        is_const: bool,
+        pattern: Pattern,
+        type_annotation: Option<TypeLiteral>,
+        value: Option<Expression>,
+    },
+    Assign {
+        target: Expression,
+        value: Expression,
+    },
+    Return(Option<Expression>),
+    For {
+        pattern: Pattern,
+        iterable: Expression,
+        body: Block,
+    },
+    While {
+        condition: Expression,
+        body: Block,
+    },
+}
+
+/// <expression> := 
+///     <unary_result>
+///   | <binary_result>
+///   | <call_result>
+///   | <field_result>
+///   | <cast_result>
+///   | <try_result>
+///   | <struct_literal>
+///   | <block_like_expression>
+///   | <accesser>
+///   | <literal>
+///   | "(" <expression> ")"
+///   | "(" ( <expression> "," )* <expression>? ")"
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Expression {
+    pub id: NodeId,
+    pub span: Span,
+    pub kind: ExpressionKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ExpressionKind {
+    Literal(Literal),
+    Accesser(Accesser),
+    Unary {
+        ope: UnaryOpe,
+        operand: Box<Expression>,
+    },
+    Binary {
+        ope: BinaryOpe,
+        left: Box<Expression>,
+        right: Box<Expression>,
+    },
+    Call {
+        callee: Box<Expression>,
+        args: Vec<Expression>,
+    },
+    Field {
+        target: Box<Expression>,
+        name: Identifier,
+    },
+    Cast {
+        value: Box<Expression>,
+        ty: TypeLiteral,
+    },
+    Tuple(Vec<Expression>),
+    /// `value?`, which is the value inside the `Some` or `Ok` that `value`
+    /// is and otherwise returns the `None` or `Err` from the function
+    Try(Box<Expression>),
+    /// a struct literal like `Point { x: 1, y: 2 }`, with its fields in the
+    /// order they are written
+    Struct {
+        class: Accesser,
+        fields: Vec<FieldInit>,
+    },
+    If(IfExpression),
+    Match(MatchExpression),
+    Block(Block),
+}
+
+impl Expression {
+    /// Block-like expressions may stand as a statement without a trailing ";".
+    pub fn is_block_like(&self) -> bool {
+        matches!(self.kind, ExpressionKind::If(_) | ExpressionKind::Match(_) | ExpressionKind::Block(_))
+    }
+}
+
+/// <IDENTIFIER> ":" <expression>
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FieldInit {
+    pub id: NodeId,
+    pub span: Span,
+    pub name: Identifier,
+    pub value: Expression,
+}
+
+/// <if_expression> := "if" <expression> <block_expression> ( "else" ( <if_expression> | <block_expression> ) )?
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct IfExpression {
+    pub condition: Box<Expression>,
+    pub then_branch: Block,
+    /// Either an `ExpressionKind::Block` or, for `else if`, an `ExpressionKind::If`.
+    pub else_branch: Option<Box<Expression>>,
+}
+
+/// <match_expression> := "match" <expression> "{" ( <match_arm> "," )* <match_arm>? "}"
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MatchExpression {
+    pub scrutinee: Box<Expression>,
+    pub arms: Vec<MatchArm>,
+}
+
+/// <match_arm> := <pattern> "=>" <expression>
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MatchArm {
+    pub id: NodeId,
+    pub span: Span,
+    pub pattern: Pattern,
+    pub value: Expression,
+}
+
+/// <block_expression> := "{" <statement>* <expression>? "}"
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Block {
+    pub id: NodeId,
+    pub span: Span,
+    pub statements: Vec<Statement>,
+    pub value: Option<Box<Expression>>,
+}
+
+/// <accesser> := <IDENTIFIER> ( "::" <type_args> )? ( "::" <IDENTIFIER> ( "::" <type_args> )? )*
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Accesser {
+    pub segments: Vec<PathSegment>,
+}
+
+/// <IDENTIFIER> ( "::" <type_args> )?
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PathSegment {
+    pub name: Identifier,
+    pub type_args: Vec<TypeLiteral>,
+}
+
+impl Accesser {
+    /// The path without type arguments, like `ns::Type::method`.
+    pub fn path(&self) -> String {
+        self.segments.iter().map(|segment| segment.name.as_str()).collect::<Vec<_>>().join("::")
+    }
+
+    /// The name when the accesser is a plain identifier such as a local variable.
+    pub fn as_identifier(&self) -> Option<&Identifier> {
+        match self.segments.as_slice() {
+            [segment] if segment.type_args.is_empty() => Some(&segment.name),
+            _ => None,
+        }
+    }
+
+    pub fn has_type_args(&self) -> bool {
+        self.segments.iter().any(|segment| !segment.type_args.is_empty())
+    }
+}
+
+/// <literal> := <STRING_LITERAL> | <INT_LITERAL> | <FLOAT_LITERAL> | <BOOL_LITERAL>
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Literal {
+    String(String),
+    /// the value as written, without sign; the checker knows which type it has
+    Int(u64),
+    Float(f64),
+    Bool(bool),
+}
+
+/// <unary_ope> := "-"
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum UnaryOpe {
+    Minus,
+}
+
+impl UnaryOpe {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            UnaryOpe::Minus => "-",
+        }
+    }
+}
+
+/// <binary_ope> := "+" | "-" | "/" | "*" | "==" | "!=" | "<" | ">" | "<=" | ">="
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum BinaryOpe {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Equal,
+    NotEqual,
+    Lesser,
+    Greater,
+    LesserEqual,
+    GreaterEqual,
+}
+
+impl BinaryOpe {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinaryOpe::Add => "+",
+            BinaryOpe::Sub => "-",
+            BinaryOpe::Mul => "*",
+            BinaryOpe::Div => "/",
+            BinaryOpe::Equal => "==",
+            BinaryOpe::NotEqual => "!=",
+            BinaryOpe::Lesser => "<",
+            BinaryOpe::Greater => ">",
+            BinaryOpe::LesserEqual => "<=",
+            BinaryOpe::GreaterEqual => ">=",
+        }
+    }
+}
+
+/// <pattern> := <literal-pattern> | <identifier-pattern> | <wildcard-pattern> | <tuple-pattern> | <enum-pattern>
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Pattern {
+    pub id: NodeId,
+    pub span: Span,
+    pub kind: PatternKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum PatternKind {
+    Literal(Literal),
+    Identifier(Identifier),
+    Wildcard,
+    Tuple(Vec<Pattern>),
+    /// a variant of an enum and patterns for its fields, like `Shape::Circle(r)`
+    Variant {
+        path: Accesser,
+        fields: PatternFields,
+    },
+}
+
+/// The fields of an <enum-pattern>, written like those of the variant.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum PatternFields {
+    Unit,
+    Tuple(Vec<Pattern>),
+    /// the fields in the order they are written; fields left out match anything
+    Struct(Vec<FieldPattern>),
+}
+
+/// <field_pattern> := <IDENTIFIER> ( ":" <pattern> )?
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FieldPattern {
+    pub id: NodeId,
+    pub span: Span,
+    pub name: Identifier,
+    pub pattern: Pattern,
+}
@@ -0,0 +1,424 @@
+//! In-place rewriting of the AST.
+//!
+//! Mirrors `visit`, but every method takes the node mutably. The `walk_*`
+//! functions also hand each node's `NodeId` and `Span` to `visit_id` and
+//! `visit_span`, so a pass can renumber or relocate a whole tree by overriding
+//! just those two.
+
+use crate::parser::*;
+
+pub trait MutVisitor: Sized {
+    fn visit_id(&mut self, _id: &mut NodeId) {}
+
+    fn visit_span(&mut self, _span: &mut Span) {}
+
+    fn visit_program(&mut self, program: &mut Program) {
+        walk_program(self, program)
+    }
+
+    fn visit_declaration(&mut self, declaration: &mut Declaration) {
+        walk_declaration(self, declaration)
+    }
+
+    fn visit_static_variable(&mut self, declaration: &mut StaticVariableDeclaration) {
+        walk_static_variable(self, declaration)
+    }
+
+    fn visit_namespace(&mut self, declaration: &mut NamespaceDeclaration) {
+        walk_namespace(self, declaration)
+    }
+
+    fn visit_function(&mut self, declaration: &mut FunctionDeclaration) {
+        walk_function(self, declaration)
+    }
+
+    fn visit_class(&mut self, declaration: &mut ClassDeclaration) {
+        walk_class(self, declaration)
+    }
+
+    fn visit_field(&mut self, declaration: &mut FieldDeclaration) {
+        walk_field(self, declaration)
+    }
+
+    fn visit_enum(&mut self, declaration: &mut EnumDeclaration) {
+        walk_enum(self, declaration)
+    }
+
+    fn visit_variant(&mut self, variant: &mut Variant) {
+        walk_variant(self, variant)
+    }
+
+    fn visit_trait(&mut self, declaration: &mut TraitDeclaration) {
+        walk_trait(self, declaration)
+    }
+
+    fn visit_impl(&mut self, declaration: &mut ImplDeclaration) {
+        walk_impl(self, declaration)
+    }
+
+    fn visit_import(&mut self, declaration: &mut ImportDeclaration) {
+        walk_import(self, declaration)
+    }
+
+    fn visit_function_signature(&mut self, signature: &mut FunctionSignature) {
+        walk_function_signature(self, signature)
+    }
+
+    fn visit_type_param(&mut self, type_param: &mut TypeParam) {
+        walk_type_param(self, type_param)
+    }
+
+    fn visit_parameter(&mut self, parameter: &mut Parameter) {
+        walk_parameter(self, parameter)
+    }
+
+    fn visit_type_literal(&mut self, type_literal: &mut TypeLiteral) {
+        walk_type_literal(self, type_literal)
+    }
+
+    fn visit_block(&mut self, block: &mut Block) {
+        walk_block(self, block)
+    }
+
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        walk_statement(self, statement)
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        walk_expression(self, expression)
+    }
+
+    fn visit_if_expression(&mut self, if_expression: &mut IfExpression) {
+        walk_if_expression(self, if_expression)
+    }
+
+    fn visit_match_expression(&mut self, match_expression: &mut MatchExpression) {
+        walk_match_expression(self, match_expression)
+    }
+
+    fn visit_match_arm(&mut self, arm: &mut MatchArm) {
+        walk_match_arm(self, arm)
+    }
+
+    fn visit_accesser(&mut self, accesser: &mut Accesser) {
+        walk_accesser(self, accesser)
+    }
+
+    fn visit_pattern(&mut self, pattern: &mut Pattern) {
+        walk_pattern(self, pattern)
+    }
+}
+
+pub fn walk_program<V: MutVisitor>(visitor: &mut V, program: &mut Program) {
+    visitor.visit_id(&mut program.id);
+    visitor.visit_span(&mut program.span);
+    for declaration in &mut program.declarations {
+        visitor.visit_declaration(declaration);
+    }
+}
+
+pub fn walk_declaration<V: MutVisitor>(visitor: &mut V, declaration: &mut Declaration) {
+    match declaration {
+        Declaration::StaticVariable(declaration) => visitor.visit_static_variable(declaration),
+        Declaration::Namespace(declaration) => visitor.visit_namespace(declaration),
+        Declaration::Function(declaration) => visitor.visit_function(declaration),
+        Declaration::Class(declaration) => visitor.visit_class(declaration),
+        Declaration::Enum(declaration) => visitor.visit_enum(declaration),
+        Declaration::Trait(declaration) => visitor.visit_trait(declaration),
+        Declaration::Impl(declaration) => visitor.visit_impl(declaration),
+        Declaration::Import(declaration) => visitor.visit_import(declaration),
+    }
+}
+
+pub fn walk_static_variable<V: MutVisitor>(visitor: &mut V, declaration: &mut StaticVariableDeclaration) {
+    visitor.visit_id(&mut declaration.id);
+    visitor.visit_span(&mut declaration.span);
+    if let Some(type_annotation) = &mut declaration.type_annotation {
+        visitor.visit_type_literal(type_annotation);
+    }
+    visitor.visit_expression(&mut declaration.value);
+}
+
+pub fn walk_namespace<V: MutVisitor>(visitor: &mut V, declaration: &mut NamespaceDeclaration) {
+    visitor.visit_id(&mut declaration.id);
+    visitor.visit_span(&mut declaration.span);
+    visitor.visit_program(&mut declaration.inner);
+}
+
+pub fn walk_function<V: MutVisitor>(visitor: &mut V, declaration: &mut FunctionDeclaration) {
+    visitor.visit_id(&mut declaration.id);
+    visitor.visit_span(&mut declaration.span);
+    visitor.visit_function_signature(&mut declaration.signature);
+    visitor.visit_block(&mut declaration.inner);
+}
+
+pub fn walk_class<V: MutVisitor>(visitor: &mut V, declaration: &mut ClassDeclaration) {
+    visitor.visit_id(&mut declaration.id);
+    visitor.visit_span(&mut declaration.span);
+    for type_param in &mut declaration.type_params {
+        visitor.visit_type_param(type_param);
+    }
+    for field in &mut declaration.fields {
+        visitor.visit_field(field);
+    }
+    for method in &mut declaration.methods {
+        visitor.visit_function(method);
+    }
+}
+
+pub fn walk_field<V: MutVisitor>(visitor: &mut V, declaration: &mut FieldDeclaration) {
+    visitor.visit_id(&mut declaration.id);
+    visitor.visit_span(&mut declaration.span);
+    visitor.visit_type_literal(&mut declaration.type_annotation);
+}
+
+pub fn walk_enum<V: MutVisitor>(visitor: &mut V, declaration: &mut EnumDeclaration) {
+    visitor.visit_id(&mut declaration.id);
+    visitor.visit_span(&mut declaration.span);
+    for type_param in &mut declaration.type_params {
+        visitor.visit_type_param(type_param);
+    }
+    for variant in &mut declaration.variants {
+        visitor.visit_variant(variant);
+    }
+}
+
+pub fn walk_variant<V: MutVisitor>(visitor: &mut V, variant: &mut Variant) {
+    visitor.visit_id(&mut variant.id);
+    visitor.visit_span(&mut variant.span);
+    match &mut variant.kind {
+        VariantKind::Unit => {}
+        VariantKind::Tuple(types) => {
+            for type_literal in types {
+                visitor.visit_type_literal(type_literal);
+            }
+        }
+        VariantKind::Struct(fields) => {
+            for field in fields {
+                visitor.visit_field(field);
+            }
+        }
+    }
+}
+
+pub fn walk_trait<V: MutVisitor>(visitor: &mut V, declaration: &mut TraitDeclaration) {
+    visitor.visit_id(&mut declaration.id);
+    visitor.visit_span(&mut declaration.span);
+    for signature in &mut declaration.signatures {
+        visitor.visit_function_signature(signature);
+    }
+    for function in &mut declaration.functions {
+        visitor.visit_function(function);
+    }
+}
+
+pub fn walk_impl<V: MutVisitor>(visitor: &mut V, declaration: &mut ImplDeclaration) {
+    visitor.visit_id(&mut declaration.id);
+    visitor.visit_span(&mut declaration.span);
+    visitor.visit_type_literal(&mut declaration.trait_type);
+    visitor.visit_type_literal(&mut declaration.self_type);
+    for function in &mut declaration.functions {
+        visitor.visit_function(function);
+    }
+}
+
+pub fn walk_import<V: MutVisitor>(visitor: &mut V, declaration: &mut ImportDeclaration) {
+    visitor.visit_id(&mut declaration.id);
+    visitor.visit_span(&mut declaration.span);
+    if let ImportKind::Items(items) = &mut declaration.kind {
+        for item in items {
+            visitor.visit_id(&mut item.id);
+            visitor.visit_span(&mut item.span);
+        }
+    }
+}
+
+pub fn walk_function_signature<V: MutVisitor>(visitor: &mut V, signature: &mut FunctionSignature) {
+    visitor.visit_id(&mut signature.id);
+    visitor.visit_span(&mut signature.span);
+    for type_param in &mut signature.type_params {
+        visitor.visit_type_param(type_param);
+    }
+    for param in &mut signature.params {
+        visitor.visit_parameter(param);
+    }
+    if let Some(return_type) = &mut signature.return_type {
+        visitor.visit_type_literal(return_type);
+    }
+}
+
+pub fn walk_parameter<V: MutVisitor>(visitor: &mut V, parameter: &mut Parameter) {
+    visitor.visit_id(&mut parameter.id);
+    visitor.visit_span(&mut parameter.span);
+    visitor.visit_pattern(&mut parameter.pattern);
+    visitor.visit_type_literal(&mut parameter.type_annotation);
+}
+
+pub fn walk_type_param<V: MutVisitor>(visitor: &mut V, type_param: &mut TypeParam) {
+    visitor.visit_id(&mut type_param.id);
+    visitor.visit_span(&mut type_param.span);
+    for bound in &mut type_param.bounds {
+        visitor.visit_type_literal(bound);
+    }
+}
+
+pub fn walk_type_literal<V: MutVisitor>(visitor: &mut V, type_literal: &mut TypeLiteral) {
+    visitor.visit_id(&mut type_literal.id);
+    visitor.visit_span(&mut type_literal.span);
+    match &mut type_literal.kind {
+        TypeLiteralKind::IntType | TypeLiteralKind::FloatType | TypeLiteralKind::CharType | TypeLiteralKind::BoolType | TypeLiteralKind::NumericType(_) | TypeLiteralKind::DynType { .. } => {}
+        TypeLiteralKind::CustomType { type_args: inner, .. } | TypeLiteralKind::TupleType { inner } => {
+            for type_literal in inner {
+                visitor.visit_type_literal(type_literal);
+            }
+        }
+    }
+}
+
+pub fn walk_block<V: MutVisitor>(visitor: &mut V, block: &mut Block) {
+    visitor.visit_id(&mut block.id);
+    visitor.visit_span(&mut block.span);
+    for statement in &mut block.statements {
+        visitor.visit_statement(statement);
+    }
+    if let Some(value) = &mut block.value {
+        visitor.visit_expression(value);
+    }
+}
+
+pub fn walk_statement<V: MutVisitor>(visitor: &mut V, statement: &mut Statement) {
+    visitor.visit_id(&mut statement.id);
+    visitor.visit_span(&mut statement.span);
+    match &mut statement.kind {
+        StatementKind::Expression(expression) => visitor.visit_expression(expression),
+        StatementKind::Let { pattern, type_annotation, value, .. } => {
+            visitor.visit_pattern(pattern);
+            if let Some(type_annotation) = type_annotation {
+                visitor.visit_type_literal(type_annotation);
+            }
+            if let Some(value) = value {
+                visitor.visit_expression(value);
+            }
+        }
+        StatementKind::Assign { target, value } => {
+            visitor.visit_expression(target);
+            visitor.visit_expression(value);
+        }
+        StatementKind::Return(value) => {
+            if let Some(value) = value {
+                visitor.visit_expression(value);
+            }
+        }
+        StatementKind::For { pattern, iterable, body } => {
+            visitor.visit_pattern(pattern);
+            visitor.visit_expression(iterable);
+            visitor.visit_block(body);
+        }
+        StatementKind::While { condition, body } => {
+            visitor.visit_expression(condition);
+            visitor.visit_block(body);
+        }
+    }
+}
+
+pub fn walk_expression<V: MutVisitor>(visitor: &mut V, expression: &mut Expression) {
+    visitor.visit_id(&mut expression.id);
+    visitor.visit_span(&mut expression.span);
+    match &mut expression.kind {
+        ExpressionKind::Literal(_) => {}
+        ExpressionKind::Accesser(accesser) => visitor.visit_accesser(accesser),
+        ExpressionKind::Unary { operand, .. } => visitor.visit_expression(operand),
+        ExpressionKind::Binary { left, right, .. } => {
+            visitor.visit_expression(left);
+            visitor.visit_expression(right);
+        }
+        ExpressionKind::Call { callee, args } => {
+            visitor.visit_expression(callee);
+            for arg in args {
+                visitor.visit_expression(arg);
+            }
+        }
+        ExpressionKind::Field { target, .. } | ExpressionKind::Try(target) => visitor.visit_expression(target),
+        ExpressionKind::Cast { value, ty } => {
+            visitor.visit_expression(value);
+            visitor.visit_type_literal(ty);
+        }
+        ExpressionKind::Tuple(inner) => {
+            for expression in inner {
+                visitor.visit_expression(expression);
+            }
+        }
+        ExpressionKind::Struct { class, fields } => {
+            visitor.visit_accesser(class);
+            for field in fields {
+                visitor.visit_id(&mut field.id);
+                visitor.visit_span(&mut field.span);
+                visitor.visit_expression(&mut field.value);
+            }
+        }
+        ExpressionKind::If(if_expression) => visitor.visit_if_expression(if_expression),
+        ExpressionKind::Match(match_expression) => visitor.visit_match_expression(match_expression),
+        ExpressionKind::Block(block) => visitor.visit_block(block),
+    }
+}
+
+pub fn walk_if_expression<V: MutVisitor>(visitor: &mut V, if_expression: &mut IfExpression) {
+    visitor.visit_expression(&mut if_expression.condition);
+    visitor.visit_block(&mut if_expression.then_branch);
+    if let Some(else_branch) = &mut if_expression.else_branch {
+        visitor.visit_expression(else_branch);
+    }
+}
+
+pub fn walk_match_expression<V: MutVisitor>(visitor: &mut V, match_expression: &mut MatchExpression) {
+    visitor.visit_expression(&mut match_expression.scrutinee);
+    for arm in &mut match_expression.arms {
+        visitor.visit_match_arm(arm);
+    }
+}
+
+pub fn walk_match_arm<V: MutVisitor>(visitor: &mut V, arm: &mut MatchArm) {
+    visitor.visit_id(&mut arm.id);
+    visitor.visit_span(&mut arm.span);
+    visitor.visit_pattern(&mut arm.pattern);
+    visitor.visit_expression(&mut arm.value);
+}
+
+pub fn walk_accesser<V: MutVisitor>(visitor: &mut V, accesser: &mut Accesser) {
+    for segment in &mut accesser.segments {
+        for type_arg in &mut segment.type_args {
+            visitor.visit_type_literal(type_arg);
+        }
+    }
+}
+
+pub fn walk_pattern<V: MutVisitor>(visitor: &mut V, pattern: &mut Pattern) {
+    visitor.visit_id(&mut pattern.id);
+    visitor.visit_span(&mut pattern.span);
+    match &mut pattern.kind {
+        PatternKind::Literal(_) | PatternKind::Identifier(_) | PatternKind::Wildcard => {}
+        PatternKind::Tuple(inner) => {
+            for pattern in inner {
+                visitor.visit_pattern(pattern);
+            }
+        }
+        PatternKind::Variant { path, fields } => {
+            visitor.visit_accesser(path);
+            match fields {
+                PatternFields::Unit => {}
+                PatternFields::Tuple(inner) => {
+                    for pattern in inner {
+                        visitor.visit_pattern(pattern);
+                    }
+                }
+                PatternFields::Struct(fields) => {
+                    for field in fields {
+                        visitor.visit_id(&mut field.id);
+                        visitor.visit_span(&mut field.span);
+                        visitor.visit_pattern(&mut field.pattern);
+                    }
+                }
+            }
+        }
+    }
+}
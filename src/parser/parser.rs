@@ -1,33 +1,852 @@
-use crate::{parser::Program, tokenizer::Token, parser::error::ParseErr};
+use crate::{parser::*, tokenizer::Token, parser::error::ParseErr};
 
 pub struct Parser {
     input: Vec<Token>,
+    /// `spans[i]` is the source range of `input[i]`.
+    spans: Vec<Span>,
     now_index: usize,
+    next_id: u32,
+    /// whether an accesser followed by `{` starts a struct literal, which it
+    /// does not in the condition of `if` and `while`, the iterable of `for`
+    /// and the value a `match` matches
+    struct_allowed: bool,
+    /// where the lines of the source after the first start
+    line_starts: Vec<usize>,
 }
 
 type ParseResult<T> = Result<T, ParseErr>;
 
+/// Binding power of each binary operator; a larger number binds tighter.
+fn binary_ope(token: &Token) -> Option<(BinaryOpe, u8)> {
+    match token {
+        Token::DoubleEqualOpe => Some((BinaryOpe::Equal, 1)),
+        Token::NotEqualOpe => Some((BinaryOpe::NotEqual, 1)),
+        Token::LesserOpe => Some((BinaryOpe::Lesser, 1)),
+        Token::GreaterOpe => Some((BinaryOpe::Greater, 1)),
+        Token::LesserEqualOpe => Some((BinaryOpe::LesserEqual, 1)),
+        Token::GreaterEqualOpe => Some((BinaryOpe::GreaterEqual, 1)),
+        Token::PlusOpe => Some((BinaryOpe::Add, 2)),
+        Token::MinusOpe => Some((BinaryOpe::Sub, 2)),
+        Token::MulOpe => Some((BinaryOpe::Mul, 3)),
+        Token::DivOpe => Some((BinaryOpe::Div, 3)),
+        _ => None,
+    }
+}
+
 impl Parser {
+    pub fn new(input: Vec<Token>, spans: Vec<Span>) -> Self {
+        Parser {
+            input,
+            spans,
+            now_index: 0,
+            next_id: 0,
+            struct_allowed: true,
+            line_starts: Vec::new(),
+        }
+    }
+
+    /// Tells the parser where lines start, like `Tokenizer::line_starts`
+    /// gives them. Without them a missing `;` is never added by `sonneko fix`,
+    /// as the token found instead can not be told to start another line.
+    pub fn with_line_starts(mut self, line_starts: Vec<usize>) -> Self {
+        self.line_starts = line_starts;
+        self
+    }
+
+    /// Hands out ids from `first` on, so several files parsed one after
+    /// another can make up one program without sharing ids.
+    pub fn with_first_id(mut self, first: NodeId) -> Self {
+        self.next_id = first.0;
+        self
+    }
+
+    /// The id the next node would get; the first free one once parsing is done.
+    pub fn next_free_id(&self) -> NodeId {
+        NodeId(self.next_id)
+    }
+
+    fn next_id(&mut self) -> NodeId {
+        let id = NodeId(self.next_id);
+        self.next_id += 1;
+        id
+    }
+
+    /// Source offset where the next token starts.
+    fn start(&self) -> usize {
+        match self.spans.get(self.now_index) {
+            Some(span) => span.start,
+            None => self.spans.last().map_or(0, |span| span.end),
+        }
+    }
+
+    /// Span from `start` to the end of the last consumed token.
+    fn span_from(&self, start: usize) -> Span {
+        let end = match self.now_index.checked_sub(1).and_then(|index| self.spans.get(index)) {
+            Some(span) => span.end,
+            None => start,
+        };
+        Span::new(start, end.max(start))
+    }
+
+    fn next_span(&self) -> Span {
+        let start = self.start();
+        self.spans.get(self.now_index).copied().unwrap_or(Span::new(start, start))
+    }
+
+    fn expression(&mut self, start: usize, kind: ExpressionKind) -> Expression {
+        Expression { id: self.next_id(), span: self.span_from(start), kind }
+    }
+
+    fn statement(&mut self, start: usize, kind: StatementKind) -> Statement {
+        Statement { id: self.next_id(), span: self.span_from(start), kind }
+    }
 
     fn next(&self) -> Option<&Token> {
         self.input.get(self.now_index)
     }
 
-    fn parse(&mut self) -> Program {
-        self.parse_program()
+    fn check_next(&mut self, expect: &Token) -> bool {
+        let ret = self.next() == Some(expect);
+        if ret {
+            self.now_index += 1;
+        }
+        ret
     }
 
-    fn parse_program(&mut self) -> Program {
-        while let Ok(()) = self.parse_declaration() {
+    fn expect(&mut self, expect: Token, description: &'static str) -> ParseResult<()> {
+        if self.check_next(&expect) {
+            Ok(())
+        } else {
+            self.unexpected(description)
+        }
+    }
 
+    fn expect_identifier(&mut self) -> ParseResult<Identifier> {
+        match self.next() {
+            Some(Token::Identifier(name)) => {
+                let name = name.clone();
+                self.now_index += 1;
+                Ok(name)
+            }
+            _ => self.unexpected("identifier"),
         }
+    }
 
+    /// Source offset where the last consumed token ends.
+    fn previous_end(&self) -> usize {
+        self.now_index.checked_sub(1).and_then(|index| self.spans.get(index)).map_or(0, |span| span.end)
+    }
 
-        Program { declarations: Vec::new() }
+    fn unexpected<T>(&self, expected: &'static str) -> ParseResult<T> {
+        match self.next() {
+            // point right after the last token rather than at the end of trailing blank lines
+            Some(Token::EOF) | None => Err(ParseErr::UnexpectedEOF(expected, Span::new(self.previous_end(), self.previous_end()))),
+            Some(token) => {
+                let (span, previous_end) = (self.next_span(), self.previous_end());
+                let on_later_line = self.line_starts.iter().any(|&line_start| previous_end < line_start && line_start <= span.start);
+                Err(ParseErr::UnexpectedToken(token.clone(), expected, span, previous_end, on_later_line))
+            }
+        }
     }
 
-    fn parse_declaration(&mut self) -> ParseResult<> {
-        unimplemented!()
+    pub fn parse(&mut self) -> ParseResult<Program> {
+        let program = self.parse_program()?;
+        self.expect(Token::EOF, "declaration")?;
+        Ok(program)
     }
 
-}
\ No newline at end of file
+    fn parse_program(&mut self) -> ParseResult<Program> {
+        let start = self.start();
+        let mut declarations = Vec::new();
+        while !matches!(self.next(), Some(Token::EOF | Token::CurlyBracketEnd) | None) {
+            declarations.push(self.parse_declaration()?);
+        }
+
+        Ok(Program { id: self.next_id(), span: self.span_from(start), declarations })
+    }
+
+    fn parse_declaration(&mut self) -> ParseResult<Declaration> {
+        let start = self.start();
+        let is_pub = self.check_next(&Token::Pub);
+        match self.next() {
+            Some(Token::Static) => Ok(Declaration::StaticVariable(self.parse_static_variable_declaration(start, is_pub)?)),
+            Some(Token::NameSpace) => Ok(Declaration::Namespace(self.parse_namespace_declaration(start, is_pub)?)),
+            Some(Token::Fn) => Ok(Declaration::Function(self.parse_function_declaration(start, is_pub)?)),
+            Some(Token::Class) => Ok(Declaration::Class(self.parse_class_declaration(start, is_pub)?)),
+            Some(Token::Enum) => Ok(Declaration::Enum(self.parse_enum_declaration(start, is_pub)?)),
+            Some(Token::Trait) => Ok(Declaration::Trait(self.parse_trait_declaration(start, is_pub)?)),
+            Some(Token::Impl) if !is_pub => Ok(Declaration::Impl(self.parse_impl_declaration(start)?)),
+            Some(Token::Import) if !is_pub => Ok(Declaration::Import(self.parse_import_declaration(start)?)),
+            _ => self.unexpected("declaration"),
+        }
+    }
+
+    fn parse_static_variable_declaration(&mut self, start: usize, is_pub: bool) -> ParseResult<StaticVariableDeclaration> {
+        self.expect(Token::Static, "`static`")?;
+        let name = self.expect_identifier()?;
+        let type_annotation = if self.check_next(&Token::Colon) {
+            Some(self.parse_type_literal()?)
+        } else {
+            None
+        };
+        self.expect(Token::EqualOpe, "`=`")?;
+        let value = self.parse_expression()?;
+        self.expect(Token::SemiColon, "`;`")?;
+        Ok(StaticVariableDeclaration { id: self.next_id(), span: self.span_from(start), is_pub, name, type_annotation, value })
+    }
+
+    fn parse_namespace_declaration(&mut self, start: usize, is_pub: bool) -> ParseResult<NamespaceDeclaration> {
+        self.expect(Token::NameSpace, "`namespace`")?;
+        let name = self.expect_identifier()?;
+        self.expect(Token::CurlyBracketStart, "`{`")?;
+        let inner = self.parse_program()?;
+        self.expect(Token::CurlyBracketEnd, "`}`")?;
+        Ok(NamespaceDeclaration { id: self.next_id(), span: self.span_from(start), is_pub, name, inner })
+    }
+
+    fn parse_function_declaration(&mut self, start: usize, is_pub: bool) -> ParseResult<FunctionDeclaration> {
+        let signature = self.parse_function_signature()?;
+        let inner = self.parse_block()?;
+        Ok(FunctionDeclaration { id: self.next_id(), span: self.span_from(start), is_pub, name: signature.name.clone(), signature, inner })
+    }
+
+    fn parse_class_declaration(&mut self, start: usize, is_pub: bool) -> ParseResult<ClassDeclaration> {
+        self.expect(Token::Class, "`class`")?;
+        let name = self.expect_identifier()?;
+        let type_params = self.parse_type_params()?;
+        self.expect(Token::CurlyBracketStart, "`{`")?;
+        let mut fields = Vec::new();
+        let mut methods = Vec::new();
+        while !self.check_next(&Token::CurlyBracketEnd) {
+            let member_start = self.start();
+            let is_pub = self.check_next(&Token::Pub);
+            if let Some(Token::Fn) = self.next() {
+                methods.push(self.parse_function_declaration(member_start, is_pub)?);
+            } else {
+                let name = self.expect_identifier()?;
+                self.expect(Token::Colon, "`:`")?;
+                let type_annotation = self.parse_type_literal()?;
+                self.expect(Token::SemiColon, "`;`")?;
+                fields.push(FieldDeclaration { id: self.next_id(), span: self.span_from(member_start), is_pub, name, type_annotation });
+            }
+        }
+        Ok(ClassDeclaration { id: self.next_id(), span: self.span_from(start), is_pub, name, type_params, fields, methods })
+    }
+
+    fn parse_enum_declaration(&mut self, start: usize, is_pub: bool) -> ParseResult<EnumDeclaration> {
+        self.expect(Token::Enum, "`enum`")?;
+        let name = self.expect_identifier()?;
+        let type_params = self.parse_type_params()?;
+        self.expect(Token::CurlyBracketStart, "`{`")?;
+        let mut variants = Vec::new();
+        while !self.check_next(&Token::CurlyBracketEnd) {
+            variants.push(self.parse_variant()?);
+            if !self.check_next(&Token::Comma) {
+                self.expect(Token::CurlyBracketEnd, "`,` or `}`")?;
+                break;
+            }
+        }
+        Ok(EnumDeclaration { id: self.next_id(), span: self.span_from(start), is_pub, name, type_params, variants })
+    }
+
+    fn parse_variant(&mut self) -> ParseResult<Variant> {
+        let start = self.start();
+        let name = self.expect_identifier()?;
+        let kind = if self.check_next(&Token::BrancketStart) {
+            let mut types = Vec::new();
+            while !self.check_next(&Token::BrancketEnd) {
+                types.push(self.parse_type_literal()?);
+                if !self.check_next(&Token::Comma) {
+                    self.expect(Token::BrancketEnd, "`,` or `)`")?;
+                    break;
+                }
+            }
+            VariantKind::Tuple(types)
+        } else if self.check_next(&Token::CurlyBracketStart) {
+            let mut fields = Vec::new();
+            while !self.check_next(&Token::CurlyBracketEnd) {
+                let field_start = self.start();
+                let name = self.expect_identifier()?;
+                self.expect(Token::Colon, "`:`")?;
+                let type_annotation = self.parse_type_literal()?;
+                fields.push(FieldDeclaration { id: self.next_id(), span: self.span_from(field_start), is_pub: true, name, type_annotation });
+                if !self.check_next(&Token::Comma) {
+                    self.expect(Token::CurlyBracketEnd, "`,` or `}`")?;
+                    break;
+                }
+            }
+            VariantKind::Struct(fields)
+        } else {
+            VariantKind::Unit
+        };
+        Ok(Variant { id: self.next_id(), span: self.span_from(start), name, kind })
+    }
+
+    fn parse_trait_declaration(&mut self, start: usize, is_pub: bool) -> ParseResult<TraitDeclaration> {
+        self.expect(Token::Trait, "`trait`")?;
+        let name = self.expect_identifier()?;
+        self.expect(Token::CurlyBracketStart, "`{`")?;
+        let mut signatures = Vec::new();
+        let mut functions = Vec::new();
+        while !self.check_next(&Token::CurlyBracketEnd) {
+            let member_start = self.start();
+            let signature = self.parse_function_signature()?;
+            if self.check_next(&Token::SemiColon) {
+                signatures.push(signature);
+            } else {
+                let inner = self.parse_block()?;
+                functions.push(FunctionDeclaration { id: self.next_id(), span: self.span_from(member_start), is_pub: false, name: signature.name.clone(), signature, inner });
+            }
+        }
+        Ok(TraitDeclaration { id: self.next_id(), span: self.span_from(start), is_pub, name, signatures, functions })
+    }
+
+    fn parse_impl_declaration(&mut self, start: usize) -> ParseResult<ImplDeclaration> {
+        self.expect(Token::Impl, "`impl`")?;
+        let trait_type = self.parse_type_literal()?;
+        self.expect(Token::For, "`for`")?;
+        let self_type = self.parse_type_literal()?;
+        self.expect(Token::CurlyBracketStart, "`{`")?;
+        let mut functions = Vec::new();
+        while !self.check_next(&Token::CurlyBracketEnd) {
+            let function_start = self.start();
+            functions.push(self.parse_function_declaration(function_start, false)?);
+        }
+        Ok(ImplDeclaration { id: self.next_id(), span: self.span_from(start), trait_type, self_type, functions })
+    }
+
+    fn parse_import_declaration(&mut self, start: usize) -> ParseResult<ImportDeclaration> {
+        self.expect(Token::Import, "`import`")?;
+        let kind = if self.check_next(&Token::CurlyBracketStart) {
+            let mut items = Vec::new();
+            while !self.check_next(&Token::CurlyBracketEnd) {
+                let item_start = self.start();
+                let name = self.expect_identifier()?;
+                items.push(ImportItem { id: self.next_id(), span: self.span_from(item_start), name });
+                if !self.check_next(&Token::Comma) {
+                    self.expect(Token::CurlyBracketEnd, "`,` or `}`")?;
+                    break;
+                }
+            }
+            ImportKind::Items(items)
+        } else {
+            ImportKind::Module(self.expect_identifier()?)
+        };
+        self.expect(Token::From, "`from`")?;
+        let path = match self.next() {
+            Some(Token::StringLiteral(path)) => path.clone(),
+            _ => return self.unexpected("string literal"),
+        };
+        self.now_index += 1;
+        self.expect(Token::SemiColon, "`;`")?;
+        Ok(ImportDeclaration { id: self.next_id(), span: self.span_from(start), kind, path })
+    }
+
+    fn parse_function_signature(&mut self) -> ParseResult<FunctionSignature> {
+        let start = self.start();
+        self.expect(Token::Fn, "`fn`")?;
+        let name = self.expect_identifier()?;
+        let type_params = self.parse_type_params()?;
+        self.expect(Token::BrancketStart, "`(`")?;
+        let mut params = Vec::new();
+        while !self.check_next(&Token::BrancketEnd) {
+            let pattern = self.parse_pattern()?;
+            let is_self = params.is_empty() && pattern.kind == PatternKind::Identifier("self".into());
+            let type_annotation = if is_self && matches!(self.next(), Some(Token::Comma | Token::BrancketEnd)) {
+                let kind = TypeLiteralKind::CustomType { name: "Self".into(), type_args: Vec::new() };
+                TypeLiteral { id: self.next_id(), span: pattern.span, kind }
+            } else {
+                self.expect(Token::Colon, "`:`")?;
+                self.parse_type_literal()?
+            };
+            let span = pattern.span.to(type_annotation.span);
+            params.push(Parameter { id: self.next_id(), span, pattern, type_annotation });
+            if !self.check_next(&Token::Comma) {
+                self.expect(Token::BrancketEnd, "`,` or `)`")?;
+                break;
+            }
+        }
+        let return_type = if self.check_next(&Token::Colon) {
+            Some(self.parse_type_literal()?)
+        } else {
+            None
+        };
+        Ok(FunctionSignature { id: self.next_id(), span: self.span_from(start), name, type_params, params, return_type })
+    }
+
+    /// <type_params> := "<" ( <type_param> "," )* <type_param>? ">"
+    ///
+    /// Nothing when the next token is not `<`.
+    fn parse_type_params(&mut self) -> ParseResult<Vec<TypeParam>> {
+        let mut type_params = Vec::new();
+        if !self.check_next(&Token::LesserOpe) {
+            return Ok(type_params);
+        }
+        while !self.check_type_args_end() {
+            let start = self.start();
+            let name = self.expect_identifier()?;
+            let mut bounds = Vec::new();
+            if self.check_next(&Token::Colon) {
+                bounds.push(self.parse_type_literal()?);
+                while self.check_next(&Token::PlusOpe) {
+                    bounds.push(self.parse_type_literal()?);
+                }
+            }
+            type_params.push(TypeParam { id: self.next_id(), span: self.span_from(start), name, bounds });
+            if !self.check_next(&Token::Comma) {
+                if !self.check_type_args_end() {
+                    return self.unexpected("`,` or `>`");
+                }
+                break;
+            }
+        }
+        Ok(type_params)
+    }
+
+    fn parse_type_literal(&mut self) -> ParseResult<TypeLiteral> {
+        let start = self.start();
+        let kind = match self.next() {
+            Some(Token::Int) => TypeLiteralKind::IntType,
+            Some(Token::Float) => TypeLiteralKind::FloatType,
+            Some(Token::Char) => TypeLiteralKind::CharType,
+            Some(Token::Bool) => TypeLiteralKind::BoolType,
+            Some(Token::NumericType(ty)) => TypeLiteralKind::NumericType(*ty),
+            Some(Token::Identifier(name)) => {
+                let name = name.clone();
+                self.now_index += 1;
+                let type_args = if let Some(Token::LesserOpe) = self.next() {
+                    self.parse_type_args()?
+                } else {
+                    Vec::new()
+                };
+                let kind = TypeLiteralKind::CustomType { name, type_args };
+                return Ok(TypeLiteral { id: self.next_id(), span: self.span_from(start), kind });
+            }
+            Some(Token::Dyn) => {
+                self.now_index += 1;
+                let Some(Token::Identifier(name)) = self.next() else { return self.unexpected("trait") };
+                TypeLiteralKind::DynType { name: name.clone() }
+            }
+            Some(Token::BrancketStart) => {
+                self.now_index += 1;
+                let mut inner = Vec::new();
+                while !self.check_next(&Token::BrancketEnd) {
+                    inner.push(self.parse_type_literal()?);
+                    if !self.check_next(&Token::Comma) {
+                        self.expect(Token::BrancketEnd, "`,` or `)`")?;
+                        break;
+                    }
+                }
+                let kind = TypeLiteralKind::TupleType { inner };
+                return Ok(TypeLiteral { id: self.next_id(), span: self.span_from(start), kind });
+            }
+            _ => return self.unexpected("type"),
+        };
+        self.now_index += 1;
+        Ok(TypeLiteral { id: self.next_id(), span: self.span_from(start), kind })
+    }
+
+    /// <type_args> := "<" ( <type_literal> "," )* <type_literal>? ">"
+    fn parse_type_args(&mut self) -> ParseResult<Vec<TypeLiteral>> {
+        self.expect(Token::LesserOpe, "`<`")?;
+        let mut type_args = Vec::new();
+        while !self.check_type_args_end() {
+            type_args.push(self.parse_type_literal()?);
+            if !self.check_next(&Token::Comma) {
+                if !self.check_type_args_end() {
+                    return self.unexpected("`,` or `>`");
+                }
+                break;
+            }
+        }
+        Ok(type_args)
+    }
+
+    /// Consumes the `>` closing type arguments. A `>=` is split so that
+    /// `let x: List<int>= ...` reads as `>` followed by `=`.
+    fn check_type_args_end(&mut self) -> bool {
+        match self.next() {
+            Some(Token::GreaterOpe) => {
+                self.now_index += 1;
+                true
+            }
+            Some(Token::GreaterEqualOpe) => {
+                let span = self.spans[self.now_index];
+                self.input[self.now_index] = Token::GreaterOpe;
+                self.spans[self.now_index] = Span::new(span.start, span.start + 1);
+                self.input.insert(self.now_index + 1, Token::EqualOpe);
+                self.spans.insert(self.now_index + 1, Span::new(span.start + 1, span.end));
+                self.now_index += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn parse_block(&mut self) -> ParseResult<Block> {
+        let start = self.start();
+        self.expect(Token::CurlyBracketStart, "`{`")?;
+        let mut statements = Vec::new();
+        let mut value = None;
+        while !self.check_next(&Token::CurlyBracketEnd) {
+            let statement_start = self.start();
+            let expression = match self.next() {
+                Some(Token::Let | Token::Const) => {
+                    statements.push(self.parse_let_statement()?);
+                    continue;
+                }
+                Some(Token::Return) => {
+                    self.now_index += 1;
+                    let value = if let Some(Token::SemiColon) = self.next() {
+                        None
+                    } else {
+                        Some(self.parse_expression()?)
+                    };
+                    self.expect(Token::SemiColon, "`;`")?;
+                    statements.push(self.statement(statement_start, StatementKind::Return(value)));
+                    continue;
+                }
+                Some(Token::While) => {
+                    self.now_index += 1;
+                    let condition = self.parse_condition()?;
+                    let body = self.parse_block()?;
+                    statements.push(self.statement(statement_start, StatementKind::While { condition, body }));
+                    continue;
+                }
+                Some(Token::For) => {
+                    self.now_index += 1;
+                    let pattern = self.parse_pattern()?;
+                    self.expect(Token::In, "`in`")?;
+                    let iterable = self.with_structs(false, Self::parse_expression)?;
+                    let body = self.parse_block()?;
+                    statements.push(self.statement(statement_start, StatementKind::For { pattern, iterable, body }));
+                    continue;
+                }
+                // a block-like expression ends the statement on its own
+                Some(Token::If | Token::Match | Token::CurlyBracketStart) => self.parse_block_like_expression()?,
+                _ => self.parse_expression()?,
+            };
+
+            if let Some(Token::CurlyBracketEnd) = self.next() {
+                value = Some(Box::new(expression));
+            } else if self.check_next(&Token::SemiColon) || expression.is_block_like() {
+                statements.push(self.statement(statement_start, StatementKind::Expression(expression)));
+            } else if let Some(Token::EqualOpe) = self.next() {
+                if !matches!(expression.kind, ExpressionKind::Accesser(_) | ExpressionKind::Field { .. }) {
+                    return Err(ParseErr::InvalidAssignTarget(expression.span));
+                }
+                self.now_index += 1;
+                let value = self.parse_expression()?;
+                self.expect(Token::SemiColon, "`;`")?;
+                statements.push(self.statement(statement_start, StatementKind::Assign { target: expression, value }));
+            } else {
+                return self.unexpected("`;` or `}`");
+            }
+        }
+        Ok(Block { id: self.next_id(), span: self.span_from(start), statements, value })
+    }
+
+    fn parse_let_statement(&mut self) -> ParseResult<Statement> {
+        let start = self.start();
+        let is_const = self.check_next(&Token::Const);
+        if !is_const {
+            self.expect(Token::Let, "`let` or `const`")?;
+        }
+        let pattern = self.parse_pattern()?;
+        let type_annotation = if self.check_next(&Token::Colon) {
+            Some(self.parse_type_literal()?)
+        } else {
+            None
+        };
+        let value = if self.check_next(&Token::EqualOpe) {
+            Some(self.parse_expression()?)
+        } else {
+            None
+        };
+        self.expect(Token::SemiColon, "`;`")?;
+        Ok(self.statement(start, StatementKind::Let { is_const, pattern, type_annotation, value }))
+    }
+
+    fn parse_expression(&mut self) -> ParseResult<Expression> {
+        self.parse_binary(0)
+    }
+
+    fn parse_binary(&mut self, min_power: u8) -> ParseResult<Expression> {
+        let start = self.start();
+        let mut left = self.parse_cast()?;
+        while let Some((ope, power)) = self.next().and_then(binary_ope) {
+            if power < min_power {
+                break;
+            }
+            self.now_index += 1;
+            let right = self.parse_binary(power + 1)?;
+            left = self.expression(start, ExpressionKind::Binary { ope, left: Box::new(left), right: Box::new(right) });
+        }
+        Ok(left)
+    }
+
+    /// <cast_result> := <expression> "as" <type_literal>
+    fn parse_cast(&mut self) -> ParseResult<Expression> {
+        let start = self.start();
+        let mut value = self.parse_unary()?;
+        while self.check_next(&Token::As) {
+            let ty = self.parse_type_literal()?;
+            value = self.expression(start, ExpressionKind::Cast { value: Box::new(value), ty });
+        }
+        Ok(value)
+    }
+
+    fn parse_unary(&mut self) -> ParseResult<Expression> {
+        let start = self.start();
+        if self.check_next(&Token::MinusOpe) {
+            let operand = self.parse_unary()?;
+            return Ok(self.expression(start, ExpressionKind::Unary { ope: UnaryOpe::Minus, operand: Box::new(operand) }));
+        }
+        self.parse_postfix()
+    }
+
+    fn parse_postfix(&mut self) -> ParseResult<Expression> {
+        let start = self.start();
+        let mut expression = self.parse_primary()?;
+        loop {
+            if self.check_next(&Token::BrancketStart) {
+                let args = self.with_structs(true, |parser| parser.parse_expression_list(Token::BrancketEnd))?;
+                expression = self.expression(start, ExpressionKind::Call { callee: Box::new(expression), args });
+            } else if self.check_next(&Token::Period) {
+                let name = self.expect_identifier()?;
+                expression = self.expression(start, ExpressionKind::Field { target: Box::new(expression), name });
+            } else if self.check_next(&Token::Question) {
+                expression = self.expression(start, ExpressionKind::Try(Box::new(expression)));
+            } else {
+                return Ok(expression);
+            }
+        }
+    }
+
+    /// Parses `( <expression> "," )* <expression>?` followed by `end`.
+    fn parse_expression_list(&mut self, end: Token) -> ParseResult<Vec<Expression>> {
+        let mut expressions = Vec::new();
+        while !self.check_next(&end) {
+            expressions.push(self.parse_expression()?);
+            if !self.check_next(&Token::Comma) {
+                self.expect(end, "`,` or closing bracket")?;
+                break;
+            }
+        }
+        Ok(expressions)
+    }
+
+    fn parse_primary(&mut self) -> ParseResult<Expression> {
+        let start = self.start();
+        let literal = match self.next() {
+            Some(Token::IntegerLiteral(value)) => Literal::Int(*value),
+            Some(Token::FloatLiteral(value)) => Literal::Float(*value),
+            Some(Token::StringLiteral(value)) => Literal::String(value.clone()),
+            Some(Token::True) => Literal::Bool(true),
+            Some(Token::False) => Literal::Bool(false),
+            Some(Token::Identifier(_)) => {
+                let accesser = self.parse_accesser()?;
+                if self.struct_allowed && self.check_next(&Token::CurlyBracketStart) {
+                    let fields = self.parse_field_inits()?;
+                    return Ok(self.expression(start, ExpressionKind::Struct { class: accesser, fields }));
+                }
+                return Ok(self.expression(start, ExpressionKind::Accesser(accesser)));
+            }
+            // inside brackets or a block `{` can not start the body of a condition
+            Some(Token::BrancketStart) => return self.with_structs(true, Self::parse_parenthesized),
+            Some(Token::If | Token::Match | Token::CurlyBracketStart) => return self.with_structs(true, Self::parse_block_like_expression),
+            _ => return self.unexpected("expression"),
+        };
+        self.now_index += 1;
+        Ok(self.expression(start, ExpressionKind::Literal(literal)))
+    }
+
+    fn parse_accesser(&mut self) -> ParseResult<Accesser> {
+        let mut segments = vec![PathSegment { name: self.expect_identifier()?, type_args: Vec::new() }];
+        while self.check_next(&Token::DoubleColon) {
+            if let Some(Token::LesserOpe) = self.next() {
+                let segment = segments.last_mut().unwrap();
+                if !segment.type_args.is_empty() {
+                    return self.unexpected("identifier");
+                }
+                segment.type_args = self.parse_type_args()?;
+            } else {
+                segments.push(PathSegment { name: self.expect_identifier()?, type_args: Vec::new() });
+            }
+        }
+        Ok(Accesser { segments })
+    }
+
+    /// The fields of a struct literal after its `{`, up to and with the `}`.
+    fn parse_field_inits(&mut self) -> ParseResult<Vec<FieldInit>> {
+        self.with_structs(true, |parser| {
+            let mut fields = Vec::new();
+            while !parser.check_next(&Token::CurlyBracketEnd) {
+                let start = parser.start();
+                let name = parser.expect_identifier()?;
+                parser.expect(Token::Colon, "`:`")?;
+                let value = parser.parse_expression()?;
+                fields.push(FieldInit { id: parser.next_id(), span: parser.span_from(start), name, value });
+                if !parser.check_next(&Token::Comma) {
+                    parser.expect(Token::CurlyBracketEnd, "`,` or `}`")?;
+                    break;
+                }
+            }
+            Ok(fields)
+        })
+    }
+
+    /// Runs `parse` with struct literals allowed or not.
+    fn with_structs<T>(&mut self, allowed: bool, parse: impl FnOnce(&mut Self) -> ParseResult<T>) -> ParseResult<T> {
+        let outer = std::mem::replace(&mut self.struct_allowed, allowed);
+        let result = parse(self);
+        self.struct_allowed = outer;
+        result
+    }
+
+    /// `()` is the empty tuple, `(a)` is just `a` and `(a,)` or `(a, b)` are tuples.
+    /// The span of `(a)` covers the parentheses.
+    fn parse_parenthesized(&mut self) -> ParseResult<Expression> {
+        let start = self.start();
+        self.expect(Token::BrancketStart, "`(`")?;
+        if self.check_next(&Token::BrancketEnd) {
+            return Ok(self.expression(start, ExpressionKind::Tuple(Vec::new())));
+        }
+        let mut first = self.parse_expression()?;
+        if self.check_next(&Token::BrancketEnd) {
+            first.span = self.span_from(start);
+            return Ok(first);
+        }
+        self.expect(Token::Comma, "`,` or `)`")?;
+        let mut inner = vec![first];
+        inner.extend(self.parse_expression_list(Token::BrancketEnd)?);
+        Ok(self.expression(start, ExpressionKind::Tuple(inner)))
+    }
+
+    fn parse_block_like_expression(&mut self) -> ParseResult<Expression> {
+        let start = self.start();
+        let kind = match self.next() {
+            Some(Token::If) => ExpressionKind::If(self.parse_if_expression()?),
+            Some(Token::Match) => ExpressionKind::Match(self.parse_match_expression()?),
+            Some(Token::CurlyBracketStart) => ExpressionKind::Block(self.parse_block()?),
+            _ => return self.unexpected("`if`, `match` or `{`"),
+        };
+        Ok(self.expression(start, kind))
+    }
+
+    /// The condition of `if` or `while`, where `=` is most likely a mistyped `==`.
+    fn parse_condition(&mut self) -> ParseResult<Expression> {
+        let condition = self.with_structs(false, Self::parse_expression)?;
+        if let Some(Token::EqualOpe) = self.next() {
+            return Err(ParseErr::AssignInCondition(self.next_span()));
+        }
+        Ok(condition)
+    }
+
+    fn parse_if_expression(&mut self) -> ParseResult<IfExpression> {
+        self.expect(Token::If, "`if`")?;
+        let condition = self.parse_condition()?;
+        let then_branch = self.parse_block()?;
+        let else_branch = if self.check_next(&Token::Else) {
+            if let Some(Token::If) = self.next() {
+                Some(Box::new(self.parse_block_like_expression()?))
+            } else {
+                let start = self.start();
+                let block = self.parse_block()?;
+                Some(Box::new(self.expression(start, ExpressionKind::Block(block))))
+            }
+        } else {
+            None
+        };
+        Ok(IfExpression { condition: Box::new(condition), then_branch, else_branch })
+    }
+
+    fn parse_match_expression(&mut self) -> ParseResult<MatchExpression> {
+        self.expect(Token::Match, "`match`")?;
+        let scrutinee = self.with_structs(false, Self::parse_expression)?;
+        self.expect(Token::CurlyBracketStart, "`{`")?;
+        let mut arms = Vec::new();
+        while !self.check_next(&Token::CurlyBracketEnd) {
+            let start = self.start();
+            let pattern = self.parse_pattern()?;
+            self.expect(Token::FatArrow, "`=>`")?;
+            let value = self.parse_expression()?;
+            let is_block_like = value.is_block_like();
+            arms.push(MatchArm { id: self.next_id(), span: self.span_from(start), pattern, value });
+            if !self.check_next(&Token::Comma) && !is_block_like {
+                self.expect(Token::CurlyBracketEnd, "`,` or `}`")?;
+                break;
+            }
+        }
+        Ok(MatchExpression { scrutinee: Box::new(scrutinee), arms })
+    }
+
+    fn parse_pattern(&mut self) -> ParseResult<Pattern> {
+        let start = self.start();
+        let kind = match self.next() {
+            Some(Token::Identifier(name)) if name == "_" => PatternKind::Wildcard,
+            // a lone identifier binds, anything longer names a variant
+            Some(Token::Identifier(_)) if matches!(self.input.get(self.now_index + 1), Some(Token::DoubleColon | Token::BrancketStart | Token::CurlyBracketStart)) => {
+                let path = self.parse_accesser()?;
+                let fields = self.parse_pattern_fields()?;
+                return Ok(Pattern { id: self.next_id(), span: self.span_from(start), kind: PatternKind::Variant { path, fields } });
+            }
+            Some(Token::Identifier(name)) => PatternKind::Identifier(name.clone()),
+            Some(Token::IntegerLiteral(value)) => PatternKind::Literal(Literal::Int(*value)),
+            Some(Token::FloatLiteral(value)) => PatternKind::Literal(Literal::Float(*value)),
+            Some(Token::StringLiteral(value)) => PatternKind::Literal(Literal::String(value.clone())),
+            Some(Token::True) => PatternKind::Literal(Literal::Bool(true)),
+            Some(Token::False) => PatternKind::Literal(Literal::Bool(false)),
+            Some(Token::BrancketStart) => {
+                self.now_index += 1;
+                let mut inner = Vec::new();
+                while !self.check_next(&Token::BrancketEnd) {
+                    inner.push(self.parse_pattern()?);
+                    if !self.check_next(&Token::Comma) {
+                        self.expect(Token::BrancketEnd, "`,` or `)`")?;
+                        break;
+                    }
+                }
+                return Ok(Pattern { id: self.next_id(), span: self.span_from(start), kind: PatternKind::Tuple(inner) });
+            }
+            _ => return self.unexpected("pattern"),
+        };
+        self.now_index += 1;
+        Ok(Pattern { id: self.next_id(), span: self.span_from(start), kind })
+    }
+
+    /// The fields of an <enum-pattern> after its path.
+    fn parse_pattern_fields(&mut self) -> ParseResult<PatternFields> {
+        if self.check_next(&Token::BrancketStart) {
+            let mut inner = Vec::new();
+            while !self.check_next(&Token::BrancketEnd) {
+                inner.push(self.parse_pattern()?);
+                if !self.check_next(&Token::Comma) {
+                    self.expect(Token::BrancketEnd, "`,` or `)`")?;
+                    break;
+                }
+            }
+            return Ok(PatternFields::Tuple(inner));
+        }
+        if !self.check_next(&Token::CurlyBracketStart) {
+            return Ok(PatternFields::Unit);
+        }
+        let mut fields = Vec::new();
+        while !self.check_next(&Token::CurlyBracketEnd) {
+            let start = self.start();
+            let name = self.expect_identifier()?;
+            let pattern = if self.check_next(&Token::Colon) {
+                self.parse_pattern()?
+            } else {
+                Pattern { id: self.next_id(), span: self.span_from(start), kind: PatternKind::Identifier(name.clone()) }
+            };
+            fields.push(FieldPattern { id: self.next_id(), span: self.span_from(start), name, pattern });
+            if !self.check_next(&Token::Comma) {
+                self.expect(Token::CurlyBracketEnd, "`,` or `}`")?;
+                break;
+            }
+        }
+        Ok(PatternFields::Struct(fields))
+    }
+
+}
@@ -0,0 +1,607 @@
+//! Prints a `Program` back to source text in canonical formatting: four
+//! space indentation, one declaration or statement per line, and only the
+//! parentheses needed to parse back to the same tree. Parsing the output gives
+//! the same `Program` up to `NodeId`s and `Span`s.
+
+use crate::parser::*;
+
+pub fn print(program: &Program) -> String {
+    let mut printer = Printer::new();
+    printer.print_program(program);
+    printer.output
+}
+
+/// Prints a type as it is written, like `(int, Point)`.
+pub fn print_type(type_literal: &TypeLiteral) -> String {
+    let mut printer = Printer::new();
+    printer.print_type_literal(type_literal);
+    printer.output
+}
+
+/// Binding power of a binary operator, the same as the parser uses.
+fn binary_power(ope: BinaryOpe) -> u8 {
+    match ope {
+        BinaryOpe::Equal | BinaryOpe::NotEqual | BinaryOpe::Lesser | BinaryOpe::Greater | BinaryOpe::LesserEqual | BinaryOpe::GreaterEqual => 1,
+        BinaryOpe::Add | BinaryOpe::Sub => 2,
+        BinaryOpe::Mul | BinaryOpe::Div => 3,
+    }
+}
+
+/// Whether the expression, printed as is, would start with `if`, `match` or `{`.
+/// At the start of a statement the parser reads those as a whole block-like
+/// statement, so such an expression has to be parenthesized there.
+fn starts_block_like(expression: &Expression) -> bool {
+    match &expression.kind {
+        ExpressionKind::If(_) | ExpressionKind::Match(_) | ExpressionKind::Block(_) => true,
+        ExpressionKind::Binary { left, .. } | ExpressionKind::Cast { value: left, .. } => starts_block_like(left),
+        ExpressionKind::Call { callee: target, .. } | ExpressionKind::Field { target, .. } | ExpressionKind::Try(target) => starts_block_like(target),
+        _ => false,
+    }
+}
+
+/// Whether the expression, printed as is, has a struct literal outside of
+/// brackets or blocks, which the parser does not read in a condition.
+fn has_bare_struct(expression: &Expression) -> bool {
+    match &expression.kind {
+        ExpressionKind::Struct { .. } => true,
+        ExpressionKind::Binary { left, right, .. } => has_bare_struct(left) || has_bare_struct(right),
+        ExpressionKind::Unary { operand: target, .. }
+        | ExpressionKind::Cast { value: target, .. }
+        | ExpressionKind::Call { callee: target, .. }
+        | ExpressionKind::Field { target, .. }
+        | ExpressionKind::Try(target) => has_bare_struct(target),
+        _ => false,
+    }
+}
+
+struct Printer {
+    output: String,
+    indent: usize,
+}
+
+impl Printer {
+    fn new() -> Self {
+        Printer {
+            output: String::new(),
+            indent: 0,
+        }
+    }
+
+    fn push(&mut self, text: &str) {
+        self.output.push_str(text);
+    }
+
+    fn new_line(&mut self) {
+        self.output.push('\n');
+        for _ in 0..self.indent {
+            self.output.push_str("    ");
+        }
+    }
+
+    fn print_separated<T>(&mut self, items: &[T], mut print_item: impl FnMut(&mut Self, &T)) {
+        for (index, item) in items.iter().enumerate() {
+            if index > 0 {
+                self.push(", ");
+            }
+            print_item(self, item);
+        }
+    }
+
+    /// Prints `(a, b)` with the trailing comma that marks a one element tuple.
+    fn print_tuple<T>(&mut self, items: &[T], print_item: impl FnMut(&mut Self, &T)) {
+        self.push("(");
+        self.print_separated(items, print_item);
+        if items.len() == 1 {
+            self.push(",");
+        }
+        self.push(")");
+    }
+
+    fn print_program(&mut self, program: &Program) {
+        for (index, declaration) in program.declarations.iter().enumerate() {
+            if index > 0 {
+                self.output.push('\n');
+                self.new_line();
+            }
+            self.print_declaration(declaration);
+        }
+        if self.indent == 0 && !program.declarations.is_empty() {
+            self.output.push('\n');
+        }
+    }
+
+    fn print_pub(&mut self, is_pub: bool) {
+        if is_pub {
+            self.push("pub ");
+        }
+    }
+
+    fn print_declaration(&mut self, declaration: &Declaration) {
+        match declaration {
+            Declaration::StaticVariable(declaration) => {
+                self.print_pub(declaration.is_pub);
+                self.push("static ");
+                self.push(&declaration.name);
+                if let Some(type_annotation) = &declaration.type_annotation {
+                    self.push(": ");
+                    self.print_type_literal(type_annotation);
+                }
+                self.push(" = ");
+                self.print_expression(&declaration.value);
+                self.push(";");
+            }
+            Declaration::Namespace(declaration) => {
+                self.print_pub(declaration.is_pub);
+                self.push("namespace ");
+                self.push(&declaration.name);
+                self.push(" {");
+                if !declaration.inner.declarations.is_empty() {
+                    self.indent += 1;
+                    self.new_line();
+                    self.print_program(&declaration.inner);
+                    self.indent -= 1;
+                    self.new_line();
+                }
+                self.push("}");
+            }
+            Declaration::Function(declaration) => self.print_function(declaration),
+            Declaration::Class(declaration) => {
+                self.print_pub(declaration.is_pub);
+                self.push("class ");
+                self.push(&declaration.name);
+                self.print_type_params(&declaration.type_params);
+                self.push(" {");
+                self.indent += 1;
+                for field in &declaration.fields {
+                    self.new_line();
+                    self.print_pub(field.is_pub);
+                    self.print_field(field);
+                    self.push(";");
+                }
+                for method in &declaration.methods {
+                    self.new_line();
+                    self.print_function(method);
+                }
+                self.indent -= 1;
+                if !declaration.fields.is_empty() || !declaration.methods.is_empty() {
+                    self.new_line();
+                }
+                self.push("}");
+            }
+            Declaration::Enum(declaration) => {
+                self.print_pub(declaration.is_pub);
+                self.push("enum ");
+                self.push(&declaration.name);
+                self.print_type_params(&declaration.type_params);
+                self.push(" {");
+                self.indent += 1;
+                for variant in &declaration.variants {
+                    self.new_line();
+                    self.push(&variant.name);
+                    match &variant.kind {
+                        VariantKind::Unit => {}
+                        VariantKind::Tuple(types) => {
+                            self.push("(");
+                            self.print_separated(types, Self::print_type_literal);
+                            self.push(")");
+                        }
+                        VariantKind::Struct(fields) if fields.is_empty() => self.push(" {}"),
+                        VariantKind::Struct(fields) => {
+                            self.push(" { ");
+                            self.print_separated(fields, Self::print_field);
+                            self.push(" }");
+                        }
+                    }
+                    self.push(",");
+                }
+                self.indent -= 1;
+                if !declaration.variants.is_empty() {
+                    self.new_line();
+                }
+                self.push("}");
+            }
+            Declaration::Trait(declaration) => {
+                self.print_pub(declaration.is_pub);
+                self.push("trait ");
+                self.push(&declaration.name);
+                self.push(" {");
+                self.indent += 1;
+                for signature in &declaration.signatures {
+                    self.new_line();
+                    self.print_function_signature(signature);
+                    self.push(";");
+                }
+                for function in &declaration.functions {
+                    self.new_line();
+                    self.print_function(function);
+                }
+                self.indent -= 1;
+                if !declaration.signatures.is_empty() || !declaration.functions.is_empty() {
+                    self.new_line();
+                }
+                self.push("}");
+            }
+            Declaration::Impl(declaration) => {
+                self.push("impl ");
+                self.print_type_literal(&declaration.trait_type);
+                self.push(" for ");
+                self.print_type_literal(&declaration.self_type);
+                self.push(" {");
+                self.indent += 1;
+                for function in &declaration.functions {
+                    self.new_line();
+                    self.print_function(function);
+                }
+                self.indent -= 1;
+                if !declaration.functions.is_empty() {
+                    self.new_line();
+                }
+                self.push("}");
+            }
+            Declaration::Import(declaration) => {
+                self.push("import ");
+                match &declaration.kind {
+                    ImportKind::Module(name) => self.push(name),
+                    ImportKind::Items(items) if items.is_empty() => self.push("{}"),
+                    ImportKind::Items(items) => {
+                        self.push("{ ");
+                        for (index, item) in items.iter().enumerate() {
+                            if index > 0 {
+                                self.push(", ");
+                            }
+                            self.push(&item.name);
+                        }
+                        self.push(" }");
+                    }
+                }
+                self.push(" from \"");
+                self.push(&declaration.path);
+                self.push("\";");
+            }
+        }
+    }
+
+    fn print_field(&mut self, field: &FieldDeclaration) {
+        self.push(&field.name);
+        self.push(": ");
+        self.print_type_literal(&field.type_annotation);
+    }
+
+    fn print_function(&mut self, declaration: &FunctionDeclaration) {
+        self.print_pub(declaration.is_pub);
+        self.print_function_signature(&declaration.signature);
+        self.push(" ");
+        self.print_block(&declaration.inner);
+    }
+
+    fn print_function_signature(&mut self, signature: &FunctionSignature) {
+        self.push("fn ");
+        self.push(&signature.name);
+        self.print_type_params(&signature.type_params);
+        self.push("(");
+        for (index, param) in signature.params.iter().enumerate() {
+            if index > 0 {
+                self.push(", ");
+            }
+            // `self` is short for `self: Self` as the first parameter
+            let is_self_type = matches!(&param.type_annotation.kind, TypeLiteralKind::CustomType { name, type_args } if name == "Self" && type_args.is_empty());
+            if index == 0 && param.is_self() && is_self_type {
+                self.push("self");
+                continue;
+            }
+            self.print_pattern(&param.pattern);
+            self.push(": ");
+            self.print_type_literal(&param.type_annotation);
+        }
+        self.push(")");
+        if let Some(return_type) = &signature.return_type {
+            self.push(": ");
+            self.print_type_literal(return_type);
+        }
+    }
+
+    fn print_type_literal(&mut self, type_literal: &TypeLiteral) {
+        match &type_literal.kind {
+            TypeLiteralKind::IntType => self.push("int"),
+            TypeLiteralKind::FloatType => self.push("float"),
+            TypeLiteralKind::CharType => self.push("char"),
+            TypeLiteralKind::BoolType => self.push("bool"),
+            TypeLiteralKind::NumericType(ty) => self.push(ty.name()),
+            TypeLiteralKind::CustomType { name, type_args } => {
+                self.push(name);
+                self.print_type_args(type_args);
+            }
+            TypeLiteralKind::TupleType { inner } => self.print_tuple(inner, Self::print_type_literal),
+            TypeLiteralKind::DynType { name } => {
+                self.push("dyn ");
+                self.push(name);
+            }
+        }
+    }
+
+    fn print_type_params(&mut self, type_params: &[TypeParam]) {
+        if !type_params.is_empty() {
+            self.push("<");
+            self.print_separated(type_params, |printer, type_param| {
+                printer.push(&type_param.name);
+                for (index, bound) in type_param.bounds.iter().enumerate() {
+                    printer.push(if index == 0 { ": " } else { " + " });
+                    printer.print_type_literal(bound);
+                }
+            });
+            self.push(">");
+        }
+    }
+
+    fn print_type_args(&mut self, type_args: &[TypeLiteral]) {
+        if !type_args.is_empty() {
+            self.push("<");
+            self.print_separated(type_args, Self::print_type_literal);
+            self.push(">");
+        }
+    }
+
+    fn print_block(&mut self, block: &Block) {
+        self.push("{");
+        self.indent += 1;
+        for (index, statement) in block.statements.iter().enumerate() {
+            self.new_line();
+            // without `;` a trailing block-like statement would be read back as the block value
+            let is_last = index + 1 == block.statements.len() && block.value.is_none();
+            self.print_statement(statement, is_last);
+        }
+        if let Some(value) = &block.value {
+            self.new_line();
+            self.print_statement_expression(value);
+        }
+        self.indent -= 1;
+        if !block.statements.is_empty() || block.value.is_some() {
+            self.new_line();
+        }
+        self.push("}");
+    }
+
+    fn print_statement(&mut self, statement: &Statement, is_last: bool) {
+        match &statement.kind {
+            StatementKind::Expression(expression) => {
+                self.print_statement_expression(expression);
+                if !expression.is_block_like() || is_last {
+                    self.push(";");
+                }
+            }
+            StatementKind::Let { is_const, pattern, type_annotation, value } => {
+                self.push(if *is_const { "const " } else { "let " });
+                self.print_pattern(pattern);
+                if let Some(type_annotation) = type_annotation {
+                    self.push(": ");
+                    self.print_type_literal(type_annotation);
+                }
+                if let Some(value) = value {
+                    self.push(" = ");
+                    self.print_expression(value);
+                }
+                self.push(";");
+            }
+            StatementKind::Assign { target, value } => {
+                self.print_statement_expression(target);
+                self.push(" = ");
+                self.print_expression(value);
+                self.push(";");
+            }
+            StatementKind::Return(value) => {
+                self.push("return");
+                if let Some(value) = value {
+                    self.push(" ");
+                    self.print_expression(value);
+                }
+                self.push(";");
+            }
+            StatementKind::For { pattern, iterable, body } => {
+                self.push("for ");
+                self.print_pattern(pattern);
+                self.push(" in ");
+                self.print_condition(iterable);
+                self.push(" ");
+                self.print_block(body);
+            }
+            StatementKind::While { condition, body } => {
+                self.push("while ");
+                self.print_condition(condition);
+                self.push(" ");
+                self.print_block(body);
+            }
+        }
+    }
+
+    /// Prints the condition of `if` or `while` or the iterable of `for`.
+    fn print_condition(&mut self, condition: &Expression) {
+        self.print_operand(condition, has_bare_struct(condition));
+    }
+
+    /// Prints an expression that starts a statement or is the value of a block.
+    fn print_statement_expression(&mut self, expression: &Expression) {
+        if !expression.is_block_like() && starts_block_like(expression) {
+            self.push("(");
+            self.print_expression(expression);
+            self.push(")");
+        } else {
+            self.print_expression(expression);
+        }
+    }
+
+    fn print_expression(&mut self, expression: &Expression) {
+        match &expression.kind {
+            ExpressionKind::Literal(literal) => self.print_literal(literal),
+            ExpressionKind::Accesser(accesser) => self.print_accesser(accesser),
+            ExpressionKind::Unary { ope: UnaryOpe::Minus, operand } => {
+                self.push("-");
+                self.print_operand(operand, matches!(operand.kind, ExpressionKind::Binary { .. } | ExpressionKind::Cast { .. }));
+            }
+            ExpressionKind::Binary { ope, left, right } => {
+                let power = binary_power(*ope);
+                let needs_parentheses = |operand: &Expression, is_right: bool| match &operand.kind {
+                    ExpressionKind::Binary { ope, .. } if is_right => binary_power(*ope) <= power,
+                    ExpressionKind::Binary { ope, .. } => binary_power(*ope) < power,
+                    _ => false,
+                };
+                self.print_operand(left, needs_parentheses(left, false));
+                self.push(" ");
+                self.push(ope.symbol());
+                self.push(" ");
+                self.print_operand(right, needs_parentheses(right, true));
+            }
+            ExpressionKind::Call { callee, args } => {
+                self.print_operand(callee, matches!(callee.kind, ExpressionKind::Unary { .. } | ExpressionKind::Binary { .. } | ExpressionKind::Cast { .. }));
+                self.push("(");
+                self.print_separated(args, Self::print_expression);
+                self.push(")");
+            }
+            ExpressionKind::Field { target, name } => {
+                // `1.x` would be read as the number `1.` followed by `x`
+                let needs_parentheses = matches!(
+                    target.kind,
+                    ExpressionKind::Unary { .. }
+                        | ExpressionKind::Binary { .. }
+                        | ExpressionKind::Cast { .. }
+                        | ExpressionKind::Literal(Literal::Int(_) | Literal::Float(_))
+                );
+                self.print_operand(target, needs_parentheses);
+                self.push(".");
+                self.push(name);
+            }
+            ExpressionKind::Try(value) => {
+                self.print_operand(value, matches!(value.kind, ExpressionKind::Unary { .. } | ExpressionKind::Binary { .. } | ExpressionKind::Cast { .. }));
+                self.push("?");
+            }
+            ExpressionKind::Cast { value, ty } => {
+                self.print_operand(value, matches!(value.kind, ExpressionKind::Binary { .. }));
+                self.push(" as ");
+                self.print_type_literal(ty);
+            }
+            ExpressionKind::Tuple(inner) => self.print_tuple(inner, Self::print_expression),
+            ExpressionKind::Struct { class, fields } => {
+                self.print_accesser(class);
+                if fields.is_empty() {
+                    self.push(" {}");
+                    return;
+                }
+                self.push(" { ");
+                self.print_separated(fields, |printer, field| {
+                    printer.push(&field.name);
+                    printer.push(": ");
+                    printer.print_expression(&field.value);
+                });
+                self.push(" }");
+            }
+            ExpressionKind::If(if_expression) => self.print_if_expression(if_expression),
+            ExpressionKind::Match(match_expression) => self.print_match_expression(match_expression),
+            ExpressionKind::Block(block) => self.print_block(block),
+        }
+    }
+
+    fn print_accesser(&mut self, accesser: &Accesser) {
+        for (index, segment) in accesser.segments.iter().enumerate() {
+            if index > 0 {
+                self.push("::");
+            }
+            self.push(&segment.name);
+            if !segment.type_args.is_empty() {
+                self.push("::");
+                self.print_type_args(&segment.type_args);
+            }
+        }
+    }
+
+    fn print_operand(&mut self, operand: &Expression, needs_parentheses: bool) {
+        if needs_parentheses {
+            self.push("(");
+        }
+        self.print_expression(operand);
+        if needs_parentheses {
+            self.push(")");
+        }
+    }
+
+    fn print_if_expression(&mut self, if_expression: &IfExpression) {
+        self.push("if ");
+        self.print_condition(&if_expression.condition);
+        self.push(" ");
+        self.print_block(&if_expression.then_branch);
+        if let Some(else_branch) = &if_expression.else_branch {
+            self.push(" else ");
+            self.print_expression(else_branch);
+        }
+    }
+
+    fn print_match_expression(&mut self, match_expression: &MatchExpression) {
+        self.push("match ");
+        self.print_condition(&match_expression.scrutinee);
+        self.push(" {");
+        self.indent += 1;
+        for arm in &match_expression.arms {
+            self.new_line();
+            self.print_pattern(&arm.pattern);
+            self.push(" => ");
+            self.print_expression(&arm.value);
+            self.push(",");
+        }
+        self.indent -= 1;
+        if !match_expression.arms.is_empty() {
+            self.new_line();
+        }
+        self.push("}");
+    }
+
+    fn print_literal(&mut self, literal: &Literal) {
+        match literal {
+            Literal::String(value) => {
+                self.push("\"");
+                self.push(value);
+                self.push("\"");
+            }
+            Literal::Int(value) => self.push(&value.to_string()),
+            Literal::Float(value) => {
+                // `Display` never uses an exponent, but drops the `.0` that makes it a float
+                let mut text = value.to_string();
+                if !text.contains('.') {
+                    text.push_str(".0");
+                }
+                self.push(&text);
+            }
+            Literal::Bool(value) => self.push(if *value { "true" } else { "false" }),
+        }
+    }
+
+    fn print_pattern(&mut self, pattern: &Pattern) {
+        match &pattern.kind {
+            PatternKind::Literal(literal) => self.print_literal(literal),
+            PatternKind::Identifier(name) => self.push(name),
+            PatternKind::Wildcard => self.push("_"),
+            PatternKind::Tuple(inner) => self.print_tuple(inner, Self::print_pattern),
+            PatternKind::Variant { path, fields } => {
+                self.print_accesser(path);
+                match fields {
+                    PatternFields::Unit => {}
+                    PatternFields::Tuple(inner) => {
+                        self.push("(");
+                        self.print_separated(inner, Self::print_pattern);
+                        self.push(")");
+                    }
+                    PatternFields::Struct(fields) if fields.is_empty() => self.push(" {}"),
+                    PatternFields::Struct(fields) => {
+                        self.push(" { ");
+                        self.print_separated(fields, |printer, field| {
+                            printer.push(&field.name);
+                            // `w` is short for `w: w`
+                            if !matches!(&field.pattern.kind, PatternKind::Identifier(name) if *name == field.name) {
+                                printer.push(": ");
+                                printer.print_pattern(&field.pattern);
+                            }
+                        });
+                        self.push(" }");
+                    }
+                }
+            }
+        }
+    }
+}
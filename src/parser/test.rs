@@ -0,0 +1,811 @@
+#[cfg(test)]
+mod tests {
+    use crate::parser::error::ParseErr;
+    use crate::parser::parser::Parser;
+    use crate::parser::*;
+    use crate::tokenizer::tokenizer::Tokenizer;
+    use crate::tokenizer::Token;
+
+    fn parse(program: &str) -> Program {
+        let mut tokenizer = Tokenizer::new(program);
+        let tokens = tokenizer.tokenize().unwrap().clone();
+        Parser::new(tokens, tokenizer.spans().clone()).parse().unwrap()
+    }
+
+    fn function_body(program: &Program) -> &Block {
+        match &program.declarations[0] {
+            Declaration::Function(function) => &function.inner,
+            _ => panic!("expected a function"),
+        }
+    }
+
+    #[test]
+    fn test_parse_empty() {
+        assert!(parse("").declarations.is_empty());
+    }
+
+    #[test]
+    fn test_parse_if_else_expression() {
+        let program = parse("fn pick(c: bool): int { let x = if c { 1 } else { 2 }; x }");
+        let body = function_body(&program);
+        let StatementKind::Let { value: Some(Expression { kind: ExpressionKind::If(if_expression), .. }), .. } = &body.statements[0].kind else {
+            panic!("expected `let` with an `if` value");
+        };
+        assert!(matches!(if_expression.condition.kind, ExpressionKind::Accesser(_)));
+        assert!(matches!(
+            if_expression.then_branch.value.as_deref().map(|value| &value.kind),
+            Some(ExpressionKind::Literal(Literal::Int(1)))
+        ));
+        let Some(ExpressionKind::Block(else_branch)) = if_expression.else_branch.as_deref().map(|branch| &branch.kind) else {
+            panic!("expected an `else` block");
+        };
+        assert!(matches!(else_branch.value.as_deref().map(|value| &value.kind), Some(ExpressionKind::Literal(Literal::Int(2)))));
+        assert!(matches!(body.value.as_deref().map(|value| &value.kind), Some(ExpressionKind::Accesser(_))));
+    }
+
+    #[test]
+    fn test_parse_else_if() {
+        let program = parse("fn sign(x: int): int { if x == 0 { 0 } else if x == 1 { 1 } else { -1 } }");
+        let Some(ExpressionKind::If(if_expression)) = function_body(&program).value.as_deref().map(|value| &value.kind) else {
+            panic!("expected an `if` as the value of the body");
+        };
+        assert!(matches!(if_expression.else_branch.as_deref().map(|branch| &branch.kind), Some(ExpressionKind::If(_))));
+    }
+
+    #[test]
+    fn test_parse_block_expression() {
+        let program = parse("fn f(): int { let y = { let a = 1; a + 2 }; if true { y = 0; } y }");
+        let body = function_body(&program);
+        let StatementKind::Let { value: Some(Expression { kind: ExpressionKind::Block(block), .. }), .. } = &body.statements[0].kind else {
+            panic!("expected `let` with a block value");
+        };
+        assert_eq!(block.statements.len(), 1);
+        assert!(matches!(block.value.as_deref().map(|value| &value.kind), Some(ExpressionKind::Binary { ope: BinaryOpe::Add, .. })));
+        // `if` without `;` is a statement when more follows
+        assert!(matches!(&body.statements[1].kind, StatementKind::Expression(expression) if expression.is_block_like()));
+        assert!(body.value.is_some());
+    }
+
+    #[test]
+    fn test_parse_precedence() {
+        let program = parse("fn f(): bool { 1 + 2 * 3 == 7 }");
+        let Some(ExpressionKind::Binary { ope: BinaryOpe::Equal, left, .. }) = function_body(&program).value.as_deref().map(|value| &value.kind) else {
+            panic!("expected `==` at the top");
+        };
+        let ExpressionKind::Binary { ope: BinaryOpe::Add, right, .. } = &left.kind else {
+            panic!("expected `+` below `==`");
+        };
+        assert!(matches!(right.kind, ExpressionKind::Binary { ope: BinaryOpe::Mul, .. }));
+    }
+
+    #[test]
+    fn test_parse_missing_semicolon() {
+        let mut tokenizer = Tokenizer::new("fn f() { let x = 1 x }");
+        let tokens = tokenizer.tokenize().unwrap().clone();
+        assert!(matches!(
+            Parser::new(tokens, tokenizer.spans().clone()).parse(),
+            Err(crate::parser::error::ParseErr::UnexpectedToken(Token::Identifier(_), "`;`", .., false))
+        ));
+        let mut tokenizer = Tokenizer::new("fn f() {\n    let x = 1\n    x\n}");
+        let tokens = tokenizer.tokenize().unwrap().clone();
+        assert_eq!(*tokenizer.line_starts(), vec![9, 23, 29]);
+        assert!(matches!(
+            Parser::new(tokens, tokenizer.spans().clone()).with_line_starts(tokenizer.line_starts().clone()).parse(),
+            Err(crate::parser::error::ParseErr::UnexpectedToken(Token::Identifier(_), "`;`", .., true))
+        ));
+        // a statement may as well be the last one, whose value is the value of the block
+        let mut tokenizer = Tokenizer::new("fn f() { g() h() }");
+        let tokens = tokenizer.tokenize().unwrap().clone();
+        assert!(matches!(
+            Parser::new(tokens, tokenizer.spans().clone()).parse(),
+            Err(crate::parser::error::ParseErr::UnexpectedToken(Token::Identifier(_), "`;` or `}`", ..))
+        ));
+    }
+
+    #[test]
+    fn test_parse_error_suggestions() {
+        let suggestion = |program: &str| {
+            let mut tokenizer = Tokenizer::new(program);
+            let tokens = tokenizer.tokenize().unwrap().clone();
+            let error = Parser::new(tokens, tokenizer.spans().clone()).parse().unwrap_err();
+            error.to_diagnostic().suggestions.into_iter().next().map(|suggestion| (suggestion.span, suggestion.replacement))
+        };
+        assert_eq!(suggestion("fn f() { let x = 1 x }"), Some((Span::new(18, 18), ";".into())));
+        assert_eq!(suggestion("fn f() { while x = 1 { } }"), Some((Span::new(17, 18), "==".into())));
+        assert_eq!(suggestion("fn f() { if x = 1 { } }"), Some((Span::new(14, 15), "==".into())));
+        assert_eq!(suggestion("fn f() { let x = 1 + }"), None);
+    }
+
+    fn let_type(program: &Program) -> &TypeLiteral {
+        let StatementKind::Let { type_annotation: Some(type_annotation), .. } = &function_body(program).statements[0].kind else {
+            panic!("expected an annotated `let`");
+        };
+        type_annotation
+    }
+
+    /// Writes a type back in source syntax so tests can compare shapes without ids and spans.
+    fn type_string(type_literal: &TypeLiteral) -> String {
+        let list = |types: &Vec<TypeLiteral>| types.iter().map(type_string).collect::<Vec<_>>().join(", ");
+        match &type_literal.kind {
+            TypeLiteralKind::IntType => "int".into(),
+            TypeLiteralKind::FloatType => "float".into(),
+            TypeLiteralKind::CharType => "char".into(),
+            TypeLiteralKind::BoolType => "bool".into(),
+            TypeLiteralKind::NumericType(numeric) => numeric.name().into(),
+            TypeLiteralKind::CustomType { name, type_args } if type_args.is_empty() => name.clone(),
+            TypeLiteralKind::CustomType { name, type_args } => format!("{}<{}>", name, list(type_args)),
+            TypeLiteralKind::TupleType { inner } if inner.len() == 1 => format!("({},)", list(inner)),
+            TypeLiteralKind::TupleType { inner } => format!("({})", list(inner)),
+            TypeLiteralKind::DynType { name } => format!("dyn {}", name),
+        }
+    }
+
+    #[test]
+    fn test_parse_cast() {
+        let program = parse("fn f(): bool { let x: u8 = -a as u8 * 2 as u8; x as u64 < 3 }");
+        let StatementKind::Let { value: Some(Expression { kind: ExpressionKind::Binary { ope: BinaryOpe::Mul, left, right }, .. }), .. } =
+            &function_body(&program).statements[0].kind
+        else {
+            panic!("expected `*` at the top");
+        };
+        // `as` binds weaker than unary `-` and stronger than `*`
+        let ExpressionKind::Cast { value, ty } = &left.kind else {
+            panic!("expected a cast on the left of `*`");
+        };
+        assert!(matches!(value.kind, ExpressionKind::Unary { .. }));
+        assert_eq!(type_string(ty), "u8");
+        assert!(matches!(right.kind, ExpressionKind::Cast { .. }));
+        assert_eq!(type_string(let_type(&program)), "u8");
+        let Some(ExpressionKind::Binary { ope: BinaryOpe::Lesser, left, .. }) = function_body(&program).value.as_deref().map(|value| &value.kind) else {
+            panic!("expected `<` after a cast");
+        };
+        assert!(matches!(&left.kind, ExpressionKind::Cast { ty, .. } if type_string(ty) == "u64"));
+    }
+
+    #[test]
+    fn test_parse_try() {
+        let program = parse("fn f(): Option<int> { let x = -g()?.y?; Some(x) }");
+        let StatementKind::Let { value: Some(value), .. } = &function_body(&program).statements[0].kind else {
+            panic!("expected a `let`");
+        };
+        // `?` binds like a call or a field, stronger than unary `-`
+        let ExpressionKind::Unary { operand, .. } = &value.kind else {
+            panic!("expected `-` at the top");
+        };
+        let ExpressionKind::Try(field) = &operand.kind else {
+            panic!("expected `?` under `-`");
+        };
+        assert!(matches!(&field.kind, ExpressionKind::Field { target, .. } if matches!(&target.kind, ExpressionKind::Try(call) if matches!(call.kind, ExpressionKind::Call { .. }))));
+    }
+
+    #[test]
+    fn test_parse_struct_literal() {
+        let program = parse("fn f(): int { let p = n::P { x: 1, y: Q {}.z, }; if p == (P { x: 1 }) { g(P { x: 2 }) } else { p.x } }");
+        let StatementKind::Let { value: Some(value), .. } = &function_body(&program).statements[0].kind else {
+            panic!("expected a `let`");
+        };
+        let ExpressionKind::Struct { class, fields } = &value.kind else {
+            panic!("expected a struct literal");
+        };
+        assert_eq!(class.path(), "n::P");
+        let names: Vec<&str> = fields.iter().map(|field| field.name.as_str()).collect();
+        assert_eq!(names, ["x", "y"]);
+        assert!(matches!(&fields[1].value.kind, ExpressionKind::Field { target, .. } if matches!(target.kind, ExpressionKind::Struct { .. })));
+        // a condition ends at the `{` after a path, and brackets allow struct literals again
+        let mut tokenizer = Tokenizer::new("fn f() { while p == P { x: 1 } { } }");
+        let tokens = tokenizer.tokenize().unwrap().clone();
+        assert!(matches!(Parser::new(tokens, tokenizer.spans().clone()).parse(), Err(ParseErr::UnexpectedToken(Token::Colon, ..))));
+    }
+
+    #[test]
+    fn test_parse_enums_and_match() {
+        let program = parse("fn f(): int { match s { Shape::Circle(r) => 1, Shape::Rect { w, h: 0 } => { 2 } Shape::Empty => 3, x => x } } enum Shape<T> { Circle(float), Rect { w: float, h: T, }, Empty, }");
+        let Declaration::Enum(declaration) = &program.declarations[1] else {
+            panic!("expected an enum");
+        };
+        assert_eq!(declaration.type_params.len(), 1);
+        let kinds: Vec<&VariantKind> = declaration.variants.iter().map(|variant| &variant.kind).collect();
+        assert!(matches!(kinds[..], [VariantKind::Tuple(types), VariantKind::Struct(fields), VariantKind::Unit] if types.len() == 1 && fields.len() == 2 && fields.iter().all(|field| field.is_pub)));
+        let Some(value) = &function_body(&program).value else {
+            panic!("expected a block value");
+        };
+        let ExpressionKind::Match(match_expression) = &value.kind else {
+            panic!("expected a match");
+        };
+        let patterns: Vec<&PatternKind> = match_expression.arms.iter().map(|arm| &arm.pattern.kind).collect();
+        assert!(matches!(patterns[0], PatternKind::Variant { path, fields: PatternFields::Tuple(inner) } if path.path() == "Shape::Circle" && inner.len() == 1));
+        let PatternKind::Variant { fields: PatternFields::Struct(fields), .. } = patterns[1] else {
+            panic!("expected a struct variant pattern");
+        };
+        // `w` is short for `w: w`
+        assert!(matches!(&fields[0].pattern.kind, PatternKind::Identifier(name) if name == "w"));
+        assert!(matches!(fields[1].pattern.kind, PatternKind::Literal(Literal::Int(0))));
+        assert!(matches!(patterns[2], PatternKind::Variant { fields: PatternFields::Unit, .. }));
+        assert!(matches!(patterns[3], PatternKind::Identifier(_)));
+        // the value matched on ends at the `{`, and an arm needs `,` unless it is block-like
+        for source in ["fn f() { match P { x: 1 } { _ => 1 } }", "fn f() { match x { a => 1 b => 2 } }", "fn f() { match x { a 1 } }"] {
+            let mut tokenizer = Tokenizer::new(source);
+            let tokens = tokenizer.tokenize().unwrap().clone();
+            assert!(Parser::new(tokens, tokenizer.spans().clone()).parse().is_err(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_parse_self_parameter() {
+        let program = parse("class P { fn f(self, self: int) { } fn g(a: int, b: Self) { } }");
+        let Declaration::Class(class) = &program.declarations[0] else { unreachable!() };
+        let params = &class.methods[0].signature.params;
+        assert!(class.methods[0].signature.has_self() && !class.methods[1].signature.has_self());
+        assert_eq!((type_string(&params[0].type_annotation), type_string(&params[1].type_annotation)), ("Self".into(), "int".into()));
+        let mut tokenizer = Tokenizer::new("fn f(a: int, self) { }");
+        let tokens = tokenizer.tokenize().unwrap().clone();
+        assert!(Parser::new(tokens, tokenizer.spans().clone()).parse().is_err());
+    }
+
+    #[test]
+    fn test_parse_impl() {
+        let program = parse("impl Shape for Point { fn area(self): int { 1 } fn zero(): int { 0 } } impl Show for (int, bool) { } fn implement() { }");
+        let Declaration::Impl(declaration) = &program.declarations[0] else { panic!("expected an impl") };
+        assert_eq!((type_string(&declaration.trait_type), type_string(&declaration.self_type)), ("Shape".into(), "Point".into()));
+        let names: Vec<&str> = declaration.functions.iter().map(|function| function.name.as_str()).collect();
+        assert_eq!(names, ["area", "zero"]);
+        assert!(declaration.functions[0].signature.has_self() && !declaration.functions[0].is_pub);
+        let Declaration::Impl(declaration) = &program.declarations[1] else { panic!("expected an impl") };
+        assert!(declaration.functions.is_empty() && type_string(&declaration.self_type) == "(int, bool)");
+        // impls and their functions have no visibility of their own
+        for source in ["pub impl Shape for Point { }", "impl Shape for Point { pub fn area(self): int { 1 } }"] {
+            let mut tokenizer = Tokenizer::new(source);
+            let tokens = tokenizer.tokenize().unwrap().clone();
+            assert!(Parser::new(tokens, tokenizer.spans().clone()).parse().is_err());
+        }
+    }
+
+    #[test]
+    fn test_parse_nested_generic_types() {
+        let program = parse("fn f() { let x: Map<int, List<List<(int, float)>>> = y; }");
+        assert_eq!(type_string(let_type(&program)), "Map<int, List<List<(int, float)>>>");
+    }
+
+    #[test]
+    fn test_parse_generics() {
+        let program = parse("fn max<T: Ord + Show, U>(a: T, b: T): T { a } class Box<T> { value: T; pub fn get(self): T { self.value } } fn f() { let b: Box<int>= max::<int>(1, 2); }");
+        let Declaration::Function(function) = &program.declarations[0] else { panic!("expected a function") };
+        let params: Vec<(&str, Vec<String>)> = function.signature.type_params.iter().map(|param| (param.name.as_str(), param.bounds.iter().map(type_string).collect())).collect();
+        assert_eq!(params, [("T", vec!["Ord".to_string(), "Show".to_string()]), ("U", vec![])]);
+        let Declaration::Class(class) = &program.declarations[1] else { panic!("expected a class") };
+        assert_eq!(class.type_params.len(), 1);
+        assert!(class.methods[0].signature.type_params.is_empty());
+        for source in ["fn f<>() { }", "fn f<T: >() { }", "fn f<T U>() { }", "class C<T { }"] {
+            let mut tokenizer = Tokenizer::new(source);
+            let tokens = tokenizer.tokenize().unwrap().clone();
+            let result = Parser::new(tokens, tokenizer.spans().clone()).parse();
+            assert_eq!(result.is_err(), source != "fn f<>() { }", "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_parse_dyn_types() {
+        let program = parse("fn f() { let x: (dyn Shape, List<dyn Area>) = y; }");
+        assert_eq!(type_string(let_type(&program)), "(dyn Shape, List<dyn Area>)");
+        for source in ["fn f(x: dyn) { }", "fn f(x: dyn int) { }"] {
+            let mut tokenizer = Tokenizer::new(source);
+            let tokens = tokenizer.tokenize().unwrap().clone();
+            assert!(Parser::new(tokens, tokenizer.spans().clone()).parse().is_err());
+        }
+    }
+
+    #[test]
+    fn test_parse_generic_type_before_equal() {
+        let program = parse("fn f() { let x: List<int>= y; }");
+        assert_eq!(type_string(let_type(&program)), "List<int>");
+    }
+
+    #[test]
+    fn test_parse_tuple_types() {
+        let program = parse("fn f(): () { let x: ((), (int,)) = y; }");
+        assert_eq!(type_string(let_type(&program)), "((), (int,))");
+    }
+
+    #[test]
+    fn test_parse_comparison_and_turbofish() {
+        let program = parse("fn f(): bool { a < b == List::<int>::new() > c }");
+        let Some(ExpressionKind::Binary { ope: BinaryOpe::Greater, left, .. }) = function_body(&program).value.as_deref().map(|value| &value.kind) else {
+            panic!("expected `>` at the top");
+        };
+        let ExpressionKind::Binary { ope: BinaryOpe::Equal, left: lesser, right: call } = &left.kind else {
+            panic!("expected `==` below `>`");
+        };
+        assert!(matches!(lesser.kind, ExpressionKind::Binary { ope: BinaryOpe::Lesser, .. }));
+        let ExpressionKind::Call { callee, .. } = &call.kind else {
+            panic!("expected a call");
+        };
+        let ExpressionKind::Accesser(accesser) = &callee.kind else {
+            panic!("expected a path");
+        };
+        assert_eq!(accesser.path(), "List::new");
+        assert_eq!(accesser.segments[0].type_args.iter().map(type_string).collect::<Vec<_>>(), vec!["int"]);
+    }
+
+    #[test]
+    fn test_parse_spans() {
+        let source = "fn f(a: int): int { let b = (a + 1) * 2; b }";
+        let program = parse(source);
+        let text = |span: Span| &source[span.start..span.end];
+        let Declaration::Function(function) = &program.declarations[0] else {
+            panic!("expected a function");
+        };
+        assert_eq!(text(function.span), source);
+        assert_eq!(text(function.signature.span), "fn f(a: int): int");
+        assert_eq!(text(function.signature.params[0].span), "a: int");
+        let statement = &function.inner.statements[0];
+        assert_eq!(text(statement.span), "let b = (a + 1) * 2;");
+        let StatementKind::Let { value: Some(value), .. } = &statement.kind else {
+            panic!("expected `let` with a value");
+        };
+        assert_eq!(text(value.span), "(a + 1) * 2");
+        let ExpressionKind::Binary { left, .. } = &value.kind else {
+            panic!("expected `*`");
+        };
+        assert_eq!(text(left.span), "(a + 1)");
+        assert_eq!(text(function.signature.return_type.as_ref().unwrap().span), "int");
+    }
+
+    #[test]
+    fn test_parse_node_ids_are_unique_and_stable() {
+        let source = "fn f(): int { let x = if true { 1 } else { 2 }; x + 3 }";
+        let program = parse(source);
+        assert_eq!(program, parse(source));
+        let Declaration::Function(function) = &program.declarations[0] else {
+            panic!("expected a function");
+        };
+        let mut ids = vec![program.id, function.id, function.signature.id, function.inner.id];
+        ids.extend(function.inner.statements.iter().map(|statement| statement.id));
+        ids.extend(function.inner.value.iter().map(|value| value.id));
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 6);
+    }
+
+    struct PathCollector(Vec<String>);
+
+    impl visit::Visitor for PathCollector {
+        fn visit_accesser(&mut self, accesser: &Accesser) {
+            self.0.push(accesser.path());
+            visit::walk_accesser(self, accesser);
+        }
+    }
+
+    #[test]
+    fn test_visitor_reaches_nested_nodes() {
+        let program = parse("namespace n { fn f(): int { let x = { g(a) }; if b { c } else { d::e } } }");
+        let mut collector = PathCollector(Vec::new());
+        visit::Visitor::visit_program(&mut collector, &program);
+        assert_eq!(collector.0, vec!["g", "a", "b", "c", "d::e"]);
+    }
+
+    struct ClearSpans;
+
+    impl mut_visit::MutVisitor for ClearSpans {
+        fn visit_span(&mut self, span: &mut Span) {
+            *span = Span::default();
+        }
+    }
+
+    #[test]
+    fn test_mut_visitor_visits_every_span() {
+        let mut left = parse("fn f(a: (int, int)): int { let (x, _) = a; x * 2 }");
+        let mut right = parse("fn f( a : ( int , int ) ) : int {\n    let ( x , _ ) = a ;\n    x*2\n}");
+        assert_ne!(left, right);
+        mut_visit::MutVisitor::visit_program(&mut ClearSpans, &mut left);
+        mut_visit::MutVisitor::visit_program(&mut ClearSpans, &mut right);
+        assert_eq!(left, right);
+    }
+
+    /// Folds `+` of two integer literals, the kind of rewrite `Fold` is for.
+    struct AddLiterals;
+
+    impl fold::Fold for AddLiterals {
+        fn fold_expression(&mut self, expression: Expression) -> Expression {
+            let expression = fold::walk_expression(self, expression);
+            match &expression.kind {
+                ExpressionKind::Binary { ope: BinaryOpe::Add, left, right } => match (&left.kind, &right.kind) {
+                    (ExpressionKind::Literal(Literal::Int(left)), ExpressionKind::Literal(Literal::Int(right))) => {
+                        Expression { kind: ExpressionKind::Literal(Literal::Int(left + right)), ..expression }
+                    }
+                    _ => expression,
+                },
+                _ => expression,
+            }
+        }
+    }
+
+    #[test]
+    fn test_fold_rewrites_bottom_up() {
+        let program = fold::Fold::fold_program(&mut AddLiterals, parse("fn f(): int { 1 + 2 + 3 }"));
+        let Some(value) = function_body(&program).value.as_deref() else {
+            panic!("expected a value");
+        };
+        assert_eq!(value.kind, ExpressionKind::Literal(Literal::Int(6)));
+    }
+
+    /// Resets every id and span so that trees parsed from differently
+    /// formatted sources compare equal.
+    struct Normalize;
+
+    impl mut_visit::MutVisitor for Normalize {
+        fn visit_id(&mut self, id: &mut NodeId) {
+            *id = NodeId(0);
+        }
+
+        fn visit_span(&mut self, span: &mut Span) {
+            *span = Span::default();
+        }
+    }
+
+    fn normalized(mut program: Program) -> Program {
+        mut_visit::MutVisitor::visit_program(&mut Normalize, &mut program);
+        program
+    }
+
+    fn assert_round_trip(source: &str) {
+        let program = normalized(parse(source));
+        let printed = printer::print(&program);
+        assert_eq!(normalized(parse(&printed)), program, "printed as:\n{}", printed);
+        // printing is canonical, so a second round changes nothing
+        assert_eq!(printer::print(&parse(&printed)), printed);
+    }
+
+    /// The programs of the tokenizer tests, rewritten where they are not valid
+    /// declarations, and the programs of the other parser tests.
+    const CORPUS: &[&str] = &[
+        "",
+        "static PI: float = 3.14; static x = 10;",
+        r#"
+fn add(a: int, b: int): int {
+    if a > 0 {
+        return a + b;
+    } else {
+        return 0;
+    }
+}
+import { util } from "module";
+fn count(list: List<int>) {
+    for i in list {
+        let count = 0;
+        count = count + 1;
+    }
+}
+"#,
+        "import util from \"util\"; import { a, b, } from \"lib/shapes\"; import {} from \"empty\";",
+        "fn f() { let x = 1; let s = \"hello world\"; let n = 2232; let y = 43.43; if (true) { } }",
+        "fn pick(c: bool): int { let x = if c { 1 } else { 2 }; x }",
+        "fn sign(x: int): int { if x == 0 { 0 } else if x == 1 { 1 } else { -1 } }",
+        "fn f(): int { let y = { let a = 1; a + 2 }; if true { y = 0; } y }",
+        "fn f(): bool { 1 + 2 * 3 == 7 }",
+        "fn f() { let x: Map<int, List<List<(int, float)>>> = y; let z: List<int>= y; }",
+        "fn f(): () { let x: ((), (int,)) = y; }",
+        "fn f(): bool { a < b == List::<int>::new() > c }",
+        "fn f(a: (int, int)): int { let (x, _) = a; (x - 1) * -(2 - x) / (a.b).c(x, (x,)) }",
+        "pub namespace math { pub fn sq(x: int): int { x * x } namespace inner { static ONE = 1; } }",
+        "pub class Point { pub x: int; y: int; pub fn norm(p: Point): int { p.x * p.x } } trait Shape { fn area(s: Self): float; fn zero(): float { 0.0 } }",
+        "fn f() { while a != b { a = a - 1; } { 1 } if c { } ({ 1 } + 2); (if a { f } else { g })(1); return; }",
+        "class P { x: int; pub fn new(): Self { Self { x: 1 } } fn get(self, y: P): int { self.x } } fn f(p: P) { while p.x == (P { x: 1 }).x { p.x = P { x: p.get(P {}) }.x; } }",
+        "trait Shape { fn area(self): int; fn twice(self): int { self.area() * 2 } } impl Shape for P { } impl Shape for int { fn area(self): int { Shape::twice(self) } }",
+        "fn f(a: u8, b: f64): i64 { let c: usize = (a + 1) as usize; -(b as i16) as i64 * (c as i64) + (a as char as u32) as i64 }",
+        "pub enum Shape<T> { Circle(float), Rect { w: float, h: T }, Empty } enum Never {} fn area(s: Shape<int>): float { match s { Shape::Circle(r) => r * r, Shape::Rect { w, h: _ } => { w } Shape::Empty => 0.0 } }",
+        "fn f(r: Result<int, bool>): Result<int, bool> { let x = (-r?)?; Ok(g()?.x? + (x as u8)?) }",
+        "fn f(s: Shape) { match (s, 1) { (Shape::Circle(_), 1) => g(), x => { } } match (P { x: 1 }).x { 1 => P { x: 2 }, _ => Shape::Rect::<int> { w: 1.0, h: 2 } }; }",
+    ];
+
+    #[test]
+    fn test_print_round_trip_corpus() {
+        for source in CORPUS {
+            assert_round_trip(source);
+        }
+    }
+
+    #[test]
+    fn test_print_canonical_format() {
+        let printed = printer::print(&parse("pub fn f(a:int):int{let x=if a>0{a}else{0-a};{x}}"));
+        assert_eq!(printed, "pub fn f(a: int): int {\n    let x = if a > 0 {\n        a\n    } else {\n        0 - a\n    };\n    {\n        x\n    }\n}\n");
+    }
+
+    /// Small deterministic generator of random ASTs, shaped like what the parser produces.
+    struct Generator {
+        state: u64,
+    }
+
+    impl Generator {
+        fn next(&mut self, bound: u64) -> u64 {
+            // xorshift64
+            self.state ^= self.state << 13;
+            self.state ^= self.state >> 7;
+            self.state ^= self.state << 17;
+            self.state % bound
+        }
+
+        fn chance(&mut self) -> bool {
+            self.next(2) == 0
+        }
+
+        fn name(&mut self) -> Identifier {
+            ["a", "b", "count", "value", "x1", "List", "util"][self.next(7) as usize].into()
+        }
+
+        fn list<T>(&mut self, max: u64, mut item: impl FnMut(&mut Self) -> T) -> Vec<T> {
+            (0..self.next(max + 1)).map(|_| item(self)).collect()
+        }
+
+        fn program(&mut self, depth: u32) -> Program {
+            let declarations = self.list(3, |generator| generator.declaration(depth));
+            Program { id: NodeId(0), span: Span::default(), declarations }
+        }
+
+        fn declaration(&mut self, depth: u32) -> Declaration {
+            let (id, span) = (NodeId(0), Span::default());
+            let is_pub = self.chance();
+            match self.next(if depth == 0 { 6 } else { 7 }) {
+                0 => Declaration::StaticVariable(StaticVariableDeclaration {
+                    id, span, is_pub,
+                    name: self.name(),
+                    type_annotation: if self.chance() { Some(self.type_literal(2)) } else { None },
+                    value: self.expression(2),
+                }),
+                1 => Declaration::Class(ClassDeclaration {
+                    id, span, is_pub,
+                    name: self.name(),
+                    type_params: self.list(2, Self::type_param),
+                    fields: self.list(2, |generator| FieldDeclaration {
+                        id, span,
+                        is_pub: generator.chance(),
+                        name: generator.name(),
+                        type_annotation: generator.type_literal(2),
+                    }),
+                    methods: self.list(2, |generator| {
+                        let is_pub = generator.chance();
+                        generator.function(is_pub)
+                    }),
+                }),
+                2 => Declaration::Trait(TraitDeclaration {
+                    id, span, is_pub,
+                    name: self.name(),
+                    signatures: self.list(2, Self::signature),
+                    functions: self.list(2, |generator| generator.function(false)),
+                }),
+                3 => {
+                    let kind = if self.chance() {
+                        ImportKind::Module(self.name())
+                    } else {
+                        ImportKind::Items(self.list(3, |generator| ImportItem { id, span, name: generator.name() }))
+                    };
+                    Declaration::Import(ImportDeclaration { id, span, kind, path: self.name() })
+                }
+                4 => Declaration::Function(self.function(is_pub)),
+                5 => Declaration::Enum(EnumDeclaration {
+                    id, span, is_pub,
+                    name: self.name(),
+                    type_params: self.list(2, Self::type_param),
+                    variants: self.list(3, |generator| {
+                        let kind = match generator.next(3) {
+                            0 => VariantKind::Unit,
+                            1 => VariantKind::Tuple(generator.list(2, |generator| generator.type_literal(2))),
+                            _ => VariantKind::Struct(generator.list(2, |generator| FieldDeclaration {
+                                id, span,
+                                is_pub: true,
+                                name: generator.name(),
+                                type_annotation: generator.type_literal(2),
+                            })),
+                        };
+                        Variant { id, span, name: generator.name(), kind }
+                    }),
+                }),
+                _ => Declaration::Namespace(NamespaceDeclaration { id, span, is_pub, name: self.name(), inner: self.program(depth - 1) }),
+            }
+        }
+
+        fn function(&mut self, is_pub: bool) -> FunctionDeclaration {
+            let signature = self.signature();
+            FunctionDeclaration { id: NodeId(0), span: Span::default(), is_pub, name: signature.name.clone(), signature, inner: self.block(3) }
+        }
+
+        fn signature(&mut self) -> FunctionSignature {
+            FunctionSignature {
+                id: NodeId(0),
+                span: Span::default(),
+                name: self.name(),
+                type_params: self.list(2, Self::type_param),
+                params: self.list(2, |generator| Parameter {
+                    id: NodeId(0),
+                    span: Span::default(),
+                    pattern: generator.pattern(2),
+                    type_annotation: generator.type_literal(2),
+                }),
+                return_type: if self.chance() { Some(self.type_literal(2)) } else { None },
+            }
+        }
+
+        fn type_param(&mut self) -> TypeParam {
+            TypeParam { id: NodeId(0), span: Span::default(), name: self.name(), bounds: self.list(2, |generator| generator.type_literal(1)) }
+        }
+
+        fn type_literal(&mut self, depth: u32) -> TypeLiteral {
+            let kind = match self.next(if depth == 0 { 6 } else { 8 }) {
+                5 => self.primitive_type(),
+                0 => TypeLiteralKind::IntType,
+                1 => TypeLiteralKind::FloatType,
+                2 => TypeLiteralKind::CharType,
+                3 => TypeLiteralKind::BoolType,
+                4 if self.chance() => TypeLiteralKind::DynType { name: self.name() },
+                4 => TypeLiteralKind::CustomType { name: self.name(), type_args: Vec::new() },
+                6 => TypeLiteralKind::CustomType { name: self.name(), type_args: self.list(2, |generator| generator.type_literal(depth - 1)) },
+                _ => TypeLiteralKind::TupleType { inner: self.list(3, |generator| generator.type_literal(depth - 1)) },
+            };
+            TypeLiteral { id: NodeId(0), span: Span::default(), kind }
+        }
+
+        /// A type `as` can convert to.
+        fn primitive_type(&mut self) -> TypeLiteralKind {
+            match self.next(NumericType::ALL.len() as u64 + 2) as usize {
+                0 => TypeLiteralKind::CharType,
+                1 => TypeLiteralKind::BoolType,
+                index => TypeLiteralKind::NumericType(NumericType::ALL[index - 2]),
+            }
+        }
+
+        fn literal(&mut self) -> Literal {
+            match self.next(4) {
+                0 => Literal::String(self.name()),
+                1 => Literal::Int(self.next(1000)),
+                2 => Literal::Float(self.next(1000) as f64 / 8.0),
+                _ => Literal::Bool(self.chance()),
+            }
+        }
+
+        fn pattern(&mut self, depth: u32) -> Pattern {
+            let kind = match self.next(if depth == 0 { 3 } else { 4 }) {
+                0 => PatternKind::Literal(self.literal()),
+                1 => PatternKind::Identifier(self.name()),
+                2 => PatternKind::Wildcard,
+                3 if self.chance() => PatternKind::Tuple(self.list(3, |generator| generator.pattern(depth - 1))),
+                _ => {
+                    let Expression { kind: ExpressionKind::Accesser(path), .. } = self.accesser() else { unreachable!() };
+                    let fields = match self.next(3) {
+                        // a lone identifier is read back as a binding
+                        0 if path.as_identifier().is_none() => PatternFields::Unit,
+                        0 | 1 => PatternFields::Tuple(self.list(2, |generator| generator.pattern(depth - 1))),
+                        _ => PatternFields::Struct(self.list(2, |generator| FieldPattern {
+                            id: NodeId(0),
+                            span: Span::default(),
+                            name: generator.name(),
+                            pattern: generator.pattern(depth - 1),
+                        })),
+                    };
+                    PatternKind::Variant { path, fields }
+                }
+            };
+            Pattern { id: NodeId(0), span: Span::default(), kind }
+        }
+
+        fn block(&mut self, depth: u32) -> Block {
+            Block {
+                id: NodeId(0),
+                span: Span::default(),
+                statements: self.list(3, |generator| generator.statement(depth)),
+                value: if self.chance() { Some(Box::new(self.expression(depth))) } else { None },
+            }
+        }
+
+        fn statement(&mut self, depth: u32) -> Statement {
+            let inner = depth.saturating_sub(1);
+            let kind = match self.next(if depth == 0 { 4 } else { 6 }) {
+                0 => StatementKind::Expression(self.expression(inner)),
+                1 => StatementKind::Let {
+                    is_const: self.chance(),
+                    pattern: self.pattern(2),
+                    type_annotation: if self.chance() { Some(self.type_literal(2)) } else { None },
+                    value: if self.chance() { Some(self.expression(inner)) } else { None },
+                },
+                2 => {
+                    let target = if self.chance() {
+                        self.accesser()
+                    } else {
+                        Self::expression_of(ExpressionKind::Field { target: Box::new(self.expression(inner)), name: self.name() })
+                    };
+                    StatementKind::Assign { target, value: self.expression(inner) }
+                }
+                3 => StatementKind::Return(if self.chance() { Some(self.expression(inner)) } else { None }),
+                4 => StatementKind::For { pattern: self.pattern(2), iterable: self.expression(inner), body: self.block(inner) },
+                _ => StatementKind::While { condition: self.expression(inner), body: self.block(inner) },
+            };
+            Statement { id: NodeId(0), span: Span::default(), kind }
+        }
+
+        fn expression_of(kind: ExpressionKind) -> Expression {
+            Expression { id: NodeId(0), span: Span::default(), kind }
+        }
+
+        fn accesser(&mut self) -> Expression {
+            let segments = (0..self.next(3) + 1).map(|_| PathSegment {
+                name: self.name(),
+                type_args: if self.next(4) == 0 { self.list(2, |generator| generator.type_literal(1)) } else { Vec::new() },
+            }).collect();
+            Self::expression_of(ExpressionKind::Accesser(Accesser { segments }))
+        }
+
+        fn expression(&mut self, depth: u32) -> Expression {
+            if depth == 0 {
+                return if self.chance() {
+                    let literal = self.literal();
+                    Self::expression_of(ExpressionKind::Literal(literal))
+                } else {
+                    self.accesser()
+                };
+            }
+            let inner = depth - 1;
+            let kind = match self.next(11) {
+                0 => return self.expression(0),
+                1 => ExpressionKind::Unary { ope: UnaryOpe::Minus, operand: Box::new(self.expression(inner)) },
+                2 | 3 => {
+                    let opes = [
+                        BinaryOpe::Add, BinaryOpe::Sub, BinaryOpe::Mul, BinaryOpe::Div, BinaryOpe::Equal,
+                        BinaryOpe::NotEqual, BinaryOpe::Lesser, BinaryOpe::Greater, BinaryOpe::LesserEqual, BinaryOpe::GreaterEqual,
+                    ];
+                    let ope = opes[self.next(opes.len() as u64) as usize];
+                    ExpressionKind::Binary { ope, left: Box::new(self.expression(inner)), right: Box::new(self.expression(inner)) }
+                }
+                4 => ExpressionKind::Call {
+                    callee: Box::new(self.expression(inner)),
+                    args: self.list(3, |generator| generator.expression(inner)),
+                },
+                5 => ExpressionKind::Field { target: Box::new(self.expression(inner)), name: self.name() },
+                6 => ExpressionKind::Tuple(self.list(3, |generator| generator.expression(inner))),
+                7 => ExpressionKind::Cast {
+                    value: Box::new(self.expression(inner)),
+                    ty: TypeLiteral { id: NodeId(0), span: Span::default(), kind: self.primitive_type() },
+                },
+                8 => {
+                    let Expression { kind: ExpressionKind::Accesser(class), .. } = self.accesser() else { unreachable!() };
+                    let fields = self.list(3, |generator| FieldInit { id: NodeId(0), span: Span::default(), name: generator.name(), value: generator.expression(inner) });
+                    ExpressionKind::Struct { class, fields }
+                }
+                9 => ExpressionKind::Try(Box::new(self.expression(inner))),
+                _ => match self.next(3) {
+                    0 => ExpressionKind::Block(self.block(inner)),
+                    1 => ExpressionKind::If(self.if_expression(inner)),
+                    _ => ExpressionKind::Match(MatchExpression {
+                        scrutinee: Box::new(self.expression(inner)),
+                        arms: self.list(3, |generator| MatchArm {
+                            id: NodeId(0),
+                            span: Span::default(),
+                            pattern: generator.pattern(2),
+                            value: generator.expression(inner),
+                        }),
+                    }),
+                },
+            };
+            Self::expression_of(kind)
+        }
+
+        fn if_expression(&mut self, depth: u32) -> IfExpression {
+            let condition = Box::new(self.expression(depth));
+            let then_branch = self.block(depth);
+            let else_branch = match self.next(3) {
+                0 => None,
+                1 => Some(ExpressionKind::Block(self.block(depth))),
+                _ => Some(ExpressionKind::If(self.if_expression(depth.saturating_sub(1)))),
+            };
+            let else_branch = else_branch.map(|kind| Box::new(Self::expression_of(kind)));
+            IfExpression { condition, then_branch, else_branch }
+        }
+    }
+
+    #[test]
+    fn test_print_round_trip_generated() {
+        for seed in 1..=300u64 {
+            let program = Generator { state: seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) }.program(2);
+            let printed = printer::print(&program);
+            assert_eq!(normalized(parse(&printed)), program, "printed as:\n{}", printed);
+            assert_round_trip(&printed);
+        }
+    }
+}
@@ -0,0 +1,365 @@
+//! Read-only traversal of the AST.
+//!
+//! Every `visit_*` method of `Visitor` defaults to the matching `walk_*`
+//! function, which visits the children of the node. An implementation
+//! overrides only the nodes it is interested in and calls `walk_*` itself when
+//! it still wants to descend into them.
+
+use crate::parser::*;
+
+pub trait Visitor: Sized {
+    fn visit_program(&mut self, program: &Program) {
+        walk_program(self, program)
+    }
+
+    fn visit_declaration(&mut self, declaration: &Declaration) {
+        walk_declaration(self, declaration)
+    }
+
+    fn visit_static_variable(&mut self, declaration: &StaticVariableDeclaration) {
+        walk_static_variable(self, declaration)
+    }
+
+    fn visit_namespace(&mut self, declaration: &NamespaceDeclaration) {
+        walk_namespace(self, declaration)
+    }
+
+    fn visit_function(&mut self, declaration: &FunctionDeclaration) {
+        walk_function(self, declaration)
+    }
+
+    fn visit_class(&mut self, declaration: &ClassDeclaration) {
+        walk_class(self, declaration)
+    }
+
+    fn visit_field(&mut self, declaration: &FieldDeclaration) {
+        walk_field(self, declaration)
+    }
+
+    fn visit_enum(&mut self, declaration: &EnumDeclaration) {
+        walk_enum(self, declaration)
+    }
+
+    fn visit_variant(&mut self, variant: &Variant) {
+        walk_variant(self, variant)
+    }
+
+    fn visit_trait(&mut self, declaration: &TraitDeclaration) {
+        walk_trait(self, declaration)
+    }
+
+    fn visit_impl(&mut self, declaration: &ImplDeclaration) {
+        walk_impl(self, declaration)
+    }
+
+    fn visit_import(&mut self, _declaration: &ImportDeclaration) {}
+
+    fn visit_function_signature(&mut self, signature: &FunctionSignature) {
+        walk_function_signature(self, signature)
+    }
+
+    fn visit_type_param(&mut self, type_param: &TypeParam) {
+        walk_type_param(self, type_param)
+    }
+
+    fn visit_parameter(&mut self, parameter: &Parameter) {
+        walk_parameter(self, parameter)
+    }
+
+    fn visit_type_literal(&mut self, type_literal: &TypeLiteral) {
+        walk_type_literal(self, type_literal)
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        walk_block(self, block)
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement)
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        walk_expression(self, expression)
+    }
+
+    fn visit_if_expression(&mut self, if_expression: &IfExpression) {
+        walk_if_expression(self, if_expression)
+    }
+
+    fn visit_match_expression(&mut self, match_expression: &MatchExpression) {
+        walk_match_expression(self, match_expression)
+    }
+
+    fn visit_match_arm(&mut self, arm: &MatchArm) {
+        walk_match_arm(self, arm)
+    }
+
+    fn visit_accesser(&mut self, accesser: &Accesser) {
+        walk_accesser(self, accesser)
+    }
+
+    fn visit_pattern(&mut self, pattern: &Pattern) {
+        walk_pattern(self, pattern)
+    }
+}
+
+pub fn walk_program<V: Visitor>(visitor: &mut V, program: &Program) {
+    for declaration in &program.declarations {
+        visitor.visit_declaration(declaration);
+    }
+}
+
+pub fn walk_declaration<V: Visitor>(visitor: &mut V, declaration: &Declaration) {
+    match declaration {
+        Declaration::StaticVariable(declaration) => visitor.visit_static_variable(declaration),
+        Declaration::Namespace(declaration) => visitor.visit_namespace(declaration),
+        Declaration::Function(declaration) => visitor.visit_function(declaration),
+        Declaration::Class(declaration) => visitor.visit_class(declaration),
+        Declaration::Enum(declaration) => visitor.visit_enum(declaration),
+        Declaration::Trait(declaration) => visitor.visit_trait(declaration),
+        Declaration::Impl(declaration) => visitor.visit_impl(declaration),
+        Declaration::Import(declaration) => visitor.visit_import(declaration),
+    }
+}
+
+pub fn walk_static_variable<V: Visitor>(visitor: &mut V, declaration: &StaticVariableDeclaration) {
+    if let Some(type_annotation) = &declaration.type_annotation {
+        visitor.visit_type_literal(type_annotation);
+    }
+    visitor.visit_expression(&declaration.value);
+}
+
+pub fn walk_namespace<V: Visitor>(visitor: &mut V, declaration: &NamespaceDeclaration) {
+    visitor.visit_program(&declaration.inner);
+}
+
+pub fn walk_function<V: Visitor>(visitor: &mut V, declaration: &FunctionDeclaration) {
+    visitor.visit_function_signature(&declaration.signature);
+    visitor.visit_block(&declaration.inner);
+}
+
+pub fn walk_class<V: Visitor>(visitor: &mut V, declaration: &ClassDeclaration) {
+    for type_param in &declaration.type_params {
+        visitor.visit_type_param(type_param);
+    }
+    for field in &declaration.fields {
+        visitor.visit_field(field);
+    }
+    for method in &declaration.methods {
+        visitor.visit_function(method);
+    }
+}
+
+pub fn walk_field<V: Visitor>(visitor: &mut V, declaration: &FieldDeclaration) {
+    visitor.visit_type_literal(&declaration.type_annotation);
+}
+
+pub fn walk_enum<V: Visitor>(visitor: &mut V, declaration: &EnumDeclaration) {
+    for type_param in &declaration.type_params {
+        visitor.visit_type_param(type_param);
+    }
+    for variant in &declaration.variants {
+        visitor.visit_variant(variant);
+    }
+}
+
+pub fn walk_variant<V: Visitor>(visitor: &mut V, variant: &Variant) {
+    match &variant.kind {
+        VariantKind::Unit => {}
+        VariantKind::Tuple(types) => {
+            for type_literal in types {
+                visitor.visit_type_literal(type_literal);
+            }
+        }
+        VariantKind::Struct(fields) => {
+            for field in fields {
+                visitor.visit_field(field);
+            }
+        }
+    }
+}
+
+pub fn walk_trait<V: Visitor>(visitor: &mut V, declaration: &TraitDeclaration) {
+    for signature in &declaration.signatures {
+        visitor.visit_function_signature(signature);
+    }
+    for function in &declaration.functions {
+        visitor.visit_function(function);
+    }
+}
+
+pub fn walk_impl<V: Visitor>(visitor: &mut V, declaration: &ImplDeclaration) {
+    visitor.visit_type_literal(&declaration.trait_type);
+    visitor.visit_type_literal(&declaration.self_type);
+    for function in &declaration.functions {
+        visitor.visit_function(function);
+    }
+}
+
+pub fn walk_function_signature<V: Visitor>(visitor: &mut V, signature: &FunctionSignature) {
+    for type_param in &signature.type_params {
+        visitor.visit_type_param(type_param);
+    }
+    for param in &signature.params {
+        visitor.visit_parameter(param);
+    }
+    if let Some(return_type) = &signature.return_type {
+        visitor.visit_type_literal(return_type);
+    }
+}
+
+pub fn walk_parameter<V: Visitor>(visitor: &mut V, parameter: &Parameter) {
+    visitor.visit_pattern(&parameter.pattern);
+    visitor.visit_type_literal(&parameter.type_annotation);
+}
+
+pub fn walk_type_param<V: Visitor>(visitor: &mut V, type_param: &TypeParam) {
+    for bound in &type_param.bounds {
+        visitor.visit_type_literal(bound);
+    }
+}
+
+pub fn walk_type_literal<V: Visitor>(visitor: &mut V, type_literal: &TypeLiteral) {
+    match &type_literal.kind {
+        TypeLiteralKind::IntType | TypeLiteralKind::FloatType | TypeLiteralKind::CharType | TypeLiteralKind::BoolType | TypeLiteralKind::NumericType(_) | TypeLiteralKind::DynType { .. } => {}
+        TypeLiteralKind::CustomType { type_args: inner, .. } | TypeLiteralKind::TupleType { inner } => {
+            for type_literal in inner {
+                visitor.visit_type_literal(type_literal);
+            }
+        }
+    }
+}
+
+pub fn walk_block<V: Visitor>(visitor: &mut V, block: &Block) {
+    for statement in &block.statements {
+        visitor.visit_statement(statement);
+    }
+    if let Some(value) = &block.value {
+        visitor.visit_expression(value);
+    }
+}
+
+pub fn walk_statement<V: Visitor>(visitor: &mut V, statement: &Statement) {
+    match &statement.kind {
+        StatementKind::Expression(expression) => visitor.visit_expression(expression),
+        StatementKind::Let { pattern, type_annotation, value, .. } => {
+            visitor.visit_pattern(pattern);
+            if let Some(type_annotation) = type_annotation {
+                visitor.visit_type_literal(type_annotation);
+            }
+            if let Some(value) = value {
+                visitor.visit_expression(value);
+            }
+        }
+        StatementKind::Assign { target, value } => {
+            visitor.visit_expression(target);
+            visitor.visit_expression(value);
+        }
+        StatementKind::Return(value) => {
+            if let Some(value) = value {
+                visitor.visit_expression(value);
+            }
+        }
+        StatementKind::For { pattern, iterable, body } => {
+            visitor.visit_pattern(pattern);
+            visitor.visit_expression(iterable);
+            visitor.visit_block(body);
+        }
+        StatementKind::While { condition, body } => {
+            visitor.visit_expression(condition);
+            visitor.visit_block(body);
+        }
+    }
+}
+
+pub fn walk_expression<V: Visitor>(visitor: &mut V, expression: &Expression) {
+    match &expression.kind {
+        ExpressionKind::Literal(_) => {}
+        ExpressionKind::Accesser(accesser) => visitor.visit_accesser(accesser),
+        ExpressionKind::Unary { operand, .. } => visitor.visit_expression(operand),
+        ExpressionKind::Binary { left, right, .. } => {
+            visitor.visit_expression(left);
+            visitor.visit_expression(right);
+        }
+        ExpressionKind::Call { callee, args } => {
+            visitor.visit_expression(callee);
+            for arg in args {
+                visitor.visit_expression(arg);
+            }
+        }
+        ExpressionKind::Field { target, .. } | ExpressionKind::Try(target) => visitor.visit_expression(target),
+        ExpressionKind::Cast { value, ty } => {
+            visitor.visit_expression(value);
+            visitor.visit_type_literal(ty);
+        }
+        ExpressionKind::Tuple(inner) => {
+            for expression in inner {
+                visitor.visit_expression(expression);
+            }
+        }
+        ExpressionKind::Struct { class, fields } => {
+            visitor.visit_accesser(class);
+            for field in fields {
+                visitor.visit_expression(&field.value);
+            }
+        }
+        ExpressionKind::If(if_expression) => visitor.visit_if_expression(if_expression),
+        ExpressionKind::Match(match_expression) => visitor.visit_match_expression(match_expression),
+        ExpressionKind::Block(block) => visitor.visit_block(block),
+    }
+}
+
+pub fn walk_if_expression<V: Visitor>(visitor: &mut V, if_expression: &IfExpression) {
+    visitor.visit_expression(&if_expression.condition);
+    visitor.visit_block(&if_expression.then_branch);
+    if let Some(else_branch) = &if_expression.else_branch {
+        visitor.visit_expression(else_branch);
+    }
+}
+
+pub fn walk_match_expression<V: Visitor>(visitor: &mut V, match_expression: &MatchExpression) {
+    visitor.visit_expression(&match_expression.scrutinee);
+    for arm in &match_expression.arms {
+        visitor.visit_match_arm(arm);
+    }
+}
+
+pub fn walk_match_arm<V: Visitor>(visitor: &mut V, arm: &MatchArm) {
+    visitor.visit_pattern(&arm.pattern);
+    visitor.visit_expression(&arm.value);
+}
+
+pub fn walk_accesser<V: Visitor>(visitor: &mut V, accesser: &Accesser) {
+    for segment in &accesser.segments {
+        for type_arg in &segment.type_args {
+            visitor.visit_type_literal(type_arg);
+        }
+    }
+}
+
+pub fn walk_pattern<V: Visitor>(visitor: &mut V, pattern: &Pattern) {
+    match &pattern.kind {
+        PatternKind::Literal(_) | PatternKind::Identifier(_) | PatternKind::Wildcard => {}
+        PatternKind::Tuple(inner) => {
+            for pattern in inner {
+                visitor.visit_pattern(pattern);
+            }
+        }
+        PatternKind::Variant { path, fields } => {
+            visitor.visit_accesser(path);
+            match fields {
+                PatternFields::Unit => {}
+                PatternFields::Tuple(inner) => {
+                    for pattern in inner {
+                        visitor.visit_pattern(pattern);
+                    }
+                }
+                PatternFields::Struct(fields) => {
+                    for field in fields {
+                        visitor.visit_pattern(&field.pattern);
+                    }
+                }
+            }
+        }
+    }
+}
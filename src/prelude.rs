@@ -0,0 +1,43 @@
+//! The items every program has without declaring or importing them.
+//!
+//! `Option<T>` is a value that may be missing and `Result<T, E>` one that may
+//! have failed with an error. Their variants can also be named without the
+//! enum, like `Some(1)` or `None`, also inside modules. The `?` operator takes
+//! the value out of a `Some` or an `Ok`, and otherwise returns the `None` or
+//! the `Err` from the function, which has to return an `Option` or a `Result`
+//! with the same error type.
+//!
+//! The prelude is parsed like any other source and its declarations come
+//! first at the top level of a program, so a top level item of the same name
+//! is a duplicate of it. Its ids and spans start at `FIRST_ID` and `START`,
+//! far past those of any program, so they never clash with them and no file
+//! of a `SourceMap` covers its spans.
+
+use crate::parser::parser::Parser;
+use crate::parser::{NodeId, Program};
+use crate::span::Span;
+use crate::tokenizer::tokenizer::Tokenizer;
+
+pub const SOURCE: &str = "pub enum Option<T> { Some(T), None }\npub enum Result<T, E> { Ok(T), Err(E) }\n";
+
+pub const OPTION: &str = "Option";
+pub const RESULT: &str = "Result";
+
+/// The id of the first node of the prelude.
+pub const FIRST_ID: NodeId = NodeId(u32::MAX / 2);
+
+/// The offset the spans of the prelude start at.
+pub const START: usize = usize::MAX / 2;
+
+/// Puts the declarations of the prelude before those of `program`.
+pub fn add(program: &mut Program) {
+    let mut tokenizer = Tokenizer::starting_at(SOURCE, START);
+    let tokens = tokenizer.tokenize().expect("the prelude tokenizes").clone();
+    let prelude = Parser::new(tokens, tokenizer.spans().clone()).with_first_id(FIRST_ID).parse().expect("the prelude parses");
+    program.declarations.splice(0..0, prelude.declarations);
+}
+
+/// Whether `span` is in the prelude rather than in a file of the program.
+pub fn contains(span: Span) -> bool {
+    span.start >= START
+}
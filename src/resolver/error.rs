@@ -0,0 +1,71 @@
+use std::fmt;
+
+use crate::diagnostic::{Applicability, Diagnostic};
+use crate::parser::Identifier;
+use crate::prelude;
+use crate::span::Span;
+
+/// The last `Span` of every variant is where the error is reported.
+pub enum ResolveErr {
+    /// the path, and a defined path it may be a typo of
+    Undefined(String, Span, Option<String>),
+    /// the name, and its first and second definition
+    Duplicate(Identifier, Span, Span),
+    /// the path, the definition of the private item and the use
+    Private(String, Span, Span),
+    /// the path, what it names, what was expected there
+    WrongKind(String, &'static str, &'static str, Span),
+    /// the name, the const it shadows and the new binding
+    ShadowedConst(Identifier, Span, Span),
+    /// the module of an import the loader did not read
+    UnloadedModule(String, Span),
+}
+
+impl ResolveErr {
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        match self {
+            Self::Undefined(path, span, similar) => {
+                let diagnostic = Diagnostic::error(format!("`{}` is not defined", path))
+                    .with_code("E0306")
+                    .with_label(*span, "not found in this scope");
+                match similar {
+                    Some(similar) => diagnostic.with_suggestion(format!("a similar name exists: `{}`", similar), *span, similar.clone(), Applicability::MaybeIncorrect),
+                    None => diagnostic,
+                }
+            }
+            Self::Duplicate(name, first, second) if prelude::contains(*first) => Diagnostic::error(format!("`{}` is defined more than once", name))
+                .with_code("E0313")
+                .with_label(*second, "defined again here")
+                .with_note(format!("the prelude defines `{}` for every program", name))
+                .with_help("give it another name, or declare it inside a namespace"),
+            Self::Duplicate(name, first, second) => Diagnostic::error(format!("`{}` is defined more than once", name))
+                .with_code("E0313")
+                .with_secondary(*first, format!("`{}` is first defined here", name))
+                .with_label(*second, "defined again here"),
+            Self::Private(path, definition, span) => Diagnostic::error(format!("`{}` is private", path))
+                .with_code("E0314")
+                .with_secondary(*definition, "declared here without `pub`")
+                .with_label(*span, "private item")
+                .with_suggestion("make it public", Span::new(definition.start, definition.start), "pub ", Applicability::MaybeIncorrect),
+            Self::WrongKind(path, found, expected, span) => Diagnostic::error(format!("expected {}, found {} `{}`", expected, found, path))
+                .with_code("E0315")
+                .with_label(*span, format!("not a {}", expected)),
+            Self::ShadowedConst(name, constant, span) => Diagnostic::error(format!("can not shadow const `{}` in the same block", name))
+                .with_code("E0316")
+                .with_secondary(*constant, "const declared here")
+                .with_label(*span, "shadows the const")
+                .with_help("use another name, or shadow it in a nested block"),
+            Self::UnloadedModule(path, span) => Diagnostic::error(format!("module `{}` is not loaded", path))
+                .with_code("E0318")
+                .with_label(*span, "imported here")
+                .with_note("imports are only followed when a program is compiled from a file"),
+        }
+    }
+}
+
+impl fmt::Debug for ResolveErr {
+    fn fmt (&self, formatter: &mut fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        writeln!(formatter, "failed to resolve names because...")?;
+        write!(formatter, "{}", self.to_diagnostic())
+    }
+}
@@ -0,0 +1,73 @@
+//! Name resolution of a parsed `Program`.
+//!
+//! Every identifier and path is bound to what it names before type checking:
+//! locals live in the scopes of functions and blocks, items in the namespace
+//! they are declared in. A path like `ns::Type::method` is looked up segment by
+//! segment, and its first segment from the innermost namespace outwards.
+//!
+//! Functions, statics and the variants of enums are values, namespaces,
+//! classes, enums and traits are types; the two kinds of names do not clash.
+//! A variant like `Shape::Rect` is also found among the types, as a struct
+//! literal names the variant it builds. An item without `pub` can only be used
+//! inside the namespace or class it is declared in. A name that no local or
+//! item takes may still be one of the arithmetic intrinsics like `wrapping_add`.
+//!
+//! The items of the `prelude` are found from every namespace and module, and
+//! their variants also by name alone: `Some` stands for `Option::Some`. An
+//! identifier pattern that names a variant like `None` matches the variant
+//! instead of binding a local.
+//!
+//! The result is a `Resolution`, a side table from the `NodeId` of every
+//! accesser expression, struct literal, custom type literal, `dyn` type
+//! literal and enum pattern to the `Res` it names. Inside a class, or an impl for a class, `Self` names the class.
+//! Inside a trait it is `Res::SelfType`, whichever type implements the trait.
+//!
+//! The type parameters of a generic function are names of types in its
+//! signature and body, those of a generic class in its fields and methods.
+//! They shadow items of the same name, and their bounds have to be traits.
+
+pub mod resolver;
+pub mod error;
+mod test;
+
+use std::collections::HashMap;
+
+use crate::numeric::Intrinsic;
+use crate::parser::NodeId;
+
+/// What a name resolved to. Items carry their full path like `math::max`, and
+/// methods are functions under the path of their class like `Point::new`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Res {
+    /// a local variable or parameter, by the id of the pattern that binds it
+    Local(NodeId),
+    Function(String),
+    Static(String),
+    Class(String),
+    Enum(String),
+    /// a variant of an enum, under the path of the enum like `Shape::Circle`
+    Variant(String),
+    Trait(String),
+    /// `Self` inside a trait, or inside an impl for a type other than a class,
+    /// by the id of the declaration: the type the functions are for
+    SelfType(NodeId),
+    /// a type parameter of a generic function or class, by the id of its declaration
+    TypeParam(NodeId),
+    Intrinsic(Intrinsic),
+}
+
+#[derive(Debug, Default)]
+pub struct Resolution {
+    names: HashMap<NodeId, Res>,
+}
+
+impl Resolution {
+    /// What the accesser expression, struct literal, type literal or enum pattern `id` names, if it resolved.
+    pub fn get(&self, id: NodeId) -> Option<&Res> {
+        self.names.get(&id)
+    }
+
+    pub fn insert(&mut self, id: NodeId, res: Res) {
+        self.names.insert(id, res);
+    }
+}
@@ -0,0 +1,731 @@
+use std::collections::HashMap;
+
+use crate::diagnostic::similar_name;
+use crate::loader::is_module;
+use crate::numeric::Intrinsic;
+use crate::parser::printer;
+use crate::parser::visit::{self, Visitor};
+use crate::parser::*;
+use crate::prelude;
+use crate::resolver::error::ResolveErr;
+use crate::resolver::{Res, Resolution};
+use crate::span::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ItemKind {
+    Namespace,
+    Class,
+    Enum,
+    Trait,
+    Function,
+    Static,
+    Variant,
+}
+
+impl ItemKind {
+    fn is_value(self) -> bool {
+        matches!(self, ItemKind::Function | ItemKind::Static | ItemKind::Variant)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ItemKind::Namespace => "namespace",
+            ItemKind::Class => "class",
+            ItemKind::Enum => "enum",
+            ItemKind::Trait => "trait",
+            ItemKind::Function => "function",
+            ItemKind::Static => "static",
+            ItemKind::Variant => "variant",
+        }
+    }
+}
+
+struct Item {
+    kind: ItemKind,
+    is_pub: bool,
+    span: Span,
+}
+
+struct Local {
+    id: NodeId,
+    span: Span,
+    is_const: bool,
+}
+
+pub struct Resolver {
+    /// items of every namespace keyed by their full path like `math::max`:
+    /// functions, statics and variants are values, namespaces, classes, enums
+    /// and traits types, and variants are types too
+    values: HashMap<String, Item>,
+    types: HashMap<String, Item>,
+    /// the full path of what an imported name stands for, by whether it is a
+    /// value and the full path of the name
+    aliases: HashMap<(bool, String), String>,
+    namespace: Vec<Identifier>,
+    /// the class or trait whose methods are being resolved, which may use its private items
+    owner: Option<String>,
+    /// the trait, or the impl for a type other than a class, in which `Self`
+    /// names the type the functions are for rather than `owner`
+    self_type: Option<NodeId>,
+    scopes: Vec<HashMap<Identifier, Local>>,
+    /// the type parameters in scope, of the class and of the function inside it
+    type_params: Vec<HashMap<Identifier, NodeId>>,
+    resolution: Resolution,
+    errors: Vec<ResolveErr>,
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        Resolver {
+            values: HashMap::new(),
+            types: HashMap::new(),
+            aliases: HashMap::new(),
+            namespace: Vec::new(),
+            owner: None,
+            self_type: None,
+            scopes: Vec::new(),
+            type_params: Vec::new(),
+            resolution: Resolution::default(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Returns what resolved along with the errors, so the program can still
+    /// be type checked where its names are fine.
+    pub fn resolve(&mut self, program: &Program) -> (Resolution, Vec<ResolveErr>) {
+        self.collect(program);
+        self.collect_imports(program);
+        self.visit_program(program);
+        (std::mem::take(&mut self.resolution), std::mem::take(&mut self.errors))
+    }
+
+    fn full_path(&self, name: &str) -> String {
+        let mut path = self.namespace.join("::");
+        if !path.is_empty() {
+            path.push_str("::");
+        }
+        path.push_str(name);
+        path
+    }
+
+    fn table(&self, values: bool) -> &HashMap<String, Item> {
+        if values {
+            &self.values
+        } else {
+            &self.types
+        }
+    }
+
+    /// Returns whether `path` was still free.
+    fn declare(&mut self, path: String, name: &Identifier, item: Item) -> bool {
+        let table = if item.kind.is_value() { &mut self.values } else { &mut self.types };
+        match table.get(&path) {
+            Some(first) => {
+                self.errors.push(ResolveErr::Duplicate(name.clone(), first.span, item.span));
+                false
+            }
+            None => {
+                table.insert(path, item);
+                true
+            }
+        }
+    }
+
+    /// The item `path` stands for, which differs for imported names.
+    fn target(&self, path: String, values: bool) -> String {
+        self.aliases.get(&(values, path.clone())).cloned().unwrap_or(path)
+    }
+
+    /// Declares every item so it can be used before its declaration.
+    fn collect(&mut self, program: &Program) {
+        for declaration in &program.declarations {
+            match declaration {
+                Declaration::Function(function) => {
+                    let item = Item { kind: ItemKind::Function, is_pub: function.is_pub, span: function.signature.span };
+                    self.declare(self.full_path(&function.name), &function.name, item);
+                }
+                Declaration::StaticVariable(variable) => {
+                    let item = Item { kind: ItemKind::Static, is_pub: variable.is_pub, span: variable.span };
+                    self.declare(self.full_path(&variable.name), &variable.name, item);
+                }
+                Declaration::Namespace(namespace) => {
+                    let item = Item { kind: ItemKind::Namespace, is_pub: namespace.is_pub, span: namespace.span };
+                    self.declare(self.full_path(&namespace.name), &namespace.name, item);
+                    self.namespace.push(namespace.name.clone());
+                    self.collect(&namespace.inner);
+                    self.namespace.pop();
+                }
+                Declaration::Class(class) => {
+                    let path = self.full_path(&class.name);
+                    let item = Item { kind: ItemKind::Class, is_pub: class.is_pub, span: class.span };
+                    self.declare(path.clone(), &class.name, item);
+                    let mut fields: HashMap<&Identifier, Span> = HashMap::new();
+                    for field in &class.fields {
+                        if let Some(first) = fields.insert(&field.name, field.span) {
+                            self.errors.push(ResolveErr::Duplicate(field.name.clone(), first, field.span));
+                        }
+                    }
+                    for method in &class.methods {
+                        let item = Item { kind: ItemKind::Function, is_pub: method.is_pub, span: method.signature.span };
+                        self.declare(format!("{}::{}", path, method.name), &method.name, item);
+                    }
+                }
+                Declaration::Enum(declaration) => {
+                    let path = self.full_path(&declaration.name);
+                    let item = Item { kind: ItemKind::Enum, is_pub: declaration.is_pub, span: declaration.span };
+                    self.declare(path.clone(), &declaration.name, item);
+                    // the variants are as visible as the enum itself
+                    for variant in &declaration.variants {
+                        let variant_path = format!("{}::{}", path, variant.name);
+                        let item = Item { kind: ItemKind::Variant, is_pub: true, span: variant.span };
+                        if self.declare(variant_path.clone(), &variant.name, item) {
+                            self.types.insert(variant_path.clone(), Item { kind: ItemKind::Variant, is_pub: true, span: variant.span });
+                        }
+                        // the variants of the prelude are also named without their enum, like `Some`
+                        if prelude::contains(declaration.span) {
+                            let item = Item { kind: ItemKind::Variant, is_pub: true, span: variant.span };
+                            if self.declare(variant.name.clone(), &variant.name, item) {
+                                self.types.insert(variant.name.clone(), Item { kind: ItemKind::Variant, is_pub: true, span: variant.span });
+                                self.aliases.insert((true, variant.name.clone()), variant_path.clone());
+                                self.aliases.insert((false, variant.name.clone()), variant_path);
+                            }
+                        }
+                        let VariantKind::Struct(fields) = &variant.kind else { continue };
+                        let mut names: HashMap<&Identifier, Span> = HashMap::new();
+                        for field in fields {
+                            if let Some(first) = names.insert(&field.name, field.span) {
+                                self.errors.push(ResolveErr::Duplicate(field.name.clone(), first, field.span));
+                            }
+                        }
+                    }
+                }
+                Declaration::Trait(declaration) => {
+                    let path = self.full_path(&declaration.name);
+                    let item = Item { kind: ItemKind::Trait, is_pub: declaration.is_pub, span: declaration.span };
+                    self.declare(path.clone(), &declaration.name, item);
+                    // what a trait declares is as visible as the trait itself
+                    let signatures = declaration.signatures.iter().chain(declaration.functions.iter().map(|function| &function.signature));
+                    for signature in signatures {
+                        let item = Item { kind: ItemKind::Function, is_pub: true, span: signature.span };
+                        self.declare(format!("{}::{}", path, signature.name), &signature.name, item);
+                    }
+                }
+                Declaration::Impl(_) | Declaration::Import(_) => {}
+            }
+        }
+    }
+
+    /// Binds the names imports bring in, once the namespaces of all modules
+    /// are collected.
+    fn collect_imports(&mut self, program: &Program) {
+        for declaration in &program.declarations {
+            match declaration {
+                Declaration::Import(import) => self.import(import),
+                Declaration::Namespace(namespace) => {
+                    self.namespace.push(namespace.name.clone());
+                    self.collect_imports(&namespace.inner);
+                    self.namespace.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn import(&mut self, import: &ImportDeclaration) {
+        // the loader puts every module into a namespace and points `path` at it
+        let is_loaded = is_module(&import.path) && self.types.get(&import.path).is_some_and(|item| item.kind == ItemKind::Namespace);
+        if !is_loaded {
+            self.errors.push(ResolveErr::UnloadedModule(import.path.clone(), import.span));
+            return;
+        }
+        match &import.kind {
+            ImportKind::Module(name) => {
+                let path = self.full_path(name);
+                let item = Item { kind: ItemKind::Namespace, is_pub: false, span: import.span };
+                if self.declare(path.clone(), name, item) {
+                    self.aliases.insert((false, path), import.path.clone());
+                }
+            }
+            ImportKind::Items(items) => {
+                for item in items {
+                    self.import_item(&import.path, item);
+                }
+            }
+        }
+    }
+
+    /// Binds `import` to the value, the type or both the module has under its name.
+    fn import_item(&mut self, module: &str, import: &ImportItem) {
+        let target = format!("{}::{}", module, import.name);
+        let mut found = false;
+        for values in [true, false] {
+            let Some(&Item { kind, is_pub, span }) = self.table(values).get(&target) else { continue };
+            found = true;
+            if !is_pub {
+                self.errors.push(ResolveErr::Private(target.clone(), span, import.span));
+            }
+            let path = self.full_path(&import.name);
+            if self.declare(path.clone(), &import.name, Item { kind, is_pub: false, span: import.span }) {
+                self.aliases.insert((values, path), target.clone());
+            }
+        }
+        if !found {
+            let prefix = format!("{}::", module);
+            let children = self.values.keys().chain(self.types.keys()).filter_map(|key| key.strip_prefix(&prefix)).filter(|name| !name.contains("::"));
+            let similar = similar_name(&import.name, children).map(str::to_string);
+            self.errors.push(ResolveErr::Undefined(target, import.span, similar));
+        }
+    }
+
+    /// How far out names are looked up: not past the namespace of a module,
+    /// which only sees what it declares or imports, and the prelude.
+    fn outermost(&self) -> usize {
+        usize::from(self.namespace.first().is_some_and(|name| is_module(name)))
+    }
+
+    /// Whether code at the current position may use the private items of `parent`.
+    fn is_inside(&self, parent: &str) -> bool {
+        let current = self.namespace.join("::");
+        current == parent || current.starts_with(&format!("{}::", parent)) || self.owner.as_deref() == Some(parent)
+    }
+
+    /// Looks `name` up from the innermost namespace outwards and then in the
+    /// prelude, and returns its full path.
+    fn lookup_outwards(&self, name: &str, values: bool) -> Option<String> {
+        let table = self.table(values);
+        let found = (self.outermost()..=self.namespace.len()).rev().find_map(|depth| {
+            let mut path = self.namespace[..depth].join("::");
+            if !path.is_empty() {
+                path.push_str("::");
+            }
+            path.push_str(name);
+            table.contains_key(&path).then_some(path)
+        });
+        found.or_else(|| table.get(name).filter(|item| prelude::contains(item.span)).map(|_| name.to_string()))
+    }
+
+    /// Names of the items in `table` that can be used without a path from the current namespace.
+    fn visible_names(&self, values: bool) -> Vec<&str> {
+        self.table(values)
+            .keys()
+            .filter_map(|key| {
+                (self.outermost()..=self.namespace.len()).find_map(|depth| {
+                    let mut prefix = self.namespace[..depth].join("::");
+                    if !prefix.is_empty() {
+                        prefix.push_str("::");
+                    }
+                    key.strip_prefix(&prefix).filter(|name| !name.contains("::"))
+                })
+            })
+            .collect()
+    }
+
+    fn lookup_local(&self, name: &str) -> Option<&Local> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    /// Resolves the path `names` to a value, or to a type when `values` is
+    /// false. Every segment but the last has to name a namespace, class, enum or trait.
+    /// A typo is only suggested when `suggest`, that is when replacing `span`
+    /// with the path does not lose type arguments.
+    fn resolve_path(&mut self, names: &[&str], span: Span, values: bool, suggest: bool) -> Option<Res> {
+        let written = names.join("::");
+        if let ([name], true) = (names, values) {
+            if let Some(local) = self.lookup_local(name) {
+                return Some(Res::Local(local.id));
+            }
+        }
+
+        let expected = |is_last: bool| match (is_last, values) {
+            (true, true) => "value",
+            (true, false) => "type",
+            (false, _) => "namespace, class, enum or trait",
+        };
+        if let ([name], false) = (names, values) {
+            if let Some(id) = self.type_params.iter().rev().find_map(|scope| scope.get(*name)) {
+                return Some(Res::TypeParam(*id));
+            }
+        }
+
+        let first_in_values = names.len() == 1 && values;
+        if let (Some(id), ["Self"], false) = (self.self_type, names, values) {
+            return Some(Res::SelfType(id));
+        }
+        // inside a class or trait `Self` is its type
+        let owner = self.owner.clone().filter(|_| names[0] == "Self" && !first_in_values);
+        let Some(path) = owner.or_else(|| self.lookup_outwards(names[0], first_in_values)) else {
+            if let Some(intrinsic) = Intrinsic::find(names[0]).filter(|_| first_in_values) {
+                return Some(Res::Intrinsic(intrinsic));
+            }
+            if let Some(other) = self.lookup_outwards(names[0], !first_in_values) {
+                let found = self.table(!first_in_values)[&other].kind.name();
+                self.errors.push(ResolveErr::WrongKind(names[0].to_string(), found, expected(names.len() == 1), span));
+                return None;
+            }
+            let intrinsics: Vec<String> = Intrinsic::all().map(Intrinsic::name).collect();
+            let similar = match names.len() {
+                1 if suggest => {
+                    let mut candidates = self.visible_names(values);
+                    if values {
+                        candidates.extend(self.scopes.iter().flat_map(|scope| scope.keys().map(String::as_str)));
+                        candidates.extend(intrinsics.iter().map(String::as_str));
+                    }
+                    similar_name(names[0], candidates).map(str::to_string)
+                }
+                _ => None,
+            };
+            self.errors.push(ResolveErr::Undefined(names[0].to_string(), span, similar));
+            return None;
+        };
+        let mut path = self.target(path, first_in_values);
+
+        for (index, name) in names.iter().enumerate().skip(1) {
+            let is_last = index + 1 == names.len();
+            let in_values = is_last && values;
+            let parent = path;
+            path = format!("{}::{}", parent, name);
+            match self.table(in_values).get(&path) {
+                Some(item) => {
+                    if !item.is_pub && !self.is_inside(&parent) {
+                        let definition = item.span;
+                        self.errors.push(ResolveErr::Private(names[..=index].join("::"), definition, span));
+                    }
+                    path = self.target(path, in_values);
+                }
+                None => {
+                    if let Some(item) = self.table(!in_values).get(&path) {
+                        self.errors.push(ResolveErr::WrongKind(names[..=index].join("::"), item.kind.name(), expected(is_last), span));
+                        return None;
+                    }
+                    let prefix = format!("{}::", parent);
+                    let similar = if is_last && suggest {
+                        let children = self.table(in_values).keys().filter_map(|key| key.strip_prefix(&prefix)).filter(|name| !name.contains("::"));
+                        similar_name(name, children).map(|similar| format!("{}::{}", names[..index].join("::"), similar))
+                    } else {
+                        None
+                    };
+                    self.errors.push(ResolveErr::Undefined(names[..=index].join("::"), span, similar));
+                    return None;
+                }
+            }
+        }
+
+        match self.table(values)[&path].kind {
+            ItemKind::Function => Some(Res::Function(path)),
+            ItemKind::Static => Some(Res::Static(path)),
+            ItemKind::Class => Some(Res::Class(path)),
+            ItemKind::Enum => Some(Res::Enum(path)),
+            ItemKind::Variant => Some(Res::Variant(path)),
+            ItemKind::Trait => Some(Res::Trait(path)),
+            ItemKind::Namespace => {
+                self.errors.push(ResolveErr::WrongKind(written, "namespace", expected(true), span));
+                None
+            }
+        }
+    }
+
+    /// Binds the names of `patterns` in the innermost scope. They are bound
+    /// together, like the parameters of a function, so one name may only
+    /// appear once among them.
+    fn bind(&mut self, patterns: &[&Pattern], is_const: bool) {
+        let mut bindings = Vec::new();
+        for pattern in patterns {
+            self.resolve_variants(pattern);
+            pattern_bindings(pattern, &mut bindings);
+        }
+        let mut bound: HashMap<&Identifier, Span> = HashMap::new();
+        for (name, id, span) in bindings {
+            if let Some(Res::Variant(_)) = self.resolution.get(id) {
+                continue;
+            }
+            if let Some(first) = bound.insert(name, span) {
+                self.errors.push(ResolveErr::Duplicate(name.clone(), first, span));
+                continue;
+            }
+            let scope = self.scopes.last_mut().unwrap();
+            let shadowed_const = scope.get(name).filter(|local| local.is_const).map(|local| local.span);
+            scope.insert(name.clone(), Local { id, span, is_const });
+            if let Some(constant) = shadowed_const {
+                self.errors.push(ResolveErr::ShadowedConst(name.clone(), constant, span));
+            }
+        }
+    }
+
+    /// Resolves the path of every enum pattern in `pattern`, which has to
+    /// name a variant. Locals do not take part, so it is looked up among the
+    /// types. An identifier that names a variant, like `None`, matches it
+    /// rather than binding a local.
+    fn resolve_variants(&mut self, pattern: &Pattern) {
+        match &pattern.kind {
+            PatternKind::Identifier(name) => {
+                let variant = self.lookup_outwards(name, false).map(|path| self.target(path, false)).filter(|path| self.types[path].kind == ItemKind::Variant);
+                if let Some(path) = variant {
+                    self.resolution.insert(pattern.id, Res::Variant(path));
+                }
+            }
+            PatternKind::Variant { path, fields } => {
+                let names: Vec<&str> = path.segments.iter().map(|segment| segment.name.as_str()).collect();
+                match self.resolve_path(&names, pattern.span, false, false) {
+                    Some(res @ Res::Variant(_)) => self.resolution.insert(pattern.id, res),
+                    Some(Res::Class(_)) => self.errors.push(ResolveErr::WrongKind(path.path(), "class", "variant", pattern.span)),
+                    Some(Res::Enum(_)) => self.errors.push(ResolveErr::WrongKind(path.path(), "enum", "variant", pattern.span)),
+                    Some(Res::Trait(_)) => self.errors.push(ResolveErr::WrongKind(path.path(), "trait", "variant", pattern.span)),
+                    Some(_) => self.errors.push(ResolveErr::WrongKind(path.path(), "type", "variant", pattern.span)),
+                    None => {}
+                }
+                visit::walk_accesser(self, path);
+                match fields {
+                    PatternFields::Unit => {}
+                    PatternFields::Tuple(inner) => {
+                        for pattern in inner {
+                            self.resolve_variants(pattern);
+                        }
+                    }
+                    PatternFields::Struct(fields) => {
+                        for field in fields {
+                            self.resolve_variants(&field.pattern);
+                        }
+                    }
+                }
+            }
+            PatternKind::Tuple(inner) => {
+                for pattern in inner {
+                    self.resolve_variants(pattern);
+                }
+            }
+            PatternKind::Literal(_) | PatternKind::Wildcard => {}
+        }
+    }
+
+    fn with_owner(&mut self, owner: Option<String>, self_type: Option<NodeId>, visit: impl FnOnce(&mut Self)) {
+        let owner = std::mem::replace(&mut self.owner, owner);
+        let self_type = std::mem::replace(&mut self.self_type, self_type);
+        visit(self);
+        self.owner = owner;
+        self.self_type = self_type;
+    }
+
+    /// Makes `type_params` names of types while `visit` runs. One name may
+    /// only appear once among them.
+    fn with_type_params(&mut self, type_params: &[TypeParam], visit: impl FnOnce(&mut Self)) {
+        let mut scope = HashMap::new();
+        let mut declared: HashMap<&Identifier, Span> = HashMap::new();
+        for type_param in type_params {
+            if let Some(first) = declared.insert(&type_param.name, type_param.span) {
+                self.errors.push(ResolveErr::Duplicate(type_param.name.clone(), first, type_param.span));
+                continue;
+            }
+            scope.insert(type_param.name.clone(), type_param.id);
+        }
+        self.type_params.push(scope);
+        visit(self);
+        self.type_params.pop();
+    }
+
+    /// Resolves the trait or the type of an impl, which has to be of the
+    /// `expected` kind. Only traits and other types are told apart here.
+    fn resolve_impl_type(&mut self, type_literal: &TypeLiteral, expected: ItemKind) -> Option<Res> {
+        let TypeLiteralKind::CustomType { name, type_args } = &type_literal.kind else {
+            match (expected, &type_literal.kind) {
+                (ItemKind::Trait, _) => self.errors.push(ResolveErr::WrongKind(printer::print_type(type_literal), "type", "trait", type_literal.span)),
+                // an impl for `dyn Trait` is one for a type other than a class
+                (_, TypeLiteralKind::DynType { .. }) => self.visit_type_literal(type_literal),
+                _ => {}
+            }
+            return None;
+        };
+        let res = self.resolve_path(&[name], type_literal.span, false, type_args.is_empty())?;
+        let found = match &res {
+            Res::Trait(_) => ItemKind::Trait,
+            Res::Enum(_) => ItemKind::Enum,
+            _ => ItemKind::Class,
+        };
+        if (found == ItemKind::Trait) != (expected == ItemKind::Trait) {
+            self.errors.push(ResolveErr::WrongKind(name.clone(), found.name(), expected.name(), type_literal.span));
+            return None;
+        }
+        self.resolution.insert(type_literal.id, res.clone());
+        Some(res)
+    }
+}
+
+impl Visitor for Resolver {
+    fn visit_namespace(&mut self, declaration: &NamespaceDeclaration) {
+        self.namespace.push(declaration.name.clone());
+        visit::walk_namespace(self, declaration);
+        self.namespace.pop();
+    }
+
+    fn visit_function(&mut self, declaration: &FunctionDeclaration) {
+        self.with_type_params(&declaration.signature.type_params, |resolver| {
+            resolver.scopes.push(HashMap::new());
+            let params: Vec<&Pattern> = declaration.signature.params.iter().map(|param| &param.pattern).collect();
+            resolver.bind(&params, false);
+            visit::walk_function(resolver, declaration);
+            resolver.scopes.pop();
+        });
+    }
+
+    fn visit_class(&mut self, declaration: &ClassDeclaration) {
+        let owner = self.full_path(&declaration.name);
+        self.with_owner(Some(owner), None, |resolver| {
+            resolver.with_type_params(&declaration.type_params, |resolver| visit::walk_class(resolver, declaration));
+        });
+    }
+
+    fn visit_enum(&mut self, declaration: &EnumDeclaration) {
+        let owner = self.full_path(&declaration.name);
+        self.with_owner(Some(owner), None, |resolver| {
+            resolver.with_type_params(&declaration.type_params, |resolver| visit::walk_enum(resolver, declaration));
+        });
+    }
+
+    fn visit_trait(&mut self, declaration: &TraitDeclaration) {
+        let owner = self.full_path(&declaration.name);
+        self.with_owner(Some(owner), Some(declaration.id), |resolver| {
+            for signature in &declaration.signatures {
+                resolver.with_type_params(&signature.type_params, |resolver| resolver.visit_function_signature(signature));
+            }
+            for function in &declaration.functions {
+                resolver.visit_function(function);
+            }
+        });
+    }
+
+    fn visit_type_param(&mut self, type_param: &TypeParam) {
+        for bound in &type_param.bounds {
+            self.resolve_impl_type(bound, ItemKind::Trait);
+            visit::walk_type_literal(self, bound);
+        }
+    }
+
+    /// Inside an impl for a class `Self` is the class, whose private items
+    /// the functions may use like its methods.
+    fn visit_impl(&mut self, declaration: &ImplDeclaration) {
+        self.resolve_impl_type(&declaration.trait_type, ItemKind::Trait);
+        let (owner, self_type) = match self.resolve_impl_type(&declaration.self_type, ItemKind::Class) {
+            Some(Res::Class(path)) => (Some(path), None),
+            _ => (None, Some(declaration.id)),
+        };
+        for type_literal in [&declaration.trait_type, &declaration.self_type] {
+            visit::walk_type_literal(self, type_literal);
+        }
+        let mut names: HashMap<&Identifier, Span> = HashMap::new();
+        for function in &declaration.functions {
+            if let Some(first) = names.insert(&function.name, function.signature.span) {
+                self.errors.push(ResolveErr::Duplicate(function.name.clone(), first, function.signature.span));
+            }
+        }
+        self.with_owner(owner, self_type, |resolver| {
+            for function in &declaration.functions {
+                resolver.visit_function(function);
+            }
+        });
+    }
+
+    fn visit_type_literal(&mut self, type_literal: &TypeLiteral) {
+        match &type_literal.kind {
+            TypeLiteralKind::CustomType { name, type_args } => {
+                if let Some(res) = self.resolve_path(&[name], type_literal.span, false, type_args.is_empty()) {
+                    self.resolution.insert(type_literal.id, res);
+                }
+            }
+            // only a trait can be `dyn`
+            TypeLiteralKind::DynType { name } => match self.resolve_path(&[name], type_literal.span, false, true) {
+                Some(res @ Res::Trait(_)) => self.resolution.insert(type_literal.id, res),
+                Some(Res::Class(_)) => self.errors.push(ResolveErr::WrongKind(name.clone(), "class", "trait", type_literal.span)),
+                Some(_) => self.errors.push(ResolveErr::WrongKind(name.clone(), "type", "trait", type_literal.span)),
+                None => {}
+            },
+            _ => {}
+        }
+        visit::walk_type_literal(self, type_literal)
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        self.scopes.push(HashMap::new());
+        visit::walk_block(self, block);
+        self.scopes.pop();
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        match &statement.kind {
+            // the value is resolved before the pattern binds, so `let x = x;` uses the outer `x`
+            StatementKind::Let { is_const, pattern, type_annotation, value } => {
+                if let Some(value) = value {
+                    self.visit_expression(value);
+                }
+                if let Some(type_annotation) = type_annotation {
+                    self.visit_type_literal(type_annotation);
+                }
+                self.bind(&[pattern], *is_const);
+            }
+            StatementKind::For { pattern, iterable, body } => {
+                self.visit_expression(iterable);
+                self.scopes.push(HashMap::new());
+                self.bind(&[pattern], true);
+                self.visit_block(body);
+                self.scopes.pop();
+            }
+            _ => visit::walk_statement(self, statement),
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &Expression) {
+        match &expression.kind {
+            ExpressionKind::Accesser(accesser) => {
+                let names: Vec<&str> = accesser.segments.iter().map(|segment| segment.name.as_str()).collect();
+                if let Some(res) = self.resolve_path(&names, expression.span, true, !accesser.has_type_args()) {
+                    self.resolution.insert(expression.id, res);
+                }
+            }
+            // the span is the whole literal, so a typo of the class is not replaced
+            ExpressionKind::Struct { class, .. } => {
+                let names: Vec<&str> = class.segments.iter().map(|segment| segment.name.as_str()).collect();
+                match self.resolve_path(&names, expression.span, false, false) {
+                    Some(Res::Trait(_)) => self.errors.push(ResolveErr::WrongKind(class.path(), "trait", "class", expression.span)),
+                    Some(Res::Enum(_)) => self.errors.push(ResolveErr::WrongKind(class.path(), "enum", "class", expression.span)),
+                    Some(Res::SelfType(_) | Res::TypeParam(_)) => self.errors.push(ResolveErr::WrongKind(class.path(), "type", "class", expression.span)),
+                    Some(res) => self.resolution.insert(expression.id, res),
+                    None => {}
+                }
+            }
+            _ => {}
+        }
+        visit::walk_expression(self, expression)
+    }
+
+    fn visit_match_arm(&mut self, arm: &MatchArm) {
+        self.scopes.push(HashMap::new());
+        self.bind(&[&arm.pattern], false);
+        self.visit_expression(&arm.value);
+        self.scopes.pop();
+    }
+
+    /// Patterns bind names rather than use them; `bind` handles them.
+    fn visit_pattern(&mut self, _pattern: &Pattern) {}
+}
+
+/// The identifiers `pattern` binds, with the id and span of each.
+fn pattern_bindings<'p>(pattern: &'p Pattern, bindings: &mut Vec<(&'p Identifier, NodeId, Span)>) {
+    match &pattern.kind {
+        PatternKind::Identifier(name) => bindings.push((name, pattern.id, pattern.span)),
+        PatternKind::Tuple(inner) | PatternKind::Variant { fields: PatternFields::Tuple(inner), .. } => {
+            for pattern in inner {
+                pattern_bindings(pattern, bindings);
+            }
+        }
+        PatternKind::Variant { fields: PatternFields::Struct(fields), .. } => {
+            for field in fields {
+                pattern_bindings(&field.pattern, bindings);
+            }
+        }
+        PatternKind::Literal(_) | PatternKind::Wildcard | PatternKind::Variant { fields: PatternFields::Unit, .. } => {}
+    }
+}
@@ -0,0 +1,295 @@
+#[cfg(test)]
+mod tests {
+    use crate::numeric::{Intrinsic, Operation, Overflow};
+    use crate::parser::*;
+    use crate::parser::parser::Parser;
+    use crate::resolver::error::ResolveErr;
+    use crate::resolver::resolver::Resolver;
+    use crate::resolver::{Res, Resolution};
+    use crate::tokenizer::tokenizer::Tokenizer;
+
+    fn parse(program: &str) -> Program {
+        let mut tokenizer = Tokenizer::new(program);
+        let tokens = tokenizer.tokenize().unwrap().clone();
+        Parser::new(tokens, tokenizer.spans().clone()).parse().unwrap()
+    }
+
+    fn resolve(program: &str) -> (Program, Resolution, Vec<ResolveErr>) {
+        let program = parse(program);
+        let (resolution, errors) = Resolver::new().resolve(&program);
+        (program, resolution, errors)
+    }
+
+    fn resolve_ok(program: &str) -> (Program, Resolution) {
+        let (program, resolution, errors) = resolve(program);
+        assert!(errors.is_empty(), "{:?}", errors);
+        (program, resolution)
+    }
+
+    fn function_body<'p>(program: &'p Program, name: &str) -> &'p Block {
+        program
+            .declarations
+            .iter()
+            .find_map(|declaration| match declaration {
+                Declaration::Function(function) if function.name == name => Some(&function.inner),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    fn let_pattern(statement: &Statement) -> &Pattern {
+        match &statement.kind {
+            StatementKind::Let { pattern, .. } => pattern,
+            _ => panic!("expected a `let`"),
+        }
+    }
+
+    #[test]
+    fn test_resolve_locals_and_shadowing() {
+        let (program, resolution) = resolve_ok("fn f(x: int): int { let x = x + 1; { let x = 2; x }; x }");
+        let Declaration::Function(function) = &program.declarations[0] else { unreachable!() };
+        let param = function.signature.params[0].pattern.id;
+        let body = &function.inner;
+        let StatementKind::Let { value: Some(value), .. } = &body.statements[0].kind else { unreachable!() };
+        let ExpressionKind::Binary { left, .. } = &value.kind else { unreachable!() };
+        // the value of a `let` still sees the binding it shadows
+        assert_eq!(resolution.get(left.id), Some(&Res::Local(param)));
+        assert_eq!(resolution.get(body.value.as_ref().unwrap().id), Some(&Res::Local(let_pattern(&body.statements[0]).id)));
+    }
+
+    #[test]
+    fn test_resolve_namespace_paths() {
+        let source = "
+            fn top(): int { 0 }
+            pub namespace geo {
+                pub class Point { pub fn origin(): int { helper() } }
+                fn helper(): int { top() }
+                pub namespace deep { pub fn f(): int { helper() + Point::origin() } }
+            }
+            fn main(): int { geo::Point::origin() + geo::deep::f() }
+        ";
+        let (program, resolution) = resolve_ok(source);
+        let ExpressionKind::Binary { left, right, .. } = &function_body(&program, "main").value.as_ref().unwrap().kind else { unreachable!() };
+        let callee = |expression: &Expression| match &expression.kind {
+            ExpressionKind::Call { callee, .. } => resolution.get(callee.id).cloned(),
+            _ => None,
+        };
+        assert_eq!(callee(left), Some(Res::Function("geo::Point::origin".into())));
+        assert_eq!(callee(right), Some(Res::Function("geo::deep::f".into())));
+    }
+
+    #[test]
+    fn test_resolve_visibility() {
+        let source = "
+            namespace a {
+                fn private(): int { 0 }
+                pub class C { fn secret(): int { C::secret() } pub fn open(): int { 0 } }
+                namespace inner { pub fn f(): int { private() } }
+            }
+            fn main(): int { a::private() + a::C::secret() + a::C::open() + a::inner::f() }
+        ";
+        let (_, _, errors) = resolve(source);
+        let private: Vec<&str> = errors
+            .iter()
+            .map(|error| match error {
+                ResolveErr::Private(path, ..) => path.as_str(),
+                error => panic!("{:?}", error),
+            })
+            .collect();
+        assert_eq!(private, ["a::private", "a::C::secret", "a::inner"]);
+    }
+
+    #[test]
+    fn test_resolve_undefined() {
+        let (_, _, errors) = resolve("namespace m { pub fn max(): int { 0 } } fn f(count: int): int { cuont + m::mx() + nope::g() }");
+        let undefined: Vec<(&str, Option<&str>)> = errors
+            .iter()
+            .map(|error| match error {
+                ResolveErr::Undefined(path, _, similar) => (path.as_str(), similar.as_deref()),
+                error => panic!("{:?}", error),
+            })
+            .collect();
+        assert_eq!(undefined, [("cuont", Some("count")), ("m::mx", Some("m::max")), ("nope", None)]);
+    }
+
+    #[test]
+    fn test_resolve_types() {
+        let (program, resolution) = resolve_ok("class Point { x: int; } namespace n { trait Shape { fn area(): int; } fn f(p: Point): Shape { p } }");
+        let Declaration::Namespace(namespace) = &program.declarations[1] else { unreachable!() };
+        let Declaration::Function(function) = &namespace.inner.declarations[1] else { unreachable!() };
+        assert_eq!(resolution.get(function.signature.params[0].type_annotation.id), Some(&Res::Class("Point".into())));
+        assert_eq!(resolution.get(function.signature.return_type.as_ref().unwrap().id), Some(&Res::Trait("n::Shape".into())));
+
+        let (_, _, errors) = resolve("namespace n { } fn f(a: n): Missing { n }");
+        assert!(matches!(
+            errors.as_slice(),
+            [ResolveErr::WrongKind(_, "namespace", "type", _), ResolveErr::Undefined(..), ResolveErr::WrongKind(_, "namespace", "value", _)]
+        ));
+    }
+
+    #[test]
+    fn test_resolve_self_and_struct_literals() {
+        let (program, resolution) = resolve_ok("namespace n { pub class P { x: int; fn f(self): Self { Self { x: 1 } } } } fn g() { let p = n::P { x: 2 }; }");
+        let Declaration::Namespace(namespace) = &program.declarations[0] else { unreachable!() };
+        let Declaration::Class(class) = &namespace.inner.declarations[0] else { unreachable!() };
+        let signature = &class.methods[0].signature;
+        assert!(signature.has_self());
+        assert_eq!(resolution.get(signature.params[0].type_annotation.id), Some(&Res::Class("n::P".into())));
+        let literal = class.methods[0].inner.value.as_deref().unwrap();
+        assert_eq!(resolution.get(literal.id), Some(&Res::Class("n::P".into())));
+        let StatementKind::Let { value: Some(value), .. } = &function_body(&program, "g").statements[0].kind else { unreachable!() };
+        assert_eq!(resolution.get(value.id), Some(&Res::Class("n::P".into())));
+
+        let (_, _, errors) = resolve("trait T { } fn f(): Self { T { } }");
+        assert!(matches!(errors.as_slice(), [ResolveErr::Undefined(name, _, None), ResolveErr::WrongKind(_, "trait", "class", _)] if name == "Self"));
+    }
+
+    #[test]
+    fn test_resolve_impls() {
+        let (program, resolution) = resolve_ok("trait T { fn f(self): Self; } class P { x: int; } impl T for P { fn f(self): Self { Self { x: self.x } } } impl T for int { fn f(self): Self { self } }");
+        let [Declaration::Trait(declaration), _, Declaration::Impl(class_impl), Declaration::Impl(int_impl)] = &program.declarations[..] else { unreachable!() };
+        let self_type = |signature: &FunctionSignature| resolution.get(signature.params[0].type_annotation.id).cloned();
+        assert_eq!(self_type(&declaration.signatures[0]), Some(Res::SelfType(declaration.id)));
+        assert_eq!(resolution.get(class_impl.trait_type.id), Some(&Res::Trait("T".into())));
+        assert_eq!(self_type(&class_impl.functions[0].signature), Some(Res::Class("P".into())));
+        assert_eq!(self_type(&int_impl.functions[0].signature), Some(Res::SelfType(int_impl.id)));
+
+        let (_, _, errors) = resolve("trait T { } class P { } impl P for T { } impl int for P { } impl T for P { fn f() { } fn f() { } }");
+        let [ResolveErr::WrongKind(_, "class", "trait", _), ResolveErr::WrongKind(_, "trait", "class", _), ResolveErr::WrongKind(int, "type", "trait", _), ResolveErr::Duplicate(name, ..)] = &errors[..] else {
+            panic!("{:?}", errors)
+        };
+        assert_eq!((int.as_str(), name.as_str()), ("int", "f"));
+    }
+
+    #[test]
+    fn test_resolve_dyn_types() {
+        let (program, resolution) = resolve_ok("trait T { } trait U { } fn f(x: (dyn T, int)) { } impl U for dyn T { }");
+        let [_, _, Declaration::Function(function), Declaration::Impl(declaration)] = &program.declarations[..] else { unreachable!() };
+        let TypeLiteralKind::TupleType { inner } = &function.signature.params[0].type_annotation.kind else { unreachable!() };
+        assert_eq!(resolution.get(inner[0].id), Some(&Res::Trait("T".into())));
+        assert_eq!(resolution.get(declaration.self_type.id), Some(&Res::Trait("T".into())));
+
+        let (_, _, errors) = resolve("class P { } trait T { fn f(self): dyn Self; } fn f(x: dyn P, y: dyn Q) { }");
+        let [ResolveErr::WrongKind(_, "type", "trait", _), ResolveErr::WrongKind(_, "class", "trait", _), ResolveErr::Undefined(name, ..)] = &errors[..] else {
+            panic!("{:?}", errors)
+        };
+        assert_eq!(name, "Q");
+    }
+
+    #[test]
+    fn test_resolve_type_params() {
+        let program = "trait Ord { } class T { } class Box<T> { value: T; fn get<U: Ord>(self, u: U): T { self.value } } fn f(x: T) { }";
+        let (program, resolution) = resolve_ok(program);
+        let [_, _, Declaration::Class(class), Declaration::Function(function)] = &program.declarations[..] else { unreachable!() };
+        let [ref param] = class.type_params[..] else { unreachable!() };
+        assert_eq!(resolution.get(class.fields[0].type_annotation.id), Some(&Res::TypeParam(param.id)));
+        let signature = &class.methods[0].signature;
+        assert_eq!(resolution.get(signature.params[1].type_annotation.id), Some(&Res::TypeParam(signature.type_params[0].id)));
+        assert_eq!(resolution.get(signature.type_params[0].bounds[0].id), Some(&Res::Trait("Ord".into())));
+        assert_eq!(resolution.get(signature.return_type.as_ref().unwrap().id), Some(&Res::TypeParam(param.id)));
+        // outside of the class `T` is the class again
+        assert_eq!(resolution.get(function.signature.params[0].type_annotation.id), Some(&Res::Class("T".into())));
+
+        let (_, _, errors) = resolve("class C { } fn f<T: C, T>(x: T): U { T { } }");
+        let [ResolveErr::Duplicate(name, ..), ResolveErr::WrongKind(_, "class", "trait", _), ResolveErr::Undefined(undefined, ..), ResolveErr::WrongKind(_, "type", "class", _)] = &errors[..] else {
+            panic!("{:?}", errors)
+        };
+        assert_eq!((name.as_str(), undefined.as_str()), ("T", "U"));
+    }
+
+    #[test]
+    fn test_resolve_enums() {
+        let source = "namespace n { pub enum Shape<T> { Circle(T), Rect { w: Self, h: int }, Empty } fn f(s: Shape): int { let a = n::Shape::Empty; let b = n::Shape::Rect { w: s, h: 1 }; match n::Shape::Circle(1) { n::Shape::Circle(r) => r, n::Shape::Rect { h } => h, _ => 0 } } }";
+        let (program, resolution) = resolve_ok(source);
+        let [Declaration::Namespace(namespace)] = &program.declarations[..] else { unreachable!() };
+        let [Declaration::Enum(declaration), Declaration::Function(function)] = &namespace.inner.declarations[..] else { unreachable!() };
+        let VariantKind::Struct(fields) = &declaration.variants[1].kind else { unreachable!() };
+        assert_eq!(resolution.get(fields[0].type_annotation.id), Some(&Res::Enum("n::Shape".into())));
+        assert_eq!(resolution.get(function.signature.params[0].type_annotation.id), Some(&Res::Enum("n::Shape".into())));
+        let values: Vec<Option<&Res>> = function.inner.statements.iter().map(|statement| match &statement.kind {
+            StatementKind::Let { value: Some(value), .. } => resolution.get(value.id),
+            _ => unreachable!(),
+        }).collect();
+        assert_eq!(values, [Some(&Res::Variant("n::Shape::Empty".into())), Some(&Res::Variant("n::Shape::Rect".into()))]);
+        let ExpressionKind::Match(match_expression) = &function.inner.value.as_ref().unwrap().kind else { unreachable!() };
+        let arm = &match_expression.arms[0];
+        assert_eq!(resolution.get(arm.pattern.id), Some(&Res::Variant("n::Shape::Circle".into())));
+        // a pattern binds its fields for the value of its arm only
+        let PatternKind::Variant { fields: PatternFields::Tuple(inner), .. } = &arm.pattern.kind else { unreachable!() };
+        assert_eq!(resolution.get(arm.value.id), Some(&Res::Local(inner[0].id)));
+
+        let (_, _, errors) = resolve("enum E { A, A } class C { } fn f(e: E) { let a = E; let b = E { }; match e { C(x) => x, E::B => 1, E::A => x } }");
+        let [ResolveErr::Duplicate(name, ..), ResolveErr::WrongKind(_, "enum", "value", _), ResolveErr::WrongKind(_, "enum", "class", _), ResolveErr::WrongKind(_, "class", "variant", _), ResolveErr::Undefined(undefined, ..), ResolveErr::Undefined(local, ..)] = &errors[..] else {
+            panic!("{:?}", errors)
+        };
+        assert_eq!((name.as_str(), undefined.as_str(), local.as_str()), ("A", "E::B", "x"));
+    }
+
+    #[test]
+    fn test_resolve_duplicates() {
+        let (_, _, errors) = resolve("fn f(a: int, a: int) { let (b, b) = (1, 2); } fn f() { } class C { x: int; x: int; } namespace f { }");
+        let names: Vec<&str> = errors
+            .iter()
+            .map(|error| match error {
+                ResolveErr::Duplicate(name, ..) => name.as_str(),
+                error => panic!("{:?}", error),
+            })
+            .collect();
+        assert_eq!(names, ["f", "x", "a", "b"]);
+    }
+
+    #[test]
+    fn test_resolve_prelude() {
+        let mut program = parse("namespace n { pub fn f(o: Option<int>): Result<int, bool> { match o { Some(x) => Ok(x), None => Err(false) } } } \
+                                 fn g(): Option<int> { let y = Option::<int>::None; Some(1) }");
+        crate::prelude::add(&mut program);
+        let (resolution, errors) = Resolver::new().resolve(&program);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let Declaration::Namespace(namespace) = &program.declarations[2] else { unreachable!() };
+        let Declaration::Function(f) = &namespace.inner.declarations[0] else { unreachable!() };
+        let ExpressionKind::Match(match_expression) = &f.inner.value.as_deref().unwrap().kind else { unreachable!() };
+        let arms = &match_expression.arms;
+        // a lone variant of the prelude names it, also as a pattern, where it binds nothing
+        assert!(matches!(&arms[1].pattern.kind, PatternKind::Identifier(name) if name == "None"));
+        assert_eq!(resolution.get(arms[0].pattern.id), Some(&Res::Variant("Option::Some".into())));
+        assert_eq!(resolution.get(arms[1].pattern.id), Some(&Res::Variant("Option::None".into())));
+        let ExpressionKind::Call { callee, .. } = &arms[1].value.kind else { unreachable!() };
+        assert_eq!(resolution.get(callee.id), Some(&Res::Variant("Result::Err".into())));
+
+        let mut program = parse("enum Option { A } fn f(): int { let None = 1; None }");
+        crate::prelude::add(&mut program);
+        let (_, errors) = Resolver::new().resolve(&program);
+        assert!(matches!(errors.as_slice(), [ResolveErr::Duplicate(name, ..)] if name == "Option"), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_resolve_unloaded_import() {
+        // without the loader an import names no module; see `loader::test` for loaded ones
+        let (_, _, errors) = resolve("import { f } from \"util\"; namespace util { pub fn f() { } }");
+        assert!(matches!(errors.as_slice(), [ResolveErr::UnloadedModule(path, _)] if path == "util"));
+    }
+
+    #[test]
+    fn test_resolve_shadowed_const() {
+        let (_, _, errors) = resolve("fn f(): int { const x = 1; let x = 2; x }");
+        assert!(matches!(errors.as_slice(), [ResolveErr::ShadowedConst(name, ..)] if name == "x"));
+        resolve_ok("fn f(): int { const x = 1; { let x = 2; x } }");
+    }
+
+    #[test]
+    fn test_resolve_intrinsics() {
+        let (program, resolution) = resolve_ok("fn f(a: u8): u8 { wrapping_add(a, 1) } fn g(): int { checked_mul(1, 2) } fn saturating_sub(): int { 0 }");
+        let value = |name| function_body(&program, name).value.as_deref().unwrap();
+        let ExpressionKind::Call { callee, .. } = &value("f").kind else { unreachable!() };
+        assert_eq!(resolution.get(callee.id), Some(&Res::Intrinsic(Intrinsic { overflow: Overflow::Wrapping, operation: Operation::Add })));
+        // items named like an intrinsic take its place
+        let (program, resolution) = resolve_ok("fn saturating_sub(): int { 0 } fn f(): int { saturating_sub() }");
+        let ExpressionKind::Call { callee, .. } = &function_body(&program, "f").value.as_deref().unwrap().kind else { unreachable!() };
+        assert_eq!(resolution.get(callee.id), Some(&Res::Function("saturating_sub".into())));
+        let (_, _, errors) = resolve("fn f(): int { wraping_add(1, 2) + math::wrapping_add(1, 2) }");
+        assert!(matches!(
+            errors.as_slice(),
+            [ResolveErr::Undefined(_, _, Some(similar)), ResolveErr::Undefined(path, _, None)] if similar == "wrapping_add" && path == "math"
+        ));
+    }
+}
@@ -1,18 +1,47 @@
 use std::fmt;
 
+use crate::diagnostic::Diagnostic;
+use crate::span::Span;
+
 pub enum TokenizeErr {
+    /// the character, escaped in the message if it does not print, and where it is
     UnexpectedChar(char, usize),
-    UnexpectedEOF(usize),
+    /// where the opening `"` is
+    UnterminatedString(usize),
+    /// the literal as written and where it starts
+    InvalidNumber(String, usize),
 }
 
-impl fmt::Debug for TokenizeErr {
-    fn fmt (&self, _formatter: &mut fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
-        println!("failed to tokenize program because...");
+impl TokenizeErr {
+    pub fn to_diagnostic(&self) -> Diagnostic {
         match self {
-            Self::UnexpectedChar(target, position) => println!("there is unexpected character: {} in {}", target, position),
-            Self::UnexpectedEOF(position) => println!("there is unexpexted EOF in {}", position),
+            Self::UnexpectedChar(target, position) => Diagnostic::error(format!("unexpected character `{}`", escape(*target)))
+                .with_code("E0102")
+                .with_label(Span::new(*position, position + target.len_utf8()), "no token starts with this character"),
+            Self::UnterminatedString(position) => Diagnostic::error("unterminated string literal")
+                .with_code("E0101")
+                .with_label(Span::new(*position, position + 1), "this string is never closed")
+                .with_help("add a `\"` where the string should end"),
+            Self::InvalidNumber(text, position) => Diagnostic::error(format!("invalid number literal `{}`", text))
+                .with_code("E0103")
+                .with_label(Span::new(*position, position + text.len()), "")
+                .with_note(format!("integers have to be at most {} and floats have at most one `.`", u64::MAX)),
         }
+    }
+}
 
-        Ok(())
+/// `target` as it reads in a message: quotes and backslashes as they are,
+/// characters that do not print as their escape like `\r`.
+fn escape(target: char) -> String {
+    match target {
+        '\'' | '"' | '\\' => target.to_string(),
+        _ => target.escape_debug().to_string(),
+    }
+}
+
+impl fmt::Debug for TokenizeErr {
+    fn fmt (&self, formatter: &mut fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        writeln!(formatter, "failed to tokenize program because...")?;
+        write!(formatter, "{}", self.to_diagnostic())
     }
 }
@@ -2,7 +2,12 @@ pub mod tokenizer;
 pub mod error;
 mod test;
 
-#[derive(Debug, PartialEq)]
+use std::fmt;
+
+use crate::numeric::NumericType;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Token {
     Identifier(String),    // my_valiable
     If,                    // if keyword
@@ -12,6 +17,7 @@ pub enum Token {
     Return,                // return keyword
     Static,                // static keyword
     Colon,                 // : keyword
+    DoubleColon,           // :: keyword
     SemiColon,             // ; keyword
     Period,                // . keyword
     Comma,                 // , keyword
@@ -22,6 +28,14 @@ pub enum Token {
     In,                    // in keyword
     Pub,                   // pub keyword
     NameSpace,             // namespace keyword
+    Class,                 // class keyword
+    Trait,                 // trait keyword
+    Impl,                  // impl keyword
+    Dyn,                   // dyn keyword
+    Enum,                  // enum keyword
+    Match,                 // match keyword
+    While,                 // while keyword
+    As,                    // as keyword
     BrancketStart,         // (
     BrancketEnd,           // )
     CurlyBracketStart,     // {
@@ -29,14 +43,20 @@ pub enum Token {
     SquareBracketStart,    // [
     SquareBracketEnd,      // ]
     EqualOpe,              // =
+    FatArrow,              // =>
+    DoubleEqualOpe,        // ==
+    NotEqualOpe,           // !=
     PlusOpe,               // +
     MinusOpe,              // -
     MulOpe,                // *
     DivOpe,                // /
-    GreaterOpe,            // <
-    LesserOpe,             // >
-    IntegerLiteral(i32),   // 20
-    FloatLiteral(f32),     // 3.2
+    LesserOpe,             // <
+    GreaterOpe,            // >
+    LesserEqualOpe,        // <=
+    GreaterEqualOpe,       // >=
+    Question,              // ?
+    IntegerLiteral(u64),   // 20
+    FloatLiteral(f64),     // 3.2
     StringLiteral(String), // "string"
     True,                  // true
     False,                 // false
@@ -44,5 +64,73 @@ pub enum Token {
     Float,                 // float type
     Char,                  // char type
     Bool,                  // bool type
+    NumericType(NumericType), // i8, u64, f64, usize, ... type
     EOF,                   // End of file
 }
+
+/// How a token is named in diagnostics: its source text, or a description for
+/// tokens whose text varies.
+impl fmt::Display for Token {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Token::Identifier(name) => return write!(formatter, "identifier `{}`", name),
+            Token::IntegerLiteral(value) => return write!(formatter, "`{}`", value),
+            Token::FloatLiteral(value) => return write!(formatter, "`{:?}`", value),
+            Token::StringLiteral(value) => return write!(formatter, "`\"{}\"`", value),
+            Token::EOF => return write!(formatter, "end of file"),
+            Token::If => "if",
+            Token::Else => "else",
+            Token::Import => "import",
+            Token::From => "from",
+            Token::Return => "return",
+            Token::Static => "static",
+            Token::Colon => ":",
+            Token::DoubleColon => "::",
+            Token::SemiColon => ";",
+            Token::Period => ".",
+            Token::Comma => ",",
+            Token::Const => "const",
+            Token::Let => "let",
+            Token::Fn => "fn",
+            Token::For => "for",
+            Token::In => "in",
+            Token::Pub => "pub",
+            Token::NameSpace => "namespace",
+            Token::Class => "class",
+            Token::Trait => "trait",
+            Token::Impl => "impl",
+            Token::Dyn => "dyn",
+            Token::Enum => "enum",
+            Token::Match => "match",
+            Token::While => "while",
+            Token::As => "as",
+            Token::BrancketStart => "(",
+            Token::BrancketEnd => ")",
+            Token::CurlyBracketStart => "{",
+            Token::CurlyBracketEnd => "}",
+            Token::SquareBracketStart => "[",
+            Token::SquareBracketEnd => "]",
+            Token::EqualOpe => "=",
+            Token::FatArrow => "=>",
+            Token::DoubleEqualOpe => "==",
+            Token::NotEqualOpe => "!=",
+            Token::PlusOpe => "+",
+            Token::MinusOpe => "-",
+            Token::MulOpe => "*",
+            Token::DivOpe => "/",
+            Token::LesserOpe => "<",
+            Token::GreaterOpe => ">",
+            Token::LesserEqualOpe => "<=",
+            Token::GreaterEqualOpe => ">=",
+            Token::Question => "?",
+            Token::True => "true",
+            Token::False => "false",
+            Token::Int => "int",
+            Token::Float => "float",
+            Token::Char => "char",
+            Token::Bool => "bool",
+            Token::NumericType(ty) => ty.name(),
+        };
+        write!(formatter, "`{}`", text)
+    }
+}
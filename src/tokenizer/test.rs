@@ -2,6 +2,7 @@
 #[cfg(test)]
 mod tests {
     use crate::tokenizer::tokenizer::Tokenizer;
+    use crate::numeric::NumericType;
     use crate::tokenizer::Token;
 
     #[test]
@@ -16,6 +17,26 @@ mod tests {
         let mut tokenizer = Tokenizer::new("   \t\n");
         let tokens = tokenizer.tokenize().unwrap();
         assert_eq!(*tokens, vec![Token::EOF]);
+        // files saved on Windows end their lines with "\r\n"
+        let mut tokenizer = Tokenizer::new("fn main(): int {\r\n    0\r\n}\r\n");
+        let tokens = tokenizer.tokenize().unwrap();
+        assert_eq!(tokens.len(), 10);
+    }
+
+    #[test]
+    fn test_tokenize_unexpected_char() {
+        let mut tokenizer = Tokenizer::new("let x = 1 @ 2;");
+        assert_eq!(tokenizer.tokenize().unwrap_err().to_diagnostic().message, "unexpected character `@`");
+        // a control character would garble the message, so it is escaped
+        let mut tokenizer = Tokenizer::new("let x = 1;\u{7}");
+        assert_eq!(tokenizer.tokenize().unwrap_err().to_diagnostic().message, "unexpected character `\\u{7}`");
+        let mut tokenizer = Tokenizer::new("let c = 'a';");
+        assert_eq!(tokenizer.tokenize().unwrap_err().to_diagnostic().message, "unexpected character `'`");
+        // a character of several bytes is reported whole, with a span over all of them
+        let mut tokenizer = Tokenizer::new("fn main() { é }");
+        let diagnostic = tokenizer.tokenize().unwrap_err().to_diagnostic();
+        assert_eq!(diagnostic.message, "unexpected character `é`");
+        assert_eq!(diagnostic.labels[0].span, crate::span::Span::new(12, 14));
     }
 
     #[test]
@@ -62,6 +83,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tokenize_match_arms() {
+        let mut tokenizer = Tokenizer::new("enum match matches => = > x?;");
+        let tokens = tokenizer.tokenize().unwrap();
+        assert_eq!(
+            *tokens,
+            vec![
+                Token::Enum,
+                Token::Match,
+                Token::Identifier("matches".into()),
+                Token::FatArrow,
+                Token::EqualOpe,
+                Token::GreaterOpe,
+                Token::Identifier("x".into()),
+                Token::Question,
+                Token::SemiColon,
+                Token::EOF,
+            ]
+        );
+    }
+
     #[test]
     fn test_tokenize_operators() {
         let mut tokenizer = Tokenizer::new("+-*/");
@@ -78,6 +120,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tokenize_comparisons() {
+        let mut tokenizer = Tokenizer::new("< > <= >= == != List<List<int>>");
+        let tokens = tokenizer.tokenize().unwrap();
+        assert_eq!(
+            *tokens,
+            vec![
+                Token::LesserOpe,
+                Token::GreaterOpe,
+                Token::LesserEqualOpe,
+                Token::GreaterEqualOpe,
+                Token::DoubleEqualOpe,
+                Token::NotEqualOpe,
+                Token::Identifier("List".into()),
+                Token::LesserOpe,
+                Token::Identifier("List".into()),
+                Token::LesserOpe,
+                Token::Int,
+                Token::GreaterOpe,
+                Token::GreaterOpe,
+                Token::EOF,
+            ]
+        );
+    }
+
     #[test]
     fn test_tokenize_mixed() {
         let mut tokenizer = Tokenizer::new("let=\"hello world\"2232 43.43; if (true)");
@@ -102,6 +169,7 @@ mod tests {
 
 
     #[test]
+    #[allow(clippy::approx_constant)] // `3.14` is the literal in the source
     fn test_tokenize_complex() {
         let mut tokenizer = Tokenizer::new(
             r#"
@@ -145,7 +213,7 @@ for i in [1, 2, 3] {
                 Token::CurlyBracketStart,
                 Token::If,
                 Token::Identifier("a".into()),
-                Token::LesserOpe,
+                Token::GreaterOpe,
                 Token::IntegerLiteral(0),
                 Token::CurlyBracketStart,
                 Token::Return,
@@ -196,4 +264,25 @@ for i in [1, 2, 3] {
         );
     }
 
+    #[test]
+    fn test_tokenize_numeric_types() {
+        let mut tokenizer = Tokenizer::new("i8 u64 usize f64 as int8 asset 18446744073709551615 0.1");
+        let tokens = tokenizer.tokenize().unwrap();
+        assert_eq!(
+            *tokens,
+            vec![
+                Token::NumericType(NumericType::I8),
+                Token::NumericType(NumericType::U64),
+                Token::NumericType(NumericType::Usize),
+                Token::NumericType(NumericType::F64),
+                Token::As,
+                Token::Identifier("int8".into()),
+                Token::Identifier("asset".into()),
+                Token::IntegerLiteral(u64::MAX),
+                Token::FloatLiteral(0.1),
+                Token::EOF,
+            ]
+        );
+        assert!(Tokenizer::new("18446744073709551616").tokenize().is_err());
+    }
 }
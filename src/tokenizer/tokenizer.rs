@@ -1,88 +1,146 @@
 use crate::tokenizer::Token;
+use crate::numeric::NumericType;
 use crate::tokenizer::error::TokenizeErr;
+use crate::span::Span;
 
 #[derive(Debug)]
 pub struct Tokenizer {
     program: String,
     output_tokens: Vec<Token>,
-    now_index: usize
+    /// `output_spans[i]` is the source range of `output_tokens[i]`.
+    output_spans: Vec<Span>,
+    now_index: usize,
+    token_start: usize,
+    /// added to every offset in spans and errors
+    offset: usize,
+    /// where every line after the first starts
+    line_starts: Vec<usize>,
 }
 
 type Result<'a> = std::result::Result<&'a Vec<Token>, TokenizeErr>;
 
 impl Tokenizer {
     pub fn new(program: &str) -> Self {
+        Self::starting_at(program, 0)
+    }
+
+    /// A tokenizer for a file whose first byte is at `offset`, like the files
+    /// of a `SourceMap` after the first.
+    pub fn starting_at(program: &str, offset: usize) -> Self {
         Tokenizer {
             program: program.into(),
             output_tokens:  Vec::new(),
+            output_spans: Vec::new(),
             now_index: 0,
+            token_start: 0,
+            offset,
+            line_starts: Vec::new(),
         }
     }
 
     fn check_next(&mut self, expect: &str) -> bool {
-        let finish_index = self.now_index + expect.len();
-        if finish_index > self.program.len() { return false };
-        let next = &self.program[self.now_index..finish_index];
-        let ret = next == expect;
+        let ret = self.program[self.now_index..].starts_with(expect);
         if ret {
             self.now_index += expect.len();
         }
         ret
     }
 
+    /// Like `check_next`, but only matches when `expect` is not immediately
+    /// followed by an identifier character, so `int` is not read as `in` + `t`.
+    fn check_keyword(&mut self, expect: &str) -> bool {
+        let rest = &self.program[self.now_index..];
+        if !rest.starts_with(expect) { return false };
+        if let Some(c) = rest[expect.len()..].chars().next() {
+            if c.is_ascii_alphanumeric() || c == '_' { return false };
+        }
+        self.now_index += expect.len();
+        true
+    }
+
     fn push(&mut self, token: Token) {
         self.output_tokens.push(token);
+        self.output_spans.push(Span::new(self.offset + self.token_start, self.offset + self.now_index));
+    }
+
+    /// Spans of the tokens produced by `tokenize`, one per token.
+    pub fn spans(&self) -> &Vec<Span> {
+        &self.output_spans
     }
 
-    fn next(&self) -> char {
-        self.program.get(self.now_index..self.now_index + 1).unwrap().chars().nth(0).unwrap()
+    /// Where every line after the first starts, once `tokenize` is done.
+    pub fn line_starts(&self) -> &Vec<usize> {
+        &self.line_starts
     }
 
-    pub fn tokenize(&mut self) -> Result {
+    fn next(&self) -> Option<char> {
+        self.program[self.now_index..].chars().next()
+    }
+
+    pub fn tokenize(&mut self) -> Result<'_> {
         loop {
+            self.token_start = self.now_index;
             if self.program.len() == self.now_index { self.push(Token::EOF); break }
-            else if self.check_next(" ") {}
-            else if self.check_next("\t") {}
-            else if self.check_next("\n") {}
+            else if self.check_next("\n") { self.line_starts.push(self.offset + self.now_index) }
+            else if self.check_next(" ") || self.check_next("\t") || self.check_next("\r") {}
             else if self.check_next(",") { self.push(Token::Comma) }
             else if self.check_next(".") { self.push(Token::Period) }
-            else if self.check_next("if") { self.push(Token::If) }
-            else if self.check_next("else") { self.push(Token::Else) }
-            else if self.check_next("import") { self.push(Token::Import) }
-            else if self.check_next("in") { self.push(Token::In) }
-            else if self.check_next("from") { self.push(Token::From)  }
-            else if self.check_next("static") { self.push(Token::Static) }
+            else if self.check_keyword("if") { self.push(Token::If) }
+            else if self.check_keyword("else") { self.push(Token::Else) }
+            else if self.check_keyword("import") { self.push(Token::Import) }
+            else if self.check_keyword("in") { self.push(Token::In) }
+            else if self.check_keyword("from") { self.push(Token::From)  }
+            else if self.check_keyword("static") { self.push(Token::Static) }
+            else if self.check_next("::") { self.push(Token::DoubleColon) }
             else if self.check_next(":") { self.push(Token::Colon) }
             else if self.check_next(";") { self.push(Token::SemiColon) }
-            else if self.check_next("const") { self.push(Token::Const) }
-            else if self.check_next("let") { self.push(Token::Let) }
-            else if self.check_next("fn") { self.push(Token::Fn) }
-            else if self.check_next("for") { self.push(Token::For) }
-            else if self.check_next("in") { self.push(Token::In) }
-            else if self.check_next("let") { self.push(Token::Let) }
-            else if self.check_next("return") { self.push(Token::Return) }
-            else if self.check_next("pub") { self.push(Token::Pub) }
-            else if self.check_next("namespace") { self.push(Token::NameSpace) }
+            else if self.check_keyword("const") { self.push(Token::Const) }
+            else if self.check_keyword("let") { self.push(Token::Let) }
+            else if self.check_keyword("fn") { self.push(Token::Fn) }
+            else if self.check_keyword("for") { self.push(Token::For) }
+            else if self.check_keyword("while") { self.push(Token::While) }
+            else if self.check_keyword("return") { self.push(Token::Return) }
+            else if self.check_keyword("pub") { self.push(Token::Pub) }
+            else if self.check_keyword("namespace") { self.push(Token::NameSpace) }
+            else if self.check_keyword("class") { self.push(Token::Class) }
+            else if self.check_keyword("trait") { self.push(Token::Trait) }
+            else if self.check_keyword("impl") { self.push(Token::Impl) }
+            else if self.check_keyword("dyn") { self.push(Token::Dyn) }
+            else if self.check_keyword("enum") { self.push(Token::Enum) }
+            else if self.check_keyword("match") { self.push(Token::Match) }
+            else if self.check_keyword("int") { self.push(Token::Int) }
+            else if self.check_keyword("float") { self.push(Token::Float) }
+            else if self.check_keyword("char") { self.push(Token::Char) }
+            else if self.check_keyword("bool") { self.push(Token::Bool) }
+            else if let Some(ty) = NumericType::ALL.into_iter().find(|ty| self.check_keyword(ty.name())) { self.push(Token::NumericType(ty)) }
+            else if self.check_keyword("as") { self.push(Token::As) }
             else if self.check_next("(") { self.push(Token::BrancketStart) }
             else if self.check_next(")") { self.push(Token::BrancketEnd) }
             else if self.check_next("[") { self.push(Token::SquareBracketStart) }
             else if self.check_next("]") { self.push(Token::SquareBracketEnd) }
             else if self.check_next("{") { self.push(Token::CurlyBracketStart) }
             else if self.check_next("}") { self.push(Token::CurlyBracketEnd) }
+            else if self.check_next("==") { self.push(Token::DoubleEqualOpe) }
+            else if self.check_next("=>") { self.push(Token::FatArrow) }
+            else if self.check_next("!=") { self.push(Token::NotEqualOpe) }
             else if self.check_next("=") { self.push(Token::EqualOpe) }
             else if self.check_next("+") { self.push(Token::PlusOpe) }
             else if self.check_next("-") { self.push(Token::MinusOpe) }
             else if self.check_next("*") { self.push(Token::MulOpe) }
             else if self.check_next("/") { self.push(Token::DivOpe) }
-            else if self.check_next("<") { self.push(Token::GreaterOpe) }
-            else if self.check_next(">") { self.push(Token::LesserOpe) }
-            else if self.check_next("true") { self.push(Token::True) }
-            else if self.check_next("false") { self.push(Token::False) }
+            else if self.check_next("<=") { self.push(Token::LesserEqualOpe) }
+            else if self.check_next(">=") { self.push(Token::GreaterEqualOpe) }
+            // there is no shift operator, so `>>` closing two generic lists is just two `>`
+            else if self.check_next("<") { self.push(Token::LesserOpe) }
+            else if self.check_next(">") { self.push(Token::GreaterOpe) }
+            else if self.check_next("?") { self.push(Token::Question) }
+            else if self.check_keyword("true") { self.push(Token::True) }
+            else if self.check_keyword("false") { self.push(Token::False) }
             else {
                 match self.next() {
-                    '0'..='9' => self.tokenize_number(),
-                    '"' => self.tokenize_string(),
-                    _ => self.tokenize_identifier(),
+                    Some('0'..='9') => self.tokenize_number()?,
+                    Some('"') => self.tokenize_string()?,
+                    _ => self.tokenize_identifier()?,
                 }
             }
         }
@@ -91,42 +149,59 @@ impl Tokenizer {
 
     }
 
-    fn tokenize_number(&mut self) {
+    fn tokenize_number(&mut self) -> std::result::Result<(), TokenizeErr> {
+        let start_index = self.now_index;
         let mut ret = Vec::<char>::new();
-        while let '0'..='9' | '.' = self.next() {
-            ret.push(self.next());
+        while let Some(c @ ('0'..='9' | '.')) = self.next() {
+            ret.push(c);
             self.now_index += 1;
         }
-        match ret.iter().collect::<String>().parse::<i32>() {
+        let text = ret.into_iter().collect::<String>();
+        match text.parse::<u64>() {
             Ok(interger) => self.push(Token::IntegerLiteral(interger)),
+            // an integer too large for `u64` is an error rather than a float
+            Err(_) if !text.contains('.') => return Err(TokenizeErr::InvalidNumber(text, self.offset + start_index)),
             Err(_) => {
-                match ret.into_iter().collect::<String>().parse::<f32>() {
+                match text.parse::<f64>() {
                     Ok(float) => self.push(Token::FloatLiteral(float)),
-                    Err(_) => {
-                        panic!("error in parse float literal");
-                    }
+                    Err(_) => return Err(TokenizeErr::InvalidNumber(text, self.offset + start_index)),
                 }
             }
         }
+        Ok(())
     }
 
-    fn tokenize_string(&mut self) {
+    fn tokenize_string(&mut self) -> std::result::Result<(), TokenizeErr> {
+        let start_index = self.now_index;
         self.now_index += 1;
         let mut ret = Vec::<char>::new();
-        while self.next() != '"' {
-            ret.push(self.next());
-            self.now_index += 1;
+        loop {
+            match self.next() {
+                Some('"') => break,
+                Some(c) => {
+                    ret.push(c);
+                    self.now_index += c.len_utf8();
+                }
+                None => return Err(TokenizeErr::UnterminatedString(self.offset + start_index)),
+            }
         }
         self.now_index += 1;
         self.push(Token::StringLiteral(ret.into_iter().collect()));
+        Ok(())
     }
 
-    fn tokenize_identifier(&mut self) {
+    fn tokenize_identifier(&mut self) -> std::result::Result<(), TokenizeErr> {
+        match self.next() {
+            Some('a'..='z' | 'A'..='Z' | '_') => {}
+            Some(c) => return Err(TokenizeErr::UnexpectedChar(c, self.offset + self.now_index)),
+            None => unreachable!("`tokenize` stops at the end of the program"),
+        }
         let mut identifier_name = Vec::<char>::new();
-        while let 'a'..='z' | 'A'..='Z' | '_' = self.next() {
-            identifier_name.push(self.next());
+        while let Some(c @ ('a'..='z' | 'A'..='Z' | '0'..='9' | '_')) = self.next() {
+            identifier_name.push(c);
             self.now_index += 1;
         }
         self.push(Token::Identifier(identifier_name.into_iter().collect()));
+        Ok(())
     }
 }
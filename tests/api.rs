@@ -0,0 +1,43 @@
+use sonneko_c_compiler::error::CompileErr;
+use sonneko_c_compiler::parser::Declaration;
+use sonneko_c_compiler::{check, check_str, parse_file, parse_str, tokenize};
+
+const PROGRAMS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/programs");
+
+#[test]
+fn test_parse_file() {
+    let program = parse_file(format!("{}/max.sn", PROGRAMS)).unwrap();
+    assert_eq!(program.declarations.len(), 2);
+    assert!(matches!(&program.declarations[1], Declaration::Function(function) if function.name == "main"));
+    check(&program).unwrap();
+}
+
+#[test]
+fn test_parse_missing_file() {
+    let error = parse_file(format!("{}/missing.sn", PROGRAMS)).unwrap_err();
+    assert!(matches!(error, CompileErr::Read(..)));
+    assert!(!error.is_internal());
+}
+
+#[test]
+fn test_check_reports_type_errors() {
+    let program = parse_file(format!("{}/type_error.sn", PROGRAMS)).unwrap();
+    let error = check(&program).unwrap_err();
+    assert!(matches!(&error, CompileErr::Check(errors) if errors.len() == 1));
+    assert!(!error.is_internal());
+}
+
+#[test]
+fn test_errors_of_each_pass() {
+    assert!(matches!(tokenize("fn $"), Err(CompileErr::Tokenize(_))));
+    assert!(matches!(parse_str("fn f( {"), Err(CompileErr::Parse(_))));
+    assert!(matches!(check_str("fn f(): int { true }"), Err(CompileErr::Check(_))));
+}
+
+#[cfg(feature = "llvm")]
+#[test]
+fn test_compile_str() {
+    let ir = sonneko_c_compiler::compile_str("fn main(): int { let x = 2; x * 21 }", "answer").unwrap();
+    // a debug build checks the multiplication for overflow
+    assert!(ir.contains("@llvm.smul.with.overflow.i32"));
+}
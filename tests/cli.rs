@@ -0,0 +1,220 @@
+use std::process::{Command, Output};
+
+const PROGRAMS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/programs");
+
+fn sonneko(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sonneko")).args(args).output().unwrap()
+}
+
+fn program(name: &str) -> String {
+    format!("{}/{}", PROGRAMS, name)
+}
+
+#[test]
+fn test_cli_version_and_help() {
+    let output = sonneko(&["--version"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("sonneko "));
+    let output = sonneko(&["--help"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("USAGE"));
+}
+
+#[test]
+fn test_cli_usage_error() {
+    let output = sonneko(&["frobnicate", "x.sn"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown command"));
+}
+
+#[test]
+fn test_cli_check() {
+    assert_eq!(sonneko(&["check", &program("max.sn")]).status.code(), Some(0));
+    let output = sonneko(&["check", &program("type_error.sn")]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with("error[E0302]: `if` and `else` have different types\n"), "{}", stderr);
+    assert!(stderr.contains("type_error.sn:5:9\n"), "{}", stderr);
+    assert!(stderr.ends_with("\nFor more information about this error, try `sonneko --explain E0302`.\n"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_error_format_json() {
+    let output = sonneko(&["check", "--error-format=json", &program("type_error.sn")]);
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.lines().count(), 1);
+    assert!(stderr.starts_with(r#"{"file":""#), "{}", stderr);
+    assert!(stderr.contains(r#"type_error.sn","severity":"error","code":"E0302","message":"`if` and `else` have different types","#), "{}", stderr);
+    assert!(stderr.contains(r#""line_start":5,"column_start":9,"line_end":5,"column_end":12,"is_primary":true"#), "{}", stderr);
+
+    let output = sonneko(&["--error-format", "json", "frobnicate", "x.sn"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with(r#"{"file":null,"severity":"error","code":null,"message":"unknown command `frobnicate`""#));
+}
+
+#[test]
+fn test_cli_explain() {
+    let output = sonneko(&["--explain", "E0302"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("The branches of an `if` expression have different types."));
+    assert!(stdout.contains("```compile_fail,E0302\n"));
+    assert_eq!(sonneko(&["--explain", "E9999"]).status.code(), Some(1));
+}
+
+#[test]
+fn test_cli_fix() {
+    let original = std::fs::read_to_string(program("fixable.sn")).unwrap();
+    let output = sonneko(&["fix", "--dry-run", &program("fixable.sn")]);
+    assert!(output.status.success());
+    let diff = String::from_utf8_lossy(&output.stdout);
+    assert!(diff.contains("\n-    let x = 1\n+    let x = 1;\n"), "{}", diff);
+    assert!(diff.contains("\n-    if x = y { 1 } else { 0 }\n+    if x == y { 1 } else { 0 }\n"), "{}", diff);
+
+    let path = std::env::temp_dir().join(format!("sonneko-fix-test-{}.sn", std::process::id()));
+    std::fs::write(&path, &original).unwrap();
+    let output = sonneko(&["fix", path.to_str().unwrap()]);
+    let fixed = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(fixed, original.replace("= 1\n", "= 1;\n").replace("x = y", "x == y"));
+}
+
+#[test]
+fn test_cli_fix_modules() {
+    let directory = std::env::temp_dir().join(format!("sonneko-fix-modules-test-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let main = directory.join("main.sn");
+    std::fs::write(&main, "import numbers from \"numbers\";\n\nfn main(): int {\n    let x = numbers::double(2)\n    x\n}\n").unwrap();
+    std::fs::write(directory.join("broken.sn"), "pub fn f(): int {\n    let y = 1\n    y\n}\n").unwrap();
+    // `numbers.sn` is only found through the import path, so the fixed file checks
+    let output = sonneko(&["fix", "-I", &program("modules"), main.to_str().unwrap()]);
+    let fixed = std::fs::read_to_string(&main).unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(fixed.contains("    let x = numbers::double(2);\n"), "{}", fixed);
+
+    // the modules `main.sn` imports are not changed, their errors are only reported
+    std::fs::write(&main, "import broken from \"broken\";\n\nfn main(): int {\n    broken::f()\n}\n").unwrap();
+    let output = sonneko(&["fix", main.to_str().unwrap()]);
+    let broken = std::fs::read_to_string(directory.join("broken.sn")).unwrap();
+    let _ = std::fs::remove_dir_all(&directory);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("broken.sn"), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(broken, "pub fn f(): int {\n    let y = 1\n    y\n}\n");
+}
+
+#[test]
+fn test_cli_check_modules() {
+    // `square.sn` is only found through the import path
+    let output = sonneko(&["check", &program("modules/main.sn")]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("error[E0501]: can not find module `square`"), "{}", String::from_utf8_lossy(&output.stderr));
+    let output = sonneko(&["check", "-I", &program("modules/shapes"), &program("modules/main.sn")]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn test_cli_lex_and_parse() {
+    let output = sonneko(&["lex", &program("max.sn")]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("0..9 NameSpace\n"));
+    let output = sonneko(&["parse", &program("max.sn")]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("name: \"max\""));
+}
+
+#[cfg(feature = "llvm")]
+#[test]
+fn test_cli_run() {
+    assert_eq!(sonneko(&["run", &program("max.sn")]).status.code(), Some(42));
+}
+
+#[cfg(feature = "llvm")]
+#[test]
+fn test_cli_run_modules() {
+    assert_eq!(sonneko(&["run", "--import-path", &program("modules/shapes"), &program("modules/main.sn")]).status.code(), Some(13));
+}
+
+#[cfg(feature = "llvm")]
+#[test]
+fn test_cli_run_casts() {
+    // 44 + 255 - 255 + 2 + 1: `as` wraps integers and saturates floats, `u32` compares unsigned
+    assert_eq!(sonneko(&["run", &program("casts.sn")]).status.code(), Some(47));
+}
+
+#[cfg(feature = "llvm")]
+#[test]
+fn test_cli_run_classes() {
+    // 2 + 2 from two steps of the left counter, 30 + 5 from one of the right
+    assert_eq!(sonneko(&["run", &program("classes.sn")]).status.code(), Some(39));
+}
+
+#[cfg(feature = "llvm")]
+#[test]
+fn test_cli_run_traits() {
+    // 18 + 11 + 10 from the shapes, 18 from the default `twice`, 8 from `double`
+    assert_eq!(sonneko(&["run", &program("traits.sn")]).status.code(), Some(65));
+}
+
+#[cfg(feature = "llvm")]
+#[test]
+fn test_cli_run_dyn() {
+    // 9 + 60 from the pair, 16 from the square `last` is reassigned to
+    assert_eq!(sonneko(&["run", &program("dyn.sn")]).status.code(), Some(85));
+}
+
+#[cfg(feature = "llvm")]
+#[test]
+fn test_cli_run_generics() {
+    // 30 from `max`, 200 from the `u8` pair and 3 from the swapped pair;
+    // `util::max<i32>` is called from two modules but compiled once
+    assert_eq!(sonneko(&["run", &program("generics/main.sn")]).status.code(), Some(233));
+}
+
+#[cfg(feature = "llvm")]
+#[test]
+fn test_cli_run_enums() {
+    // 3 + 8 from the tile, 3 + 20 from the maybes and 5 + 7 + 9 + 100 from the depths
+    assert_eq!(sonneko(&["run", &program("enums.sn")]).status.code(), Some(155));
+}
+
+#[cfg(feature = "llvm")]
+#[test]
+fn test_cli_run_try() {
+    // 3 from the first sum, 100 + 40 from the error of the second, 10 + 7 from the
+    // options and 40 + 7 from doubling 20 and 200, which overflows a `u8`
+    assert_eq!(sonneko(&["run", &program("try.sn")]).status.code(), Some(207));
+}
+
+#[cfg(feature = "llvm")]
+#[test]
+fn test_cli_run_overflow() {
+    let debug = sonneko(&["run", &program("overflow.sn")]);
+    assert_eq!(debug.status.code(), Some(101));
+    let stderr = String::from_utf8_lossy(&debug.stderr);
+    assert!(stderr.ends_with("overflow.sn:2:5: attempt to multiply with overflow\n"), "{}", stderr);
+    // 400 wraps to 144 in a `u8`
+    assert_eq!(sonneko(&["run", "--release", &program("overflow.sn")]).status.code(), Some(144));
+}
+
+#[cfg(feature = "llvm")]
+#[test]
+fn test_cli_build() {
+    let output = std::env::temp_dir().join(format!("sonneko-cli-test-{}", std::process::id()));
+    let build = sonneko(&["build", &program("max.sn"), "-o", output.to_str().unwrap()]);
+    assert!(build.status.success(), "{}", String::from_utf8_lossy(&build.stderr));
+    let status = Command::new(&output).status().unwrap();
+    let _ = std::fs::remove_file(&output);
+    assert_eq!(status.code(), Some(42));
+}
+
+#[cfg(not(feature = "llvm"))]
+#[test]
+fn test_cli_build_without_llvm() {
+    for command in ["build", "run"] {
+        let output = sonneko(&[command, &program("max.sn")]);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("need the `llvm` feature"));
+    }
+}